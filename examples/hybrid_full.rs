@@ -31,6 +31,7 @@ async fn main() -> Result<()> {
         .with_shards(4)
         .with_eviction_config(LruConfig {
             high_priority_pool_ratio: 0.1,
+            ..Default::default()
         })
         .with_object_pool_capacity(1024)
         .with_hash_builder(ahash::RandomState::default())
@@ -50,6 +50,14 @@ impl Countdown {
             Ordering::Relaxed,
         );
     }
+
+    /// Extend [`Countdown`] to return `false` for `counter` more times.
+    pub fn extend(&self, counter: usize) {
+        let counter = isize::try_from(counter).expect("`counter` must NOT exceed `isize::MAX`.");
+        let _ = self
+            .counter
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |c| Some(c.max(0) + counter));
+    }
 }
 
 #[cfg(test)]
@@ -78,4 +86,19 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_countdown_extend() {
+        let cd = Countdown::new(1);
+        assert!(!cd.countdown());
+        assert!(cd.countdown());
+        assert!(cd.countdown());
+
+        cd.extend(2);
+        assert!(!cd.countdown());
+        cd.extend(1);
+        assert!(!cd.countdown());
+        assert!(!cd.countdown());
+        assert!(cd.countdown());
+    }
 }
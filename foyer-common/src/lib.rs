@@ -35,6 +35,8 @@ pub mod event;
 pub mod future;
 /// The shared metrics for foyer.
 pub mod metrics;
+/// Miss ratio curve estimation with spatially sampled reuse distances.
+pub mod mrc;
 /// A concurrent object pool.
 pub mod object_pool;
 /// The range extensions.
@@ -37,14 +37,22 @@ pub struct Metrics {
     /// ... ...
     pub memory_reinsert: Counter,
     /// ... ...
+    pub memory_reject: Counter,
+    /// ... ...
     pub memory_release: Counter,
     /// ... ...
     pub memory_queue: Counter,
     /// ... ...
     pub memory_fetch: Counter,
+    /// ... ...
+    pub memory_reclaim_defer: Counter,
+    /// ... ...
+    pub memory_reclaim_overflow: Counter,
 
     /// ... ...
     pub memory_usage: Gauge,
+    /// ... ...
+    pub memory_reclaim_pending: Gauge,
 
     /* disk cache metrics */
     /// ... ...
@@ -91,6 +99,11 @@ pub struct Metrics {
     /// ... ...
     pub storage_disk_flush_duration: Histogram,
 
+    /// ... ...
+    pub storage_disk_write_throttle_duration: Histogram,
+    /// ... ...
+    pub storage_disk_read_throttle_duration: Histogram,
+
     /// ... ...
     pub storage_region_total: Gauge,
     /// ... ...
@@ -115,6 +128,8 @@ pub struct Metrics {
     pub hybrid_miss: Counter,
     /// ... ...
     pub hybrid_remove: Counter,
+    /// ... ...
+    pub hybrid_tier_error: Counter,
 
     /// ... ...
     pub hybrid_insert_duration: Histogram,
@@ -147,11 +162,17 @@ impl Metrics {
         let memory_evict = counter!(format!("foyer_memory_op_total"), "name" => name.to_string(), "op" => "evict");
         let memory_reinsert =
             counter!(format!("foyer_memory_op_total"), "name" => name.to_string(), "op" => "reinsert");
+        let memory_reject = counter!(format!("foyer_memory_op_total"), "name" => name.to_string(), "op" => "reject");
         let memory_release = counter!(format!("foyer_memory_op_total"), "name" => name.to_string(), "op" => "release");
         let memory_queue = counter!(format!("foyer_memory_op_total"), "name" => name.to_string(), "op" => "queue");
         let memory_fetch = counter!(format!("foyer_memory_op_total"), "name" => name.to_string(), "op" => "fetch");
+        let memory_reclaim_defer =
+            counter!(format!("foyer_memory_op_total"), "name" => name.to_string(), "op" => "reclaim_defer");
+        let memory_reclaim_overflow =
+            counter!(format!("foyer_memory_op_total"), "name" => name.to_string(), "op" => "reclaim_overflow");
 
         let memory_usage = gauge!(format!("foyer_memory_usage"), "name" => name.to_string(), "op" => "usage");
+        let memory_reclaim_pending = gauge!(format!("foyer_memory_reclaim_pending"), "name" => name.to_string());
 
         /* disk cache metrics */
 
@@ -196,6 +217,11 @@ impl Metrics {
         let storage_disk_flush_duration =
             histogram!(format!("foyer_storage_disk_io_duration"), "name" => name.to_string(), "op" => "flush");
 
+        let storage_disk_write_throttle_duration =
+            histogram!(format!("foyer_storage_disk_throttle_duration"), "name" => name.to_string(), "op" => "write");
+        let storage_disk_read_throttle_duration =
+            histogram!(format!("foyer_storage_disk_throttle_duration"), "name" => name.to_string(), "op" => "read");
+
         let storage_region_total =
             gauge!(format!("foyer_storage_region"), "name" => name.to_string(), "type" => "total");
         let storage_region_clean =
@@ -215,6 +241,8 @@ impl Metrics {
         let hybrid_hit = counter!(format!("foyer_hybrid_op_total"), "name" => name.to_string(), "op" => "hit");
         let hybrid_miss = counter!(format!("foyer_hybrid_op_total"), "name" => name.to_string(), "op" => "miss");
         let hybrid_remove = counter!(format!("foyer_hybrid_op_total"), "name" => name.to_string(), "op" => "remove");
+        let hybrid_tier_error =
+            counter!(format!("foyer_hybrid_op_total"), "name" => name.to_string(), "op" => "tier_error");
 
         let hybrid_insert_duration =
             histogram!(format!("foyer_hybrid_op_duration"), "name" => name.to_string(), "op" => "insert");
@@ -235,10 +263,14 @@ impl Metrics {
             memory_remove,
             memory_evict,
             memory_reinsert,
+            memory_reject,
             memory_release,
             memory_queue,
             memory_fetch,
+            memory_reclaim_defer,
+            memory_reclaim_overflow,
             memory_usage,
+            memory_reclaim_pending,
 
             storage_enqueue,
             storage_hit,
@@ -259,6 +291,8 @@ impl Metrics {
             storage_disk_write_duration,
             storage_disk_read_duration,
             storage_disk_flush_duration,
+            storage_disk_write_throttle_duration,
+            storage_disk_read_throttle_duration,
             storage_region_total,
             storage_region_clean,
             storage_region_evictable,
@@ -270,6 +304,7 @@ impl Metrics {
             hybrid_hit,
             hybrid_miss,
             hybrid_remove,
+            hybrid_tier_error,
             hybrid_insert_duration,
             hybrid_hit_duration,
             hybrid_miss_duration,
@@ -278,3 +313,131 @@ impl Metrics {
         }
     }
 }
+
+/// In-memory cache metrics of a priority.
+#[derive(Clone)]
+pub struct MemoryPriorityMetrics {
+    /// ... ...
+    pub insert: Counter,
+    /// ... ...
+    pub hit: Counter,
+    /// ... ...
+    pub evict: Counter,
+
+    /// ... ...
+    pub usage: Gauge,
+}
+
+impl Debug for MemoryPriorityMetrics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MemoryPriorityMetrics").finish()
+    }
+}
+
+impl MemoryPriorityMetrics {
+    /// Create a new in-memory cache metrics with the given name and priority.
+    pub fn new(name: &str, priority: usize) -> Self {
+        let priority = priority.to_string();
+
+        let insert = counter!(
+            format!("foyer_memory_priority_op_total"),
+            "name" => name.to_string(), "op" => "insert", "priority" => priority.clone()
+        );
+        let hit = counter!(
+            format!("foyer_memory_priority_op_total"),
+            "name" => name.to_string(), "op" => "hit", "priority" => priority.clone()
+        );
+        let evict = counter!(
+            format!("foyer_memory_priority_op_total"),
+            "name" => name.to_string(), "op" => "evict", "priority" => priority.clone()
+        );
+
+        let usage = gauge!(format!("foyer_memory_priority_usage"), "name" => name.to_string(), "priority" => priority);
+
+        Self {
+            insert,
+            hit,
+            evict,
+            usage,
+        }
+    }
+}
+
+/// In-memory cache metrics of the memory pressure monitor.
+#[derive(Clone)]
+pub struct MemoryPressureMetrics {
+    /// ... ...
+    pub shrink: Counter,
+    /// ... ...
+    pub restore: Counter,
+
+    /// ... ...
+    pub effective_capacity: Gauge,
+}
+
+impl Debug for MemoryPressureMetrics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MemoryPressureMetrics").finish()
+    }
+}
+
+impl MemoryPressureMetrics {
+    /// Create a new memory pressure metrics with the given name.
+    pub fn new(name: &str) -> Self {
+        let shrink = counter!(format!("foyer_memory_pressure_op_total"), "name" => name.to_string(), "op" => "shrink");
+        let restore =
+            counter!(format!("foyer_memory_pressure_op_total"), "name" => name.to_string(), "op" => "restore");
+
+        let effective_capacity = gauge!(format!("foyer_memory_effective_capacity"), "name" => name.to_string());
+
+        Self {
+            shrink,
+            restore,
+            effective_capacity,
+        }
+    }
+}
+
+/// Miss ratio curve metrics of a cache tier.
+#[derive(Clone)]
+pub struct MissRatioCurveMetrics {
+    /// ... ...
+    pub sample: Counter,
+
+    /// The estimated miss ratio of each capacity point.
+    pub miss_ratio: Vec<Gauge>,
+    /// ... ...
+    pub sample_rate: Gauge,
+}
+
+impl Debug for MissRatioCurveMetrics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MissRatioCurveMetrics").finish()
+    }
+}
+
+impl MissRatioCurveMetrics {
+    /// Create a new miss ratio curve metrics with the given name, tier and capacity points.
+    ///
+    /// The tier is the prefix of the metric names after `foyer_`, which is either `memory` or `storage`.
+    pub fn new(name: &str, tier: &'static str, capacities: &[usize]) -> Self {
+        let sample = counter!(format!("foyer_{tier}_mrc_sample_total"), "name" => name.to_string());
+
+        let miss_ratio = capacities
+            .iter()
+            .map(|capacity| {
+                gauge!(
+                    format!("foyer_{tier}_mrc_miss_ratio"),
+                    "name" => name.to_string(), "capacity" => capacity.to_string()
+                )
+            })
+            .collect();
+        let sample_rate = gauge!(format!("foyer_{tier}_mrc_sample_rate"), "name" => name.to_string());
+
+        Self {
+            sample,
+            miss_ratio,
+            sample_rate,
+        }
+    }
+}
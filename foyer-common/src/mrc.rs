@@ -0,0 +1,433 @@
+//  Copyright 2024 foyer Project Authors
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::{
+    collections::BTreeSet,
+    fmt::Debug,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use hashbrown::HashMap;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+use crate::metrics::MissRatioCurveMetrics;
+
+/// The modulus of the spatial sampling. A key is sampled if its spot is lower than the threshold.
+const SPOT_MODULUS: u64 = 1 << 24;
+
+/// Update the miss ratio metrics every the count of sampled references.
+const METRICS_INTERVAL: u64 = 256;
+
+/// Miss ratio curve tracker config.
+///
+/// The tracker estimates the miss ratio of an LRU cache of each given capacity with SHARDS (spatially hashed
+/// approximate reuse distance sampling). Only the keys whose hashes fall into the sampled space are tracked, and their
+/// reuse distances are scaled by the sampling rate.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MissRatioCurveConfig {
+    /// The capacities to estimate the miss ratio for.
+    ///
+    /// The unit is the same as the capacity of the tracked tier: the weight for the in-memory cache, and bytes for the
+    /// disk cache.
+    ///
+    /// Must not be empty.
+    pub capacities: Vec<usize>,
+
+    /// The initial sampling rate of the keys.
+    ///
+    /// The sampling rate is lowered automatically if the sampled keys exceed `max_samples`.
+    ///
+    /// Must be in (0, 1].
+    pub sample_rate: f64,
+
+    /// The max count of the tracked sampled keys, which bounds the memory usage of the tracker.
+    ///
+    /// Must be positive.
+    pub max_samples: usize,
+}
+
+impl Default for MissRatioCurveConfig {
+    fn default() -> Self {
+        Self {
+            capacities: vec![],
+            sample_rate: 0.01,
+            max_samples: 8192,
+        }
+    }
+}
+
+/// The estimated miss ratio curve.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MissRatioCurve {
+    /// The count of the sampled references.
+    pub references: u64,
+    /// The current sampling rate.
+    pub sample_rate: f64,
+    /// The estimated miss ratio of each capacity, ordered by the capacity.
+    ///
+    /// The miss ratios are `0` before any sampled reference.
+    pub points: Vec<(usize, f64)>,
+}
+
+/// Fenwick tree of the weights of the sampled keys, indexed by the logical time of their last accesses.
+#[derive(Debug)]
+struct Fenwick {
+    tree: Vec<i64>,
+}
+
+impl Fenwick {
+    fn new(len: usize) -> Self {
+        Self { tree: vec![0; len + 1] }
+    }
+
+    fn len(&self) -> usize {
+        self.tree.len() - 1
+    }
+
+    fn add(&mut self, index: usize, delta: i64) {
+        let mut i = index + 1;
+        while i < self.tree.len() {
+            self.tree[i] += delta;
+            i += i & i.wrapping_neg();
+        }
+    }
+
+    /// Sum of `[0, index)`.
+    fn prefix(&self, index: usize) -> i64 {
+        let mut i = index;
+        let mut sum = 0;
+        while i > 0 {
+            sum += self.tree[i];
+            i -= i & i.wrapping_neg();
+        }
+        sum
+    }
+
+    fn clear(&mut self) {
+        self.tree.iter_mut().for_each(|v| *v = 0);
+    }
+}
+
+#[derive(Debug)]
+struct Sample {
+    time: usize,
+    weight: usize,
+}
+
+#[derive(Debug)]
+struct MissRatioCurveTrackerInner {
+    capacities: Vec<usize>,
+    max_samples: usize,
+
+    samples: HashMap<u64, Sample>,
+    /// The sampled keys ordered by the spot, to drop the highest ones when the sampling rate is lowered.
+    spots: BTreeSet<(u64, u64)>,
+    weights: Fenwick,
+    time: usize,
+
+    references: u64,
+    misses: Vec<u64>,
+}
+
+impl MissRatioCurveTrackerInner {
+    fn record(&mut self, hash: u64, spot: u64, weight: Option<usize>, reference: bool, threshold: &AtomicU64) {
+        if reference {
+            self.references += 1;
+        }
+
+        match self.samples.get_mut(&hash) {
+            Some(sample) => {
+                let weight = weight.unwrap_or(sample.weight);
+                if reference {
+                    // The reuse distance is the total weight of the distinct keys accessed since the last access of
+                    // the key, including itself.
+                    let rate = threshold.load(Ordering::Relaxed) as f64 / SPOT_MODULUS as f64;
+                    let distance = self.weights.prefix(self.time) - self.weights.prefix(sample.time + 1);
+                    let distance = (distance as usize + weight) as f64 / rate;
+                    for (capacity, misses) in self.capacities.iter().zip(self.misses.iter_mut()) {
+                        if distance > *capacity as f64 {
+                            *misses += 1;
+                        }
+                    }
+                }
+                self.weights.add(sample.time, -(sample.weight as i64));
+                self.weights.add(self.time, weight as i64);
+                sample.time = self.time;
+                sample.weight = weight;
+            }
+            None => {
+                // Cold miss.
+                if reference {
+                    self.misses.iter_mut().for_each(|misses| *misses += 1);
+                }
+                let weight = weight.unwrap_or_default();
+                self.samples.insert(
+                    hash,
+                    Sample {
+                        time: self.time,
+                        weight,
+                    },
+                );
+                self.spots.insert((spot, hash));
+                self.weights.add(self.time, weight as i64);
+
+                if self.samples.len() > self.max_samples {
+                    self.lower(threshold);
+                }
+            }
+        }
+
+        self.time += 1;
+        if self.time == self.weights.len() {
+            self.compact();
+        }
+    }
+
+    /// Lower the sampling threshold to the highest spot, and drop the keys that are no longer sampled.
+    fn lower(&mut self, threshold: &AtomicU64) {
+        let Some(&(highest, _)) = self.spots.last() else {
+            return;
+        };
+        threshold.store(highest, Ordering::Relaxed);
+        while let Some(&(spot, hash)) = self.spots.last() {
+            if spot < highest {
+                break;
+            }
+            self.spots.pop_last();
+            let sample = self.samples.remove(&hash).unwrap();
+            self.weights.add(sample.time, -(sample.weight as i64));
+        }
+    }
+
+    /// Renumber the logical time of the sampled keys from 0 to reuse the fenwick tree.
+    fn compact(&mut self) {
+        let mut samples = self.samples.values_mut().collect::<Vec<_>>();
+        samples.sort_by_key(|sample| sample.time);
+
+        self.weights.clear();
+        for (time, sample) in samples.into_iter().enumerate() {
+            sample.time = time;
+            self.weights.add(time, sample.weight as i64);
+        }
+        self.time = self.samples.len();
+    }
+
+    fn curve(&self, threshold: u64) -> MissRatioCurve {
+        let points = self
+            .capacities
+            .iter()
+            .zip(self.misses.iter())
+            .map(|(capacity, misses)| {
+                let ratio = match self.references {
+                    0 => 0.0,
+                    references => *misses as f64 / references as f64,
+                };
+                (*capacity, ratio)
+            })
+            .collect();
+        MissRatioCurve {
+            references: self.references,
+            sample_rate: threshold as f64 / SPOT_MODULUS as f64,
+            points,
+        }
+    }
+}
+
+/// Miss ratio curve tracker of a cache tier.
+///
+/// See [`MissRatioCurveConfig`] for details.
+///
+/// The cache reports the references with [`MissRatioCurveTracker::access`], and the fills with
+/// [`MissRatioCurveTracker::insert`]. The keys that are not sampled are filtered out without locking.
+pub struct MissRatioCurveTracker {
+    threshold: AtomicU64,
+    inner: Mutex<MissRatioCurveTrackerInner>,
+    metrics: MissRatioCurveMetrics,
+}
+
+impl Debug for MissRatioCurveTracker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MissRatioCurveTracker")
+            .field("threshold", &self.threshold)
+            .finish()
+    }
+}
+
+impl MissRatioCurveTracker {
+    /// Create a new miss ratio curve tracker with the given name as the metrics label, and the given tier (`memory` or
+    /// `storage`) as the metrics prefix.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the config is invalid.
+    pub fn new(name: &str, tier: &'static str, config: &MissRatioCurveConfig) -> Self {
+        assert!(!config.capacities.is_empty(), "capacities must not be empty");
+        assert!(
+            config.sample_rate > 0.0 && config.sample_rate <= 1.0,
+            "sample_rate must be in (0, 1], given: {}",
+            config.sample_rate
+        );
+        assert!(config.max_samples > 0, "max_samples must be positive");
+
+        let mut capacities = config.capacities.clone();
+        capacities.sort_unstable();
+        capacities.dedup();
+
+        let metrics = MissRatioCurveMetrics::new(name, tier, &capacities);
+        let threshold = ((SPOT_MODULUS as f64 * config.sample_rate) as u64).max(1);
+        metrics.sample_rate.set(threshold as f64 / SPOT_MODULUS as f64);
+
+        let inner = MissRatioCurveTrackerInner {
+            misses: vec![0; capacities.len()],
+            capacities,
+            max_samples: config.max_samples,
+            samples: HashMap::default(),
+            spots: BTreeSet::default(),
+            weights: Fenwick::new((config.max_samples * 2).max(64)),
+            time: 0,
+            references: 0,
+        };
+
+        Self {
+            threshold: AtomicU64::new(threshold),
+            inner: Mutex::new(inner),
+            metrics,
+        }
+    }
+
+    fn spot(&self, hash: u64) -> Option<u64> {
+        // Mix the hash again, so the sampling is independent of the sharding by the same hash.
+        let mut z = hash.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        let spot = (z ^ (z >> 31)) % SPOT_MODULUS;
+        (spot < self.threshold.load(Ordering::Relaxed)).then_some(spot)
+    }
+
+    /// Record a reference to the key with the given hash, which is either a hit or a miss of the cache.
+    ///
+    /// The weight is unknown on a miss, and the weight recorded by the last access or insertion is used.
+    pub fn access(&self, hash: u64, weight: Option<usize>) {
+        let Some(spot) = self.spot(hash) else {
+            return;
+        };
+        let mut inner = self.inner.lock();
+        // The threshold may be lowered after the check without the lock.
+        if spot >= self.threshold.load(Ordering::Relaxed) {
+            return;
+        }
+        inner.record(hash, spot, weight, true, &self.threshold);
+        self.metrics.sample.increment(1);
+        if inner.references % METRICS_INTERVAL == 0 {
+            self.update_metrics(&inner);
+        }
+    }
+
+    /// Record an insertion of the key with the given hash and weight.
+    ///
+    /// An insertion refreshes the recency and the weight of the key, but is not counted as a reference.
+    pub fn insert(&self, hash: u64, weight: usize) {
+        let Some(spot) = self.spot(hash) else {
+            return;
+        };
+        let mut inner = self.inner.lock();
+        if spot >= self.threshold.load(Ordering::Relaxed) {
+            return;
+        }
+        inner.record(hash, spot, Some(weight), false, &self.threshold);
+    }
+
+    /// Get the estimated miss ratio curve.
+    pub fn curve(&self) -> MissRatioCurve {
+        let inner = self.inner.lock();
+        self.update_metrics(&inner);
+        inner.curve(self.threshold.load(Ordering::Relaxed))
+    }
+
+    fn update_metrics(&self, inner: &MissRatioCurveTrackerInner) {
+        let curve = inner.curve(self.threshold.load(Ordering::Relaxed));
+        for ((_, ratio), gauge) in curve.points.iter().zip(self.metrics.miss_ratio.iter()) {
+            gauge.set(*ratio);
+        }
+        self.metrics.sample_rate.set(curve.sample_rate);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracker(sample_rate: f64, max_samples: usize) -> MissRatioCurveTracker {
+        MissRatioCurveTracker::new(
+            "test",
+            "memory",
+            &MissRatioCurveConfig {
+                capacities: vec![500, 1000, 2000],
+                sample_rate,
+                max_samples,
+            },
+        )
+    }
+
+    fn hash(key: u64) -> u64 {
+        key.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    #[test]
+    fn test_mrc_loop() {
+        // Loop over 1000 keys with weight 1: an LRU cache misses everything if the capacity is lower than the loop,
+        // and hits everything except the cold misses otherwise.
+        let tracker = tracker(1.0, 4096);
+        for _ in 0..10 {
+            for key in 0..1000 {
+                tracker.access(hash(key), Some(1));
+            }
+        }
+        let curve = tracker.curve();
+        assert_eq!(curve.references, 10000);
+        assert_eq!(curve.points[0], (500, 1.0));
+        assert_eq!(curve.points[1], (1000, 0.1));
+        assert_eq!(curve.points[2], (2000, 0.1));
+    }
+
+    #[test]
+    fn test_mrc_weight() {
+        let tracker = tracker(1.0, 4096);
+        for _ in 0..10 {
+            for key in 0..500 {
+                // The weight is recorded by the insertion.
+                tracker.access(hash(key), None);
+                tracker.insert(hash(key), 2);
+            }
+        }
+        let curve = tracker.curve();
+        assert_eq!(curve.points[0], (500, 1.0));
+        assert_eq!(curve.points[1], (1000, 0.1));
+    }
+
+    #[test]
+    fn test_mrc_sampling() {
+        let tracker = tracker(0.5, 256);
+        for _ in 0..20 {
+            for key in 0..1000 {
+                tracker.access(hash(key), Some(1));
+            }
+        }
+        let curve = tracker.curve();
+        assert!(curve.sample_rate < 0.5);
+        assert!(curve.points[0].1 > 0.9, "{curve:?}");
+        assert!(curve.points[2].1 < 0.2, "{curve:?}");
+    }
+}
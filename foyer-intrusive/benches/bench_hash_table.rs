@@ -0,0 +1,164 @@
+//  Copyright 2024 foyer Project Authors
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+//! Compare the intrusive hash table with the hashbrown hash table that stores the pointers of the items, which is the
+//! layout of the hashbrown-based indexer of the in-memory cache.
+
+use std::{
+    hint::black_box,
+    ptr::NonNull,
+    time::{Duration, Instant},
+};
+
+use foyer_intrusive::{
+    hash_table::{HashTable, HashTableLink},
+    intrusive_adapter,
+};
+
+const ITEMS: u64 = 1 << 20;
+const LOOKUPS: u64 = 1 << 22;
+
+#[derive(Debug, Default)]
+struct Item {
+    link: HashTableLink,
+    key: u64,
+}
+
+intrusive_adapter! { ItemAdapter = Item { link: HashTableLink } }
+
+fn hash(key: u64) -> u64 {
+    // splitmix64
+    let mut z = key.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+fn items() -> Vec<NonNull<Item>> {
+    (0..ITEMS)
+        .map(|key| {
+            let item = Box::new(Item {
+                link: HashTableLink::default(),
+                key,
+            });
+            unsafe { NonNull::new_unchecked(Box::into_raw(item)) }
+        })
+        .collect()
+}
+
+fn free(items: Vec<NonNull<Item>>) {
+    for item in items {
+        let _ = unsafe { Box::from_raw(item.as_ptr()) };
+    }
+}
+
+struct Report {
+    name: &'static str,
+    insert: Vec<Duration>,
+    lookup: Duration,
+}
+
+impl Report {
+    fn print(mut self) {
+        self.insert.sort();
+        let percentile = |p: f64| self.insert[((self.insert.len() - 1) as f64 * p) as usize];
+        println!(
+            "{:<12} insert p50: {:>8?}, p99: {:>8?}, p99.99: {:>10?}, max: {:>10?} | lookup avg: {:>8?}",
+            self.name,
+            percentile(0.5),
+            percentile(0.99),
+            percentile(0.9999),
+            self.insert.last().unwrap(),
+            self.lookup
+        );
+    }
+}
+
+fn lookup(f: impl Fn(u64) -> Option<NonNull<Item>>) -> Duration {
+    let now = Instant::now();
+    for i in 0..LOOKUPS {
+        let key = hash(i) % ITEMS;
+        black_box(f(key));
+    }
+    now.elapsed() / LOOKUPS as u32
+}
+
+fn bench_intrusive() -> Report {
+    let items = items();
+    let mut table = HashTable::<ItemAdapter>::new();
+
+    let insert = items
+        .iter()
+        .map(|&ptr| {
+            let key = unsafe { ptr.as_ref().key };
+            let now = Instant::now();
+            table.insert(hash(key), ptr, |item| item.key == key);
+            now.elapsed()
+        })
+        .collect();
+    let lookup = lookup(|key| table.find(hash(key), |item| item.key == key));
+
+    table.drain().for_each(|_| {});
+    free(items);
+
+    Report {
+        name: "intrusive",
+        insert,
+        lookup,
+    }
+}
+
+fn bench_hashbrown() -> Report {
+    let items = items();
+    let mut table = hashbrown::HashTable::<NonNull<Item>>::new();
+
+    let insert = items
+        .iter()
+        .map(|&ptr| {
+            let key = unsafe { ptr.as_ref().key };
+            let now = Instant::now();
+            match table.entry(
+                hash(key),
+                |p| unsafe { p.as_ref().key } == key,
+                |p| hash(unsafe { p.as_ref().key }),
+            ) {
+                hashbrown::hash_table::Entry::Occupied(mut o) => {
+                    *o.get_mut() = ptr;
+                }
+                hashbrown::hash_table::Entry::Vacant(v) => {
+                    v.insert(ptr);
+                }
+            }
+            now.elapsed()
+        })
+        .collect();
+    let lookup = lookup(|key| table.find(hash(key), |p| unsafe { p.as_ref().key } == key).copied());
+
+    drop(table);
+    free(items);
+
+    Report {
+        name: "hashbrown",
+        insert,
+        lookup,
+    }
+}
+
+fn main() {
+    println!("items: {ITEMS}, lookups: {LOOKUPS}");
+    println!();
+
+    bench_intrusive().print();
+    bench_hashbrown().print();
+}
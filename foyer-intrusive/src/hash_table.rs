@@ -0,0 +1,531 @@
+//  Copyright 2024 foyer Project Authors
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+//! An intrusive chained hash table implementation with incremental resizing.
+//!
+//! The items in the same bucket are linked through the link in the items, so the hash table takes no extra allocation
+//! per item.
+//!
+//! When the item count exceeds the bucket count, the hash table allocates a bucket array twice as large, and migrates
+//! the buckets from the old bucket array a few at a time on each following insertion and removal, instead of
+//! rehashing all items at once.
+
+use std::ptr::NonNull;
+
+use foyer_common::strict_assert;
+
+use crate::adapter::{Adapter, Link};
+
+/// The initial bucket count of the intrusive hash table.
+const INITIAL_BUCKETS: usize = 16;
+
+/// The count of the buckets to migrate on each insertion and removal while resizing.
+///
+/// With a load factor of 1 and a growth factor of 2, migrating at least 1 bucket per insertion guarantees the
+/// migration finishes before the next resize.
+const MIGRATE_BUCKETS: usize = 4;
+
+type Bucket = Option<NonNull<HashTableLink>>;
+
+/// The link for the intrusive hash table.
+#[derive(Debug, Default)]
+pub struct HashTableLink {
+    next: Option<NonNull<HashTableLink>>,
+    hash: u64,
+    is_linked: bool,
+}
+
+impl HashTableLink {
+    /// Get the hash of the linked item.
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+}
+
+unsafe impl Send for HashTableLink {}
+unsafe impl Sync for HashTableLink {}
+
+impl Link for HashTableLink {
+    fn is_linked(&self) -> bool {
+        self.is_linked
+    }
+}
+
+fn buckets(len: usize) -> Box<[Bucket]> {
+    vec![None; len].into_boxed_slice()
+}
+
+#[inline(always)]
+fn index(hash: u64, buckets: usize) -> usize {
+    strict_assert!(buckets.is_power_of_two());
+    hash as usize & (buckets - 1)
+}
+
+/// Intrusive chained hash table with incremental resizing.
+#[derive(Debug)]
+pub struct HashTable<A>
+where
+    A: Adapter<Link = HashTableLink>,
+{
+    /// The bucket array that the new items are inserted into.
+    table: Box<[Bucket]>,
+    /// The old bucket array that is being migrated to `table`.
+    old: Option<Box<[Bucket]>>,
+    /// The buckets of `old` before the cursor are migrated.
+    cursor: usize,
+
+    len: usize,
+
+    adapter: A,
+}
+
+unsafe impl<A> Send for HashTable<A> where A: Adapter<Link = HashTableLink> {}
+unsafe impl<A> Sync for HashTable<A> where A: Adapter<Link = HashTableLink> {}
+
+impl<A> Drop for HashTable<A>
+where
+    A: Adapter<Link = HashTableLink>,
+{
+    fn drop(&mut self) {
+        self.drain().for_each(|_| {});
+        assert!(self.is_empty());
+    }
+}
+
+impl<A> HashTable<A>
+where
+    A: Adapter<Link = HashTableLink>,
+{
+    /// Create a new intrusive hash table. No bucket array is allocated until the first insertion.
+    pub fn new() -> Self {
+        Self::with_adapter(A::new())
+    }
+
+    /// Create a new intrusive hash table with the given adapter.
+    pub fn with_adapter(adapter: A) -> Self {
+        Self {
+            table: buckets(0),
+            old: None,
+            cursor: 0,
+            len: 0,
+            adapter,
+        }
+    }
+
+    /// Insert an item with the given hash.
+    ///
+    /// If there is an item with the same hash that `eq` returns `true`, it is replaced and returned.
+    pub fn insert(
+        &mut self,
+        hash: u64,
+        ptr: NonNull<A::Item>,
+        eq: impl Fn(&A::Item) -> bool,
+    ) -> Option<NonNull<A::Item>> {
+        let old = self.remove_inner(hash, &eq);
+
+        if self.len >= self.table.len() {
+            self.grow();
+        }
+
+        unsafe {
+            let mut link = self.adapter.ptr2link(ptr);
+            strict_assert!(!link.as_ref().is_linked);
+            let bucket = &mut self.table[index(hash, self.table.len())];
+            link.as_mut().next = *bucket;
+            link.as_mut().hash = hash;
+            link.as_mut().is_linked = true;
+            *bucket = Some(link);
+        }
+        self.len += 1;
+
+        self.migrate(MIGRATE_BUCKETS);
+
+        old
+    }
+
+    /// Find the item with the given hash that `eq` returns `true`.
+    pub fn find(&self, hash: u64, eq: impl Fn(&A::Item) -> bool) -> Option<NonNull<A::Item>> {
+        unsafe {
+            if !self.table.is_empty() {
+                if let Some(link) = self.find_in(self.table[index(hash, self.table.len())], hash, &eq) {
+                    return Some(self.adapter.link2ptr(link));
+                }
+            }
+            if let Some(old) = self.old.as_ref() {
+                let i = index(hash, old.len());
+                if i >= self.cursor {
+                    if let Some(link) = self.find_in(old[i], hash, &eq) {
+                        return Some(self.adapter.link2ptr(link));
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Remove the item with the given hash that `eq` returns `true`.
+    pub fn remove(&mut self, hash: u64, eq: impl Fn(&A::Item) -> bool) -> Option<NonNull<A::Item>> {
+        let res = self.remove_inner(hash, &eq);
+        self.migrate(MIGRATE_BUCKETS);
+        res
+    }
+
+    /// Remove all items from the intrusive hash table and return them with an iterator.
+    ///
+    /// The bucket arrays are released.
+    pub fn drain(&mut self) -> HashTableDrain<'_, A> {
+        let mut tables = vec![std::mem::replace(&mut self.table, buckets(0))];
+        if let Some(old) = self.old.take() {
+            tables.push(old);
+        }
+        self.cursor = 0;
+        HashTableDrain {
+            tables,
+            table: 0,
+            bucket: 0,
+            link: None,
+            hash_table: self,
+        }
+    }
+
+    /// Get the iterator of the items of the intrusive hash table in arbitrary order.
+    pub fn iter(&self) -> HashTableIter<'_, A> {
+        HashTableIter {
+            hash_table: self,
+            table: 0,
+            bucket: 0,
+            link: None,
+        }
+    }
+
+    /// Get the count of the items of the intrusive hash table.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return `true` if the intrusive hash table is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get the bucket count of the intrusive hash table.
+    pub fn buckets(&self) -> usize {
+        self.table.len()
+    }
+
+    /// Return `true` if the intrusive hash table is migrating the items from the old bucket array.
+    pub fn is_resizing(&self) -> bool {
+        self.old.is_some()
+    }
+
+    /// Get the adapter of the intrusive hash table.
+    pub fn adapter(&self) -> &A {
+        &self.adapter
+    }
+
+    unsafe fn find_in(&self, mut cursor: Bucket, hash: u64, eq: &impl Fn(&A::Item) -> bool) -> Bucket {
+        while let Some(link) = cursor {
+            if link.as_ref().hash == hash && eq(self.adapter.link2ptr(link).as_ref()) {
+                return Some(link);
+            }
+            cursor = link.as_ref().next;
+        }
+        None
+    }
+
+    fn remove_inner(&mut self, hash: u64, eq: &impl Fn(&A::Item) -> bool) -> Option<NonNull<A::Item>> {
+        let adapter = &self.adapter;
+        let mut link = None;
+        if !self.table.is_empty() {
+            let i = index(hash, self.table.len());
+            link = unsafe { Self::unlink_from(adapter, &mut self.table[i], hash, eq) };
+        }
+        if link.is_none() {
+            if let Some(old) = self.old.as_mut() {
+                let i = index(hash, old.len());
+                if i >= self.cursor {
+                    link = unsafe { Self::unlink_from(adapter, &mut old[i], hash, eq) };
+                }
+            }
+        }
+        link.map(|link| {
+            self.len -= 1;
+            unsafe { self.adapter.link2ptr(link) }
+        })
+    }
+
+    unsafe fn unlink_from(adapter: &A, mut slot: &mut Bucket, hash: u64, eq: &impl Fn(&A::Item) -> bool) -> Bucket {
+        while let Some(mut link) = *slot {
+            if link.as_ref().hash == hash && eq(adapter.link2ptr(link).as_ref()) {
+                *slot = link.as_mut().next.take();
+                link.as_mut().is_linked = false;
+                return Some(link);
+            }
+            slot = &mut link.as_mut().next;
+        }
+        None
+    }
+
+    fn grow(&mut self) {
+        // Finish the unfinished migration first. It doesn't happen unless `MIGRATE_BUCKETS` is 0.
+        self.migrate(usize::MAX);
+
+        let len = (self.table.len() * 2).max(INITIAL_BUCKETS);
+        let old = std::mem::replace(&mut self.table, buckets(len));
+        if !old.is_empty() {
+            self.old = Some(old);
+            self.cursor = 0;
+        }
+    }
+
+    /// Migrate at most `count` buckets from the old bucket array.
+    fn migrate(&mut self, count: usize) {
+        let Some(old) = self.old.as_mut() else {
+            return;
+        };
+
+        let end = old.len().min(self.cursor.saturating_add(count));
+        for bucket in old[self.cursor..end].iter_mut() {
+            let mut cursor = bucket.take();
+            while let Some(mut link) = cursor {
+                unsafe {
+                    cursor = link.as_mut().next;
+                    let i = index(link.as_ref().hash, self.table.len());
+                    link.as_mut().next = self.table[i];
+                    self.table[i] = Some(link);
+                }
+            }
+        }
+        self.cursor = end;
+
+        if self.cursor == old.len() {
+            self.old = None;
+            self.cursor = 0;
+        }
+    }
+}
+
+/// Immutable iterator for the intrusive hash table.
+pub struct HashTableIter<'a, A>
+where
+    A: Adapter<Link = HashTableLink>,
+{
+    hash_table: &'a HashTable<A>,
+    /// 0 for the current bucket array, 1 for the old one.
+    table: usize,
+    bucket: usize,
+    link: Bucket,
+}
+
+impl<'a, A> Iterator for HashTableIter<'a, A>
+where
+    A: Adapter<Link = HashTableLink>,
+{
+    type Item = &'a A::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(link) = self.link {
+                unsafe {
+                    self.link = link.as_ref().next;
+                    return Some(self.hash_table.adapter.link2ptr(link).as_ref());
+                }
+            }
+            let table = match self.table {
+                0 => &self.hash_table.table,
+                1 => self.hash_table.old.as_ref()?,
+                _ => return None,
+            };
+            if self.table == 1 && self.bucket < self.hash_table.cursor {
+                self.bucket = self.hash_table.cursor;
+            }
+            if self.bucket < table.len() {
+                self.link = table[self.bucket];
+                self.bucket += 1;
+            } else {
+                self.table += 1;
+                self.bucket = 0;
+            }
+        }
+    }
+}
+
+/// Draining iterator for the intrusive hash table.
+///
+/// All items are unlinked even if the iterator is dropped before exhausted.
+pub struct HashTableDrain<'a, A>
+where
+    A: Adapter<Link = HashTableLink>,
+{
+    hash_table: &'a mut HashTable<A>,
+    tables: Vec<Box<[Bucket]>>,
+    table: usize,
+    bucket: usize,
+    link: Bucket,
+}
+
+impl<A> Iterator for HashTableDrain<'_, A>
+where
+    A: Adapter<Link = HashTableLink>,
+{
+    type Item = NonNull<A::Item>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(mut link) = self.link {
+                unsafe {
+                    self.link = link.as_mut().next.take();
+                    link.as_mut().is_linked = false;
+                    self.hash_table.len -= 1;
+                    return Some(self.hash_table.adapter.link2ptr(link));
+                }
+            }
+            let table = self.tables.get_mut(self.table)?;
+            if self.bucket < table.len() {
+                self.link = table[self.bucket].take();
+                self.bucket += 1;
+            } else {
+                self.table += 1;
+                self.bucket = 0;
+            }
+        }
+    }
+}
+
+impl<A> Drop for HashTableDrain<'_, A>
+where
+    A: Adapter<Link = HashTableLink>,
+{
+    fn drop(&mut self) {
+        self.for_each(|_| {});
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use itertools::Itertools;
+
+    use super::*;
+    use crate::intrusive_adapter;
+
+    #[derive(Debug, Default)]
+    struct HashTableItem {
+        link: HashTableLink,
+        key: u64,
+    }
+
+    intrusive_adapter! { HashTableItemAdapter = HashTableItem { link: HashTableLink } }
+
+    fn hash(key: u64) -> u64 {
+        // Keep the collisions in the low bits to cover the chains.
+        key.wrapping_mul(0x9e37_79b9_7f4a_7c15) & !0xf
+    }
+
+    fn item(key: u64) -> NonNull<HashTableItem> {
+        let item = Box::new(HashTableItem {
+            link: HashTableLink::default(),
+            key,
+        });
+        unsafe { NonNull::new_unchecked(Box::into_raw(item)) }
+    }
+
+    fn free(ptr: NonNull<HashTableItem>) -> u64 {
+        let item = unsafe { Box::from_raw(ptr.as_ptr()) };
+        assert!(!item.link.is_linked());
+        item.key
+    }
+
+    fn find(table: &HashTable<HashTableItemAdapter>, key: u64) -> Option<u64> {
+        table
+            .find(hash(key), |item| item.key == key)
+            .map(|ptr| unsafe { ptr.as_ref().key })
+    }
+
+    #[test]
+    fn test_hash_table_simple() {
+        let mut table = HashTable::<HashTableItemAdapter>::new();
+        assert_eq!(table.buckets(), 0);
+        assert_eq!(find(&table, 1), None);
+
+        for key in 0..1000 {
+            assert!(table.insert(hash(key), item(key), |item| item.key == key).is_none());
+            // Every key inserted so far can be found while resizing.
+            assert_eq!(find(&table, key / 2), Some(key / 2));
+        }
+        assert_eq!(table.len(), 1000);
+        assert!(table.buckets() >= 1000);
+        assert_eq!(
+            table.iter().map(|item| item.key).sorted().collect_vec(),
+            (0..1000).collect_vec()
+        );
+
+        // Replace.
+        let old = table.insert(hash(42), item(42), |item| item.key == 42).unwrap();
+        assert_eq!(free(old), 42);
+        assert_eq!(table.len(), 1000);
+
+        for key in (0..1000).step_by(2) {
+            assert_eq!(free(table.remove(hash(key), |item| item.key == key).unwrap()), key);
+            assert!(table.remove(hash(key), |item| item.key == key).is_none());
+        }
+        assert_eq!(table.len(), 500);
+        for key in 0..1000 {
+            assert_eq!(find(&table, key), (key % 2 == 1).then_some(key));
+        }
+
+        let drained = table.drain().map(free).collect::<HashSet<_>>();
+        assert_eq!(drained, (0..1000).filter(|key| key % 2 == 1).collect());
+        assert!(table.is_empty());
+        assert_eq!(table.buckets(), 0);
+    }
+
+    #[test]
+    fn test_hash_table_incremental_resize() {
+        let mut table = HashTable::<HashTableItemAdapter>::new();
+        let mut ptrs = vec![];
+        let mut insert = |table: &mut HashTable<HashTableItemAdapter>, key: u64| {
+            let ptr = item(key);
+            ptrs.push(ptr);
+            table.insert(hash(key), ptr, |item| item.key == key);
+        };
+
+        for key in 0..(INITIAL_BUCKETS as u64 * 4 + 1) {
+            insert(&mut table, key);
+        }
+        // The resize from 64 to 128 buckets has just started.
+        assert_eq!(table.buckets(), INITIAL_BUCKETS * 8);
+        assert!(table.is_resizing());
+        assert_eq!(table.iter().count(), table.len());
+        for key in 0..(INITIAL_BUCKETS as u64 * 4 + 1) {
+            assert_eq!(find(&table, key), Some(key));
+        }
+
+        let mut key = INITIAL_BUCKETS as u64 * 4 + 1;
+        while table.is_resizing() {
+            insert(&mut table, key);
+            key += 1;
+        }
+        assert_eq!(table.buckets(), INITIAL_BUCKETS * 8);
+        assert_eq!(table.len() as u64, key);
+
+        // All items are unlinked even if the drain iterator is dropped before exhausted.
+        let mut drain = table.drain();
+        drain.next().unwrap();
+        drop(drain);
+        assert!(table.is_empty());
+        assert_eq!(ptrs.into_iter().map(free).count() as u64, key);
+    }
+}
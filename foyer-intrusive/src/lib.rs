@@ -46,3 +46,4 @@ macro_rules! container_of {
 
 pub mod adapter;
 pub mod dlist;
+pub mod hash_table;
@@ -86,7 +86,7 @@ fn moka_cache_hit(cache: &moka::sync::Cache<CacheKey, CacheValue>, keys: &[Strin
 fn new_fifo_cache(capacity: usize) -> Cache<CacheKey, CacheValue> {
     CacheBuilder::new(capacity)
         .with_shards(SHARDS)
-        .with_eviction_config(FifoConfig {})
+        .with_eviction_config(FifoConfig::default())
         .with_object_pool_capacity(OBJECT_POOL_CAPACITY)
         .build()
 }
@@ -96,6 +96,7 @@ fn new_lru_cache(capacity: usize) -> Cache<CacheKey, CacheValue> {
         .with_shards(SHARDS)
         .with_eviction_config(LruConfig {
             high_priority_pool_ratio: 0.1,
+            ..Default::default()
         })
         .with_object_pool_capacity(OBJECT_POOL_CAPACITY)
         .build()
@@ -109,6 +110,7 @@ fn new_lfu_cache(capacity: usize) -> Cache<CacheKey, CacheValue> {
             protected_capacity_ratio: 0.8,
             cmsketch_eps: 0.001,
             cmsketch_confidence: 0.9,
+            ..Default::default()
         })
         .with_object_pool_capacity(OBJECT_POOL_CAPACITY)
         .build()
@@ -121,6 +123,7 @@ fn new_s3fifo_cache_wo_ghost(capacity: usize) -> Cache<CacheKey, CacheValue> {
             small_queue_capacity_ratio: 0.1,
             ghost_queue_capacity_ratio: 0.0,
             small_to_main_freq_threshold: 2,
+            ..Default::default()
         })
         .with_object_pool_capacity(OBJECT_POOL_CAPACITY)
         .build()
@@ -133,6 +136,7 @@ fn new_s3fifo_cache_w_ghost(capacity: usize) -> Cache<CacheKey, CacheValue> {
             small_queue_capacity_ratio: 0.1,
             ghost_queue_capacity_ratio: 1.0,
             small_to_main_freq_threshold: 2,
+            ..Default::default()
         })
         .with_object_pool_capacity(OBJECT_POOL_CAPACITY)
         .build()
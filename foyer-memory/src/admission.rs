@@ -0,0 +1,205 @@
+//  Copyright 2024 foyer Project Authors
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use cmsketch::CMSketchU16;
+use serde::{Deserialize, Serialize};
+
+/// Admission filter config of the in-memory cache.
+///
+/// The admission filter decides whether an inserted entry is kept by the cache. A rejected entry is still returned by
+/// `insert` so the caller can use it (e.g. the hybrid cache writes it to the disk cache), but it is never indexed,
+/// never takes part in eviction, and its weight is not counted into the usage. It is released as soon as the last
+/// holder drops it. If the cache holds an older entry with the same key, the older entry is removed, so the stale value
+/// is never served.
+///
+/// The filter only applies to `insert`. Entries inserted by `deposit` are always admitted.
+///
+/// All filters are disabled by default.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct AdmissionConfig {
+    /// Reject the entries whose weight exceeds the given weight.
+    pub max_entry_weight: Option<usize>,
+
+    /// Reject the entries whose weight exceeds the given ratio of the capacity of the shard it belongs to.
+    ///
+    /// Must be in (0, 1].
+    pub max_shard_capacity_ratio: Option<f64>,
+
+    /// Reject the entries that would evict a more frequently accessed entry, with a TinyLFU doorkeeper.
+    ///
+    /// The doorkeeper records the accesses of the keys with a count-min sketch. When an inserted entry needs to evict
+    /// others, its estimated frequency is compared with the next victim of the eviction container, and it is admitted
+    /// only if it is accessed more frequently. The comparison is skipped if the eviction algorithm cannot tell the next
+    /// victim (see [`Eviction::peek`](crate::Eviction::peek)).
+    pub doorkeeper: Option<DoorkeeperConfig>,
+}
+
+/// TinyLFU doorkeeper config of the admission filter.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DoorkeeperConfig {
+    /// Error of the count-min sketch.
+    ///
+    /// See [`CMSketchU16::new`].
+    pub cmsketch_eps: f64,
+
+    /// Confidence of the count-min sketch.
+    ///
+    /// See [`CMSketchU16::new`].
+    pub cmsketch_confidence: f64,
+}
+
+impl Default for DoorkeeperConfig {
+    fn default() -> Self {
+        Self {
+            cmsketch_eps: 0.001,
+            cmsketch_confidence: 0.9,
+        }
+    }
+}
+
+struct Doorkeeper {
+    frequencies: CMSketchU16,
+    step: usize,
+    decay: usize,
+}
+
+/// The per-shard admission filter.
+pub(crate) struct AdmissionFilter {
+    max_entry_weight: Option<usize>,
+    max_shard_capacity_ratio: Option<f64>,
+    doorkeeper: Option<Doorkeeper>,
+}
+
+impl AdmissionFilter {
+    /// Create a new admission filter.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the config is invalid.
+    pub fn new(config: &AdmissionConfig) -> Self {
+        if let Some(ratio) = config.max_shard_capacity_ratio {
+            assert!(
+                ratio > 0.0 && ratio <= 1.0,
+                "max_shard_capacity_ratio must be in (0, 1], given: {ratio}"
+            );
+        }
+
+        let doorkeeper = config.doorkeeper.as_ref().map(|config| {
+            let frequencies = CMSketchU16::new(config.cmsketch_eps, config.cmsketch_confidence);
+            let decay = frequencies.width();
+            Doorkeeper {
+                frequencies,
+                step: 0,
+                decay,
+            }
+        });
+
+        Self {
+            max_entry_weight: config.max_entry_weight,
+            max_shard_capacity_ratio: config.max_shard_capacity_ratio,
+            doorkeeper,
+        }
+    }
+
+    /// Record an access of the key hash for the doorkeeper.
+    pub fn record(&mut self, hash: u64) {
+        if let Some(doorkeeper) = self.doorkeeper.as_mut() {
+            doorkeeper.frequencies.inc(hash);
+            doorkeeper.step += 1;
+            if doorkeeper.step >= doorkeeper.decay {
+                doorkeeper.step >>= 1;
+                doorkeeper.frequencies.halve();
+            }
+        }
+    }
+
+    /// Return `true` if an entry with the given weight is allowed by the size limits of a shard with the given
+    /// capacity.
+    pub fn admit_weight(&self, weight: usize, capacity: usize) -> bool {
+        if let Some(max) = self.max_entry_weight {
+            if weight > max {
+                return false;
+            }
+        }
+        if let Some(ratio) = self.max_shard_capacity_ratio {
+            if weight as f64 > capacity as f64 * ratio {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Return `true` if the candidate is accessed more frequently than the victim, or the doorkeeper is disabled.
+    pub fn admit_frequency(&self, candidate: u64, victim: u64) -> bool {
+        match self.doorkeeper.as_ref() {
+            Some(doorkeeper) => {
+                candidate == victim
+                    || doorkeeper.frequencies.estimate(candidate) > doorkeeper.frequencies.estimate(victim)
+            }
+            None => true,
+        }
+    }
+
+    /// Return `true` if the doorkeeper is enabled.
+    pub fn has_doorkeeper(&self) -> bool {
+        self.doorkeeper.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_admission_filter_weight() {
+        let filter = AdmissionFilter::new(&AdmissionConfig {
+            max_entry_weight: Some(100),
+            max_shard_capacity_ratio: Some(0.5),
+            doorkeeper: None,
+        });
+
+        assert!(filter.admit_weight(100, 1000));
+        assert!(!filter.admit_weight(101, 1000));
+        assert!(filter.admit_weight(50, 100));
+        assert!(!filter.admit_weight(51, 100));
+        assert!(filter.admit_frequency(1, 2));
+    }
+
+    #[test]
+    fn test_admission_filter_doorkeeper() {
+        let mut filter = AdmissionFilter::new(&AdmissionConfig {
+            doorkeeper: Some(DoorkeeperConfig::default()),
+            ..Default::default()
+        });
+
+        for _ in 0..4 {
+            filter.record(1);
+        }
+        filter.record(2);
+
+        assert!(filter.admit_frequency(1, 2));
+        assert!(!filter.admit_frequency(2, 1));
+        assert!(!filter.admit_frequency(3, 2));
+        assert!(filter.admit_frequency(3, 3));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_admission_filter_invalid_ratio() {
+        AdmissionFilter::new(&AdmissionConfig {
+            max_shard_capacity_ratio: Some(1.5),
+            ..Default::default()
+        });
+    }
+}
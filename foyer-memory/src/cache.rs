@@ -19,6 +19,7 @@ use foyer_common::{
     code::{HashBuilder, Key, Value},
     event::EventListener,
     future::Diversion,
+    mrc::{MissRatioCurve, MissRatioCurveConfig},
     runtime::SingletonHandle,
 };
 use futures::Future;
@@ -27,51 +28,51 @@ use serde::{Deserialize, Serialize};
 use tokio::sync::oneshot;
 
 use crate::{
+    admission::AdmissionConfig,
     context::CacheContext,
+    custom::{self, CustomCache, CustomCacheConfig, CustomCacheEntry, CustomCacheFactory, CustomFetch},
     eviction::{
         fifo::{Fifo, FifoHandle},
         lfu::{Lfu, LfuHandle},
         lru::{Lru, LruHandle},
         s3fifo::{S3Fifo, S3FifoHandle},
         sanity::SanityEviction,
+        Eviction,
     },
     generic::{FetchMark, FetchState, GenericCache, GenericCacheConfig, GenericCacheEntry, GenericFetch, Weighter},
-    indexer::{hash_table::HashTableIndexer, sanity::SanityIndexer},
+    handle::Handle,
+    indexer::{sanity::SanityIndexer, DefaultIndexer},
+    metadata::EntryMetadata,
     FifoConfig, LfuConfig, LruConfig, S3FifoConfig,
 };
 
 pub type FifoCache<K, V, S = RandomState> =
-    GenericCache<K, V, SanityEviction<Fifo<(K, V)>>, SanityIndexer<HashTableIndexer<K, FifoHandle<(K, V)>>>, S>;
+    GenericCache<K, V, SanityEviction<Fifo<(K, V)>>, SanityIndexer<DefaultIndexer<K, FifoHandle<(K, V)>>>, S>;
 pub type FifoCacheEntry<K, V, S = RandomState> =
-    GenericCacheEntry<K, V, SanityEviction<Fifo<(K, V)>>, SanityIndexer<HashTableIndexer<K, FifoHandle<(K, V)>>>, S>;
+    GenericCacheEntry<K, V, SanityEviction<Fifo<(K, V)>>, SanityIndexer<DefaultIndexer<K, FifoHandle<(K, V)>>>, S>;
 pub type FifoFetch<K, V, ER, S = RandomState> =
-    GenericFetch<K, V, SanityEviction<Fifo<(K, V)>>, SanityIndexer<HashTableIndexer<K, FifoHandle<(K, V)>>>, S, ER>;
+    GenericFetch<K, V, SanityEviction<Fifo<(K, V)>>, SanityIndexer<DefaultIndexer<K, FifoHandle<(K, V)>>>, S, ER>;
 
 pub type LruCache<K, V, S = RandomState> =
-    GenericCache<K, V, SanityEviction<Lru<(K, V)>>, SanityIndexer<HashTableIndexer<K, LruHandle<(K, V)>>>, S>;
+    GenericCache<K, V, SanityEviction<Lru<(K, V)>>, SanityIndexer<DefaultIndexer<K, LruHandle<(K, V)>>>, S>;
 pub type LruCacheEntry<K, V, S = RandomState> =
-    GenericCacheEntry<K, V, SanityEviction<Lru<(K, V)>>, SanityIndexer<HashTableIndexer<K, LruHandle<(K, V)>>>, S>;
+    GenericCacheEntry<K, V, SanityEviction<Lru<(K, V)>>, SanityIndexer<DefaultIndexer<K, LruHandle<(K, V)>>>, S>;
 pub type LruFetch<K, V, ER, S = RandomState> =
-    GenericFetch<K, V, SanityEviction<Lru<(K, V)>>, SanityIndexer<HashTableIndexer<K, LruHandle<(K, V)>>>, S, ER>;
+    GenericFetch<K, V, SanityEviction<Lru<(K, V)>>, SanityIndexer<DefaultIndexer<K, LruHandle<(K, V)>>>, S, ER>;
 
 pub type LfuCache<K, V, S = RandomState> =
-    GenericCache<K, V, SanityEviction<Lfu<(K, V)>>, SanityIndexer<HashTableIndexer<K, LfuHandle<(K, V)>>>, S>;
+    GenericCache<K, V, SanityEviction<Lfu<(K, V)>>, SanityIndexer<DefaultIndexer<K, LfuHandle<(K, V)>>>, S>;
 pub type LfuCacheEntry<K, V, S = RandomState> =
-    GenericCacheEntry<K, V, SanityEviction<Lfu<(K, V)>>, SanityIndexer<HashTableIndexer<K, LfuHandle<(K, V)>>>, S>;
+    GenericCacheEntry<K, V, SanityEviction<Lfu<(K, V)>>, SanityIndexer<DefaultIndexer<K, LfuHandle<(K, V)>>>, S>;
 pub type LfuFetch<K, V, ER, S = RandomState> =
-    GenericFetch<K, V, SanityEviction<Lfu<(K, V)>>, SanityIndexer<HashTableIndexer<K, LfuHandle<(K, V)>>>, S, ER>;
+    GenericFetch<K, V, SanityEviction<Lfu<(K, V)>>, SanityIndexer<DefaultIndexer<K, LfuHandle<(K, V)>>>, S, ER>;
 
 pub type S3FifoCache<K, V, S = RandomState> =
-    GenericCache<K, V, SanityEviction<S3Fifo<(K, V)>>, SanityIndexer<HashTableIndexer<K, S3FifoHandle<(K, V)>>>, S>;
-pub type S3FifoCacheEntry<K, V, S = RandomState> = GenericCacheEntry<
-    K,
-    V,
-    SanityEviction<S3Fifo<(K, V)>>,
-    SanityIndexer<HashTableIndexer<K, S3FifoHandle<(K, V)>>>,
-    S,
->;
+    GenericCache<K, V, SanityEviction<S3Fifo<(K, V)>>, SanityIndexer<DefaultIndexer<K, S3FifoHandle<(K, V)>>>, S>;
+pub type S3FifoCacheEntry<K, V, S = RandomState> =
+    GenericCacheEntry<K, V, SanityEviction<S3Fifo<(K, V)>>, SanityIndexer<DefaultIndexer<K, S3FifoHandle<(K, V)>>>, S>;
 pub type S3FifoFetch<K, V, ER, S = RandomState> =
-    GenericFetch<K, V, SanityEviction<S3Fifo<(K, V)>>, SanityIndexer<HashTableIndexer<K, S3FifoHandle<(K, V)>>>, S, ER>;
+    GenericFetch<K, V, SanityEviction<S3Fifo<(K, V)>>, SanityIndexer<DefaultIndexer<K, S3FifoHandle<(K, V)>>>, S, ER>;
 
 /// A cached entry holder of the in-memory cache.
 #[derive(Debug)]
@@ -89,6 +90,8 @@ where
     Lfu(LfuCacheEntry<K, V, S>),
     /// A cached entry holder of the in-memory S3FIFO cache.
     S3Fifo(S3FifoCacheEntry<K, V, S>),
+    /// A cached entry holder of the in-memory cache with a user-defined eviction algorithm.
+    Custom(CustomCacheEntry<K, V>),
 }
 
 impl<K, V, S> Clone for CacheEntry<K, V, S>
@@ -103,6 +106,7 @@ where
             Self::Lru(entry) => Self::Lru(entry.clone()),
             Self::Lfu(entry) => Self::Lfu(entry.clone()),
             Self::S3Fifo(entry) => Self::S3Fifo(entry.clone()),
+            Self::Custom(entry) => Self::Custom(entry.clone()),
         }
     }
 }
@@ -121,6 +125,7 @@ where
             CacheEntry::Lru(entry) => entry.deref(),
             CacheEntry::Lfu(entry) => entry.deref(),
             CacheEntry::S3Fifo(entry) => entry.deref(),
+            CacheEntry::Custom(entry) => entry.deref(),
         }
     }
 }
@@ -169,6 +174,17 @@ where
     }
 }
 
+impl<K, V, S> From<CustomCacheEntry<K, V>> for CacheEntry<K, V, S>
+where
+    K: Key,
+    V: Value,
+    S: HashBuilder,
+{
+    fn from(entry: CustomCacheEntry<K, V>) -> Self {
+        Self::Custom(entry)
+    }
+}
+
 impl<K, V, S> CacheEntry<K, V, S>
 where
     K: Key,
@@ -182,6 +198,7 @@ where
             CacheEntry::Lru(entry) => entry.hash(),
             CacheEntry::Lfu(entry) => entry.hash(),
             CacheEntry::S3Fifo(entry) => entry.hash(),
+            CacheEntry::Custom(entry) => entry.hash(),
         }
     }
 
@@ -192,6 +209,7 @@ where
             CacheEntry::Lru(entry) => entry.key(),
             CacheEntry::Lfu(entry) => entry.key(),
             CacheEntry::S3Fifo(entry) => entry.key(),
+            CacheEntry::Custom(entry) => entry.key(),
         }
     }
 
@@ -202,6 +220,7 @@ where
             CacheEntry::Lru(entry) => entry.value(),
             CacheEntry::Lfu(entry) => entry.value(),
             CacheEntry::S3Fifo(entry) => entry.value(),
+            CacheEntry::Custom(entry) => entry.value(),
         }
     }
 
@@ -212,6 +231,7 @@ where
             CacheEntry::Lru(entry) => entry.context().clone().into(),
             CacheEntry::Lfu(entry) => entry.context().clone().into(),
             CacheEntry::S3Fifo(entry) => entry.context().clone().into(),
+            CacheEntry::Custom(entry) => entry.context(),
         }
     }
 
@@ -222,6 +242,7 @@ where
             CacheEntry::Lru(entry) => entry.weight(),
             CacheEntry::Lfu(entry) => entry.weight(),
             CacheEntry::S3Fifo(entry) => entry.weight(),
+            CacheEntry::Custom(entry) => entry.weight(),
         }
     }
 
@@ -232,6 +253,7 @@ where
             CacheEntry::Lru(entry) => entry.refs(),
             CacheEntry::Lfu(entry) => entry.refs(),
             CacheEntry::S3Fifo(entry) => entry.refs(),
+            CacheEntry::Custom(entry) => entry.refs(),
         }
     }
 
@@ -242,6 +264,51 @@ where
             CacheEntry::Lru(entry) => entry.is_outdated(),
             CacheEntry::Lfu(entry) => entry.is_outdated(),
             CacheEntry::S3Fifo(entry) => entry.is_outdated(),
+            CacheEntry::Custom(entry) => entry.is_outdated(),
+        }
+    }
+
+    /// If the entry is rejected by the admission filter on insertion.
+    ///
+    /// A rejected entry is never cached, and is released after the last holder drops it.
+    pub fn is_rejected(&self) -> bool {
+        match self {
+            CacheEntry::Fifo(entry) => entry.is_rejected(),
+            CacheEntry::Lru(entry) => entry.is_rejected(),
+            CacheEntry::Lfu(entry) => entry.is_rejected(),
+            CacheEntry::S3Fifo(entry) => entry.is_rejected(),
+            CacheEntry::Custom(entry) => entry.is_rejected(),
+        }
+    }
+
+    /// Metadata of the cached entry, including the insertion time, the last access time and the hit count.
+    ///
+    /// Returns `None` if the metadata is disabled. See [`CacheBuilder::with_entry_metadata`].
+    pub fn metadata(&self) -> Option<EntryMetadata> {
+        match self {
+            CacheEntry::Fifo(entry) => entry.metadata(),
+            CacheEntry::Lru(entry) => entry.metadata(),
+            CacheEntry::Lfu(entry) => entry.metadata(),
+            CacheEntry::S3Fifo(entry) => entry.metadata(),
+            CacheEntry::Custom(entry) => entry.metadata(),
+        }
+    }
+
+    /// Recompute the weight of the cached entry with the weighter, e.g. after the value is mutated through interior
+    /// mutability. Returns the new weight.
+    ///
+    /// The weight is recomputed with the shard locked, and the usage of the in-memory cache is adjusted in place
+    /// without removing and reinserting the entry. Entries (maybe including this one) are evicted if the usage exceeds
+    /// the capacity after the weight grows.
+    ///
+    /// The weighter must NOT access the cache, or it may deadlock.
+    pub fn reweigh(&self) -> usize {
+        match self {
+            CacheEntry::Fifo(entry) => entry.reweigh(),
+            CacheEntry::Lru(entry) => entry.reweigh(),
+            CacheEntry::Lfu(entry) => entry.reweigh(),
+            CacheEntry::S3Fifo(entry) => entry.reweigh(),
+            CacheEntry::Custom(entry) => entry.reweigh(),
         }
     }
 }
@@ -295,12 +362,18 @@ where
     capacity: usize,
     shards: usize,
     eviction_config: EvictionConfig,
+    custom_eviction: Option<CustomCacheFactory<K, V>>,
     object_pool_capacity: usize,
+    pinned_capacity: usize,
+    entry_metadata: bool,
 
     hash_builder: S,
     weighter: Arc<dyn Weighter<K, V>>,
 
     event_listener: Option<Arc<dyn EventListener<Key = K, Value = V>>>,
+    reclaim_queue_capacity: Option<usize>,
+    admission_config: AdmissionConfig,
+    miss_ratio_curve_config: Option<MissRatioCurveConfig>,
 }
 
 impl<K, V> CacheBuilder<K, V, RandomState>
@@ -320,12 +393,19 @@ where
                 protected_capacity_ratio: 0.8,
                 cmsketch_eps: 0.001,
                 cmsketch_confidence: 0.9,
+                ..Default::default()
             }
             .into(),
+            custom_eviction: None,
             object_pool_capacity: 1024,
+            entry_metadata: false,
+            pinned_capacity: 0,
             hash_builder: RandomState::default(),
             weighter: Arc::new(|_, _| 1),
             event_listener: None,
+            reclaim_queue_capacity: None,
+            admission_config: AdmissionConfig::default(),
+            miss_ratio_curve_config: None,
         }
     }
 }
@@ -358,6 +438,22 @@ where
     /// The default value is a general-used w-TinyLFU algorithm.
     pub fn with_eviction_config(mut self, eviction_config: impl Into<EvictionConfig>) -> Self {
         self.eviction_config = eviction_config.into();
+        self.custom_eviction = None;
+        self
+    }
+
+    /// Set a user-defined in-memory cache eviction algorithm `E`.
+    ///
+    /// The built cache will be [`Cache::Custom`]. The implementation of `E` must follow the contract described on
+    /// [`Eviction`].
+    ///
+    /// It overrides the eviction algorithm set by [`CacheBuilder::with_eviction_config`], and vice versa.
+    pub fn with_custom_eviction<E>(mut self, eviction_config: E::Config) -> Self
+    where
+        E: Eviction,
+        E::Handle: Handle<Data = (K, V)>,
+    {
+        self.custom_eviction = Some(custom::factory::<K, V, E>(eviction_config));
         self
     }
 
@@ -371,6 +467,28 @@ where
         self
     }
 
+    /// Set the max total weight of the pinned entries. See [`Cache::pin`].
+    ///
+    /// The capacity is evenly distributed to the shards.
+    ///
+    /// The default value is `0`, which means no entry can be pinned.
+    pub fn with_pinned_capacity(mut self, pinned_capacity: usize) -> Self {
+        self.pinned_capacity = pinned_capacity;
+        self
+    }
+
+    /// Set whether to maintain the metadata of the entries, including the insertion time, the last access time and
+    /// the hit count. See [`CacheEntry::metadata`].
+    ///
+    /// The metadata is allocated out of the handle only if enabled, so the entries pay nothing for it otherwise.
+    /// Enabling it also reads the clock on each insertion and access.
+    ///
+    /// The default value is `false`.
+    pub fn with_entry_metadata(mut self, enabled: bool) -> Self {
+        self.entry_metadata = enabled;
+        self
+    }
+
     /// Set in-memory cache hash builder.
     pub fn with_hash_builder<OS>(self, hash_builder: OS) -> CacheBuilder<K, V, OS>
     where
@@ -381,10 +499,16 @@ where
             capacity: self.capacity,
             shards: self.shards,
             eviction_config: self.eviction_config,
+            custom_eviction: self.custom_eviction,
             object_pool_capacity: self.object_pool_capacity,
+            pinned_capacity: self.pinned_capacity,
+            entry_metadata: self.entry_metadata,
             hash_builder,
             weighter: self.weighter,
             event_listener: self.event_listener,
+            reclaim_queue_capacity: self.reclaim_queue_capacity,
+            admission_config: self.admission_config,
+            miss_ratio_curve_config: self.miss_ratio_curve_config,
         }
     }
 
@@ -400,6 +524,43 @@ where
         self
     }
 
+    /// Enable the background reclamation, which drops the released entries and calls
+    /// [`EventListener::on_memory_release`] on a dedicated thread instead of the caller thread.
+    ///
+    /// It helps to keep the operations fast if dropping the values is slow, e.g. the values own large allocations or
+    /// file handles.
+    ///
+    /// At most `queue_capacity` entries can be waiting to be released. Entries exceeding the bound are released on the
+    /// caller thread as usual.
+    ///
+    /// The background reclamation is disabled by default.
+    pub fn with_background_reclamation(mut self, queue_capacity: usize) -> Self {
+        self.reclaim_queue_capacity = Some(queue_capacity);
+        self
+    }
+
+    /// Set the admission filter config, which rejects the oversized entries or the entries that are less frequently
+    /// accessed than the victims.
+    ///
+    /// A rejected entry is still returned by `insert`, and can be told by [`CacheEntry::is_rejected`].
+    ///
+    /// See [`AdmissionConfig`] for more details. All filters are disabled by default.
+    pub fn with_admission_config(mut self, admission_config: AdmissionConfig) -> Self {
+        self.admission_config = admission_config;
+        self
+    }
+
+    /// Enable the miss ratio curve estimation, which estimates the miss ratio of the in-memory cache with each of the
+    /// given capacities. The capacities are in the unit of the weight.
+    ///
+    /// The estimation is exported as metrics, and can be read with [`Cache::miss_ratio_curve`].
+    ///
+    /// The miss ratio curve estimation is disabled by default.
+    pub fn with_miss_ratio_curve(mut self, config: MissRatioCurveConfig) -> Self {
+        self.miss_ratio_curve_config = Some(config);
+        self
+    }
+
     /// Build in-memory cache with the given configuration.
     pub fn build(self) -> Cache<K, V, S> {
         if self.capacity < self.shards {
@@ -410,46 +571,83 @@ where
             );
         }
 
+        if let Some(factory) = self.custom_eviction {
+            let config = CustomCacheConfig {
+                name: self.name,
+                capacity: self.capacity,
+                pinned_capacity: self.pinned_capacity,
+                shards: self.shards,
+                object_pool_capacity: self.object_pool_capacity,
+                entry_metadata: self.entry_metadata,
+                weighter: self.weighter,
+                event_listener: self.event_listener,
+                reclaim_queue_capacity: self.reclaim_queue_capacity,
+                admission_config: self.admission_config,
+                miss_ratio_curve_config: self.miss_ratio_curve_config,
+            };
+            return Cache::Custom(Arc::new(CustomCache::new(factory, config, self.hash_builder)));
+        }
+
         match self.eviction_config {
             EvictionConfig::Fifo(eviction_config) => Cache::Fifo(Arc::new(GenericCache::new(GenericCacheConfig {
                 name: self.name,
                 capacity: self.capacity,
+                pinned_capacity: self.pinned_capacity,
                 shards: self.shards,
                 eviction_config,
                 object_pool_capacity: self.object_pool_capacity,
+                entry_metadata: self.entry_metadata,
                 hash_builder: self.hash_builder,
                 weighter: self.weighter,
                 event_listener: self.event_listener,
+                reclaim_queue_capacity: self.reclaim_queue_capacity,
+                admission_config: self.admission_config,
+                miss_ratio_curve_config: self.miss_ratio_curve_config,
             }))),
             EvictionConfig::Lru(eviction_config) => Cache::Lru(Arc::new(GenericCache::new(GenericCacheConfig {
                 name: self.name,
                 capacity: self.capacity,
+                pinned_capacity: self.pinned_capacity,
                 shards: self.shards,
                 eviction_config,
                 object_pool_capacity: self.object_pool_capacity,
+                entry_metadata: self.entry_metadata,
                 hash_builder: self.hash_builder,
                 weighter: self.weighter,
                 event_listener: self.event_listener,
+                reclaim_queue_capacity: self.reclaim_queue_capacity,
+                admission_config: self.admission_config,
+                miss_ratio_curve_config: self.miss_ratio_curve_config,
             }))),
             EvictionConfig::Lfu(eviction_config) => Cache::Lfu(Arc::new(GenericCache::new(GenericCacheConfig {
                 name: self.name,
                 capacity: self.capacity,
+                pinned_capacity: self.pinned_capacity,
                 shards: self.shards,
                 eviction_config,
                 object_pool_capacity: self.object_pool_capacity,
+                entry_metadata: self.entry_metadata,
                 hash_builder: self.hash_builder,
                 weighter: self.weighter,
                 event_listener: self.event_listener,
+                reclaim_queue_capacity: self.reclaim_queue_capacity,
+                admission_config: self.admission_config,
+                miss_ratio_curve_config: self.miss_ratio_curve_config,
             }))),
             EvictionConfig::S3Fifo(eviction_config) => Cache::S3Fifo(Arc::new(GenericCache::new(GenericCacheConfig {
                 name: self.name,
                 capacity: self.capacity,
+                pinned_capacity: self.pinned_capacity,
                 shards: self.shards,
                 eviction_config,
                 object_pool_capacity: self.object_pool_capacity,
+                entry_metadata: self.entry_metadata,
                 hash_builder: self.hash_builder,
                 weighter: self.weighter,
                 event_listener: self.event_listener,
+                reclaim_queue_capacity: self.reclaim_queue_capacity,
+                admission_config: self.admission_config,
+                miss_ratio_curve_config: self.miss_ratio_curve_config,
             }))),
         }
     }
@@ -470,6 +668,8 @@ where
     Lfu(Arc<LfuCache<K, V, S>>),
     /// In-memory S3FIFO cache.
     S3Fifo(Arc<S3FifoCache<K, V, S>>),
+    /// In-memory cache with a user-defined eviction algorithm.
+    Custom(Arc<CustomCache<K, V, S>>),
 }
 
 impl<K, V, S> Debug for Cache<K, V, S>
@@ -484,6 +684,7 @@ where
             Self::Lru(_) => f.debug_tuple("Cache::LruCache").finish(),
             Self::Lfu(_) => f.debug_tuple("Cache::LfuCache").finish(),
             Self::S3Fifo(_) => f.debug_tuple("Cache::S3FifoCache").finish(),
+            Self::Custom(_) => f.debug_tuple("Cache::CustomCache").finish(),
         }
     }
 }
@@ -500,6 +701,7 @@ where
             Self::Lru(cache) => Self::Lru(cache.clone()),
             Self::Lfu(cache) => Self::Lfu(cache.clone()),
             Self::S3Fifo(cache) => Self::S3Fifo(cache.clone()),
+            Self::Custom(cache) => Self::Custom(cache.clone()),
         }
     }
 }
@@ -518,6 +720,7 @@ where
             Cache::Lru(cache) => cache.insert(key, value).into(),
             Cache::Lfu(cache) => cache.insert(key, value).into(),
             Cache::S3Fifo(cache) => cache.insert(key, value).into(),
+            Cache::Custom(cache) => cache.insert(key, value).into(),
         }
     }
 
@@ -529,6 +732,7 @@ where
             Cache::Lru(cache) => cache.insert_with_context(key, value, context).into(),
             Cache::Lfu(cache) => cache.insert_with_context(key, value, context).into(),
             Cache::S3Fifo(cache) => cache.insert_with_context(key, value, context).into(),
+            Cache::Custom(cache) => cache.insert_with_context(key, value, context).into(),
         }
     }
 
@@ -544,6 +748,7 @@ where
             Cache::Lru(cache) => cache.deposit(key, value).into(),
             Cache::Lfu(cache) => cache.deposit(key, value).into(),
             Cache::S3Fifo(cache) => cache.deposit(key, value).into(),
+            Cache::Custom(cache) => cache.deposit(key, value).into(),
         }
     }
 
@@ -559,6 +764,7 @@ where
             Cache::Lru(cache) => cache.deposit_with_context(key, value, context).into(),
             Cache::Lfu(cache) => cache.deposit_with_context(key, value, context).into(),
             Cache::S3Fifo(cache) => cache.deposit_with_context(key, value, context).into(),
+            Cache::Custom(cache) => cache.deposit_with_context(key, value, context).into(),
         }
     }
 
@@ -574,6 +780,7 @@ where
             Cache::Lru(cache) => cache.remove(key).map(CacheEntry::from),
             Cache::Lfu(cache) => cache.remove(key).map(CacheEntry::from),
             Cache::S3Fifo(cache) => cache.remove(key).map(CacheEntry::from),
+            Cache::Custom(cache) => cache.remove(key).map(CacheEntry::from),
         }
     }
 
@@ -589,6 +796,7 @@ where
             Cache::Lru(cache) => cache.get(key).map(CacheEntry::from),
             Cache::Lfu(cache) => cache.get(key).map(CacheEntry::from),
             Cache::S3Fifo(cache) => cache.get(key).map(CacheEntry::from),
+            Cache::Custom(cache) => cache.get(key).map(CacheEntry::from),
         }
     }
 
@@ -604,6 +812,7 @@ where
             Cache::Lru(cache) => cache.contains(key),
             Cache::Lfu(cache) => cache.contains(key),
             Cache::S3Fifo(cache) => cache.contains(key),
+            Cache::Custom(cache) => cache.contains(key),
         }
     }
 
@@ -621,6 +830,66 @@ where
             Cache::Lru(cache) => cache.touch(key),
             Cache::Lfu(cache) => cache.touch(key),
             Cache::S3Fifo(cache) => cache.touch(key),
+            Cache::Custom(cache) => cache.touch(key),
+        }
+    }
+
+    /// Pin the cached entry with the given key.
+    ///
+    /// A pinned entry is taken out of the eviction container, so it is never evicted no matter how high the memory
+    /// pressure is, until it is unpinned or removed. Pinning an entry that is already pinned is a no-op.
+    ///
+    /// Return `false` if there is no entry with the given key, or pinning it would exceed the pinned capacity of the
+    /// shard. See [`CacheBuilder::with_pinned_capacity`].
+    #[fastrace::trace(name = "foyer::memory::cache::pin")]
+    pub fn pin<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self {
+            Cache::Fifo(cache) => cache.pin(key),
+            Cache::Lru(cache) => cache.pin(key),
+            Cache::Lfu(cache) => cache.pin(key),
+            Cache::S3Fifo(cache) => cache.pin(key),
+            Cache::Custom(cache) => cache.pin(key),
+        }
+    }
+
+    /// Unpin the cached entry with the given key, so it can be evicted again.
+    ///
+    /// Return `false` if there is no entry with the given key or the entry is not pinned.
+    #[fastrace::trace(name = "foyer::memory::cache::unpin")]
+    pub fn unpin<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self {
+            Cache::Fifo(cache) => cache.unpin(key),
+            Cache::Lru(cache) => cache.unpin(key),
+            Cache::Lfu(cache) => cache.unpin(key),
+            Cache::S3Fifo(cache) => cache.unpin(key),
+            Cache::Custom(cache) => cache.unpin(key),
+        }
+    }
+
+    /// Recompute the weight of the cached entry with the given key with the weighter, e.g. after the value is mutated
+    /// through interior mutability.
+    ///
+    /// Returns `false` if the entry is not found. See [`CacheEntry::reweigh`].
+    #[fastrace::trace(name = "foyer::memory::cache::update_weight")]
+    pub fn update_weight<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self {
+            Cache::Fifo(cache) => cache.update_weight(key),
+            Cache::Lru(cache) => cache.update_weight(key),
+            Cache::Lfu(cache) => cache.update_weight(key),
+            Cache::S3Fifo(cache) => cache.update_weight(key),
+            Cache::Custom(cache) => cache.update_weight(key),
         }
     }
 
@@ -632,6 +901,135 @@ where
             Cache::Lru(cache) => cache.clear(),
             Cache::Lfu(cache) => cache.clear(),
             Cache::S3Fifo(cache) => cache.clear(),
+            Cache::Custom(cache) => cache.clear(),
+        }
+    }
+
+    /// Iterate over the entries of the in-memory cache, one shard at a time.
+    ///
+    /// Only one shard is locked at a time, and only while the references of its entries are taken. The yielded
+    /// entries keep the values alive even if they are evicted or removed meanwhile.
+    ///
+    /// Guarantees under concurrent operations:
+    ///
+    /// - Each entry that stays in the cache during the whole iteration is yielded exactly once.
+    /// - Entries inserted, replaced or removed during the iteration may or may not be yielded, depending on whether
+    ///   their shard has been visited.
+    /// - Neither the iteration nor dropping the yielded entries updates the eviction order. An entry evicted while
+    ///   it is held by the iteration is not reinserted.
+    ///
+    /// Use [`Cache::keys`] to enumerate the cache without holding the entries.
+    pub fn iter(&self) -> impl Iterator<Item = CacheEntry<K, V, S>> + '_ {
+        (0..self.shards()).flat_map(|shard| self.shard_entries(shard))
+    }
+
+    fn shard_entries(&self, shard: usize) -> Vec<CacheEntry<K, V, S>> {
+        match self {
+            Cache::Fifo(cache) => cache.shard_entries(shard).into_iter().map(CacheEntry::from).collect(),
+            Cache::Lru(cache) => cache.shard_entries(shard).into_iter().map(CacheEntry::from).collect(),
+            Cache::Lfu(cache) => cache.shard_entries(shard).into_iter().map(CacheEntry::from).collect(),
+            Cache::S3Fifo(cache) => cache.shard_entries(shard).into_iter().map(CacheEntry::from).collect(),
+            Cache::Custom(cache) => cache.shard_entries(shard).into_iter().map(CacheEntry::from).collect(),
+        }
+    }
+
+    /// Iterate over the keys of the in-memory cache, one shard at a time.
+    ///
+    /// Only one shard is locked at a time, and only while its keys are cloned. The guarantees under concurrent
+    /// operations are the same as [`Cache::iter`], and the eviction order is never updated.
+    pub fn keys(&self) -> impl Iterator<Item = K> + '_
+    where
+        K: Clone,
+    {
+        (0..self.shards()).flat_map(|shard| {
+            let mut keys = vec![];
+            let mut f = |key: &K| keys.push(key.clone());
+            match self {
+                Cache::Fifo(cache) => cache.visit_shard_keys(shard, &mut f),
+                Cache::Lru(cache) => cache.visit_shard_keys(shard, &mut f),
+                Cache::Lfu(cache) => cache.visit_shard_keys(shard, &mut f),
+                Cache::S3Fifo(cache) => cache.visit_shard_keys(shard, &mut f),
+                Cache::Custom(cache) => cache.visit_shard_keys(shard, &mut f),
+            }
+            keys
+        })
+    }
+
+    /// Retain only the entries that the predicate returns `true`, and remove the others from the in-memory cache.
+    ///
+    /// The shards are filtered one at a time, with the shard locked while the predicate is called. So the predicate
+    /// must be cheap and must NOT access the cache, or it may deadlock.
+    ///
+    /// Entries inserted into a shard after the shard is filtered are not filtered. Removed entries that are still held
+    /// by others are released after they are dropped, the same as [`Cache::remove`].
+    #[fastrace::trace(name = "foyer::memory::cache::retain")]
+    pub fn retain(&self, f: impl FnMut(&K, &V) -> bool) {
+        match self {
+            Cache::Fifo(cache) => cache.retain(f),
+            Cache::Lru(cache) => cache.retain(f),
+            Cache::Lfu(cache) => cache.retain(f),
+            Cache::S3Fifo(cache) => cache.retain(f),
+            Cache::Custom(cache) => cache.retain(f),
+        }
+    }
+
+    /// Resize the capacity of the in-memory cache.
+    ///
+    /// The new capacity is distributed evenly among the shards. If the usage exceeds the new capacity, entries are
+    /// evicted until it fits. Entries still referenced by the caller are released when their last reference drops.
+    ///
+    /// It is safe to resize the cache while it is serving traffic.
+    #[fastrace::trace(name = "foyer::memory::cache::resize")]
+    pub fn resize(&self, capacity: usize) {
+        match self {
+            Cache::Fifo(cache) => cache.resize(capacity),
+            Cache::Lru(cache) => cache.resize(capacity),
+            Cache::Lfu(cache) => cache.resize(capacity),
+            Cache::S3Fifo(cache) => cache.resize(capacity),
+            Cache::Custom(cache) => cache.resize(capacity),
+        }
+    }
+
+    /// Update the eviction config of the in-memory cache, e.g. the priority pool ratios of LRU.
+    ///
+    /// The eviction algorithm cannot be changed, only its tunables. Entries may be evicted if the new config shrinks a
+    /// part of the eviction container.
+    ///
+    /// It is safe to update the eviction config while the cache is serving traffic.
+    ///
+    /// Returns the config back if it is for a different eviction algorithm, or if it is invalid (e.g. a ratio out of
+    /// range). The cache is left unchanged in that case.
+    #[fastrace::trace(name = "foyer::memory::cache::update_eviction_config")]
+    pub fn update_eviction_config(
+        &self,
+        eviction_config: impl Into<EvictionConfig>,
+    ) -> std::result::Result<(), EvictionConfig> {
+        match (self, eviction_config.into()) {
+            (Cache::Fifo(cache), EvictionConfig::Fifo(config)) => {
+                cache.update_eviction_config(config).map_err(EvictionConfig::Fifo)
+            }
+            (Cache::Lru(cache), EvictionConfig::Lru(config)) => {
+                cache.update_eviction_config(config).map_err(EvictionConfig::Lru)
+            }
+            (Cache::Lfu(cache), EvictionConfig::Lfu(config)) => {
+                cache.update_eviction_config(config).map_err(EvictionConfig::Lfu)
+            }
+            (Cache::S3Fifo(cache), EvictionConfig::S3Fifo(config)) => {
+                cache.update_eviction_config(config).map_err(EvictionConfig::S3Fifo)
+            }
+            (Cache::Custom(cache), EvictionConfig::Fifo(config)) => {
+                cache.update_eviction_config(config).map_err(EvictionConfig::Fifo)
+            }
+            (Cache::Custom(cache), EvictionConfig::Lru(config)) => {
+                cache.update_eviction_config(config).map_err(EvictionConfig::Lru)
+            }
+            (Cache::Custom(cache), EvictionConfig::Lfu(config)) => {
+                cache.update_eviction_config(config).map_err(EvictionConfig::Lfu)
+            }
+            (Cache::Custom(cache), EvictionConfig::S3Fifo(config)) => {
+                cache.update_eviction_config(config).map_err(EvictionConfig::S3Fifo)
+            }
+            (_, config) => Err(config),
         }
     }
 
@@ -642,16 +1040,56 @@ where
             Cache::Lru(cache) => cache.capacity(),
             Cache::Lfu(cache) => cache.capacity(),
             Cache::S3Fifo(cache) => cache.capacity(),
+            Cache::Custom(cache) => cache.capacity(),
         }
     }
 
     /// Get the usage of the in-memory cache.
+    ///
+    /// The usage includes the weight of the pinned entries.
     pub fn usage(&self) -> usize {
         match self {
             Cache::Fifo(cache) => cache.usage(),
             Cache::Lru(cache) => cache.usage(),
             Cache::Lfu(cache) => cache.usage(),
             Cache::S3Fifo(cache) => cache.usage(),
+            Cache::Custom(cache) => cache.usage(),
+        }
+    }
+
+    /// Get the estimated miss ratio curve of the in-memory cache.
+    ///
+    /// Returns `None` if the miss ratio curve estimation is not enabled with
+    /// [`CacheBuilder::with_miss_ratio_curve`].
+    pub fn miss_ratio_curve(&self) -> Option<MissRatioCurve> {
+        match self {
+            Cache::Fifo(cache) => cache.miss_ratio_curve(),
+            Cache::Lru(cache) => cache.miss_ratio_curve(),
+            Cache::Lfu(cache) => cache.miss_ratio_curve(),
+            Cache::S3Fifo(cache) => cache.miss_ratio_curve(),
+            Cache::Custom(cache) => cache.miss_ratio_curve(),
+        }
+    }
+
+    /// Get the capacity of the pinned entries of the in-memory cache.
+    pub fn pinned_capacity(&self) -> usize {
+        match self {
+            Cache::Fifo(cache) => cache.pinned_capacity(),
+            Cache::Lru(cache) => cache.pinned_capacity(),
+            Cache::Lfu(cache) => cache.pinned_capacity(),
+            Cache::S3Fifo(cache) => cache.pinned_capacity(),
+            Cache::Custom(cache) => cache.pinned_capacity(),
+        }
+    }
+
+    /// Get the usage of the pinned entries of the in-memory cache.
+    pub fn pinned_usage(&self) -> usize {
+        match self {
+            Cache::Fifo(cache) => cache.pinned_usage(),
+            Cache::Lru(cache) => cache.pinned_usage(),
+            Cache::Lfu(cache) => cache.pinned_usage(),
+            Cache::S3Fifo(cache) => cache.pinned_usage(),
+            Cache::Custom(cache) => cache.pinned_usage(),
         }
     }
 
@@ -671,6 +1109,7 @@ where
             Cache::Lru(cache) => cache.hash_builder(),
             Cache::Lfu(cache) => cache.hash_builder(),
             Cache::S3Fifo(cache) => cache.hash_builder(),
+            Cache::Custom(cache) => cache.hash_builder(),
         }
     }
 
@@ -681,6 +1120,7 @@ where
             Cache::Lru(cache) => cache.shards(),
             Cache::Lfu(cache) => cache.shards(),
             Cache::S3Fifo(cache) => cache.shards(),
+            Cache::Custom(cache) => cache.shards(),
         }
     }
 }
@@ -701,6 +1141,9 @@ where
     Lfu(#[pin] LfuFetch<K, V, ER, S>),
     /// A future that is used to get entry value from the remote storage for the in-memory S3FIFO cache.
     S3Fifo(#[pin] S3FifoFetch<K, V, ER, S>),
+    /// A future that is used to get entry value from the remote storage for the in-memory cache with a user-defined
+    /// eviction algorithm.
+    Custom(#[pin] CustomFetch<K, V, ER>),
 }
 
 impl<K, V, ER, S> From<FifoFetch<K, V, ER, S>> for Fetch<K, V, ER, S>
@@ -747,6 +1190,17 @@ where
     }
 }
 
+impl<K, V, ER, S> From<CustomFetch<K, V, ER>> for Fetch<K, V, ER, S>
+where
+    K: Key,
+    V: Value,
+    S: HashBuilder,
+{
+    fn from(entry: CustomFetch<K, V, ER>) -> Self {
+        Self::Custom(entry)
+    }
+}
+
 impl<K, V, ER, S> Future for Fetch<K, V, ER, S>
 where
     K: Key,
@@ -762,6 +1216,7 @@ where
             FetchProj::Lru(entry) => entry.poll(cx).map(|res| res.map(CacheEntry::from)),
             FetchProj::Lfu(entry) => entry.poll(cx).map(|res| res.map(CacheEntry::from)),
             FetchProj::S3Fifo(entry) => entry.poll(cx).map(|res| res.map(CacheEntry::from)),
+            FetchProj::Custom(entry) => entry.poll(cx).map(|res| res.map(CacheEntry::from)),
         }
     }
 }
@@ -779,6 +1234,7 @@ where
             Fetch::Lru(fetch) => fetch.state(),
             Fetch::Lfu(fetch) => fetch.state(),
             Fetch::S3Fifo(fetch) => fetch.state(),
+            Fetch::Custom(fetch) => fetch.state(),
         }
     }
 
@@ -790,6 +1246,7 @@ where
             Fetch::Lru(fetch) => fetch.store(),
             Fetch::Lfu(fetch) => fetch.store(),
             Fetch::S3Fifo(fetch) => fetch.store(),
+            Fetch::Custom(fetch) => fetch.store(),
         }
     }
 }
@@ -817,6 +1274,7 @@ where
             Cache::Lru(cache) => Fetch::from(cache.fetch(key, fetch)),
             Cache::Lfu(cache) => Fetch::from(cache.fetch(key, fetch)),
             Cache::S3Fifo(cache) => Fetch::from(cache.fetch(key, fetch)),
+            Cache::Custom(cache) => Fetch::from(cache.fetch(key, fetch)),
         }
     }
 
@@ -837,6 +1295,7 @@ where
             Cache::Lru(cache) => Fetch::from(cache.fetch_with_context(key, context, fetch)),
             Cache::Lfu(cache) => Fetch::from(cache.fetch_with_context(key, context, fetch)),
             Cache::S3Fifo(cache) => Fetch::from(cache.fetch_with_context(key, context, fetch)),
+            Cache::Custom(cache) => Fetch::from(cache.fetch_with_context(key, context, fetch)),
         }
     }
 
@@ -866,20 +1325,25 @@ where
             Cache::Lru(cache) => Fetch::from(cache.fetch_inner(key, context, fetch, runtime)),
             Cache::Lfu(cache) => Fetch::from(cache.fetch_inner(key, context, fetch, runtime)),
             Cache::S3Fifo(cache) => Fetch::from(cache.fetch_inner(key, context, fetch, runtime)),
+            Cache::Custom(cache) => Fetch::from(cache.fetch_inner(key, context, fetch, runtime)),
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::{ops::Range, time::Duration};
+    use std::{
+        ops::Range,
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Duration,
+    };
 
     use futures::future::join_all;
     use itertools::Itertools;
     use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
 
     use super::*;
-    use crate::{eviction::s3fifo::S3FifoConfig, FifoConfig, LfuConfig, LruConfig};
+    use crate::{eviction::s3fifo::S3FifoConfig, DoorkeeperConfig, FifoConfig, LfuConfig, LruConfig};
 
     const CAPACITY: usize = 100;
     const SHARDS: usize = 4;
@@ -891,7 +1355,7 @@ mod tests {
     fn fifo() -> Cache<u64, u64> {
         CacheBuilder::new(CAPACITY)
             .with_shards(SHARDS)
-            .with_eviction_config(FifoConfig {})
+            .with_eviction_config(FifoConfig::default())
             .with_object_pool_capacity(OBJECT_POOL_CAPACITY)
             .build()
     }
@@ -901,6 +1365,7 @@ mod tests {
             .with_shards(SHARDS)
             .with_eviction_config(LruConfig {
                 high_priority_pool_ratio: 0.1,
+                ..Default::default()
             })
             .with_object_pool_capacity(OBJECT_POOL_CAPACITY)
             .build()
@@ -914,6 +1379,7 @@ mod tests {
                 protected_capacity_ratio: 0.8,
                 cmsketch_eps: 0.001,
                 cmsketch_confidence: 0.9,
+                ..Default::default()
             })
             .with_object_pool_capacity(OBJECT_POOL_CAPACITY)
             .build()
@@ -926,6 +1392,7 @@ mod tests {
                 small_queue_capacity_ratio: 0.1,
                 ghost_queue_capacity_ratio: 10.0,
                 small_to_main_freq_threshold: 2,
+                ..Default::default()
             })
             .with_object_pool_capacity(OBJECT_POOL_CAPACITY)
             .build()
@@ -1015,4 +1482,637 @@ mod tests {
     async fn test_cache_with_zero_object_pool() {
         case(CacheBuilder::new(8).with_object_pool_capacity(0).build()).await
     }
+
+    fn management_cache(eviction_config: impl Into<EvictionConfig>) -> Cache<u64, u64> {
+        CacheBuilder::new(CAPACITY)
+            .with_shards(SHARDS)
+            .with_eviction_config(eviction_config)
+            .with_object_pool_capacity(OBJECT_POOL_CAPACITY)
+            .with_pinned_capacity(CAPACITY / 2)
+            .build()
+    }
+
+    /// Mix the entry management operations into the operations of [`operate`].
+    async fn operate_management(cache: &Cache<u64, u64>, rng: &mut StdRng) {
+        let i = rng.gen_range(RANGE);
+        match rng.gen_range(0..=7) {
+            0..=3 => operate(cache, rng).await,
+            4 => {
+                cache.pin(&i);
+            }
+            5 => {
+                cache.unpin(&i);
+            }
+            6 => {
+                for entry in cache.iter() {
+                    assert_eq!(entry.key(), entry.value());
+                }
+            }
+            7 => {
+                cache.retain(|k, v| {
+                    assert_eq!(k, v);
+                    *k != i
+                });
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    async fn management_case(cache: Cache<u64, u64>) {
+        let mut rng = StdRng::seed_from_u64(42);
+
+        init_cache(&cache, &mut rng);
+
+        let handles = (0..CONCURRENCY)
+            .map(|_| {
+                let cache = cache.clone();
+                let mut rng = rng.clone();
+                tokio::spawn(async move {
+                    for _ in 0..OPS {
+                        operate_management(&cache, &mut rng).await;
+                    }
+                })
+            })
+            .collect_vec();
+
+        join_all(handles).await;
+
+        assert!(cache.pinned_usage() <= cache.pinned_capacity());
+    }
+
+    #[tokio::test]
+    async fn test_fifo_cache_management() {
+        management_case(management_cache(FifoConfig::default())).await
+    }
+
+    #[tokio::test]
+    async fn test_lru_cache_management() {
+        management_case(management_cache(LruConfig {
+            high_priority_pool_ratio: 0.1,
+            ..Default::default()
+        }))
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_lfu_cache_management() {
+        management_case(management_cache(LfuConfig::default())).await
+    }
+
+    #[tokio::test]
+    async fn test_s3fifo_cache_management() {
+        management_case(management_cache(S3FifoConfig::default())).await
+    }
+
+    /// Run the test case with the cache builder of each built-in eviction algorithm, and of a built-in eviction
+    /// algorithm plugged in as a custom eviction.
+    ///
+    /// The case is also given a non-default eviction config of the same algorithm, e.g. to update the config.
+    fn for_each_eviction<V>(capacity: usize, case: impl Fn(CacheBuilder<u64, V, RandomState>, EvictionConfig))
+    where
+        V: Value,
+    {
+        let lru = LruConfig {
+            priority_pool_ratios: vec![0.5, 0.2, 0.0],
+            ..Default::default()
+        };
+        case(
+            CacheBuilder::new(capacity).with_eviction_config(FifoConfig::default()),
+            FifoConfig {
+                priority_pool_ratios: vec![0.5],
+            }
+            .into(),
+        );
+        case(
+            CacheBuilder::new(capacity).with_eviction_config(LruConfig::default()),
+            lru.clone().into(),
+        );
+        case(
+            CacheBuilder::new(capacity).with_eviction_config(LfuConfig::default()),
+            LfuConfig {
+                window_capacity_ratio: 0.2,
+                protected_capacity_ratio: 0.6,
+                cmsketch_eps: 0.01,
+                cmsketch_confidence: 0.9,
+                ..Default::default()
+            }
+            .into(),
+        );
+        case(
+            CacheBuilder::new(capacity).with_eviction_config(S3FifoConfig::default()),
+            S3FifoConfig {
+                small_queue_capacity_ratio: 0.2,
+                ghost_queue_capacity_ratio: 0.5,
+                small_to_main_freq_threshold: 1,
+                ..Default::default()
+            }
+            .into(),
+        );
+        case(
+            CacheBuilder::new(capacity)
+                .with_custom_eviction::<crate::eviction::lru::Lru<(u64, V)>>(LruConfig::default()),
+            lru.into(),
+        );
+    }
+
+    fn pin_case(builder: CacheBuilder<u64, u64, RandomState>) {
+        let cache = builder.with_shards(1).with_pinned_capacity(3).build();
+        assert_eq!(cache.pinned_capacity(), 3);
+
+        for i in 0..4 {
+            cache.insert(i, i);
+        }
+        assert!(cache.pin(&0));
+        assert!(cache.pin(&1));
+        assert!(cache.pin(&1));
+        assert!(cache.pin(&2));
+        // Exceeds the pinned capacity.
+        assert!(!cache.pin(&3));
+        // Not in the cache.
+        assert!(!cache.pin(&42));
+        assert_eq!(cache.pinned_usage(), 3);
+
+        for i in 100..200 {
+            cache.insert(i, i);
+        }
+        assert!(cache.contains(&0));
+        assert!(cache.contains(&1));
+        assert!(cache.contains(&2));
+        assert!(!cache.contains(&3));
+        assert_eq!(cache.usage(), 10);
+        assert_eq!(cache.pinned_usage(), 3);
+
+        assert!(cache.unpin(&1));
+        assert!(!cache.unpin(&1));
+        assert_eq!(cache.pinned_usage(), 2);
+        // Access the new entries to make them hotter than the unpinned one for frequency-based algorithms.
+        for i in 200..300 {
+            cache.insert(i, i);
+            cache.get(&i);
+            cache.get(&i);
+        }
+        assert!(!cache.contains(&1));
+
+        cache.remove(&0);
+        assert_eq!(cache.pinned_usage(), 1);
+
+        // The replaced entry is not pinned.
+        cache.insert(2, 2);
+        assert_eq!(cache.pinned_usage(), 0);
+
+        // A deposit entry is kept after pinned even if it is never accessed.
+        let entry = cache.deposit(4, 4);
+        assert!(cache.pin(&4));
+        drop(entry);
+        for i in 300..400 {
+            cache.insert(i, i);
+        }
+        assert!(cache.contains(&4));
+
+        // An entry unpinned while held externally is kept after released, and can be evicted again.
+        let entry = cache.get(&4).unwrap();
+        assert!(cache.unpin(&4));
+        drop(entry);
+        assert!(cache.contains(&4));
+        for i in 400..500 {
+            cache.insert(i, i);
+            cache.get(&i);
+            cache.get(&i);
+        }
+        assert!(!cache.contains(&4));
+
+        cache.clear();
+        assert_eq!(cache.usage(), 0);
+        assert_eq!(cache.pinned_usage(), 0);
+    }
+
+    #[test]
+    fn test_cache_pin() {
+        for_each_eviction(10, |builder, _| pin_case(builder));
+    }
+
+    fn resize_case(builder: CacheBuilder<u64, u64, RandomState>, eviction_config: EvictionConfig) {
+        let cache = builder.with_shards(4).build();
+
+        for i in 0..100 {
+            cache.insert(i, i);
+        }
+        assert!(cache.usage() > 40);
+
+        // An entry held by the caller is released only after it is dropped.
+        let entry = cache.insert(1000, 1000);
+        cache.resize(40);
+        assert_eq!(cache.capacity(), 40);
+        assert!(cache.usage() <= 40);
+        cache.resize(0);
+        assert_eq!(cache.usage(), 1);
+        drop(entry);
+        assert_eq!(cache.usage(), 0);
+
+        cache.resize(200);
+        assert_eq!(cache.capacity(), 200);
+        for i in 100..400 {
+            cache.insert(i, i);
+        }
+        assert!(cache.usage() > 100);
+        assert!(cache.usage() <= 200);
+
+        cache.update_eviction_config(eviction_config).unwrap();
+        assert!(cache.usage() <= 200);
+        for i in 400..800 {
+            cache.insert(i, i);
+            cache.get(&i);
+        }
+        assert!(cache.usage() <= 200);
+
+        cache.resize(0);
+        assert_eq!(cache.usage(), 0);
+    }
+
+    #[test]
+    fn test_cache_resize() {
+        for_each_eviction(100, resize_case);
+    }
+
+    #[test]
+    fn test_cache_update_eviction_config_mismatch() {
+        let cache: Cache<u64, u64> = CacheBuilder::new(100)
+            .with_eviction_config(FifoConfig::default())
+            .build();
+        assert!(matches!(
+            cache.update_eviction_config(LruConfig::default()),
+            Err(EvictionConfig::Lru(_))
+        ));
+    }
+
+    #[test]
+    fn test_cache_update_eviction_config_invalid() {
+        let config = LruConfig {
+            priority_pool_ratios: vec![0.5, 0.5],
+            ..Default::default()
+        };
+        let cache: Cache<u64, u64> = CacheBuilder::new(100).with_eviction_config(config).build();
+        for i in 0..100 {
+            cache.insert(i, i);
+        }
+
+        assert!(matches!(
+            cache.update_eviction_config(LruConfig {
+                priority_pool_ratios: vec![0.6, 0.6],
+                ..Default::default()
+            }),
+            Err(EvictionConfig::Lru(_))
+        ));
+        assert!(matches!(
+            cache.update_eviction_config(LfuConfig::default()),
+            Err(EvictionConfig::Lfu(_))
+        ));
+
+        let cache: Cache<u64, u64> = CacheBuilder::new(100)
+            .with_eviction_config(LfuConfig::default())
+            .build();
+        assert!(matches!(
+            cache.update_eviction_config(LfuConfig {
+                window_capacity_ratio: 0.5,
+                protected_capacity_ratio: 0.5,
+                ..Default::default()
+            }),
+            Err(EvictionConfig::Lfu(_))
+        ));
+
+        let cache: Cache<u64, u64> = CacheBuilder::new(100)
+            .with_custom_eviction::<crate::eviction::fifo::Fifo<(u64, u64)>>(FifoConfig::default())
+            .build();
+        assert!(matches!(
+            cache.update_eviction_config(FifoConfig {
+                priority_pool_ratios: vec![1.5],
+            }),
+            Err(EvictionConfig::Fifo(_))
+        ));
+        cache
+            .update_eviction_config(FifoConfig {
+                priority_pool_ratios: vec![0.5],
+            })
+            .unwrap();
+    }
+
+    fn iter_case(builder: CacheBuilder<u64, u64, RandomState>) {
+        let cache = builder.with_shards(4).with_pinned_capacity(1000).build();
+        for i in 0..100 {
+            cache.insert(i, i);
+        }
+
+        let entries = cache.iter().collect_vec();
+        assert_eq!(
+            entries.iter().map(|entry| *entry.key()).sorted().collect_vec(),
+            (0..100).collect_vec()
+        );
+        for entry in entries.iter() {
+            assert_eq!(entry.key(), entry.value());
+        }
+        assert_eq!(cache.keys().sorted().collect_vec(), (0..100).collect_vec());
+
+        // The yielded entries keep the values alive.
+        cache.clear();
+        assert_eq!(cache.keys().count(), 0);
+        assert_eq!(cache.usage(), 100);
+        assert_eq!(entries.iter().map(|entry| *entry.value()).sum::<u64>(), (0..100).sum());
+        drop(entries);
+        assert_eq!(cache.usage(), 0);
+
+        for i in 0..100 {
+            cache.insert(i, i);
+        }
+        let entry = cache.get(&1).unwrap();
+        cache.retain(|k, _| k % 2 == 0);
+        assert_eq!(cache.keys().sorted().collect_vec(), (0..100).step_by(2).collect_vec());
+        assert!(entry.is_outdated());
+        assert_eq!(cache.usage(), 51);
+        drop(entry);
+        assert_eq!(cache.usage(), 50);
+
+        // Retain removes pinned entries as well.
+        assert!(cache.pin(&0));
+        cache.retain(|_, _| false);
+        assert_eq!(cache.usage(), 0);
+        assert_eq!(cache.pinned_usage(), 0);
+    }
+
+    #[test]
+    fn test_cache_iter() {
+        for_each_eviction(1000, |builder, _| iter_case(builder));
+    }
+
+    #[test]
+    fn test_cache_iter_keeps_eviction_order() {
+        let cache: Cache<u64, u64> = CacheBuilder::new(10)
+            .with_shards(1)
+            .with_eviction_config(LruConfig::default())
+            .build();
+        for i in 0..10 {
+            cache.insert(i, i);
+        }
+
+        assert_eq!(cache.iter().count(), 10);
+
+        // The least recently used entries are still evicted first.
+        for i in 10..15 {
+            cache.insert(i, i);
+        }
+        assert_eq!(cache.keys().sorted().collect_vec(), (5..15).collect_vec());
+
+        // An entry evicted while held by iteration is not reinserted.
+        let entries = cache.iter().collect_vec();
+        for i in 15..25 {
+            cache.insert(i, i);
+        }
+        drop(entries);
+        assert!(cache.keys().all(|k| k >= 15));
+        assert_eq!(cache.usage(), cache.keys().count());
+
+        // The key inserted again while the evicted entry is held by iteration is kept after the entry is released.
+        cache.clear();
+        cache.insert(0, 0);
+        let entry = cache.iter().next().unwrap();
+        for i in 1..11 {
+            cache.insert(i, i);
+        }
+        cache.insert(0, 42);
+        drop(entry);
+        assert_eq!(cache.get(&0).map(|entry| *entry.value()), Some(42));
+        assert_eq!(cache.usage(), cache.keys().count());
+    }
+
+    #[test]
+    fn test_cache_entry_metadata() {
+        let cache: Cache<u64, u64> = CacheBuilder::new(100).with_entry_metadata(true).build();
+        let inserted = cache.insert(1, 1).metadata().unwrap();
+        assert_eq!(inserted.hits(), 0);
+        assert_eq!(inserted.inserted_at(), inserted.last_accessed_at());
+
+        std::thread::sleep(Duration::from_millis(10));
+        cache.get(&1).unwrap();
+        assert!(cache.touch(&1));
+        // Neither `contains` nor the iteration counts as an access.
+        assert!(cache.contains(&1));
+        let entry = cache.iter().next().unwrap();
+        let accessed = entry.metadata().unwrap();
+        assert_eq!(accessed.hits(), 2);
+        assert_eq!(accessed.inserted_at(), inserted.inserted_at());
+        assert!(accessed.last_accessed_at() > inserted.last_accessed_at());
+        assert!(accessed.age() >= Duration::from_millis(10));
+        drop(entry);
+
+        // The metadata is reset after the entry is replaced.
+        let replaced = cache.insert(1, 2).metadata().unwrap();
+        assert_eq!(replaced.hits(), 0);
+        assert!(replaced.inserted_at() > inserted.inserted_at());
+
+        let cache: Cache<u64, u64> = CacheBuilder::new(100).build();
+        cache.insert(1, 1);
+        assert_eq!(cache.get(&1).unwrap().metadata(), None);
+    }
+
+    fn reweigh_case(builder: CacheBuilder<u64, Arc<AtomicUsize>, RandomState>) {
+        let cache = builder
+            .with_shards(1)
+            .with_pinned_capacity(100)
+            .with_weighter(|_, v: &Arc<AtomicUsize>| v.load(Ordering::Relaxed))
+            .build();
+        for i in 0..10 {
+            cache.insert(i, Arc::new(AtomicUsize::new(1)));
+        }
+        assert_eq!(cache.usage(), 10);
+
+        cache.get(&0).unwrap().store(50, Ordering::Relaxed);
+        assert!(cache.update_weight(&0));
+        assert_eq!(cache.usage(), 59);
+        assert!(!cache.update_weight(&42));
+
+        let entry = cache.get(&1).unwrap();
+        entry.store(41, Ordering::Relaxed);
+        assert_eq!(entry.reweigh(), 41);
+        assert_eq!(entry.weight(), 41);
+        assert_eq!(cache.usage(), 99);
+        drop(entry);
+
+        // Shrink in place.
+        cache.get(&0).unwrap().store(10, Ordering::Relaxed);
+        assert!(cache.update_weight(&0));
+        assert_eq!(cache.usage(), 59);
+        assert!(cache.contains(&0));
+
+        // Grow over the capacity, the pinned entry is kept.
+        assert!(cache.pin(&2));
+        cache.get(&2).unwrap().store(60, Ordering::Relaxed);
+        assert!(cache.update_weight(&2));
+        assert_eq!(cache.pinned_usage(), 60);
+        assert!(cache.contains(&2));
+        assert!(cache.usage() <= 100);
+
+        cache.clear();
+        assert_eq!(cache.usage(), 0);
+        assert_eq!(cache.pinned_usage(), 0);
+    }
+
+    #[test]
+    fn test_cache_reweigh() {
+        for_each_eviction(100, |builder, _| reweigh_case(builder));
+    }
+
+    struct ReleaseRecorder(parking_lot::Mutex<Vec<(u64, Option<String>)>>);
+
+    impl EventListener for ReleaseRecorder {
+        type Key = u64;
+        type Value = u64;
+
+        fn on_memory_release(&self, key: Self::Key, _: Self::Value)
+        where
+            Self::Key: Key,
+            Self::Value: Value,
+        {
+            let thread = std::thread::current().name().map(|name| name.to_string());
+            self.0.lock().push((key, thread));
+        }
+    }
+
+    #[test]
+    fn test_cache_background_reclamation() {
+        let recorder = Arc::new(ReleaseRecorder(parking_lot::Mutex::new(vec![])));
+        let cache: Cache<u64, u64> = CacheBuilder::new(10)
+            .with_name("reclaim")
+            .with_shards(1)
+            .with_eviction_config(FifoConfig::default())
+            .with_event_listener(recorder.clone())
+            .with_background_reclamation(1024)
+            .build();
+
+        for i in 0..20 {
+            cache.insert(i, i);
+        }
+        cache.remove(&19);
+
+        let start = std::time::Instant::now();
+        while recorder.0.lock().len() < 11 {
+            assert!(start.elapsed() < Duration::from_secs(10));
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        assert!(recorder
+            .0
+            .lock()
+            .iter()
+            .all(|(_, thread)| thread.as_deref() == Some("reclaim-reclaimer")));
+        assert_eq!(cache.usage(), 9);
+    }
+
+    #[test]
+    fn test_cache_admission_oversized() {
+        let cache: Cache<u64, Vec<u8>> = CacheBuilder::new(100)
+            .with_shards(1)
+            .with_eviction_config(LruConfig::default())
+            .with_weighter(|_, v: &Vec<u8>| v.len())
+            .with_admission_config(AdmissionConfig {
+                max_entry_weight: Some(60),
+                max_shard_capacity_ratio: Some(0.5),
+                ..Default::default()
+            })
+            .build();
+
+        for i in 0..4 {
+            assert!(!cache.insert(i, vec![0; 20]).is_rejected());
+        }
+        assert_eq!(cache.usage(), 80);
+
+        // Rejected by the shard capacity ratio. The giant entry must not evict the others.
+        let e = cache.insert(1, vec![0; 51]);
+        assert!(e.is_rejected());
+        assert!(e.is_outdated());
+        assert_eq!(e.len(), 51);
+        // The stale entry with the same key is removed.
+        assert!(!cache.contains(&1));
+        assert!(cache.contains(&0) && cache.contains(&2) && cache.contains(&3));
+        assert_eq!(cache.usage(), 60);
+        drop(e);
+        assert_eq!(cache.usage(), 60);
+
+        let e = cache.insert(4, vec![0; 50]);
+        assert!(!e.is_rejected());
+        drop(e);
+        assert_eq!(cache.usage(), 90);
+    }
+
+    #[test]
+    fn test_cache_admission_doorkeeper() {
+        for cache in [
+            CacheBuilder::new(4)
+                .with_shards(1)
+                .with_eviction_config(FifoConfig::default())
+                .with_admission_config(AdmissionConfig {
+                    doorkeeper: Some(DoorkeeperConfig::default()),
+                    ..Default::default()
+                })
+                .build(),
+            CacheBuilder::new(4)
+                .with_shards(1)
+                .with_eviction_config(LruConfig::default())
+                .with_admission_config(AdmissionConfig {
+                    doorkeeper: Some(DoorkeeperConfig::default()),
+                    ..Default::default()
+                })
+                .build(),
+        ] {
+            let cache: Cache<u64, u64> = cache;
+            for i in 0..4 {
+                cache.insert(i, i);
+                for _ in 0..4 {
+                    cache.get(&i);
+                }
+            }
+
+            // The one-hit wonders never replace the frequently accessed entries.
+            for i in 100..200 {
+                assert!(cache.insert(i, i).is_rejected());
+            }
+            for i in 0..4 {
+                assert!(cache.contains(&i));
+            }
+
+            // A key that becomes hot enough is admitted.
+            for _ in 0..8 {
+                cache.get(&1000);
+            }
+            assert!(!cache.insert(1000, 1000).is_rejected());
+            assert!(cache.contains(&1000));
+            assert_eq!(cache.usage(), 4);
+        }
+    }
+
+    #[test]
+    fn test_cache_miss_ratio_curve() {
+        let cache: Cache<u64, u64> = CacheBuilder::new(100)
+            .with_shards(1)
+            .with_eviction_config(LruConfig::default())
+            .with_miss_ratio_curve(MissRatioCurveConfig {
+                capacities: vec![50, 100, 200],
+                sample_rate: 1.0,
+                ..Default::default()
+            })
+            .build();
+
+        // Loop over 150 keys: the cache misses everything with capacity 50 or 100, and only misses the cold keys with
+        // capacity 200.
+        for _ in 0..10 {
+            for i in 0..150 {
+                if cache.get(&i).is_none() {
+                    cache.insert(i, i);
+                }
+            }
+        }
+
+        let curve = cache.miss_ratio_curve().unwrap();
+        assert_eq!(curve.references, 1500);
+        assert_eq!(curve.points, vec![(50, 1.0), (100, 1.0), (200, 0.1)]);
+
+        assert!(fifo().miss_ratio_curve().is_none());
+    }
 }
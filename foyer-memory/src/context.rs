@@ -15,14 +15,37 @@
 /// Context of the cache entry.
 ///
 /// It may be used by the eviction algorithm.
+///
+/// Each context maps to a priority. A larger priority means the entry is more valuable to keep. How the priority is
+/// honored differs from different eviction algorithm, and the count of the priority levels is configured by the
+/// per-priority options of the eviction algorithm config. Priorities beyond the configured levels are treated as the
+/// highest configured one.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CacheContext {
     /// The default context shared by all eviction container implementations.
+    ///
+    /// Has priority `1`.
     Default,
     /// Mark the entry as low-priority.
     ///
-    /// The behavior differs from different eviction algorithm.
+    /// Has priority `0`.
     LowPriority,
+    /// Mark the entry with the given priority.
+    Priority(u8),
+}
+
+impl CacheContext {
+    /// Get the priority of the context.
+    ///
+    /// Different contexts may have the same priority, e.g. `Priority(1)` and `Default`. Compare the priorities instead
+    /// of the contexts to tell if two entries are treated the same by the eviction algorithm.
+    pub fn priority(&self) -> usize {
+        match self {
+            CacheContext::LowPriority => 0,
+            CacheContext::Default => 1,
+            CacheContext::Priority(priority) => *priority as usize,
+        }
+    }
 }
 
 impl Default for CacheContext {
@@ -35,3 +58,24 @@ impl Default for CacheContext {
 pub trait Context: From<CacheContext> + Into<CacheContext> + Send + Sync + 'static + Clone {}
 
 impl<T> Context for T where T: From<CacheContext> + Into<CacheContext> + Send + Sync + 'static + Clone {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_context_priority() {
+        assert_eq!(CacheContext::LowPriority.priority(), 0);
+        assert_eq!(CacheContext::Default.priority(), 1);
+        assert_eq!(CacheContext::Priority(2).priority(), 2);
+        assert_eq!(CacheContext::Priority(u8::MAX).priority(), u8::MAX as usize);
+
+        assert_eq!(
+            CacheContext::Priority(0).priority(),
+            CacheContext::LowPriority.priority()
+        );
+        assert_eq!(CacheContext::Priority(1).priority(), CacheContext::Default.priority());
+        assert_ne!(CacheContext::Priority(1), CacheContext::Default);
+        assert_ne!(CacheContext::Priority(200), CacheContext::Priority(3));
+    }
+}
@@ -0,0 +1,1180 @@
+//  Copyright 2024 foyer Project Authors
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::{
+    any::Any,
+    borrow::Borrow,
+    fmt::Debug,
+    future::Future,
+    hash::Hash,
+    ops::Deref,
+    pin::Pin,
+    ptr::NonNull,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use ahash::RandomState;
+use foyer_common::{
+    code::{HashBuilder, Key, Value},
+    event::EventListener,
+    future::{Diversion, DiversionFuture},
+    mrc::{MissRatioCurve, MissRatioCurveConfig},
+    runtime::SingletonHandle,
+};
+use futures::{future::BoxFuture, FutureExt};
+use hashbrown::Equivalent;
+use tokio::sync::oneshot;
+
+use crate::{
+    admission::AdmissionConfig,
+    eviction::{sanity::SanityEviction, Eviction},
+    generic::{
+        FetchMark, FetchState, GenericCache, GenericCacheConfig, GenericCacheEntry, GenericFetchInner, Weighter,
+    },
+    handle::{Handle, KeyedHandle},
+    indexer::{sanity::SanityIndexer, DefaultIndexer, Indexer},
+    metadata::EntryMetadata,
+    CacheContext,
+};
+
+/// The config of the in-memory cache, except the eviction algorithm and the hash builder.
+pub(crate) struct CustomCacheConfig<K, V>
+where
+    K: Key,
+    V: Value,
+{
+    pub name: String,
+    pub capacity: usize,
+    pub pinned_capacity: usize,
+    pub shards: usize,
+    pub object_pool_capacity: usize,
+    pub entry_metadata: bool,
+    pub weighter: Arc<dyn Weighter<K, V>>,
+    pub event_listener: Option<Arc<dyn EventListener<Key = K, Value = V>>>,
+    pub reclaim_queue_capacity: Option<usize>,
+    pub admission_config: AdmissionConfig,
+    pub miss_ratio_curve_config: Option<MissRatioCurveConfig>,
+}
+
+type CustomGenericCache<K, V, E> =
+    GenericCache<K, V, SanityEviction<E>, SanityIndexer<DefaultIndexer<K, <E as Eviction>::Handle>>>;
+
+/// Build the type-erased generic cache with the eviction algorithm captured by [`factory`].
+pub(crate) type CustomCacheFactory<K, V> =
+    Box<dyn FnOnce(CustomCacheConfig<K, V>) -> Arc<dyn DynCache<K, V>> + Send + Sync + 'static>;
+
+/// Create a [`CustomCacheFactory`] that builds the generic cache with eviction algorithm `E`.
+pub(crate) fn factory<K, V, E>(eviction_config: E::Config) -> CustomCacheFactory<K, V>
+where
+    K: Key,
+    V: Value,
+    E: Eviction,
+    E::Handle: Handle<Data = (K, V)>,
+{
+    Box::new(move |config: CustomCacheConfig<K, V>| {
+        let cache: CustomGenericCache<K, V, E> = GenericCache::new(GenericCacheConfig {
+            name: config.name,
+            capacity: config.capacity,
+            pinned_capacity: config.pinned_capacity,
+            shards: config.shards,
+            eviction_config,
+            object_pool_capacity: config.object_pool_capacity,
+            entry_metadata: config.entry_metadata,
+            // The key hash is always calculated by `CustomCache`.
+            hash_builder: RandomState::default(),
+            weighter: config.weighter,
+            event_listener: config.event_listener,
+            reclaim_queue_capacity: config.reclaim_queue_capacity,
+            admission_config: config.admission_config,
+            miss_ratio_curve_config: config.miss_ratio_curve_config,
+        });
+        Arc::new(cache) as Arc<dyn DynCache<K, V>>
+    })
+}
+
+/// Type-erased error of the fetch future of [`DynCache`].
+pub(crate) trait DynFetchError: Debug + Send + 'static {
+    fn into_any(self: Box<Self>) -> Box<dyn Any + Send>;
+}
+
+impl<T> DynFetchError for T
+where
+    T: Debug + Send + 'static,
+{
+    fn into_any(self: Box<Self>) -> Box<dyn Any + Send> {
+        self
+    }
+}
+
+type DynFetchResult<T> = Diversion<std::result::Result<T, Box<dyn DynFetchError>>, FetchMark>;
+type DynFetchFn<'a, V> = Box<dyn FnOnce() -> BoxFuture<'static, DynFetchResult<V>> + 'a>;
+
+/// Equivalent adapter for the type-erased key comparison.
+///
+/// `Q: ?Sized` keys cannot be casted into trait objects, so the comparison is passed as a closure.
+struct EqFn<'a, K>(&'a dyn Fn(&K) -> bool);
+
+impl<K> Equivalent<K> for EqFn<'_, K> {
+    fn equivalent(&self, key: &K) -> bool {
+        (self.0)(key)
+    }
+}
+
+/// Type-erased generic cache with a user-defined eviction algorithm.
+///
+/// The key hash is calculated by [`CustomCache`] with its own hash builder, so the hash builder is not a part of the
+/// erased type. All key hashes passed in must be calculated by the same hash builder.
+pub(crate) trait DynCache<K, V>: Send + Sync + 'static
+where
+    K: Key,
+    V: Value,
+{
+    fn emplace(
+        self: Arc<Self>,
+        hash: u64,
+        key: K,
+        value: V,
+        context: CacheContext,
+        deposit: bool,
+    ) -> CustomCacheEntry<K, V>;
+
+    fn remove(self: Arc<Self>, hash: u64, eq: &dyn Fn(&K) -> bool) -> Option<CustomCacheEntry<K, V>>;
+
+    fn get(self: Arc<Self>, hash: u64, eq: &dyn Fn(&K) -> bool) -> Option<CustomCacheEntry<K, V>>;
+
+    fn contains(&self, hash: u64, eq: &dyn Fn(&K) -> bool) -> bool;
+
+    fn touch(&self, hash: u64, eq: &dyn Fn(&K) -> bool) -> bool;
+
+    fn pin(&self, hash: u64, eq: &dyn Fn(&K) -> bool) -> bool;
+
+    fn unpin(&self, hash: u64, eq: &dyn Fn(&K) -> bool) -> bool;
+
+    fn update_weight(&self, hash: u64, eq: &dyn Fn(&K) -> bool) -> bool;
+
+    fn clear(&self);
+
+    fn shard_entries(self: Arc<Self>, shard: usize) -> Vec<CustomCacheEntry<K, V>>;
+
+    fn visit_shard_keys(&self, shard: usize, f: &mut dyn FnMut(&K));
+
+    fn retain(&self, f: &mut dyn FnMut(&K, &V) -> bool);
+
+    fn resize(&self, capacity: usize);
+
+    /// Returns the config back if it is not the eviction config type of the erased cache.
+    fn update_eviction_config(&self, config: Box<dyn Any + Send>) -> std::result::Result<(), Box<dyn Any + Send>>;
+
+    fn capacity(&self) -> usize;
+
+    fn usage(&self) -> usize;
+
+    fn miss_ratio_curve(&self) -> Option<MissRatioCurve>;
+
+    fn pinned_capacity(&self) -> usize;
+
+    fn pinned_usage(&self) -> usize;
+
+    fn shards(&self) -> usize;
+
+    fn fetch(
+        self: Arc<Self>,
+        hash: u64,
+        key: K,
+        clone_key: &dyn Fn(&K) -> K,
+        context: CacheContext,
+        fetch: DynFetchFn<'_, V>,
+        runtime: &SingletonHandle,
+    ) -> DynFetchInner<K, V>;
+
+    // Accessors of the type-erased handle held by [`CustomCacheEntry`].
+    //
+    // # Safety
+    //
+    // The handle must be taken from this cache and referenced by the entry.
+
+    unsafe fn entry_hash(&self, ptr: NonNull<()>) -> u64;
+
+    unsafe fn entry_key(&self, ptr: NonNull<()>) -> &K;
+
+    unsafe fn entry_value(&self, ptr: NonNull<()>) -> &V;
+
+    unsafe fn entry_context(&self, ptr: NonNull<()>) -> CacheContext;
+
+    unsafe fn entry_weight(&self, ptr: NonNull<()>) -> usize;
+
+    unsafe fn entry_refs(&self, ptr: NonNull<()>) -> usize;
+
+    unsafe fn entry_is_outdated(&self, ptr: NonNull<()>) -> bool;
+
+    unsafe fn entry_is_rejected(&self, ptr: NonNull<()>) -> bool;
+
+    unsafe fn entry_metadata(&self, ptr: NonNull<()>) -> Option<EntryMetadata>;
+
+    unsafe fn entry_reweigh(&self, ptr: NonNull<()>) -> usize;
+
+    unsafe fn entry_inc_refs(&self, ptr: NonNull<()>);
+
+    unsafe fn entry_release(&self, ptr: NonNull<()>, passive: bool);
+}
+
+impl<K, V, E, I, S> DynCache<K, V> for GenericCache<K, V, E, I, S>
+where
+    K: Key,
+    V: Value,
+    E: Eviction,
+    E::Handle: KeyedHandle<Key = K, Data = (K, V)>,
+    I: Indexer<Key = K, Handle = E::Handle>,
+    S: HashBuilder,
+{
+    fn emplace(
+        self: Arc<Self>,
+        hash: u64,
+        key: K,
+        value: V,
+        context: CacheContext,
+        deposit: bool,
+    ) -> CustomCacheEntry<K, V> {
+        CustomCacheEntry::new(GenericCache::emplace(&self, hash, key, value, context, deposit))
+    }
+
+    fn remove(self: Arc<Self>, hash: u64, eq: &dyn Fn(&K) -> bool) -> Option<CustomCacheEntry<K, V>> {
+        GenericCache::remove_with_hash(&self, hash, &EqFn(eq)).map(CustomCacheEntry::new)
+    }
+
+    fn get(self: Arc<Self>, hash: u64, eq: &dyn Fn(&K) -> bool) -> Option<CustomCacheEntry<K, V>> {
+        GenericCache::get_with_hash(&self, hash, &EqFn(eq)).map(CustomCacheEntry::new)
+    }
+
+    fn contains(&self, hash: u64, eq: &dyn Fn(&K) -> bool) -> bool {
+        GenericCache::contains_with_hash(self, hash, &EqFn(eq))
+    }
+
+    fn touch(&self, hash: u64, eq: &dyn Fn(&K) -> bool) -> bool {
+        GenericCache::touch_with_hash(self, hash, &EqFn(eq))
+    }
+
+    fn pin(&self, hash: u64, eq: &dyn Fn(&K) -> bool) -> bool {
+        GenericCache::pin_with_hash(self, hash, &EqFn(eq))
+    }
+
+    fn unpin(&self, hash: u64, eq: &dyn Fn(&K) -> bool) -> bool {
+        GenericCache::unpin_with_hash(self, hash, &EqFn(eq))
+    }
+
+    fn update_weight(&self, hash: u64, eq: &dyn Fn(&K) -> bool) -> bool {
+        GenericCache::update_weight_with_hash(self, hash, &EqFn(eq))
+    }
+
+    fn clear(&self) {
+        GenericCache::clear(self)
+    }
+
+    fn shard_entries(self: Arc<Self>, shard: usize) -> Vec<CustomCacheEntry<K, V>> {
+        GenericCache::shard_entries(&self, shard)
+            .into_iter()
+            .map(CustomCacheEntry::new)
+            .collect()
+    }
+
+    fn visit_shard_keys(&self, shard: usize, f: &mut dyn FnMut(&K)) {
+        GenericCache::visit_shard_keys(self, shard, f)
+    }
+
+    fn retain(&self, f: &mut dyn FnMut(&K, &V) -> bool) {
+        GenericCache::retain(self, f)
+    }
+
+    fn resize(&self, capacity: usize) {
+        GenericCache::resize(self, capacity)
+    }
+
+    fn update_eviction_config(&self, config: Box<dyn Any + Send>) -> std::result::Result<(), Box<dyn Any + Send>> {
+        let config = config.downcast::<E::Config>()?;
+        GenericCache::update_eviction_config(self, *config).map_err(|config| Box::new(config) as Box<dyn Any + Send>)
+    }
+
+    fn capacity(&self) -> usize {
+        GenericCache::capacity(self)
+    }
+
+    fn usage(&self) -> usize {
+        GenericCache::usage(self)
+    }
+
+    fn miss_ratio_curve(&self) -> Option<MissRatioCurve> {
+        GenericCache::miss_ratio_curve(self)
+    }
+
+    fn pinned_capacity(&self) -> usize {
+        GenericCache::pinned_capacity(self)
+    }
+
+    fn pinned_usage(&self) -> usize {
+        GenericCache::pinned_usage(self)
+    }
+
+    fn shards(&self) -> usize {
+        GenericCache::shards(self)
+    }
+
+    fn fetch(
+        self: Arc<Self>,
+        hash: u64,
+        key: K,
+        clone_key: &dyn Fn(&K) -> K,
+        context: CacheContext,
+        fetch: DynFetchFn<'_, V>,
+        runtime: &SingletonHandle,
+    ) -> DynFetchInner<K, V> {
+        match GenericCache::fetch_with_hash(&self, hash, key, clone_key, context, fetch, runtime) {
+            GenericFetchInner::Hit(entry) => DynFetchInner::Hit(entry.map(CustomCacheEntry::new)),
+            GenericFetchInner::Wait(waiter) => {
+                DynFetchInner::Wait(waiter.map(|res| res.map(CustomCacheEntry::new)).boxed())
+            }
+            GenericFetchInner::Miss(join) => DynFetchInner::Miss(
+                join.map(|join| {
+                    let Diversion { target, store } = join.unwrap();
+                    Diversion {
+                        target: target.map(CustomCacheEntry::new),
+                        store,
+                    }
+                })
+                .boxed(),
+            ),
+        }
+    }
+
+    unsafe fn entry_hash(&self, ptr: NonNull<()>) -> u64 {
+        ptr.cast::<E::Handle>().as_ref().base().hash()
+    }
+
+    unsafe fn entry_key(&self, ptr: NonNull<()>) -> &K {
+        &ptr.cast::<E::Handle>().as_ref().base().data_unwrap_unchecked().0
+    }
+
+    unsafe fn entry_value(&self, ptr: NonNull<()>) -> &V {
+        &ptr.cast::<E::Handle>().as_ref().base().data_unwrap_unchecked().1
+    }
+
+    unsafe fn entry_context(&self, ptr: NonNull<()>) -> CacheContext {
+        ptr.cast::<E::Handle>().as_ref().base().context().clone().into()
+    }
+
+    unsafe fn entry_weight(&self, ptr: NonNull<()>) -> usize {
+        ptr.cast::<E::Handle>().as_ref().base().weight()
+    }
+
+    unsafe fn entry_refs(&self, ptr: NonNull<()>) -> usize {
+        ptr.cast::<E::Handle>().as_ref().base().refs()
+    }
+
+    unsafe fn entry_is_outdated(&self, ptr: NonNull<()>) -> bool {
+        !ptr.cast::<E::Handle>().as_ref().base().is_in_indexer()
+    }
+
+    unsafe fn entry_is_rejected(&self, ptr: NonNull<()>) -> bool {
+        ptr.cast::<E::Handle>().as_ref().base().is_rejected()
+    }
+
+    unsafe fn entry_metadata(&self, ptr: NonNull<()>) -> Option<EntryMetadata> {
+        ptr.cast::<E::Handle>()
+            .as_ref()
+            .base()
+            .metadata()
+            .map(|metadata| metadata.snapshot())
+    }
+
+    unsafe fn entry_reweigh(&self, ptr: NonNull<()>) -> usize {
+        GenericCache::reweigh_handle(self, ptr.cast())
+    }
+
+    unsafe fn entry_inc_refs(&self, ptr: NonNull<()>) {
+        GenericCache::inc_refs(self, ptr.cast())
+    }
+
+    unsafe fn entry_release(&self, ptr: NonNull<()>, passive: bool) {
+        GenericCache::try_release_external_handle(self, ptr.cast(), passive)
+    }
+}
+
+/// A cached entry holder of the in-memory cache with a user-defined eviction algorithm.
+pub struct CustomCacheEntry<K, V>
+where
+    K: Key,
+    V: Value,
+{
+    /// The entry holds a reference of the handle, so the erased handle is cloned and released without allocation.
+    cache: Arc<dyn DynCache<K, V>>,
+    ptr: NonNull<()>,
+    passive: bool,
+}
+
+impl<K, V> CustomCacheEntry<K, V>
+where
+    K: Key,
+    V: Value,
+{
+    fn new<E, I, S>(entry: GenericCacheEntry<K, V, E, I, S>) -> Self
+    where
+        E: Eviction,
+        E::Handle: KeyedHandle<Key = K, Data = (K, V)>,
+        I: Indexer<Key = K, Handle = E::Handle>,
+        S: HashBuilder,
+    {
+        let (cache, ptr, passive) = entry.into_raw();
+        Self {
+            cache,
+            ptr: ptr.cast(),
+            passive,
+        }
+    }
+
+    /// Key hash of the cached entry.
+    pub fn hash(&self) -> u64 {
+        unsafe { self.cache.entry_hash(self.ptr) }
+    }
+
+    /// Key of the cached entry.
+    pub fn key(&self) -> &K {
+        unsafe { self.cache.entry_key(self.ptr) }
+    }
+
+    /// Value of the cached entry.
+    pub fn value(&self) -> &V {
+        unsafe { self.cache.entry_value(self.ptr) }
+    }
+
+    /// Context of the cached entry.
+    pub fn context(&self) -> CacheContext {
+        unsafe { self.cache.entry_context(self.ptr) }
+    }
+
+    /// Weight of the cached entry.
+    pub fn weight(&self) -> usize {
+        unsafe { self.cache.entry_weight(self.ptr) }
+    }
+
+    /// External reference count of the cached entry.
+    pub fn refs(&self) -> usize {
+        unsafe { self.cache.entry_refs(self.ptr) }
+    }
+
+    /// If the cached entry is updated and outdated.
+    pub fn is_outdated(&self) -> bool {
+        unsafe { self.cache.entry_is_outdated(self.ptr) }
+    }
+
+    /// If the entry is rejected by the admission filter on insertion.
+    pub fn is_rejected(&self) -> bool {
+        unsafe { self.cache.entry_is_rejected(self.ptr) }
+    }
+
+    /// Metadata of the cached entry.
+    ///
+    /// See [`CacheEntry::metadata`](crate::CacheEntry::metadata).
+    pub fn metadata(&self) -> Option<EntryMetadata> {
+        unsafe { self.cache.entry_metadata(self.ptr) }
+    }
+
+    /// Recompute the weight of the cached entry.
+    ///
+    /// See [`CacheEntry::reweigh`](crate::CacheEntry::reweigh).
+    pub fn reweigh(&self) -> usize {
+        unsafe { self.cache.entry_reweigh(self.ptr) }
+    }
+}
+
+impl<K, V> Debug for CustomCacheEntry<K, V>
+where
+    K: Key,
+    V: Value,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CustomCacheEntry")
+            .field("hash", &self.hash())
+            .field("weight", &self.weight())
+            .finish()
+    }
+}
+
+impl<K, V> Clone for CustomCacheEntry<K, V>
+where
+    K: Key,
+    V: Value,
+{
+    fn clone(&self) -> Self {
+        unsafe { self.cache.entry_inc_refs(self.ptr) };
+        Self {
+            cache: self.cache.clone(),
+            ptr: self.ptr,
+            passive: self.passive,
+        }
+    }
+}
+
+impl<K, V> Drop for CustomCacheEntry<K, V>
+where
+    K: Key,
+    V: Value,
+{
+    fn drop(&mut self) {
+        unsafe { self.cache.entry_release(self.ptr, self.passive) };
+    }
+}
+
+impl<K, V> Deref for CustomCacheEntry<K, V>
+where
+    K: Key,
+    V: Value,
+{
+    type Target = V;
+
+    fn deref(&self) -> &Self::Target {
+        self.value()
+    }
+}
+
+unsafe impl<K, V> Send for CustomCacheEntry<K, V>
+where
+    K: Key,
+    V: Value,
+{
+}
+unsafe impl<K, V> Sync for CustomCacheEntry<K, V>
+where
+    K: Key,
+    V: Value,
+{
+}
+
+pub(crate) enum DynFetchInner<K, V>
+where
+    K: Key,
+    V: Value,
+{
+    Hit(Option<CustomCacheEntry<K, V>>),
+    Wait(BoxFuture<'static, std::result::Result<CustomCacheEntry<K, V>, oneshot::error::RecvError>>),
+    Miss(BoxFuture<'static, DynFetchResult<CustomCacheEntry<K, V>>>),
+}
+
+/// The inner future of [`CustomFetch`].
+pub struct CustomFetchInner<K, V, ER>
+where
+    K: Key,
+    V: Value,
+{
+    inner: DynFetchInner<K, V>,
+    /// Recover the type-erased fetch error. `ER` is always `'static` when the fetch future is created.
+    downcast: fn(Box<dyn DynFetchError>) -> ER,
+}
+
+impl<K, V, ER> CustomFetchInner<K, V, ER>
+where
+    K: Key,
+    V: Value,
+{
+    /// Get the fetch state.
+    pub fn state(&self) -> FetchState {
+        match self.inner {
+            DynFetchInner::Hit(_) => FetchState::Hit,
+            DynFetchInner::Wait(_) => FetchState::Wait,
+            DynFetchInner::Miss(_) => FetchState::Miss,
+        }
+    }
+}
+
+impl<K, V, ER> Future for CustomFetchInner<K, V, ER>
+where
+    K: Key,
+    V: Value,
+    ER: From<oneshot::error::RecvError>,
+{
+    type Output = Diversion<std::result::Result<CustomCacheEntry<K, V>, ER>, FetchMark>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        match &mut this.inner {
+            DynFetchInner::Hit(opt) => Poll::Ready(Ok(opt.take().unwrap()).into()),
+            DynFetchInner::Wait(waiter) => waiter.poll_unpin(cx).map_err(|err| err.into()).map(Diversion::from),
+            DynFetchInner::Miss(handle) => handle.poll_unpin(cx).map(|Diversion { target, store }| Diversion {
+                target: target.map_err(this.downcast),
+                store,
+            }),
+        }
+    }
+}
+
+/// A future that is used to get entry value from the remote storage for the in-memory cache with a user-defined
+/// eviction algorithm.
+pub type CustomFetch<K, V, ER> =
+    DiversionFuture<CustomFetchInner<K, V, ER>, std::result::Result<CustomCacheEntry<K, V>, ER>, FetchMark>;
+
+fn downcast<ER>(err: Box<dyn DynFetchError>) -> ER
+where
+    ER: 'static,
+{
+    *err.into_any().downcast::<ER>().unwrap()
+}
+
+/// In-memory cache with a user-defined eviction algorithm.
+///
+/// Use [`CacheBuilder::with_custom_eviction`](crate::CacheBuilder::with_custom_eviction) to build it.
+pub struct CustomCache<K, V, S = RandomState>
+where
+    K: Key,
+    V: Value,
+    S: HashBuilder,
+{
+    cache: Arc<dyn DynCache<K, V>>,
+    hash_builder: S,
+}
+
+impl<K, V, S> CustomCache<K, V, S>
+where
+    K: Key,
+    V: Value,
+    S: HashBuilder,
+{
+    pub(crate) fn new(factory: CustomCacheFactory<K, V>, config: CustomCacheConfig<K, V>, hash_builder: S) -> Self {
+        Self {
+            cache: factory(config),
+            hash_builder,
+        }
+    }
+
+    /// Insert cache entry to the in-memory cache.
+    pub fn insert(&self, key: K, value: V) -> CustomCacheEntry<K, V> {
+        self.insert_with_context(key, value, CacheContext::default())
+    }
+
+    /// Insert cache entry with cache context to the in-memory cache.
+    pub fn insert_with_context(&self, key: K, value: V, context: CacheContext) -> CustomCacheEntry<K, V> {
+        let hash = self.hash_builder.hash_one(&key);
+        self.cache.clone().emplace(hash, key, value, context, false)
+    }
+
+    /// Temporarily insert cache entry to the in-memory cache.
+    pub fn deposit(&self, key: K, value: V) -> CustomCacheEntry<K, V> {
+        self.deposit_with_context(key, value, CacheContext::default())
+    }
+
+    /// Temporarily insert cache entry with cache context to the in-memory cache.
+    pub fn deposit_with_context(&self, key: K, value: V, context: CacheContext) -> CustomCacheEntry<K, V> {
+        let hash = self.hash_builder.hash_one(&key);
+        self.cache.clone().emplace(hash, key, value, context, true)
+    }
+
+    /// Remove a cached entry with the given key from the in-memory cache.
+    pub fn remove<Q>(&self, key: &Q) -> Option<CustomCacheEntry<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let hash = self.hash_builder.hash_one(key);
+        self.cache.clone().remove(hash, &|k: &K| k.borrow() == key)
+    }
+
+    /// Get cached entry with the given key from the in-memory cache.
+    pub fn get<Q>(&self, key: &Q) -> Option<CustomCacheEntry<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let hash = self.hash_builder.hash_one(key);
+        self.cache.clone().get(hash, &|k: &K| k.borrow() == key)
+    }
+
+    /// Check if the in-memory cache contains a cached entry with the given key.
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let hash = self.hash_builder.hash_one(key);
+        self.cache.contains(hash, &|k: &K| k.borrow() == key)
+    }
+
+    /// Access the cached entry with the given key but don't return.
+    pub fn touch<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let hash = self.hash_builder.hash_one(key);
+        self.cache.touch(hash, &|k: &K| k.borrow() == key)
+    }
+
+    /// Pin the cached entry with the given key, so it is never evicted until unpinned or removed.
+    ///
+    /// See [`Cache::pin`](crate::Cache::pin).
+    pub fn pin<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let hash = self.hash_builder.hash_one(key);
+        self.cache.pin(hash, &|k: &K| k.borrow() == key)
+    }
+
+    /// Unpin the cached entry with the given key, so it can be evicted again.
+    ///
+    /// See [`Cache::unpin`](crate::Cache::unpin).
+    pub fn unpin<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let hash = self.hash_builder.hash_one(key);
+        self.cache.unpin(hash, &|k: &K| k.borrow() == key)
+    }
+
+    /// Recompute the weight of the cached entry with the given key.
+    ///
+    /// See [`Cache::update_weight`](crate::Cache::update_weight).
+    pub fn update_weight<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let hash = self.hash_builder.hash_one(key);
+        self.cache.update_weight(hash, &|k: &K| k.borrow() == key)
+    }
+
+    /// Clear the in-memory cache.
+    pub fn clear(&self) {
+        self.cache.clear()
+    }
+
+    /// Iterate over the entries of the in-memory cache, one shard at a time.
+    ///
+    /// See [`Cache::iter`](crate::Cache::iter).
+    pub fn iter(&self) -> impl Iterator<Item = CustomCacheEntry<K, V>> + '_ {
+        (0..self.cache.shards()).flat_map(|shard| self.shard_entries(shard))
+    }
+
+    pub(crate) fn shard_entries(&self, shard: usize) -> Vec<CustomCacheEntry<K, V>> {
+        self.cache.clone().shard_entries(shard)
+    }
+
+    /// Iterate over the keys of the in-memory cache, one shard at a time.
+    ///
+    /// See [`Cache::keys`](crate::Cache::keys).
+    pub fn keys(&self) -> impl Iterator<Item = K> + '_
+    where
+        K: Clone,
+    {
+        (0..self.cache.shards()).flat_map(|shard| {
+            let mut keys = vec![];
+            self.visit_shard_keys(shard, &mut |key: &K| keys.push(key.clone()));
+            keys
+        })
+    }
+
+    pub(crate) fn visit_shard_keys(&self, shard: usize, f: &mut dyn FnMut(&K)) {
+        self.cache.visit_shard_keys(shard, f)
+    }
+
+    /// Retain only the entries that the predicate returns `true`, one shard at a time.
+    ///
+    /// See [`Cache::retain`](crate::Cache::retain).
+    pub fn retain(&self, mut f: impl FnMut(&K, &V) -> bool) {
+        self.cache.retain(&mut f)
+    }
+
+    /// Resize the capacity of the in-memory cache.
+    ///
+    /// See [`Cache::resize`](crate::Cache::resize).
+    pub fn resize(&self, capacity: usize) {
+        self.cache.resize(capacity)
+    }
+
+    /// Update the eviction config of the in-memory cache.
+    ///
+    /// See [`Cache::update_eviction_config`](crate::Cache::update_eviction_config).
+    ///
+    /// Returns the config back if it is not the config type of the eviction algorithm the cache is built with, or if it
+    /// is invalid. The cache is left unchanged in that case.
+    pub fn update_eviction_config<C>(&self, config: C) -> std::result::Result<(), C>
+    where
+        C: Any + Send,
+    {
+        self.cache
+            .update_eviction_config(Box::new(config))
+            .map_err(|config| *config.downcast::<C>().unwrap())
+    }
+
+    /// Get the capacity of the in-memory cache.
+    pub fn capacity(&self) -> usize {
+        self.cache.capacity()
+    }
+
+    /// Get the usage of the in-memory cache.
+    pub fn usage(&self) -> usize {
+        self.cache.usage()
+    }
+
+    /// Get the estimated miss ratio curve of the in-memory cache.
+    ///
+    /// See [`Cache::miss_ratio_curve`](crate::Cache::miss_ratio_curve).
+    pub fn miss_ratio_curve(&self) -> Option<MissRatioCurve> {
+        self.cache.miss_ratio_curve()
+    }
+
+    /// Get the capacity of the pinned entries of the in-memory cache.
+    pub fn pinned_capacity(&self) -> usize {
+        self.cache.pinned_capacity()
+    }
+
+    /// Get the usage of the pinned entries of the in-memory cache.
+    pub fn pinned_usage(&self) -> usize {
+        self.cache.pinned_usage()
+    }
+
+    /// Get the hash builder of the in-memory cache.
+    pub fn hash_builder(&self) -> &S {
+        &self.hash_builder
+    }
+
+    /// Get the shards of the in-memory cache.
+    pub fn shards(&self) -> usize {
+        self.cache.shards()
+    }
+}
+
+impl<K, V, S> CustomCache<K, V, S>
+where
+    K: Key + Clone,
+    V: Value,
+    S: HashBuilder,
+{
+    /// Get the cached entry with the given key from the in-memory cache.
+    ///
+    /// Use `fetch` to fetch the cache value from the remote storage on cache miss.
+    ///
+    /// The concurrent fetch requests will be deduplicated.
+    pub fn fetch<F, FU, ER>(&self, key: K, fetch: F) -> CustomFetch<K, V, ER>
+    where
+        F: FnOnce() -> FU,
+        FU: Future<Output = std::result::Result<V, ER>> + Send + 'static,
+        ER: Send + 'static + Debug,
+    {
+        self.fetch_inner(
+            key,
+            CacheContext::default(),
+            fetch,
+            &tokio::runtime::Handle::current().into(),
+        )
+    }
+
+    /// Get the cached entry with the given key and context from the in-memory cache.
+    ///
+    /// Use `fetch` to fetch the cache value from the remote storage on cache miss.
+    ///
+    /// The concurrent fetch requests will be deduplicated.
+    pub fn fetch_with_context<F, FU, ER>(&self, key: K, context: CacheContext, fetch: F) -> CustomFetch<K, V, ER>
+    where
+        F: FnOnce() -> FU,
+        FU: Future<Output = std::result::Result<V, ER>> + Send + 'static,
+        ER: Send + 'static + Debug,
+    {
+        self.fetch_inner(key, context, fetch, &tokio::runtime::Handle::current().into())
+    }
+
+    #[doc(hidden)]
+    pub fn fetch_inner<F, FU, ER, ID>(
+        &self,
+        key: K,
+        context: CacheContext,
+        fetch: F,
+        runtime: &SingletonHandle,
+    ) -> CustomFetch<K, V, ER>
+    where
+        F: FnOnce() -> FU,
+        FU: Future<Output = ID> + Send + 'static,
+        ER: Send + 'static + Debug,
+        ID: Into<Diversion<std::result::Result<V, ER>, FetchMark>>,
+    {
+        let hash = self.hash_builder.hash_one(&key);
+        let fetch: DynFetchFn<'_, V> = Box::new(move || {
+            let future = fetch();
+            async move {
+                let Diversion { target, store } = future.await.into();
+                Diversion {
+                    target: target.map_err(|e| Box::new(e) as Box<dyn DynFetchError>),
+                    store,
+                }
+            }
+            .boxed()
+        });
+        let inner = self.cache.clone().fetch(hash, key, &K::clone, context, fetch, runtime);
+        CustomFetch::new(CustomFetchInner {
+            inner,
+            downcast: downcast::<ER>,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::{
+        eviction::test_utils::{conformance, TestEviction},
+        handle::BaseHandle,
+        Cache, CacheBuilder, CacheEntry, EvictionConfig, FifoConfig,
+    };
+
+    /// A LIFO eviction container, which evicts the most recently inserted entry first.
+    struct Lifo<T>
+    where
+        T: Send + Sync + 'static,
+    {
+        stack: Vec<NonNull<LifoHandle<T>>>,
+    }
+
+    unsafe impl<T> Send for Lifo<T> where T: Send + Sync + 'static {}
+    unsafe impl<T> Sync for Lifo<T> where T: Send + Sync + 'static {}
+
+    struct LifoHandle<T>
+    where
+        T: Send + Sync + 'static,
+    {
+        base: BaseHandle<T, CacheContext>,
+    }
+
+    impl<T> Default for LifoHandle<T>
+    where
+        T: Send + Sync + 'static,
+    {
+        fn default() -> Self {
+            Self {
+                base: BaseHandle::new(),
+            }
+        }
+    }
+
+    impl<T> Handle for LifoHandle<T>
+    where
+        T: Send + Sync + 'static,
+    {
+        type Data = T;
+        type Context = CacheContext;
+
+        fn base(&self) -> &BaseHandle<Self::Data, Self::Context> {
+            &self.base
+        }
+
+        fn base_mut(&mut self) -> &mut BaseHandle<Self::Data, Self::Context> {
+            &mut self.base
+        }
+    }
+
+    #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+    struct LifoConfig;
+
+    impl<T> Eviction for Lifo<T>
+    where
+        T: Send + Sync + 'static,
+    {
+        type Handle = LifoHandle<T>;
+        type Config = LifoConfig;
+
+        unsafe fn new(_capacity: usize, _config: &Self::Config) -> Self
+        where
+            Self: Sized,
+        {
+            Self { stack: vec![] }
+        }
+
+        unsafe fn push(&mut self, mut ptr: NonNull<Self::Handle>) {
+            self.stack.push(ptr);
+            ptr.as_mut().base_mut().set_in_eviction(true);
+        }
+
+        unsafe fn pop(&mut self) -> Option<NonNull<Self::Handle>> {
+            self.stack.pop().map(|mut ptr| {
+                ptr.as_mut().base_mut().set_in_eviction(false);
+                ptr
+            })
+        }
+
+        unsafe fn acquire(&mut self, _: NonNull<Self::Handle>) {}
+
+        unsafe fn release(&mut self, _: NonNull<Self::Handle>) {}
+
+        unsafe fn remove(&mut self, mut ptr: NonNull<Self::Handle>) {
+            let index = self.stack.iter().position(|p| *p == ptr).unwrap();
+            self.stack.remove(index);
+            ptr.as_mut().base_mut().set_in_eviction(false);
+        }
+
+        unsafe fn clear(&mut self) -> Vec<NonNull<Self::Handle>> {
+            let mut res = std::mem::take(&mut self.stack);
+            res.iter_mut()
+                .for_each(|ptr| ptr.as_mut().base_mut().set_in_eviction(false));
+            res
+        }
+
+        fn len(&self) -> usize {
+            self.stack.len()
+        }
+
+        fn is_empty(&self) -> bool {
+            self.stack.is_empty()
+        }
+    }
+
+    impl<T> TestEviction for Lifo<T>
+    where
+        T: Send + Sync + 'static + Clone,
+    {
+        fn dump(&self) -> Vec<T> {
+            self.stack
+                .iter()
+                .map(|ptr| unsafe { ptr.as_ref().base().data_unwrap_unchecked().clone() })
+                .collect()
+        }
+    }
+
+    fn lifo(capacity: usize) -> Cache<u64, u64> {
+        CacheBuilder::new(capacity)
+            .with_shards(1)
+            .with_custom_eviction::<Lifo<(u64, u64)>>(LifoConfig)
+            .build()
+    }
+
+    #[test]
+    fn test_lifo_conformance() {
+        conformance::<Lifo<u64>>(&LifoConfig);
+    }
+
+    #[test]
+    fn test_custom_cache_eviction() {
+        let cache = lifo(4);
+        assert!(matches!(cache, Cache::Custom(_)));
+
+        for i in 0..4 {
+            cache.insert(i, i);
+        }
+        // The most recently inserted entry is evicted.
+        let e4 = cache.insert(4, 4);
+        assert!(matches!(e4, CacheEntry::Custom(_)));
+        assert_eq!(*e4, 4);
+        assert!(!cache.contains(&3));
+        for i in [0, 1, 2, 4] {
+            assert_eq!(*cache.get(&i).unwrap(), i);
+        }
+        assert_eq!(cache.usage(), 4);
+
+        let e2 = cache.remove(&2).unwrap();
+        assert!(e2.is_outdated());
+        assert_eq!(cache.usage(), 4);
+        drop(e2);
+        assert_eq!(cache.usage(), 3);
+
+        cache.clear();
+        assert_eq!(cache.usage(), 1);
+        drop(e4);
+        assert_eq!(cache.usage(), 0);
+    }
+
+    #[test]
+    fn test_custom_cache_deposit() {
+        let cache = lifo(4);
+        let e = cache.deposit(1, 1);
+        assert!(cache.contains(&1));
+        drop(e);
+        assert!(!cache.contains(&1));
+    }
+
+    #[test]
+    fn test_custom_cache_entry() {
+        let cache = lifo(4);
+        let e1 = cache.insert(1, 1);
+        assert_eq!(e1.refs(), 1);
+        let e2 = e1.clone();
+        assert_eq!(e1.refs(), 2);
+        drop(e2);
+        assert_eq!(e1.refs(), 1);
+
+        let debug = format!("{e1:?}");
+        assert!(debug.contains(&format!("hash: {}", e1.hash())), "{debug}");
+        assert!(debug.contains("weight: 1"), "{debug}");
+    }
+
+    #[test]
+    fn test_custom_cache_update_eviction_config() {
+        let cache = lifo(4);
+        assert!(matches!(
+            cache.update_eviction_config(FifoConfig::default()),
+            Err(EvictionConfig::Fifo(_))
+        ));
+
+        let Cache::Custom(cache) = cache else { unreachable!() };
+        cache.update_eviction_config(LifoConfig).unwrap();
+        assert_eq!(cache.update_eviction_config(42u64), Err(42));
+    }
+
+    #[test]
+    fn test_custom_cache_hash_builder() {
+        let cache: Cache<u64, u64, ahash::RandomState> = CacheBuilder::new(4)
+            .with_custom_eviction::<Lifo<(u64, u64)>>(LifoConfig)
+            .with_hash_builder(ahash::RandomState::with_seeds(1, 2, 3, 4))
+            .build();
+        assert!(matches!(cache, Cache::Custom(_)));
+        let e = cache.insert(1, 1);
+        assert_eq!(e.hash(), cache.hash(&1));
+        assert_eq!(cache.get(&1).unwrap().hash(), e.hash());
+
+        let cache: Cache<u64, u64> = CacheBuilder::new(4)
+            .with_custom_eviction::<Lifo<(u64, u64)>>(LifoConfig)
+            .with_eviction_config(crate::FifoConfig::default())
+            .build();
+        assert!(matches!(cache, Cache::Fifo(_)));
+    }
+
+    #[tokio::test]
+    async fn test_custom_cache_fetch() {
+        let cache = lifo(4);
+
+        let fetch = cache.fetch(1, || async move { Ok::<_, anyhow::Error>(1) });
+        assert_eq!(fetch.state(), FetchState::Miss);
+        assert_eq!(*fetch.await.unwrap(), 1);
+
+        let fetch = cache.fetch(1, || async move { Ok::<_, anyhow::Error>(0) });
+        assert_eq!(fetch.state(), FetchState::Hit);
+        assert_eq!(*fetch.await.unwrap(), 1);
+
+        let (tx, rx) = oneshot::channel::<()>();
+        let f1 = cache.fetch(2, || async move {
+            rx.await.unwrap();
+            Ok::<_, anyhow::Error>(2)
+        });
+        let f2 = cache.fetch(2, || async move { Ok::<_, anyhow::Error>(0) });
+        assert_eq!(f1.state(), FetchState::Miss);
+        assert_eq!(f2.state(), FetchState::Wait);
+        tx.send(()).unwrap();
+        assert_eq!(*f1.await.unwrap(), 2);
+        assert_eq!(*f2.await.unwrap(), 2);
+
+        let res = cache
+            .fetch(3, || async move { Err::<u64, _>(anyhow::anyhow!("fetch error")) })
+            .await;
+        assert_eq!(res.unwrap_err().to_string(), "fetch error");
+        assert!(!cache.contains(&3));
+    }
+}
@@ -14,14 +14,16 @@
 
 use std::{fmt::Debug, ptr::NonNull};
 
+use foyer_common::{assert::OptionExt, strict_assert, strict_assert_eq};
 use foyer_intrusive::{
+    adapter::Link,
     dlist::{Dlist, DlistLink},
     intrusive_adapter,
 };
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    eviction::Eviction,
+    eviction::{priority_pool_capacities, validate_priority_pool_ratios, Eviction},
     handle::{BaseHandle, Handle},
     CacheContext,
 };
@@ -47,6 +49,7 @@ where
 {
     link: DlistLink,
     base: BaseHandle<T, FifoContext>,
+    pool: usize,
 }
 
 impl<T> Debug for FifoHandle<T>
@@ -68,6 +71,7 @@ where
         Self {
             link: DlistLink::default(),
             base: BaseHandle::new(),
+            pool: 0,
         }
     }
 }
@@ -90,13 +94,72 @@ where
 
 /// Fifo eviction algorithm config.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
-pub struct FifoConfig {}
+pub struct FifoConfig {
+    /// The capacity ratio of the pool of each priority above the lowest one.
+    ///
+    /// The `i`-th ratio is the ratio of the pool of priority `i + 1`, and the pool of the lowest priority is unbounded.
+    /// Entries are pushed to the pool of their priority, and entries with priorities beyond the pools are pushed to the
+    /// pool of the highest priority. When the weight of a pool exceeds `capacity * ratio`, the oldest entries of the
+    /// pool are overflowed to the pool of the next lower priority. Entries are always popped from the pool of the
+    /// lowest priority first.
+    ///
+    /// The default value (no ratio) makes the eviction a plain FIFO.
+    ///
+    /// # Panic
+    ///
+    /// Panics if any value is not in [0, 1.0], or the sum of the values exceeds 1.0.
+    #[serde(default)]
+    pub priority_pool_ratios: Vec<f64>,
+}
 
 pub struct Fifo<T>
 where
     T: Send + Sync + 'static,
 {
-    queue: Dlist<FifoHandleDlistAdapter<T>>,
+    pools: Vec<Dlist<FifoHandleDlistAdapter<T>>>,
+
+    weights: Vec<usize>,
+    weight_capacities: Vec<usize>,
+}
+
+impl<T> Fifo<T>
+where
+    T: Send + Sync + 'static,
+{
+    unsafe fn may_overflow_pools(&mut self, priority: usize) {
+        for pool in (1..=priority).rev() {
+            while self.weights[pool] > self.weight_capacities[pool] {
+                strict_assert!(!self.pools[pool].is_empty());
+
+                // overflow the oldest entry to the pool of the next lower priority
+                let mut ptr = self.pools[pool].pop_front().strict_unwrap_unchecked();
+                let weight = ptr.as_ref().base().weight();
+                strict_assert_eq!(ptr.as_ref().pool, pool);
+                ptr.as_mut().pool = pool - 1;
+                self.weights[pool] -= weight;
+                self.weights[pool - 1] += weight;
+                self.pools[pool - 1].push_back(ptr);
+            }
+        }
+    }
+
+    /// Change the count of the pools, the entries of the removed pools are moved to the pool of the highest priority.
+    unsafe fn resize_pools(&mut self, pools: usize) {
+        while self.pools.len() < pools {
+            self.pools.push(Dlist::new());
+            self.weights.push(0);
+        }
+        while self.pools.len() > pools {
+            let mut removed = self.pools.pop().strict_unwrap_unchecked();
+            let weight = self.weights.pop().strict_unwrap_unchecked();
+            let pool = self.pools.len() - 1;
+            while let Some(mut ptr) = removed.pop_front() {
+                ptr.as_mut().pool = pool;
+                self.pools[pool].push_back(ptr);
+            }
+            self.weights[pool] += weight;
+        }
+    }
 }
 
 impl<T> Eviction for Fifo<T>
@@ -106,23 +169,64 @@ where
     type Handle = FifoHandle<T>;
     type Config = FifoConfig;
 
-    unsafe fn new(_capacity: usize, _config: &Self::Config) -> Self
+    unsafe fn new(capacity: usize, config: &Self::Config) -> Self
     where
         Self: Sized,
     {
-        Self { queue: Dlist::new() }
+        let weight_capacities = priority_pool_capacities(capacity, &config.priority_pool_ratios);
+
+        Self {
+            pools: (0..weight_capacities.len()).map(|_| Dlist::new()).collect(),
+            weights: vec![0; weight_capacities.len()],
+            weight_capacities,
+        }
+    }
+
+    fn update(&mut self, capacity: usize, config: &Self::Config) {
+        let weight_capacities = priority_pool_capacities(capacity, &config.priority_pool_ratios);
+        let pools = weight_capacities.len();
+        unsafe { self.resize_pools(pools) };
+        self.weight_capacities = weight_capacities;
+        unsafe { self.may_overflow_pools(pools - 1) };
+    }
+
+    fn validate(config: &Self::Config) -> std::result::Result<(), String> {
+        validate_priority_pool_ratios(&config.priority_pool_ratios)
+    }
+
+    fn priorities(config: &Self::Config) -> usize {
+        (config.priority_pool_ratios.len() + 1).max(2)
     }
 
     unsafe fn push(&mut self, mut ptr: NonNull<Self::Handle>) {
-        self.queue.push_back(ptr);
-        ptr.as_mut().base_mut().set_in_eviction(true);
+        let handle = ptr.as_mut();
+
+        strict_assert!(!handle.link.is_linked());
+
+        // Priorities beyond the pools share the pool of the highest priority.
+        let priority = handle.base().context().0.priority().min(self.pools.len() - 1);
+        handle.pool = priority;
+        self.weights[priority] += handle.base().weight();
+        self.pools[priority].push_back(ptr);
+        handle.base_mut().set_in_eviction(true);
+
+        self.may_overflow_pools(priority);
     }
 
     unsafe fn pop(&mut self) -> Option<NonNull<Self::Handle>> {
-        self.queue.pop_front().map(|mut ptr| {
-            ptr.as_mut().base_mut().set_in_eviction(false);
-            ptr
-        })
+        let pool = self.pools.iter().position(|pool| !pool.is_empty())?;
+        let mut ptr = self.pools[pool].pop_front().strict_unwrap_unchecked();
+
+        let handle = ptr.as_mut();
+        strict_assert_eq!(handle.pool, pool);
+        self.weights[pool] -= handle.base().weight();
+        handle.base_mut().set_in_eviction(false);
+
+        Some(ptr)
+    }
+
+    unsafe fn peek(&self) -> Option<NonNull<Self::Handle>> {
+        self.pools.iter().find_map(|pool| pool.front()).map(NonNull::from)
     }
 
     unsafe fn release(&mut self, _: NonNull<Self::Handle>) {}
@@ -130,22 +234,36 @@ where
     unsafe fn acquire(&mut self, _: NonNull<Self::Handle>) {}
 
     unsafe fn remove(&mut self, mut ptr: NonNull<Self::Handle>) {
-        let p = self.queue.iter_mut_from_raw(ptr.as_mut().link.raw()).remove().unwrap();
+        let handle = ptr.as_mut();
+        let pool = handle.pool;
+        let p = self.pools[pool].iter_mut_from_raw(handle.link.raw()).remove().unwrap();
         assert_eq!(p, ptr);
-        ptr.as_mut().base_mut().set_in_eviction(false);
+        self.weights[pool] -= handle.base().weight();
+        handle.base_mut().set_in_eviction(false);
+    }
+
+    unsafe fn reweigh(&mut self, mut ptr: NonNull<Self::Handle>, weight: usize) {
+        let handle = ptr.as_mut();
+        let pool = handle.pool;
+        self.weights[pool] = self.weights[pool] - handle.base().weight() + weight;
+        handle.base_mut().set_weight(weight);
+        self.may_overflow_pools(pool);
     }
 
     unsafe fn clear(&mut self) -> Vec<NonNull<Self::Handle>> {
         let mut res = Vec::with_capacity(self.len());
-        while let Some(mut ptr) = self.queue.pop_front() {
-            ptr.as_mut().base_mut().set_in_eviction(false);
-            res.push(ptr);
+        for pool in self.pools.iter_mut() {
+            while let Some(mut ptr) = pool.pop_front() {
+                ptr.as_mut().base_mut().set_in_eviction(false);
+                res.push(ptr);
+            }
         }
+        self.weights.iter_mut().for_each(|weight| *weight = 0);
         res
     }
 
     fn len(&self) -> usize {
-        self.queue.len()
+        self.pools.iter().map(|pool| pool.len()).sum()
     }
 
     fn is_empty(&self) -> bool {
@@ -162,15 +280,19 @@ pub mod tests {
     use itertools::Itertools;
 
     use super::*;
-    use crate::{eviction::test_utils::TestEviction, handle::HandleExt};
+    use crate::{
+        eviction::test_utils::{conformance, TestEviction},
+        handle::HandleExt,
+    };
 
     impl<T> TestEviction for Fifo<T>
     where
         T: Send + Sync + 'static + Clone,
     {
         fn dump(&self) -> Vec<T> {
-            self.queue
+            self.pools
                 .iter()
+                .flat_map(|pool| pool.iter())
                 .map(|handle| handle.base().data_unwrap_unchecked().clone())
                 .collect_vec()
         }
@@ -180,11 +302,26 @@ pub mod tests {
     type TestFifo = Fifo<u64>;
 
     unsafe fn new_test_fifo_handle_ptr(data: u64) -> NonNull<TestFifoHandle> {
+        new_test_fifo_handle_ptr_with_context(data, CacheContext::Default)
+    }
+
+    unsafe fn new_test_fifo_handle_ptr_with_context(data: u64, context: CacheContext) -> NonNull<TestFifoHandle> {
         let mut handle = Box::<TestFifoHandle>::default();
-        handle.init(0, data, 1, FifoContext(CacheContext::Default));
+        handle.init(0, data, 1, FifoContext(context));
         NonNull::new_unchecked(Box::into_raw(handle))
     }
 
+    fn dump_test_fifo(fifo: &TestFifo) -> Vec<Vec<u64>> {
+        fifo.pools
+            .iter()
+            .map(|pool| {
+                pool.iter()
+                    .map(|handle| *handle.base().data_unwrap_unchecked())
+                    .collect_vec()
+            })
+            .collect_vec()
+    }
+
     unsafe fn del_test_fifo_handle_ptr(ptr: NonNull<TestFifoHandle>) {
         let _ = Box::from_raw(ptr.as_ptr());
     }
@@ -194,7 +331,7 @@ pub mod tests {
         unsafe {
             let ptrs = (0..8).map(|i| new_test_fifo_handle_ptr(i)).collect_vec();
 
-            let mut fifo = TestFifo::new(100, &FifoConfig {});
+            let mut fifo = TestFifo::new(100, &FifoConfig::default());
 
             // 0, 1, 2, 3
             fifo.push(ptrs[0]);
@@ -225,4 +362,77 @@ pub mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_fifo_priority_pools() {
+        unsafe {
+            let ptrs = (0..12)
+                .map(|i| new_test_fifo_handle_ptr_with_context(i, CacheContext::Priority((i / 3) as u8)))
+                .collect_vec();
+
+            let config = FifoConfig {
+                priority_pool_ratios: vec![0.1, 0.1, 0.2],
+            };
+            let mut fifo = TestFifo::new(10, &config);
+            assert_eq!(&fifo.weight_capacities[1..], &[1, 1, 2]);
+            assert_eq!(TestFifo::priorities(&config), 4);
+            assert_eq!(TestFifo::priorities(&FifoConfig::default()), 2);
+
+            // [], [], [9], [10, 11]
+            fifo.push(ptrs[9]);
+            fifo.push(ptrs[10]);
+            fifo.push(ptrs[11]);
+            assert_eq!(dump_test_fifo(&fifo), vec![vec![], vec![], vec![9], vec![10, 11]]);
+
+            // [9], [3], [6], [10, 11]
+            fifo.push(ptrs[6]);
+            fifo.push(ptrs[3]);
+            assert_eq!(dump_test_fifo(&fifo), vec![vec![9], vec![3], vec![6], vec![10, 11]]);
+
+            // overflow cascades through multiple pools
+            //
+            // [9, 3, 0], [6], [7], [10, 11]
+            fifo.push(ptrs[7]);
+            fifo.push(ptrs[0]);
+            assert_eq!(
+                dump_test_fifo(&fifo),
+                vec![vec![9, 3, 0], vec![6], vec![7], vec![10, 11]]
+            );
+            assert_eq!(fifo.weights, vec![3, 1, 1, 2]);
+
+            // entries of the lowest priority pool are evicted first
+            assert_eq!(fifo.pop(), Some(ptrs[9]));
+            assert_eq!(fifo.pop(), Some(ptrs[3]));
+            assert_eq!(fifo.pop(), Some(ptrs[0]));
+            assert_eq!(fifo.pop(), Some(ptrs[6]));
+
+            // [], [], [7], [11]
+            fifo.remove(ptrs[10]);
+            assert_eq!(dump_test_fifo(&fifo), vec![vec![], vec![], vec![7], vec![11]]);
+            assert_eq!(fifo.weights, vec![0, 0, 1, 1]);
+
+            assert_eq!(fifo.clear(), vec![ptrs[7], ptrs[11]]);
+            assert_eq!(fifo.weights, vec![0, 0, 0, 0]);
+
+            for ptr in ptrs {
+                del_test_fifo_handle_ptr(ptr);
+            }
+        }
+    }
+
+    #[test]
+    fn test_fifo_conformance() {
+        conformance::<Fifo<u64>>(&FifoConfig::default());
+    }
+
+    #[test]
+    fn test_fifo_config_serde() {
+        use serde::de::value::MapDeserializer;
+
+        // The config serialized before the priority pools are introduced.
+        let deserializer: MapDeserializer<'_, _, serde::de::value::Error> =
+            MapDeserializer::new(std::iter::empty::<(&str, f64)>());
+        let config = FifoConfig::deserialize(deserializer).unwrap();
+        assert!(config.priority_pool_ratios.is_empty());
+    }
 }
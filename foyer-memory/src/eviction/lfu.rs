@@ -54,6 +54,18 @@ pub struct LfuConfig {
     ///
     /// See [`CMSketchU16::new`].
     pub cmsketch_confidence: f64,
+
+    /// The frequency weight of each priority, from the lowest priority to the highest one.
+    ///
+    /// When choosing the victim between the heads of `window` and `probation`, the estimated frequency of each
+    /// candidate is multiplied by the weight of its priority. Higher weights make entries of the priority harder to be
+    /// evicted. Priorities beyond the given weights use the weight of the highest priority.
+    ///
+    /// The default value (no weight) weighs all priorities with `1.0`.
+    ///
+    /// Must be non-negative.
+    #[serde(default)]
+    pub priority_frequency_weights: Vec<f64>,
 }
 
 impl Default for LfuConfig {
@@ -63,6 +75,7 @@ impl Default for LfuConfig {
             protected_capacity_ratio: 0.8,
             cmsketch_eps: 0.001,
             cmsketch_confidence: 0.9,
+            priority_frequency_weights: vec![],
         }
     }
 }
@@ -153,7 +166,7 @@ unsafe impl<T> Sync for LfuHandle<T> where T: Send + Sync + 'static {}
 /// When `protected` is full, entries from it will overflow to `probation`.
 ///
 /// When evicting, the entry with a lower frequency from `window` or `probation` will be evicted first, then from
-/// `protected`.
+/// `protected`. The frequencies are weighted by the priorities of the entries.
 pub struct Lfu<T>
 where
     T: Send + Sync + 'static,
@@ -173,6 +186,11 @@ where
 
     step: usize,
     decay: usize,
+
+    priority_frequency_weights: Vec<f64>,
+
+    cmsketch_eps: f64,
+    cmsketch_confidence: f64,
 }
 
 impl<T> Lfu<T>
@@ -199,6 +217,41 @@ where
         }
     }
 
+    unsafe fn overflow_window(&mut self) {
+        // If `window` weight exceeds the capacity, overflow entry from `window` to `probation`.
+        while self.window_weight > self.window_weight_capacity {
+            strict_assert!(!self.window.is_empty());
+            let mut ptr = self.window.pop_front().strict_unwrap_unchecked();
+            let handle = ptr.as_mut();
+            self.decrease_queue_weight(handle);
+            handle.queue = Queue::Probation;
+            self.increase_queue_weight(handle);
+            self.probation.push_back(ptr);
+        }
+    }
+
+    unsafe fn overflow_protected(&mut self) {
+        // If `protected` weight exceeds the capacity, overflow entry from `protected` to `probation`.
+        while self.protected_weight > self.protected_weight_capacity {
+            strict_assert!(!self.protected.is_empty());
+            let mut ptr = self.protected.pop_front().strict_unwrap_unchecked();
+            let handle = ptr.as_mut();
+            self.decrease_queue_weight(handle);
+            handle.queue = Queue::Probation;
+            self.increase_queue_weight(handle);
+            self.probation.push_back(ptr);
+        }
+    }
+
+    fn weighted_frequency(&self, handle: &LfuHandle<T>) -> f64 {
+        let priority = handle.base().context().0.priority();
+        let weight = match self.priority_frequency_weights.as_slice() {
+            [] => 1.0,
+            weights => weights[priority.min(weights.len() - 1)],
+        };
+        self.frequencies.estimate(handle.base().hash()) as f64 * weight
+    }
+
     fn update_frequencies(&mut self, hash: u64) {
         self.frequencies.inc(hash);
         self.step += 1;
@@ -220,23 +273,9 @@ where
     where
         Self: Sized,
     {
-        assert!(
-            config.window_capacity_ratio > 0.0 && config.window_capacity_ratio < 1.0,
-            "window_capacity_ratio must be in (0, 1), given: {}",
-            config.window_capacity_ratio
-        );
-
-        assert!(
-            config.protected_capacity_ratio > 0.0 && config.protected_capacity_ratio < 1.0,
-            "protected_capacity_ratio must be in (0, 1), given: {}",
-            config.protected_capacity_ratio
-        );
-
-        assert!(
-            config.window_capacity_ratio + config.protected_capacity_ratio < 1.0,
-            "must guarantee: window_capacity_ratio + protected_capacity_ratio < 1, given: {}",
-            config.window_capacity_ratio + config.protected_capacity_ratio
-        );
+        if let Err(e) = Self::validate(config) {
+            panic!("{e}");
+        }
 
         let window_weight_capacity = (capacity as f64 * config.window_capacity_ratio) as usize;
         let protected_weight_capacity = (capacity as f64 * config.protected_capacity_ratio) as usize;
@@ -255,9 +294,68 @@ where
             frequencies,
             step: 0,
             decay,
+            priority_frequency_weights: config.priority_frequency_weights.clone(),
+            cmsketch_eps: config.cmsketch_eps,
+            cmsketch_confidence: config.cmsketch_confidence,
         }
     }
 
+    fn update(&mut self, capacity: usize, config: &Self::Config) {
+        self.window_weight_capacity = (capacity as f64 * config.window_capacity_ratio) as usize;
+        self.protected_weight_capacity = (capacity as f64 * config.protected_capacity_ratio) as usize;
+        self.priority_frequency_weights = config.priority_frequency_weights.clone();
+
+        // The count-min sketch can only be rebuilt, which drops the collected frequencies.
+        if self.cmsketch_eps != config.cmsketch_eps || self.cmsketch_confidence != config.cmsketch_confidence {
+            self.frequencies = CMSketchU16::new(config.cmsketch_eps, config.cmsketch_confidence);
+            self.decay = self.frequencies.width();
+            self.step = 0;
+            self.cmsketch_eps = config.cmsketch_eps;
+            self.cmsketch_confidence = config.cmsketch_confidence;
+        }
+
+        unsafe {
+            self.overflow_window();
+            self.overflow_protected();
+        }
+    }
+
+    fn validate(config: &Self::Config) -> std::result::Result<(), String> {
+        if !(config.window_capacity_ratio > 0.0 && config.window_capacity_ratio < 1.0) {
+            return Err(format!(
+                "window_capacity_ratio must be in (0, 1), given: {}",
+                config.window_capacity_ratio
+            ));
+        }
+
+        if !(config.protected_capacity_ratio > 0.0 && config.protected_capacity_ratio < 1.0) {
+            return Err(format!(
+                "protected_capacity_ratio must be in (0, 1), given: {}",
+                config.protected_capacity_ratio
+            ));
+        }
+
+        if config.window_capacity_ratio + config.protected_capacity_ratio >= 1.0 {
+            return Err(format!(
+                "must guarantee: window_capacity_ratio + protected_capacity_ratio < 1, given: {}",
+                config.window_capacity_ratio + config.protected_capacity_ratio
+            ));
+        }
+
+        if !config.priority_frequency_weights.iter().all(|weight| *weight >= 0.0) {
+            return Err(format!(
+                "priority_frequency_weights must be non-negative, given: {:?}",
+                config.priority_frequency_weights
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn priorities(config: &Self::Config) -> usize {
+        config.priority_frequency_weights.len().max(2)
+    }
+
     unsafe fn push(&mut self, mut ptr: NonNull<Self::Handle>) {
         let handle = ptr.as_mut();
 
@@ -272,16 +370,7 @@ where
         self.increase_queue_weight(handle);
         self.update_frequencies(handle.base().hash());
 
-        // If `window` weight exceeds the capacity, overflow entry from `window` to `probation`.
-        while self.window_weight > self.window_weight_capacity {
-            strict_assert!(!self.window.is_empty());
-            let mut ptr = self.window.pop_front().strict_unwrap_unchecked();
-            let handle = ptr.as_mut();
-            self.decrease_queue_weight(handle);
-            handle.queue = Queue::Probation;
-            self.increase_queue_weight(handle);
-            self.probation.push_back(ptr);
-        }
+        self.overflow_window();
     }
 
     unsafe fn pop(&mut self) -> Option<NonNull<Self::Handle>> {
@@ -292,8 +381,7 @@ where
             (None, Some(_)) => self.probation.pop_front(),
             (Some(_), None) => self.window.pop_front(),
             (Some(window), Some(probation)) => {
-                if self.frequencies.estimate(window.base().hash()) < self.frequencies.estimate(probation.base().hash())
-                {
+                if self.weighted_frequency(window) < self.weighted_frequency(probation) {
                     self.window.pop_front()
 
                     // TODO(MrCroxx): Rotate probation to prevent a high frequency but cold head holds back promotion
@@ -318,6 +406,23 @@ where
         Some(ptr)
     }
 
+    unsafe fn peek(&self) -> Option<NonNull<Self::Handle>> {
+        // Keep the same order as `pop`.
+        match (self.window.front(), self.probation.front()) {
+            (None, None) => self.protected.front(),
+            (None, Some(probation)) => Some(probation),
+            (Some(window), None) => Some(window),
+            (Some(window), Some(probation)) => {
+                if self.weighted_frequency(window) < self.weighted_frequency(probation) {
+                    Some(window)
+                } else {
+                    Some(probation)
+                }
+            }
+        }
+        .map(NonNull::from)
+    }
+
     unsafe fn release(&mut self, mut ptr: NonNull<Self::Handle>) {
         let handle = ptr.as_mut();
 
@@ -346,16 +451,7 @@ where
                 self.increase_queue_weight(handle);
                 self.protected.push_back(ptr);
 
-                // If `protected` weight exceeds the capacity, overflow entry from `protected` to `probation`.
-                while self.protected_weight > self.protected_weight_capacity {
-                    strict_assert!(!self.protected.is_empty());
-                    let mut ptr = self.protected.pop_front().strict_unwrap_unchecked();
-                    let handle = ptr.as_mut();
-                    self.decrease_queue_weight(handle);
-                    handle.queue = Queue::Probation;
-                    self.increase_queue_weight(handle);
-                    self.probation.push_back(ptr);
-                }
+                self.overflow_protected();
             }
             Queue::Protected => {
                 // Move to MRU position of `protected`.
@@ -392,6 +488,15 @@ where
         handle.base_mut().set_in_eviction(false);
     }
 
+    unsafe fn reweigh(&mut self, mut ptr: NonNull<Self::Handle>, weight: usize) {
+        let handle = ptr.as_mut();
+        self.decrease_queue_weight(handle);
+        handle.base_mut().set_weight(weight);
+        self.increase_queue_weight(handle);
+        self.overflow_window();
+        self.overflow_protected();
+    }
+
     unsafe fn clear(&mut self) -> Vec<NonNull<Self::Handle>> {
         let mut res = Vec::with_capacity(self.len());
 
@@ -424,7 +529,10 @@ mod tests {
     use itertools::Itertools;
 
     use super::*;
-    use crate::{eviction::test_utils::TestEviction, handle::HandleExt};
+    use crate::{
+        eviction::test_utils::{conformance, TestEviction},
+        handle::HandleExt,
+    };
 
     impl<T> TestEviction for Lfu<T>
     where
@@ -484,6 +592,7 @@ mod tests {
                 protected_capacity_ratio: 0.6,
                 cmsketch_eps: 0.01,
                 cmsketch_confidence: 0.95,
+                ..Default::default()
             };
             let mut lfu = TestLfu::new(10, &config);
 
@@ -561,4 +670,52 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_lfu_priority() {
+        unsafe {
+            let ptrs = (0..4)
+                .map(|i| {
+                    let context = if i == 0 {
+                        CacheContext::Priority(3)
+                    } else {
+                        CacheContext::Default
+                    };
+                    let mut handle = Box::<TestLfuHandle>::default();
+                    handle.init(i, i, 1, LfuContext(context));
+                    NonNull::new_unchecked(Box::into_raw(handle))
+                })
+                .collect_vec();
+
+            // window: 2, probation: 2, protected: 6
+            let config = LfuConfig {
+                window_capacity_ratio: 0.2,
+                protected_capacity_ratio: 0.6,
+                priority_frequency_weights: vec![1.0, 1.0, 4.0],
+                ..Default::default()
+            };
+            let mut lfu = TestLfu::new(10, &config);
+
+            for ptr in ptrs.iter() {
+                lfu.push(*ptr);
+            }
+            assert_test_lfu(&lfu, 4, 2, 2, 0, vec![2, 3, 0, 1]);
+
+            // All entries share the same frequency, but the weighted frequency of `0` is higher.
+            assert_eq!(lfu.pop(), Some(ptrs[2]));
+            assert_eq!(lfu.pop(), Some(ptrs[3]));
+            assert_eq!(lfu.pop(), Some(ptrs[0]));
+            assert_eq!(lfu.pop(), Some(ptrs[1]));
+            assert!(lfu.is_empty());
+
+            for ptr in ptrs {
+                let _ = Box::from_raw(ptr.as_ptr());
+            }
+        }
+    }
+
+    #[test]
+    fn test_lfu_conformance() {
+        conformance::<Lfu<u64>>(&LfuConfig::default());
+    }
 }
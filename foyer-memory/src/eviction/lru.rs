@@ -12,7 +12,7 @@
 //  See the License for the specific language governing permissions and
 //  limitations under the License.
 
-use std::{fmt::Debug, ptr::NonNull};
+use std::{borrow::Cow, fmt::Debug, ptr::NonNull};
 
 use foyer_common::{assert::OptionExt, strict_assert, strict_assert_eq};
 use foyer_intrusive::{
@@ -23,54 +23,79 @@ use foyer_intrusive::{
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    eviction::Eviction,
+    eviction::{priority_pool_capacities, validate_priority_pool_ratios, Eviction},
     handle::{BaseHandle, Handle},
     CacheContext,
 };
 
 /// LRU eviction algorithm config.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct LruConfig {
     /// The ratio of the high priority pool occupied.
     ///
     /// [`Lru`] guarantees that the high priority weight are always as larger as
     /// but no larger that the capacity * high priority pool ratio.
     ///
+    /// The high priority pool is the pool of [`CacheContext::Default`] and the higher priorities, entries with
+    /// [`CacheContext::LowPriority`] are kept in the low priority pool. It is ignored if
+    /// [`LruConfig::priority_pool_ratios`] is not empty.
+    ///
     /// # Panic
     ///
     /// Panics if the value is not in [0, 1.0].
     pub high_priority_pool_ratio: f64,
+
+    /// The capacity ratio of the pool of each priority above the lowest one, which overrides the high priority pool
+    /// ratio if not empty.
+    ///
+    /// The `i`-th ratio is the ratio of the pool of priority `i + 1`, and the pool of the lowest priority is unbounded.
+    /// Entries are pushed to (or moved to on access) the tail of the pool of their priority, and entries with
+    /// priorities beyond the pools go to the pool of the highest priority. When the weight of a pool exceeds
+    /// `capacity * ratio`, the least recently used entries of the pool are overflowed to the pool of the next lower
+    /// priority. Entries are always popped from the pool of the lowest priority first.
+    ///
+    /// [`Lru`] guarantees that the weight of each bounded pool is always as larger as but no larger than
+    /// the capacity * its ratio.
+    ///
+    /// # Panic
+    ///
+    /// Panics if any value is not in [0, 1.0], or the sum of the values exceeds 1.0.
+    pub priority_pool_ratios: Vec<f64>,
 }
 
 impl Default for LruConfig {
     fn default() -> Self {
         Self {
             high_priority_pool_ratio: 0.0,
+            priority_pool_ratios: vec![],
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub enum LruContext {
-    HighPriority,
-    LowPriority,
+impl LruConfig {
+    /// The capacity ratio of the pool of each priority above the lowest one in effect.
+    fn pool_ratios(&self) -> Cow<'_, [f64]> {
+        if self.priority_pool_ratios.is_empty() {
+            Cow::Owned(vec![self.high_priority_pool_ratio])
+        } else {
+            Cow::Borrowed(&self.priority_pool_ratios)
+        }
+    }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LruContext(pub CacheContext);
+
 impl From<CacheContext> for LruContext {
-    fn from(value: CacheContext) -> Self {
-        match value {
-            CacheContext::Default => Self::HighPriority,
-            CacheContext::LowPriority => Self::LowPriority,
-        }
+    fn from(context: CacheContext) -> Self {
+        Self(context)
     }
 }
 
 impl From<LruContext> for CacheContext {
-    fn from(value: LruContext) -> Self {
-        match value {
-            LruContext::HighPriority => CacheContext::Default,
-            LruContext::LowPriority => CacheContext::LowPriority,
-        }
+    fn from(context: LruContext) -> Self {
+        context.0
     }
 }
 
@@ -80,7 +105,7 @@ where
 {
     link: DlistLink,
     base: BaseHandle<T, LruContext>,
-    in_high_priority_pool: bool,
+    pool: usize,
 }
 
 impl<T> Debug for LruHandle<T>
@@ -102,7 +127,7 @@ where
         Self {
             link: DlistLink::default(),
             base: BaseHandle::new(),
-            in_high_priority_pool: false,
+            pool: 0,
         }
     }
 }
@@ -130,27 +155,48 @@ pub struct Lru<T>
 where
     T: Send + Sync + 'static,
 {
-    high_priority_list: Dlist<LruHandleDlistAdapter<T>>,
-    list: Dlist<LruHandleDlistAdapter<T>>,
+    pools: Vec<Dlist<LruHandleDlistAdapter<T>>>,
 
-    high_priority_weight: usize,
-    high_priority_weight_capacity: usize,
+    weights: Vec<usize>,
+    weight_capacities: Vec<usize>,
 }
 
 impl<T> Lru<T>
 where
     T: Send + Sync + 'static,
 {
-    unsafe fn may_overflow_high_priority_pool(&mut self) {
-        while self.high_priority_weight > self.high_priority_weight_capacity {
-            strict_assert!(!self.high_priority_list.is_empty());
-
-            // overflow last entry in high priority pool to low priority pool
-            let mut ptr = self.high_priority_list.pop_front().strict_unwrap_unchecked();
-            strict_assert!(ptr.as_ref().in_high_priority_pool);
-            ptr.as_mut().in_high_priority_pool = false;
-            self.high_priority_weight -= ptr.as_ref().base().weight();
-            self.list.push_back(ptr);
+    unsafe fn may_overflow_pools(&mut self, priority: usize) {
+        for pool in (1..=priority).rev() {
+            while self.weights[pool] > self.weight_capacities[pool] {
+                strict_assert!(!self.pools[pool].is_empty());
+
+                // overflow the least recently used entry to the pool of the next lower priority
+                let mut ptr = self.pools[pool].pop_front().strict_unwrap_unchecked();
+                let weight = ptr.as_ref().base().weight();
+                strict_assert_eq!(ptr.as_ref().pool, pool);
+                ptr.as_mut().pool = pool - 1;
+                self.weights[pool] -= weight;
+                self.weights[pool - 1] += weight;
+                self.pools[pool - 1].push_back(ptr);
+            }
+        }
+    }
+
+    /// Change the count of the pools, the entries of the removed pools are moved to the pool of the highest priority.
+    unsafe fn resize_pools(&mut self, pools: usize) {
+        while self.pools.len() < pools {
+            self.pools.push(Dlist::new());
+            self.weights.push(0);
+        }
+        while self.pools.len() > pools {
+            let mut removed = self.pools.pop().strict_unwrap_unchecked();
+            let weight = self.weights.pop().strict_unwrap_unchecked();
+            let pool = self.pools.len() - 1;
+            while let Some(mut ptr) = removed.pop_front() {
+                ptr.as_mut().pool = pool;
+                self.pools[pool].push_back(ptr);
+            }
+            self.weights[pool] += weight;
         }
     }
 }
@@ -166,60 +212,64 @@ where
     where
         Self: Sized,
     {
-        assert!(
-            config.high_priority_pool_ratio >= 0.0 && config.high_priority_pool_ratio <= 1.0,
-            "high_priority_pool_ratio_percentage must be in [0, 100], given: {}",
-            config.high_priority_pool_ratio
-        );
-
-        let high_priority_weight_capacity = (capacity as f64 * config.high_priority_pool_ratio) as usize;
+        let weight_capacities = priority_pool_capacities(capacity, &config.pool_ratios());
 
         Self {
-            high_priority_list: Dlist::new(),
-            list: Dlist::new(),
-            high_priority_weight: 0,
-            high_priority_weight_capacity,
+            pools: (0..weight_capacities.len()).map(|_| Dlist::new()).collect(),
+            weights: vec![0; weight_capacities.len()],
+            weight_capacities,
         }
     }
 
+    fn update(&mut self, capacity: usize, config: &Self::Config) {
+        let weight_capacities = priority_pool_capacities(capacity, &config.pool_ratios());
+        let pools = weight_capacities.len();
+        unsafe { self.resize_pools(pools) };
+        self.weight_capacities = weight_capacities;
+        unsafe { self.may_overflow_pools(pools - 1) };
+    }
+
+    fn validate(config: &Self::Config) -> std::result::Result<(), String> {
+        validate_priority_pool_ratios(&config.pool_ratios())
+    }
+
+    fn priorities(config: &Self::Config) -> usize {
+        (config.pool_ratios().len() + 1).max(2)
+    }
+
     unsafe fn push(&mut self, mut ptr: NonNull<Self::Handle>) {
         let handle = ptr.as_mut();
 
         strict_assert!(!handle.link.is_linked());
 
-        match handle.base().context() {
-            LruContext::HighPriority => {
-                handle.in_high_priority_pool = true;
-                self.high_priority_weight += handle.base().weight();
-                self.high_priority_list.push_back(ptr);
-
-                self.may_overflow_high_priority_pool();
-            }
-            LruContext::LowPriority => {
-                handle.in_high_priority_pool = false;
-                self.list.push_back(ptr);
-            }
-        }
-
+        // Priorities beyond the pools share the pool of the highest priority.
+        let priority = handle.base().context().0.priority().min(self.pools.len() - 1);
+        handle.pool = priority;
+        self.weights[priority] += handle.base().weight();
+        self.pools[priority].push_back(ptr);
         handle.base_mut().set_in_eviction(true);
+
+        self.may_overflow_pools(priority);
     }
 
     unsafe fn pop(&mut self) -> Option<NonNull<Self::Handle>> {
-        let mut ptr = self.list.pop_front().or_else(|| self.high_priority_list.pop_front())?;
+        let pool = self.pools.iter().position(|pool| !pool.is_empty())?;
+        let mut ptr = self.pools[pool].pop_front().strict_unwrap_unchecked();
 
         let handle = ptr.as_mut();
         strict_assert!(!handle.link.is_linked());
+        strict_assert_eq!(handle.pool, pool);
 
-        if handle.in_high_priority_pool {
-            self.high_priority_weight -= handle.base().weight();
-            handle.in_high_priority_pool = false;
-        }
-
+        self.weights[pool] -= handle.base().weight();
         handle.base_mut().set_in_eviction(false);
 
         Some(ptr)
     }
 
+    unsafe fn peek(&self) -> Option<NonNull<Self::Handle>> {
+        self.pools.iter().find_map(|pool| pool.front()).map(NonNull::from)
+    }
+
     unsafe fn acquire(&mut self, _: NonNull<Self::Handle>) {}
 
     unsafe fn release(&mut self, mut ptr: NonNull<Self::Handle>) {
@@ -241,43 +291,39 @@ where
         let handle = ptr.as_mut();
         strict_assert!(handle.link.is_linked());
 
-        if handle.in_high_priority_pool {
-            self.high_priority_weight -= handle.base.weight();
-            self.high_priority_list.remove_raw(handle.link.raw());
-            handle.in_high_priority_pool = false;
-        } else {
-            self.list.remove_raw(handle.link.raw());
-        }
+        self.weights[handle.pool] -= handle.base.weight();
+        self.pools[handle.pool].remove_raw(handle.link.raw());
 
         strict_assert!(!handle.link.is_linked());
 
         handle.base_mut().set_in_eviction(false);
     }
 
+    unsafe fn reweigh(&mut self, mut ptr: NonNull<Self::Handle>, weight: usize) {
+        let handle = ptr.as_mut();
+        let pool = handle.pool;
+        self.weights[pool] = self.weights[pool] - handle.base().weight() + weight;
+        handle.base_mut().set_weight(weight);
+        self.may_overflow_pools(pool);
+    }
+
     unsafe fn clear(&mut self) -> Vec<NonNull<Self::Handle>> {
         let mut res = Vec::with_capacity(self.len());
 
-        while !self.list.is_empty() {
-            let mut ptr = self.list.pop_front().strict_unwrap_unchecked();
-            ptr.as_mut().base_mut().set_in_eviction(false);
-            res.push(ptr);
-        }
-
-        while !self.high_priority_list.is_empty() {
-            let mut ptr = self.high_priority_list.pop_front().strict_unwrap_unchecked();
-            ptr.as_mut().base_mut().set_in_eviction(false);
-            ptr.as_mut().in_high_priority_pool = false;
-            self.high_priority_weight -= ptr.as_ref().base().weight();
-            res.push(ptr);
+        for (pool, weight) in self.pools.iter_mut().zip(self.weights.iter_mut()) {
+            while let Some(mut ptr) = pool.pop_front() {
+                ptr.as_mut().base_mut().set_in_eviction(false);
+                *weight -= ptr.as_ref().base().weight();
+                res.push(ptr);
+            }
+            strict_assert_eq!(*weight, 0);
         }
 
-        strict_assert_eq!(self.high_priority_weight, 0);
-
         res
     }
 
     fn len(&self) -> usize {
-        self.high_priority_list.len() + self.list.len()
+        self.pools.iter().map(|pool| pool.len()).sum()
     }
 
     fn is_empty(&self) -> bool {
@@ -294,16 +340,19 @@ pub mod tests {
     use itertools::Itertools;
 
     use super::*;
-    use crate::{eviction::test_utils::TestEviction, handle::HandleExt};
+    use crate::{
+        eviction::test_utils::{conformance, TestEviction},
+        handle::HandleExt,
+    };
 
     impl<T> TestEviction for Lru<T>
     where
         T: Send + Sync + 'static + Clone,
     {
         fn dump(&self) -> Vec<T> {
-            self.list
+            self.pools
                 .iter()
-                .chain(self.high_priority_list.iter())
+                .flat_map(|pool| pool.iter())
                 .map(|handle| handle.base().data_unwrap_unchecked().clone())
                 .collect_vec()
         }
@@ -324,11 +373,11 @@ pub mod tests {
 
     unsafe fn dump_test_lru(lru: &TestLru) -> (Vec<u64>, Vec<u64>) {
         (
-            lru.list
+            lru.pools[0]
                 .iter()
                 .map(|handle| *handle.base().data_unwrap_unchecked())
                 .collect_vec(),
-            lru.high_priority_list
+            lru.pools[1]
                 .iter()
                 .map(|handle| *handle.base().data_unwrap_unchecked())
                 .collect_vec(),
@@ -343,9 +392,9 @@ pub mod tests {
                     new_test_lru_handle_ptr(
                         i,
                         if i < 10 {
-                            LruContext::HighPriority
+                            LruContext(CacheContext::Default)
                         } else {
-                            LruContext::LowPriority
+                            LruContext(CacheContext::LowPriority)
                         },
                     )
                 })
@@ -353,10 +402,11 @@ pub mod tests {
 
             let config = LruConfig {
                 high_priority_pool_ratio: 0.5,
+                ..Default::default()
             };
             let mut lru = TestLru::new(8, &config);
 
-            assert_eq!(lru.high_priority_weight_capacity, 4);
+            assert_eq!(lru.weight_capacities[1], 4);
 
             // [0, 1, 2, 3]
             lru.push(ptrs[0]);
@@ -364,59 +414,59 @@ pub mod tests {
             lru.push(ptrs[2]);
             lru.push(ptrs[3]);
             assert_eq!(lru.len(), 4);
-            assert_eq!(lru.high_priority_weight, 4);
-            assert_eq!(lru.high_priority_list.len(), 4);
+            assert_eq!(lru.weights[1], 4);
+            assert_eq!(lru.pools[1].len(), 4);
             assert_eq!(dump_test_lru(&lru), (vec![], vec![0, 1, 2, 3]));
 
             // 0, [1, 2, 3, 4]
             lru.push(ptrs[4]);
             assert_eq!(lru.len(), 5);
-            assert_eq!(lru.high_priority_weight, 4);
-            assert_eq!(lru.high_priority_list.len(), 4);
+            assert_eq!(lru.weights[1], 4);
+            assert_eq!(lru.pools[1].len(), 4);
             assert_eq!(dump_test_lru(&lru), (vec![0], vec![1, 2, 3, 4]));
 
             // 0, 10, [1, 2, 3, 4]
             lru.push(ptrs[10]);
             assert_eq!(lru.len(), 6);
-            assert_eq!(lru.high_priority_weight, 4);
-            assert_eq!(lru.high_priority_list.len(), 4);
+            assert_eq!(lru.weights[1], 4);
+            assert_eq!(lru.pools[1].len(), 4);
             assert_eq!(dump_test_lru(&lru), (vec![0, 10], vec![1, 2, 3, 4]));
 
             // 10, [1, 2, 3, 4]
             let p0 = lru.pop().unwrap();
             assert_eq!(ptrs[0], p0);
             assert_eq!(lru.len(), 5);
-            assert_eq!(lru.high_priority_weight, 4);
-            assert_eq!(lru.high_priority_list.len(), 4);
+            assert_eq!(lru.weights[1], 4);
+            assert_eq!(lru.pools[1].len(), 4);
             assert_eq!(dump_test_lru(&lru), (vec![10], vec![1, 2, 3, 4]));
 
             // 10, [1, 3, 4]
             lru.remove(ptrs[2]);
             assert_eq!(lru.len(), 4);
-            assert_eq!(lru.high_priority_weight, 3);
-            assert_eq!(lru.high_priority_list.len(), 3);
+            assert_eq!(lru.weights[1], 3);
+            assert_eq!(lru.pools[1].len(), 3);
             assert_eq!(dump_test_lru(&lru), (vec![10], vec![1, 3, 4]));
 
             // 10, 11, [1, 3, 4]
             lru.push(ptrs[11]);
             assert_eq!(lru.len(), 5);
-            assert_eq!(lru.high_priority_weight, 3);
-            assert_eq!(lru.high_priority_list.len(), 3);
+            assert_eq!(lru.weights[1], 3);
+            assert_eq!(lru.pools[1].len(), 3);
             assert_eq!(dump_test_lru(&lru), (vec![10, 11], vec![1, 3, 4]));
 
             // 10, 11, 1, [3, 4, 5, 6]
             lru.push(ptrs[5]);
             lru.push(ptrs[6]);
             assert_eq!(lru.len(), 7);
-            assert_eq!(lru.high_priority_weight, 4);
-            assert_eq!(lru.high_priority_list.len(), 4);
+            assert_eq!(lru.weights[1], 4);
+            assert_eq!(lru.pools[1].len(), 4);
             assert_eq!(dump_test_lru(&lru), (vec![10, 11, 1], vec![3, 4, 5, 6]));
 
             // 10, 11, 1, 3, [4, 5, 6, 0]
             lru.push(ptrs[0]);
             assert_eq!(lru.len(), 8);
-            assert_eq!(lru.high_priority_weight, 4);
-            assert_eq!(lru.high_priority_list.len(), 4);
+            assert_eq!(lru.weights[1], 4);
+            assert_eq!(lru.pools[1].len(), 4);
             assert_eq!(dump_test_lru(&lru), (vec![10, 11, 1, 3], vec![4, 5, 6, 0]));
 
             let ps = lru.clear();
@@ -427,4 +477,164 @@ pub mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_lru_priority_pools() {
+        unsafe {
+            let ptrs = (0..9)
+                .map(|i| {
+                    let context = match i {
+                        0..4 => CacheContext::Priority(2),
+                        4..8 => CacheContext::Default,
+                        _ => CacheContext::Priority(3),
+                    };
+                    new_test_lru_handle_ptr(i, LruContext(context))
+                })
+                .collect_vec();
+
+            let config = LruConfig {
+                priority_pool_ratios: vec![0.2, 0.2, 0.0],
+                ..Default::default()
+            };
+            let mut lru = TestLru::new(10, &config);
+            assert_eq!(&lru.weight_capacities[1..], &[2, 2, 0]);
+            assert_eq!(TestLru::priorities(&config), 4);
+            assert_eq!(TestLru::priorities(&LruConfig::default()), 2);
+
+            let dump = |lru: &TestLru| {
+                lru.pools
+                    .iter()
+                    .map(|pool| {
+                        pool.iter()
+                            .map(|handle| *handle.base().data_unwrap_unchecked())
+                            .collect_vec()
+                    })
+                    .collect_vec()
+            };
+
+            // [0], [4, 5], [1, 2], []
+            lru.push(ptrs[0]);
+            lru.push(ptrs[1]);
+            lru.push(ptrs[2]);
+            lru.push(ptrs[4]);
+            lru.push(ptrs[5]);
+            assert_eq!(dump(&lru), vec![vec![0], vec![4, 5], vec![1, 2], vec![]]);
+
+            // accessed entry goes back to the pool of its priority
+            //
+            // [4], [5, 1], [2, 0], []
+            lru.acquire(ptrs[0]);
+            lru.release(ptrs[0]);
+            assert_eq!(dump(&lru), vec![vec![4], vec![5, 1], vec![2, 0], vec![]]);
+
+            // [4, 5], [1, 2], [0, 8], []
+            lru.push(ptrs[8]);
+            assert_eq!(dump(&lru), vec![vec![4, 5], vec![1, 2], vec![0, 8], vec![]]);
+            assert_eq!(lru.weights, vec![2, 2, 2, 0]);
+
+            assert_eq!(lru.pop(), Some(ptrs[4]));
+            assert_eq!(lru.pop(), Some(ptrs[5]));
+            assert_eq!(lru.pop(), Some(ptrs[1]));
+            assert_eq!(lru.weights, vec![0, 1, 2, 0]);
+
+            assert_eq!(lru.clear(), vec![ptrs[2], ptrs[0], ptrs[8]]);
+
+            for ptr in ptrs {
+                del_test_lru_handle_ptr(ptr);
+            }
+        }
+    }
+
+    #[test]
+    fn test_lru_conformance() {
+        conformance::<Lru<u64>>(&LruConfig::default());
+    }
+
+    #[test]
+    fn test_lru_update_pools() {
+        unsafe {
+            let ptrs = (0..6)
+                .map(|i| new_test_lru_handle_ptr(i, LruContext(CacheContext::Priority(i as u8))))
+                .collect_vec();
+
+            let config = LruConfig {
+                priority_pool_ratios: vec![0.5, 0.5],
+                ..Default::default()
+            };
+            let mut lru = TestLru::new(10, &config);
+
+            // Priorities beyond the pools go to the pool of the highest priority.
+            //
+            // [0], [1], [2, 3, 4, 5]
+            for ptr in ptrs.iter() {
+                lru.push(*ptr);
+            }
+            assert_eq!(lru.pools.len(), 3);
+            assert_eq!(lru.weights, vec![1, 1, 4]);
+
+            // More pools with smaller capacities overflow the entries to the lower pools.
+            //
+            // [0, 1, 2, 3], [4], [5], [], [], []
+            lru.update(
+                10,
+                &LruConfig {
+                    priority_pool_ratios: vec![0.1; 5],
+                    ..Default::default()
+                },
+            );
+            assert_eq!(lru.pools.len(), 6);
+            assert_eq!(lru.weights, vec![4, 1, 1, 0, 0, 0]);
+
+            // [0, 1, 2, 3], [4], [], [], [], [5]
+            lru.acquire(ptrs[5]);
+            lru.release(ptrs[5]);
+            assert_eq!(lru.weights, vec![4, 1, 0, 0, 0, 1]);
+
+            // Fewer pools merge the entries of the removed pools into the pool of the highest priority.
+            //
+            // [0, 1, 2, 3], [4, 5]
+            lru.update(
+                10,
+                &LruConfig {
+                    high_priority_pool_ratio: 0.3,
+                    ..Default::default()
+                },
+            );
+            assert_eq!(lru.pools.len(), 2);
+            assert_eq!(lru.weights, vec![4, 2]);
+            assert_eq!(lru.dump(), vec![0, 1, 2, 3, 4, 5]);
+
+            assert_eq!(lru.clear().len(), 6);
+            for ptr in ptrs {
+                del_test_lru_handle_ptr(ptr);
+            }
+        }
+    }
+
+    #[test]
+    fn test_lru_conformance_with_priority_pools() {
+        conformance::<Lru<u64>>(&LruConfig {
+            priority_pool_ratios: vec![0.3, 0.3],
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    fn test_lru_config_serde() {
+        use serde::de::{value::MapDeserializer, IntoDeserializer};
+
+        // The config serialized before the priority pools are introduced.
+        let deserializer: MapDeserializer<'_, _, serde::de::value::Error> =
+            MapDeserializer::new([("high_priority_pool_ratio", 0.1.into_deserializer())].into_iter());
+        let config = LruConfig::deserialize(deserializer).unwrap();
+        assert_eq!(config.high_priority_pool_ratio, 0.1);
+        assert!(config.priority_pool_ratios.is_empty());
+        assert_eq!(&*config.pool_ratios(), &[0.1]);
+
+        let deserializer: MapDeserializer<'_, _, serde::de::value::Error> =
+            MapDeserializer::new([("priority_pool_ratios", vec![0.1, 0.2, 0.0].into_deserializer())].into_iter());
+        let config = LruConfig::deserialize(deserializer).unwrap();
+        assert_eq!(config.high_priority_pool_ratio, 0.0);
+        assert_eq!(&*config.pool_ratios(), &[0.1, 0.2, 0.0]);
+    }
 }
@@ -21,11 +21,28 @@ use crate::handle::Handle;
 pub trait EvictionConfig: Send + Sync + 'static + Clone + Serialize + DeserializeOwned + Default {}
 impl<T> EvictionConfig for T where T: Send + Sync + 'static + Clone + Serialize + DeserializeOwned + Default {}
 
-/// The lifetime of `handle: Self::H` is managed by [`Indexer`].
+/// The eviction container of the in-memory cache, which decides the order of eviction.
 ///
-/// Each `handle`'s lifetime in [`Indexer`] must outlive the raw pointer in [`Eviction`].
+/// The lifetime of `handle: Self::Handle` is managed by the indexer of the cache.
+///
+/// Each `handle`'s lifetime in the indexer must outlive the raw pointer in [`Eviction`].
+///
+/// A user-defined eviction algorithm can be plugged into the cache with
+/// [`CacheBuilder::with_custom_eviction`](crate::CacheBuilder::with_custom_eviction). The implementation must follow
+/// the contract below, which is checked by the cache with the `sanity` feature enabled:
+///
+/// - The eviction container never allocates or frees handles. It only links and unlinks the given pointers.
+/// - The eviction container sets [`BaseHandle::set_in_eviction`](crate::BaseHandle::set_in_eviction) to `true` on
+///   `push`, and to `false` for the pointers returned by `pop` and `clear` and the pointer given to `remove`.
+/// - `acquire` and `release` may reorder the pointer. `release` may also push a pointer that is not in the
+///   eviction container back to it, which means the entry is reinserted.
+/// - The eviction container never touches the flags other than `in_eviction`, the reference count, or the entry of
+///   the base handle.
+/// - All methods are called with the lock of the cache shard held, so the implementation needs no synchronization.
 pub trait Eviction: Send + Sync + 'static {
+    /// The handle type of the eviction container.
     type Handle: Handle;
+    /// The config type of the eviction container.
     type Config: EvictionConfig;
 
     /// Create a new empty eviction container.
@@ -35,6 +52,47 @@ pub trait Eviction: Send + Sync + 'static {
     where
         Self: Sized;
 
+    /// Update the capacity and the config of the eviction container at runtime.
+    ///
+    /// The handles in the eviction container must be kept. The eviction container may rebalance its internal
+    /// structures for the new capacity and config, but must not pop any handle. The cache is responsible for evicting
+    /// entries if the usage exceeds the new capacity.
+    ///
+    /// The default implementation ignores the update.
+    ///
+    /// The cache only updates the eviction container with the config accepted by [`Eviction::validate`].
+    #[expect(unused_variables)]
+    fn update(&mut self, capacity: usize, config: &Self::Config) {}
+
+    /// Validate the config of the eviction container.
+    ///
+    /// The cache rejects the config to update with the returned reason if it is invalid, before any shard is updated.
+    /// [`Eviction::new`] may panic with the config that is invalid.
+    ///
+    /// The default implementation accepts any config.
+    #[expect(unused_variables)]
+    fn validate(config: &Self::Config) -> std::result::Result<(), String>
+    where
+        Self: Sized,
+    {
+        Ok(())
+    }
+
+    /// Return the count of the priority levels configured by the config, at least 2 for
+    /// [`CacheContext::LowPriority`](crate::CacheContext::LowPriority) and
+    /// [`CacheContext::Default`](crate::CacheContext::Default).
+    ///
+    /// The priorities beyond the levels are treated as the highest level.
+    ///
+    /// The default implementation returns 2.
+    #[expect(unused_variables)]
+    fn priorities(config: &Self::Config) -> usize
+    where
+        Self: Sized,
+    {
+        2
+    }
+
     /// Push a handle `ptr` into the eviction container.
     ///
     /// The caller guarantees that the `ptr` is NOT in the eviction container.
@@ -56,6 +114,18 @@ pub trait Eviction: Send + Sync + 'static {
     /// The base handle associated to the `ptr` must be set NOT in cache.
     unsafe fn pop(&mut self) -> Option<NonNull<Self::Handle>>;
 
+    /// Peek the handle that the next `pop` would return, without popping it.
+    ///
+    /// The admission filter of the cache compares the entry to insert with the returned victim. The default
+    /// implementation returns `None`, which means the victim is unknown and the comparison is skipped.
+    ///
+    /// # Safety
+    ///
+    /// The returned `ptr` is only valid until the eviction container is modified.
+    unsafe fn peek(&self) -> Option<NonNull<Self::Handle>> {
+        None
+    }
+
     /// Notify the eviction container that the `ptr` is acquired by **AN** external user.
     ///
     /// # Safety
@@ -81,6 +151,27 @@ pub trait Eviction: Send + Sync + 'static {
     /// The base handle associated to the `ptr` must be set NOT in cache.
     unsafe fn remove(&mut self, ptr: NonNull<Self::Handle>);
 
+    /// Update the weight of the given `ptr` in the eviction container.
+    ///
+    /// The implementation must set the weight of the base handle with [`BaseHandle::set_weight`], and should keep the
+    /// position of the `ptr` in the eviction container if possible. The cache is responsible for evicting entries if
+    /// the usage exceeds the capacity.
+    ///
+    /// The default implementation removes the `ptr`, updates the weight and pushes it back. So it moves the `ptr` to
+    /// where a newly inserted entry goes (e.g. the MRU end of a LRU list), and resets any per-entry state that the
+    /// implementation initializes on `push`. Override it to reweigh in place if the entry must keep its position.
+    ///
+    /// # Safety
+    ///
+    /// The `ptr` must be in the eviction container.
+    ///
+    /// [`BaseHandle::set_weight`]: crate::BaseHandle::set_weight
+    unsafe fn reweigh(&mut self, mut ptr: NonNull<Self::Handle>, weight: usize) {
+        self.remove(ptr);
+        ptr.as_mut().base_mut().set_weight(weight);
+        self.push(ptr);
+    }
+
     /// Remove all `ptr`s from the eviction container and reset.
     ///
     /// # Safety
@@ -97,6 +188,38 @@ pub trait Eviction: Send + Sync + 'static {
     fn is_empty(&self) -> bool;
 }
 
+/// Validate the capacity ratio of the pool of each priority for the eviction algorithms with per-priority pools.
+///
+/// Each ratio must be in [0, 1.0], and the sum of the ratios must not exceed 1.0.
+pub(crate) fn validate_priority_pool_ratios(ratios: &[f64]) -> std::result::Result<(), String> {
+    if !ratios.iter().all(|ratio| (0.0..=1.0).contains(ratio)) {
+        return Err(format!("priority pool ratio must be in [0, 1.0], given: {ratios:?}"));
+    }
+    if ratios.iter().sum::<f64>() > 1.0 {
+        return Err(format!(
+            "sum of priority pool ratios must not exceed 1.0, given: {ratios:?}"
+        ));
+    }
+    Ok(())
+}
+
+/// Calculate the weight capacity of the pool of each priority for the eviction algorithms with per-priority pools.
+///
+/// The pool of the lowest priority is unbounded, and there is a bounded pool for each ratio of the higher priorities.
+///
+/// # Panic
+///
+/// Panics if the ratios are invalid, see [`validate_priority_pool_ratios`].
+pub(crate) fn priority_pool_capacities(capacity: usize, ratios: &[f64]) -> Vec<usize> {
+    if let Err(e) = validate_priority_pool_ratios(ratios) {
+        panic!("{e}");
+    }
+
+    std::iter::once(usize::MAX)
+        .chain(ratios.iter().map(|ratio| (capacity as f64 * ratio) as usize))
+        .collect()
+}
+
 pub mod fifo;
 pub mod lfu;
 pub mod lru;
@@ -104,5 +227,5 @@ pub mod s3fifo;
 
 pub mod sanity;
 
-#[cfg(test)]
+#[cfg(any(test, feature = "test-utils"))]
 pub mod test_utils;
@@ -81,8 +81,8 @@ where
     T: Send + Sync + 'static,
 {
     #[inline(always)]
-    pub fn freq_inc(&mut self) {
-        self.freq = std::cmp::min(self.freq + 1, MAX_FREQ);
+    pub fn freq_inc(&mut self, weight: u8) {
+        self.freq = std::cmp::min(self.freq.saturating_add(weight), MAX_FREQ);
     }
 
     #[inline(always)]
@@ -130,6 +130,15 @@ pub struct S3FifoConfig {
     pub ghost_queue_capacity_ratio: f64,
     /// Minimum access times when population entry from small queue to main queue.
     pub small_to_main_freq_threshold: u8,
+    /// The frequency increment of an access for each priority, from the lowest priority to the highest one.
+    ///
+    /// Higher weights make entries of the priority easier to be promoted to the main queue and harder to be evicted
+    /// from it. A weight of `0` makes accesses to entries of the priority never counted. Priorities beyond the given
+    /// weights use the weight of the highest priority.
+    ///
+    /// The default value (no weight) weighs all priorities with `1`.
+    #[serde(default)]
+    pub priority_frequency_weights: Vec<u8>,
 }
 
 impl Default for S3FifoConfig {
@@ -138,6 +147,7 @@ impl Default for S3FifoConfig {
             small_queue_capacity_ratio: 0.1,
             ghost_queue_capacity_ratio: 1.0,
             small_to_main_freq_threshold: 1,
+            priority_frequency_weights: vec![],
         }
     }
 }
@@ -156,6 +166,8 @@ where
     main_weight: usize,
 
     small_to_main_freq_threshold: u8,
+
+    priority_frequency_weights: Vec<u8>,
 }
 
 impl<T> S3Fifo<T>
@@ -245,14 +257,29 @@ where
             small_weight: 0,
             main_weight: 0,
             small_to_main_freq_threshold: config.small_to_main_freq_threshold.min(MAX_FREQ),
+            priority_frequency_weights: config.priority_frequency_weights.clone(),
         }
     }
 
+    fn update(&mut self, capacity: usize, config: &Self::Config) {
+        self.ghost_queue
+            .resize((capacity as f64 * config.ghost_queue_capacity_ratio) as usize);
+        self.small_weight_capacity = (capacity as f64 * config.small_queue_capacity_ratio) as usize;
+        self.small_to_main_freq_threshold = config.small_to_main_freq_threshold.min(MAX_FREQ);
+        self.priority_frequency_weights = config.priority_frequency_weights.clone();
+    }
+
+    fn priorities(config: &Self::Config) -> usize {
+        config.priority_frequency_weights.len().max(2)
+    }
+
     unsafe fn push(&mut self, mut ptr: NonNull<Self::Handle>) {
         let handle = ptr.as_mut();
-        strict_assert_eq!(handle.freq, 0);
         strict_assert_eq!(handle.queue, Queue::None);
 
+        // The handle may have been accessed while it is out of the eviction container (e.g. pinned).
+        handle.freq = 0;
+
         if self.ghost_queue.contains(handle.base().hash()) {
             handle.queue = Queue::Main;
             self.main_queue.push_back(ptr);
@@ -283,7 +310,12 @@ where
 
     unsafe fn acquire(&mut self, ptr: NonNull<Self::Handle>) {
         let mut ptr = ptr;
-        ptr.as_mut().freq_inc();
+        let handle = ptr.as_mut();
+        let weight = match self.priority_frequency_weights.as_slice() {
+            [] => 1,
+            weights => weights[handle.base().context().0.priority().min(weights.len() - 1)],
+        };
+        handle.freq_inc(weight);
     }
 
     unsafe fn remove(&mut self, mut ptr: NonNull<Self::Handle>) {
@@ -322,6 +354,17 @@ where
         }
     }
 
+    unsafe fn reweigh(&mut self, mut ptr: NonNull<Self::Handle>, weight: usize) {
+        let handle = ptr.as_mut();
+        let queue_weight = match handle.queue {
+            Queue::None => unreachable!(),
+            Queue::Main => &mut self.main_weight,
+            Queue::Small => &mut self.small_weight,
+        };
+        *queue_weight = *queue_weight - handle.base().weight() + weight;
+        handle.base_mut().set_weight(weight);
+    }
+
     unsafe fn clear(&mut self) -> Vec<NonNull<Self::Handle>> {
         let mut res = Vec::with_capacity(self.len());
         while let Some(ptr) = self.pop() {
@@ -371,6 +414,13 @@ impl GhostQueue {
         self.weight += weight;
     }
 
+    fn resize(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.weight > self.capacity {
+            self.pop();
+        }
+    }
+
     fn pop(&mut self) {
         if let Some((hash, weight)) = self.queue.pop_front() {
             self.weight -= weight;
@@ -390,7 +440,10 @@ mod tests {
     use itertools::Itertools;
 
     use super::*;
-    use crate::{eviction::test_utils::TestEviction, handle::HandleExt};
+    use crate::{
+        eviction::test_utils::{conformance, TestEviction},
+        handle::HandleExt,
+    };
 
     impl<T> TestEviction for S3Fifo<T>
     where
@@ -440,6 +493,7 @@ mod tests {
                 small_queue_capacity_ratio: 0.25,
                 ghost_queue_capacity_ratio: 10.0,
                 small_to_main_freq_threshold: 2,
+                ..Default::default()
             };
             let mut s3fifo = TestS3Fifo::new(8, &config);
 
@@ -483,4 +537,53 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_s3fifo_priority() {
+        unsafe {
+            let ptrs = (0..4)
+                .map(|i| {
+                    let context = match i {
+                        0 => CacheContext::LowPriority,
+                        3 => CacheContext::Priority(3),
+                        _ => CacheContext::Default,
+                    };
+                    let mut handle = Box::<TestS3FifoHandle>::default();
+                    handle.init(i, i, 1, S3FifoContext(context));
+                    NonNull::new_unchecked(Box::into_raw(handle))
+                })
+                .collect_vec();
+
+            // capacity: 4, small: 1, ghost: 0
+            let config = S3FifoConfig {
+                small_queue_capacity_ratio: 0.25,
+                ghost_queue_capacity_ratio: 0.0,
+                small_to_main_freq_threshold: 2,
+                priority_frequency_weights: vec![0, 1, 1, 2],
+            };
+            let mut s3fifo = TestS3Fifo::new(4, &config);
+
+            [3, 0, 1, 2].into_iter().for_each(|i| s3fifo.push(ptrs[i]));
+            (0..4).for_each(|i| s3fifo.acquire(ptrs[i]));
+            s3fifo.acquire(ptrs[0]);
+            assert_count(&ptrs, 0..1, 0);
+            assert_count(&ptrs, 1..3, 1);
+            assert_count(&ptrs, 3..4, 2);
+
+            // `0` is never counted, `3` reaches the threshold with a single access.
+            assert_eq!(s3fifo.pop(), Some(ptrs[0]));
+            assert_test_s3fifo(&s3fifo, vec![1, 2], vec![3]);
+            assert_eq!(s3fifo.pop(), Some(ptrs[1]));
+            assert_test_s3fifo(&s3fifo, vec![2], vec![3]);
+
+            for ptr in ptrs {
+                let _ = Box::from_raw(ptr.as_ptr());
+            }
+        }
+    }
+
+    #[test]
+    fn test_s3fifo_conformance() {
+        conformance::<S3Fifo<u64>>(&S3FifoConfig::default());
+    }
 }
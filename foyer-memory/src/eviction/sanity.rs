@@ -40,6 +40,18 @@ where
         }
     }
 
+    fn update(&mut self, capacity: usize, config: &Self::Config) {
+        self.eviction.update(capacity, config)
+    }
+
+    fn validate(config: &Self::Config) -> std::result::Result<(), String> {
+        E::validate(config)
+    }
+
+    fn priorities(config: &Self::Config) -> usize {
+        E::priorities(config)
+    }
+
     unsafe fn push(&mut self, ptr: std::ptr::NonNull<Self::Handle>) {
         assert!(!ptr.as_ref().base().is_in_eviction());
         self.eviction.push(ptr);
@@ -54,6 +66,14 @@ where
         res
     }
 
+    unsafe fn peek(&self) -> Option<std::ptr::NonNull<Self::Handle>> {
+        let res = self.eviction.peek();
+        if let Some(ptr) = res {
+            assert!(ptr.as_ref().base().is_in_eviction());
+        }
+        res
+    }
+
     unsafe fn acquire(&mut self, ptr: std::ptr::NonNull<Self::Handle>) {
         self.eviction.acquire(ptr)
     }
@@ -68,6 +88,13 @@ where
         assert!(!ptr.as_ref().base().is_in_eviction());
     }
 
+    unsafe fn reweigh(&mut self, ptr: std::ptr::NonNull<Self::Handle>, weight: usize) {
+        assert!(ptr.as_ref().base().is_in_eviction());
+        self.eviction.reweigh(ptr, weight);
+        assert!(ptr.as_ref().base().is_in_eviction());
+        assert_eq!(ptr.as_ref().base().weight(), weight);
+    }
+
     unsafe fn clear(&mut self) -> Vec<std::ptr::NonNull<Self::Handle>> {
         let res = self.eviction.clear();
         res.iter()
@@ -101,6 +128,18 @@ where
         }
     }
 
+    fn update(&mut self, capacity: usize, config: &Self::Config) {
+        self.eviction.update(capacity, config)
+    }
+
+    fn validate(config: &Self::Config) -> std::result::Result<(), String> {
+        E::validate(config)
+    }
+
+    fn priorities(config: &Self::Config) -> usize {
+        E::priorities(config)
+    }
+
     unsafe fn push(&mut self, ptr: std::ptr::NonNull<Self::Handle>) {
         self.eviction.push(ptr)
     }
@@ -109,6 +148,10 @@ where
         self.eviction.pop()
     }
 
+    unsafe fn peek(&self) -> Option<std::ptr::NonNull<Self::Handle>> {
+        self.eviction.peek()
+    }
+
     unsafe fn acquire(&mut self, ptr: std::ptr::NonNull<Self::Handle>) {
         self.eviction.acquire(ptr)
     }
@@ -121,6 +164,10 @@ where
         self.eviction.remove(ptr)
     }
 
+    unsafe fn reweigh(&mut self, ptr: std::ptr::NonNull<Self::Handle>, weight: usize) {
+        self.eviction.reweigh(ptr, weight)
+    }
+
     unsafe fn clear(&mut self) -> Vec<std::ptr::NonNull<Self::Handle>> {
         self.eviction.clear()
     }
@@ -134,8 +181,8 @@ where
     }
 }
 
-#[cfg(test)]
-pub mod tests {
+#[cfg(any(test, feature = "test-utils"))]
+mod test_utils {
     use super::*;
     use crate::{eviction::test_utils::TestEviction, handle::Handle};
 
@@ -12,12 +12,110 @@
 //  See the License for the specific language governing permissions and
 //  limitations under the License.
 
-use super::Eviction;
-use crate::handle::Handle;
+//! Utilities to test the eviction containers, including the user-defined ones.
+//!
+//! Enabled with the `test-utils` feature.
 
+use std::{collections::BTreeSet, ptr::NonNull};
+
+use itertools::Itertools;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use super::{sanity::SanityEviction, Eviction};
+use crate::{
+    handle::{Handle, HandleExt},
+    CacheContext,
+};
+
+/// An eviction container that can dump its content for the checks.
 pub trait TestEviction: Eviction
 where
     Self::Handle: Handle,
 {
+    /// Dump the data of all handles in the eviction container.
     fn dump(&self) -> Vec<<Self::Handle as Handle>::Data>;
 }
+
+/// Check that the eviction container follows the contract described on [`Eviction`].
+///
+/// Random operations are applied on the eviction container wrapped with the sanity checker. The `in_eviction` flag,
+/// the length and the content of the eviction container are checked against the expected ones after each operation.
+pub fn conformance<E>(config: &E::Config)
+where
+    E: TestEviction,
+    E::Handle: Handle<Data = u64>,
+{
+    const CAPACITY: usize = 16;
+    const HANDLES: u64 = 64;
+    const OPS: usize = 10000;
+
+    let mut rng = StdRng::seed_from_u64(42);
+
+    unsafe {
+        let mut eviction = SanityEviction::<E>::new(CAPACITY, config);
+        let ptrs = (0..HANDLES)
+            .map(|i| {
+                let mut handle = Box::<E::Handle>::default();
+                handle.init(i, i, 1, CacheContext::Default.into());
+                NonNull::new_unchecked(Box::into_raw(handle))
+            })
+            .collect_vec();
+        let mut expected = BTreeSet::new();
+
+        for _ in 0..OPS {
+            let i = rng.gen_range(0..HANDLES);
+            let ptr = ptrs[i as usize];
+            match rng.gen_range(0..100) {
+                0..40 => {
+                    if expected.insert(i) {
+                        // Only newly inserted entries are pushed by the cache, reset the handle to simulate it.
+                        std::ptr::drop_in_place(ptr.as_ptr());
+                        std::ptr::write(ptr.as_ptr(), E::Handle::default());
+                        (*ptr.as_ptr()).init(i, i, 1, CacheContext::Default.into());
+                        eviction.push(ptr);
+                    }
+                }
+                40..50 => {
+                    if let Some(ptr) = eviction.pop() {
+                        assert!(expected.remove(ptr.as_ref().base().data_unwrap_unchecked()));
+                    }
+                }
+                50..60 => {
+                    if expected.remove(&i) {
+                        eviction.remove(ptr);
+                    }
+                }
+                60..75 => eviction.acquire(ptr),
+                75..99 => {
+                    eviction.release(ptr);
+                    // The eviction container is allowed to reinsert the released handle.
+                    if ptr.as_ref().base().is_in_eviction() {
+                        expected.insert(i);
+                    }
+                }
+                _ => {
+                    let cleared = eviction
+                        .clear()
+                        .into_iter()
+                        .map(|ptr| *ptr.as_ref().base().data_unwrap_unchecked())
+                        .collect::<BTreeSet<_>>();
+                    assert_eq!(cleared, expected);
+                    expected.clear();
+                }
+            }
+
+            for (i, ptr) in ptrs.iter().enumerate() {
+                assert_eq!(ptr.as_ref().base().is_in_eviction(), expected.contains(&(i as u64)));
+            }
+            assert_eq!(eviction.len(), expected.len());
+            assert_eq!(eviction.is_empty(), expected.is_empty());
+            let dumped = eviction.dump().into_iter().collect::<BTreeSet<_>>();
+            assert_eq!(dumped, expected);
+        }
+
+        eviction.clear();
+        for ptr in ptrs {
+            let _ = Box::from_raw(ptr.as_ptr());
+        }
+    }
+}
@@ -17,12 +17,13 @@ use std::{
     fmt::Debug,
     future::Future,
     hash::Hash,
+    mem::ManuallyDrop,
     ops::Deref,
     pin::Pin,
     ptr::NonNull,
     sync::{
         atomic::{AtomicUsize, Ordering},
-        Arc,
+        Arc, OnceLock,
     },
     task::{Context, Poll},
 };
@@ -33,21 +34,28 @@ use foyer_common::{
     code::{HashBuilder, Key, Value},
     event::EventListener,
     future::{Diversion, DiversionFuture},
-    metrics::Metrics,
+    metrics::{MemoryPriorityMetrics, Metrics},
+    mrc::{MissRatioCurve, MissRatioCurveConfig, MissRatioCurveTracker},
     object_pool::ObjectPool,
     runtime::SingletonHandle,
     strict_assert, strict_assert_eq,
 };
-use hashbrown::hash_map::{Entry as HashMapEntry, HashMap};
+use hashbrown::{
+    hash_map::{Entry as HashMapEntry, HashMap},
+    Equivalent,
+};
 use itertools::Itertools;
 use parking_lot::{lock_api::MutexGuard, Mutex, RawMutex};
 use pin_project::pin_project;
 use tokio::{sync::oneshot, task::JoinHandle};
 
 use crate::{
+    admission::{AdmissionConfig, AdmissionFilter},
     eviction::Eviction,
     handle::{Handle, HandleExt, KeyedHandle},
     indexer::Indexer,
+    metadata::EntryMetadata,
+    reclaimer::Reclaimer,
     CacheContext,
 };
 
@@ -60,9 +68,17 @@ impl<K, V, T> Weighter<K, V> for T where T: Fn(&K, &V) -> usize + Send + Sync +
 
 struct SharedState<K, V, T> {
     metrics: Arc<Metrics>,
+    name: String,
+    /// The metrics of each priority level configured on creation, indexed by [`CacheContext::priority`] and registered
+    /// on the first use. The priorities beyond the levels are counted in the highest level.
+    priority_metrics: Box<[OnceLock<MemoryPriorityMetrics>]>,
     /// The object pool to avoid frequent handle allocating, shared by all shards.
     object_pool: ObjectPool<Box<T>>,
+    /// Maintain the metadata of the entries if enabled.
+    entry_metadata: bool,
     event_listener: Option<Arc<dyn EventListener<Key = K, Value = V>>>,
+    /// Release the entries on the background thread if enabled.
+    reclaimer: Option<Reclaimer<K, V>>,
 }
 
 #[expect(clippy::type_complexity)]
@@ -77,10 +93,14 @@ where
 {
     indexer: I,
     eviction: E,
+    admission: AdmissionFilter,
 
     capacity: usize,
     usage: Arc<AtomicUsize>,
 
+    pinned_capacity: usize,
+    pinned_usage: Arc<AtomicUsize>,
+
     waiters: HashMap<K, Vec<oneshot::Sender<GenericCacheEntry<K, V, E, I, S>>>>,
 
     state: Arc<SharedState<K, V, E::Handle>>,
@@ -98,17 +118,24 @@ where
     fn new(
         capacity: usize,
         eviction_config: &E::Config,
+        admission_config: &AdmissionConfig,
         usage: Arc<AtomicUsize>,
+        pinned_capacity: usize,
+        pinned_usage: Arc<AtomicUsize>,
         context: Arc<SharedState<K, V, E::Handle>>,
     ) -> Self {
         let indexer = I::new();
         let eviction = unsafe { E::new(capacity, eviction_config) };
+        let admission = AdmissionFilter::new(admission_config);
         let waiters = HashMap::default();
         Self {
             indexer,
             eviction,
+            admission,
             capacity,
             usage,
+            pinned_capacity,
+            pinned_usage,
             waiters,
             state: context,
         }
@@ -134,8 +161,19 @@ where
         strict_assert!(!handle.base().is_in_eviction());
 
         handle.init(hash, (key, value), weight, context);
+        if self.state.entry_metadata {
+            handle.base_mut().init_metadata();
+        }
         let mut ptr = unsafe { NonNull::new_unchecked(Box::into_raw(handle)) };
 
+        if !deposit {
+            self.admission.record(hash);
+            if !self.admit(hash, weight) {
+                self.reject(ptr, to_release);
+                return ptr;
+            }
+        }
+
         self.evict(weight, to_release);
 
         strict_assert!(!ptr.as_ref().base().is_in_indexer());
@@ -146,6 +184,7 @@ where
             if old.as_ref().base().is_in_eviction() {
                 self.eviction.remove(old);
             }
+            self.may_unpin_removed(old);
             strict_assert!(!old.as_ref().base().is_in_eviction());
             // Because the `old` handle is removed from the indexer, it will not be reinserted again.
             if let Some(entry) = self.try_release_handle(old, false) {
@@ -153,6 +192,7 @@ where
             }
         } else {
             self.state.metrics.memory_insert.increment(1);
+            self.priority_metrics(ptr.as_ref()).insert.increment(1);
         }
         strict_assert!(ptr.as_ref().base().is_in_indexer());
 
@@ -164,6 +204,7 @@ where
 
         self.usage.fetch_add(weight, Ordering::Relaxed);
         self.state.metrics.memory_usage.increment(weight as f64);
+        self.priority_metrics(ptr.as_ref()).usage.increment(weight as f64);
         ptr.as_mut().base_mut().inc_refs();
 
         ptr
@@ -171,12 +212,14 @@ where
 
     unsafe fn get<Q>(&mut self, hash: u64, key: &Q) -> Option<NonNull<E::Handle>>
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: Equivalent<K> + ?Sized,
     {
+        self.admission.record(hash);
+
         let mut ptr = match self.indexer.get(hash, key) {
             Some(ptr) => {
                 self.state.metrics.memory_hit.increment(1);
+                self.priority_metrics(ptr.as_ref()).hit.increment(1);
                 ptr
             }
             None => {
@@ -189,26 +232,72 @@ where
 
         base.set_deposit(false);
         base.inc_refs();
+        if let Some(metadata) = base.metadata() {
+            metadata.record_access();
+        }
         self.eviction.acquire(ptr);
 
         Some(ptr)
     }
 
+    /// Return `true` if the entry with the given key hash and weight passes the admission filter.
+    unsafe fn admit(&self, hash: u64, weight: usize) -> bool {
+        if !self.admission.admit_weight(weight, self.capacity) {
+            return false;
+        }
+        // The doorkeeper only works when the new entry needs to evict others.
+        if !self.admission.has_doorkeeper() || self.usage.load(Ordering::Relaxed) + weight <= self.capacity {
+            return true;
+        }
+        match self.eviction.peek() {
+            Some(victim) => self.admission.admit_frequency(hash, victim.as_ref().base().hash()),
+            None => true,
+        }
+    }
+
+    /// Mark the new handle as rejected by the admission filter, so it is released after the last holder drops it.
+    ///
+    /// The older entry with the same key is removed, so the stale value is never served.
+    unsafe fn reject(
+        &mut self,
+        mut ptr: NonNull<E::Handle>,
+        to_release: &mut Vec<(K, V, <E::Handle as Handle>::Context, usize)>,
+    ) {
+        self.state.metrics.memory_reject.increment(1);
+
+        if let Some(old) = self.indexer.remove(ptr.as_ref().base().hash(), ptr.as_ref().key()) {
+            self.state.metrics.memory_remove.increment(1);
+            if old.as_ref().base().is_in_eviction() {
+                self.eviction.remove(old);
+            }
+            self.may_unpin_removed(old);
+            strict_assert!(!old.as_ref().base().is_in_eviction());
+            if let Some(entry) = self.try_release_handle(old, false) {
+                to_release.push(entry);
+            }
+        }
+
+        let base = ptr.as_mut().base_mut();
+        base.set_rejected(true);
+        base.inc_refs();
+    }
+
     unsafe fn contains<Q>(&mut self, hash: u64, key: &Q) -> bool
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: Equivalent<K> + ?Sized,
     {
         self.indexer.get(hash, key).is_some()
     }
 
     unsafe fn touch<Q>(&mut self, hash: u64, key: &Q) -> bool
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: Equivalent<K> + ?Sized,
     {
         let res = self.indexer.get(hash, key);
         if let Some(ptr) = res {
+            if let Some(metadata) = ptr.as_ref().base().metadata() {
+                metadata.record_access();
+            }
             self.eviction.acquire(ptr);
         }
         res.is_some()
@@ -219,8 +308,7 @@ where
     /// Return `Some(..)` if the handle is released, or `None` if the handle is still in use.
     unsafe fn remove<Q>(&mut self, hash: u64, key: &Q) -> Option<NonNull<E::Handle>>
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: Equivalent<K> + ?Sized,
     {
         let mut ptr = self.indexer.remove(hash, key)?;
         let handle = ptr.as_mut();
@@ -230,6 +318,7 @@ where
         if handle.base().is_in_eviction() {
             self.eviction.remove(ptr);
         }
+        self.may_unpin_removed(ptr);
 
         strict_assert!(!handle.base().is_in_indexer());
         strict_assert!(!handle.base().is_in_eviction());
@@ -239,6 +328,178 @@ where
         Some(ptr)
     }
 
+    /// Recompute the weight of the entry, then evict entries if the usage exceeds the capacity.
+    ///
+    /// Return the new weight.
+    unsafe fn reweigh(
+        &mut self,
+        mut ptr: NonNull<E::Handle>,
+        weighter: &dyn Weighter<K, V>,
+        to_release: &mut Vec<(K, V, <E::Handle as Handle>::Context, usize)>,
+    ) -> usize {
+        let (key, value) = ptr.as_ref().base().data_unwrap_unchecked();
+        let weight = weighter(key, value);
+        let old = ptr.as_ref().base().weight();
+        if weight == old {
+            return weight;
+        }
+
+        // The weight of a rejected entry is never charged.
+        if ptr.as_ref().base().is_rejected() {
+            ptr.as_mut().base_mut().set_weight(weight);
+            return weight;
+        }
+
+        if ptr.as_ref().base().is_in_eviction() {
+            self.eviction.reweigh(ptr, weight);
+        } else {
+            ptr.as_mut().base_mut().set_weight(weight);
+        }
+        strict_assert_eq!(ptr.as_ref().base().weight(), weight);
+
+        if weight > old {
+            let delta = weight - old;
+            if ptr.as_ref().base().is_pinned() {
+                self.pinned_usage.fetch_add(delta, Ordering::Relaxed);
+            }
+            self.usage.fetch_add(delta, Ordering::Relaxed);
+            self.state.metrics.memory_usage.increment(delta as f64);
+            self.priority_metrics(ptr.as_ref()).usage.increment(delta as f64);
+        } else {
+            let delta = old - weight;
+            if ptr.as_ref().base().is_pinned() {
+                self.pinned_usage.fetch_sub(delta, Ordering::Relaxed);
+            }
+            self.usage.fetch_sub(delta, Ordering::Relaxed);
+            self.state.metrics.memory_usage.decrement(delta as f64);
+            self.priority_metrics(ptr.as_ref()).usage.decrement(delta as f64);
+        }
+
+        self.evict(0, to_release);
+
+        weight
+    }
+
+    /// Take references of all entries in the shard.
+    ///
+    /// Unlike `get`, the eviction container is not notified.
+    unsafe fn entries(&mut self) -> Vec<NonNull<E::Handle>> {
+        self.indexer
+            .iter()
+            .map(|mut ptr| {
+                ptr.as_mut().base_mut().inc_refs();
+                ptr
+            })
+            .collect_vec()
+    }
+
+    /// Remove all entries that the predicate returns `false` from the shard.
+    unsafe fn retain(
+        &mut self,
+        f: &mut impl FnMut(&K, &V) -> bool,
+        to_release: &mut Vec<(K, V, <E::Handle as Handle>::Context, usize)>,
+    ) {
+        let ptrs = self
+            .indexer
+            .iter()
+            .filter(|ptr| {
+                let (key, value) = ptr.as_ref().base().data_unwrap_unchecked();
+                !f(key, value)
+            })
+            .collect_vec();
+
+        self.state.metrics.memory_remove.increment(ptrs.len() as _);
+
+        for ptr in ptrs {
+            let handle = ptr.as_ref();
+            self.indexer.remove(handle.base().hash(), handle.key());
+            if handle.base().is_in_eviction() {
+                self.eviction.remove(ptr);
+            }
+            self.may_unpin_removed(ptr);
+
+            strict_assert!(!handle.base().is_in_indexer());
+            strict_assert!(!handle.base().is_in_eviction());
+
+            if let Some(entry) = self.try_release_handle(ptr, false) {
+                to_release.push(entry);
+            }
+        }
+    }
+
+    /// Pin the entry with the given key, so it is removed from the eviction container and never evicted.
+    ///
+    /// Return `false` if the entry is not found, or pinning it would exceed the pinned capacity.
+    unsafe fn pin<Q>(&mut self, hash: u64, key: &Q) -> bool
+    where
+        Q: Equivalent<K> + ?Sized,
+    {
+        let mut ptr = match self.indexer.get(hash, key) {
+            Some(ptr) => ptr,
+            None => return false,
+        };
+        let handle = ptr.as_mut();
+
+        if handle.base().is_pinned() {
+            return true;
+        }
+
+        let weight = handle.base().weight();
+        if self.pinned_usage.load(Ordering::Relaxed) + weight > self.pinned_capacity {
+            return false;
+        }
+
+        if handle.base().is_in_eviction() {
+            self.eviction.remove(ptr);
+        }
+        strict_assert!(!handle.base().is_in_eviction());
+
+        // A pinned entry is always kept, no matter if it has been accessed.
+        handle.base_mut().set_deposit(false);
+        handle.base_mut().set_pinned(true);
+        self.pinned_usage.fetch_add(weight, Ordering::Relaxed);
+
+        true
+    }
+
+    /// Unpin the entry with the given key, so it can be evicted again.
+    ///
+    /// Return `false` if the entry is not found or not pinned.
+    unsafe fn unpin<Q>(&mut self, hash: u64, key: &Q) -> bool
+    where
+        Q: Equivalent<K> + ?Sized,
+    {
+        let mut ptr = match self.indexer.get(hash, key) {
+            Some(ptr) => ptr,
+            None => return false,
+        };
+        let handle = ptr.as_mut();
+
+        if !handle.base().is_pinned() {
+            return false;
+        }
+
+        handle.base_mut().set_pinned(false);
+        self.pinned_usage.fetch_sub(handle.base().weight(), Ordering::Relaxed);
+
+        // Give the entry back to the eviction container even if it is still held externally, because `release` of
+        // some eviction algorithms never reinserts an entry.
+        strict_assert!(!handle.base().is_in_eviction());
+        self.eviction.push(ptr);
+
+        true
+    }
+
+    /// Release the pinned weight of the handle that has just been removed from the indexer.
+    unsafe fn may_unpin_removed(&mut self, mut ptr: NonNull<E::Handle>) {
+        let handle = ptr.as_mut();
+        strict_assert!(!handle.base().is_in_indexer());
+        if handle.base().is_pinned() {
+            handle.base_mut().set_pinned(false);
+            self.pinned_usage.fetch_sub(handle.base().weight(), Ordering::Relaxed);
+        }
+    }
+
     /// Clear all cache entries.
     unsafe fn clear(&mut self, to_release: &mut Vec<(K, V, <E::Handle as Handle>::Context, usize)>) {
         // TODO(MrCroxx): Avoid collecting here?
@@ -260,12 +521,25 @@ where
         for ptr in ptrs {
             strict_assert!(!ptr.as_ref().base().is_in_indexer());
             strict_assert!(!ptr.as_ref().base().is_in_eviction());
+            self.may_unpin_removed(ptr);
             if let Some(entry) = self.try_release_handle(ptr, false) {
                 to_release.push(entry);
             }
         }
     }
 
+    /// Apply the new capacity and eviction config to the shard, then evict entries until the usage fits.
+    unsafe fn update(
+        &mut self,
+        capacity: usize,
+        eviction_config: &E::Config,
+        to_release: &mut Vec<(K, V, <E::Handle as Handle>::Context, usize)>,
+    ) {
+        self.eviction.update(capacity, eviction_config);
+        self.capacity = capacity;
+        self.evict(0, to_release);
+    }
+
     #[fastrace::trace(name = "foyer::memory::generic::shard::evict")]
     unsafe fn evict(&mut self, weight: usize, to_release: &mut Vec<(K, V, <E::Handle as Handle>::Context, usize)>) {
         // TODO(MrCroxx): Use `let_chains` here after it is stable.
@@ -275,6 +549,7 @@ where
                 None => break,
             };
             self.state.metrics.memory_evict.increment(1);
+            self.priority_metrics(evicted.as_ref()).evict.increment(1);
             let base = evicted.as_ref().base();
             strict_assert!(base.is_in_indexer());
             strict_assert!(!base.is_in_eviction());
@@ -284,6 +559,12 @@ where
         }
     }
 
+    fn priority_metrics(&self, handle: &E::Handle) -> &MemoryPriorityMetrics {
+        let context: CacheContext = handle.base().context().clone().into();
+        let priority = context.priority().min(self.state.priority_metrics.len() - 1);
+        self.state.priority_metrics[priority].get_or_init(|| MemoryPriorityMetrics::new(&self.state.name, priority))
+    }
+
     /// Release a handle used by an external user.
     ///
     /// Return `Some(..)` if the handle is released, or `None` if the handle is still in use.
@@ -295,6 +576,31 @@ where
         self.try_release_handle(ptr, true)
     }
 
+    /// Release a handle taken without notifying the eviction container, e.g. by iteration.
+    ///
+    /// The eviction container is not notified either, so the order of eviction is kept. An entry that has been
+    /// popped from the eviction container while held is not reinserted, and it is removed from the indexer only if the
+    /// indexer still maps its key to it, for the key may have been inserted again while held.
+    ///
+    /// Return `Some(..)` if the handle is released, or `None` if the handle is still in use.
+    unsafe fn try_release_passive_handle(
+        &mut self,
+        mut ptr: NonNull<E::Handle>,
+    ) -> Option<(K, V, <E::Handle as Handle>::Context, usize)> {
+        ptr.as_mut().base_mut().dec_refs();
+        let base = ptr.as_ref().base();
+        if base.is_in_eviction() {
+            return None;
+        }
+        if !base.has_refs()
+            && base.is_in_indexer()
+            && self.indexer.get(base.hash(), ptr.as_ref().key()) != Some(ptr)
+        {
+            ptr.as_mut().base_mut().set_in_indexer(false);
+        }
+        self.try_release_handle(ptr, false)
+    }
+
     /// Try release handle if there is no external reference and no reinsertion is needed.
     ///
     /// Return the entry if the handle is released.
@@ -321,6 +627,12 @@ where
             strict_assert!(!handle.base().is_in_indexer());
         }
 
+        // A pinned entry is kept in the cache until it is unpinned or removed.
+        if handle.base().is_in_indexer() && handle.base().is_pinned() {
+            strict_assert!(!handle.base().is_in_eviction());
+            return None;
+        }
+
         // If the entry is not updated or removed from the cache, try to reinsert it or remove it from the indexer and
         // the eviction container.
         if handle.base().is_in_indexer() {
@@ -356,8 +668,14 @@ where
 
         self.state.metrics.memory_release.increment(1);
 
-        self.usage.fetch_sub(handle.base().weight(), Ordering::Relaxed);
-        self.state.metrics.memory_usage.decrement(handle.base().weight() as f64);
+        // The weight of a rejected entry is never charged.
+        if !handle.base().is_rejected() {
+            self.usage.fetch_sub(handle.base().weight(), Ordering::Relaxed);
+            self.state.metrics.memory_usage.decrement(handle.base().weight() as f64);
+            self.priority_metrics(handle)
+                .usage
+                .decrement(handle.base().weight() as f64);
+        }
         let ((key, value), context, weight) = handle.base_mut().take();
 
         let handle = Box::from_raw(ptr.as_ptr());
@@ -377,12 +695,17 @@ where
 {
     pub name: String,
     pub capacity: usize,
+    pub pinned_capacity: usize,
     pub shards: usize,
     pub eviction_config: E::Config,
     pub object_pool_capacity: usize,
+    pub entry_metadata: bool,
     pub hash_builder: S,
     pub weighter: Arc<dyn Weighter<K, V>>,
     pub event_listener: Option<Arc<dyn EventListener<Key = K, Value = V>>>,
+    pub reclaim_queue_capacity: Option<usize>,
+    pub admission_config: AdmissionConfig,
+    pub miss_ratio_curve_config: Option<MissRatioCurveConfig>,
 }
 
 type GenericFetchHit<K, V, E, I, S> = Option<GenericCacheEntry<K, V, E, I, S>>;
@@ -476,14 +799,22 @@ where
 {
     shards: Vec<Mutex<GenericCacheShard<K, V, E, I, S>>>,
 
-    capacity: usize,
+    capacity: AtomicUsize,
     usages: Vec<Arc<AtomicUsize>>,
 
+    pinned_capacity: usize,
+    pinned_usages: Vec<Arc<AtomicUsize>>,
+
+    /// Serializes runtime updates of the capacity and the eviction config.
+    eviction_config: Mutex<E::Config>,
+
     context: Arc<SharedState<K, V, E::Handle>>,
 
     hash_builder: S,
     weighter: Arc<dyn Weighter<K, V>>,
 
+    mrc: Option<MissRatioCurveTracker>,
+
     _metrics: Arc<Metrics>,
 }
 
@@ -500,29 +831,57 @@ where
         let metrics = Arc::new(Metrics::new(&config.name));
 
         let usages = (0..config.shards).map(|_| Arc::new(AtomicUsize::new(0))).collect_vec();
+        let pinned_usages = (0..config.shards).map(|_| Arc::new(AtomicUsize::new(0))).collect_vec();
         let context = Arc::new(SharedState {
             metrics: metrics.clone(),
+            name: config.name.clone(),
+            priority_metrics: (0..E::priorities(&config.eviction_config))
+                .map(|_| OnceLock::new())
+                .collect(),
             object_pool: ObjectPool::new_with_create(config.object_pool_capacity, Box::default),
+            entry_metadata: config.entry_metadata,
+            reclaimer: config
+                .reclaim_queue_capacity
+                .map(|capacity| Reclaimer::new(&config.name, capacity, config.event_listener.clone(), metrics.clone())),
             event_listener: config.event_listener,
         });
 
+        let mrc = config
+            .miss_ratio_curve_config
+            .as_ref()
+            .map(|mrc_config| MissRatioCurveTracker::new(&config.name, "memory", mrc_config));
+
         let shard_capacity = config.capacity / config.shards;
+        let shard_pinned_capacity = config.pinned_capacity / config.shards;
 
         let shards = usages
             .iter()
-            .map(|usage| {
-                GenericCacheShard::new(shard_capacity, &config.eviction_config, usage.clone(), context.clone())
+            .zip(pinned_usages.iter())
+            .map(|(usage, pinned_usage)| {
+                GenericCacheShard::new(
+                    shard_capacity,
+                    &config.eviction_config,
+                    &config.admission_config,
+                    usage.clone(),
+                    shard_pinned_capacity,
+                    pinned_usage.clone(),
+                    context.clone(),
+                )
             })
             .map(Mutex::new)
             .collect_vec();
 
         Self {
             shards,
-            capacity: config.capacity,
+            capacity: AtomicUsize::new(config.capacity),
             usages,
+            pinned_capacity: config.pinned_capacity,
+            pinned_usages,
+            eviction_config: Mutex::new(config.eviction_config),
             context,
             hash_builder: config.hash_builder,
             weighter: config.weighter,
+            mrc,
             _metrics: metrics,
         }
     }
@@ -539,7 +898,8 @@ where
         value: V,
         context: CacheContext,
     ) -> GenericCacheEntry<K, V, E, I, S> {
-        self.emplace(key, value, context, false)
+        let hash = self.hash_builder.hash_one(&key);
+        self.emplace(hash, key, value, context, false)
     }
 
     #[fastrace::trace(name = "foyer::memory::generic::deposit")]
@@ -554,18 +914,20 @@ where
         value: V,
         context: CacheContext,
     ) -> GenericCacheEntry<K, V, E, I, S> {
-        self.emplace(key, value, context, true)
+        let hash = self.hash_builder.hash_one(&key);
+        self.emplace(hash, key, value, context, true)
     }
 
+    /// Insert an entry whose key hash has already been calculated with the hash builder of the cache.
     #[fastrace::trace(name = "foyer::memory::generic::emplace")]
-    fn emplace(
+    pub(crate) fn emplace(
         self: &Arc<Self>,
+        hash: u64,
         key: K,
         value: V,
         context: CacheContext,
         deposit: bool,
     ) -> GenericCacheEntry<K, V, E, I, S> {
-        let hash = self.hash_builder.hash_one(&key);
         let weight = (self.weighter)(&key, &value);
 
         let mut to_release = vec![];
@@ -582,6 +944,7 @@ where
             let entry = GenericCacheEntry {
                 cache: self.clone(),
                 ptr,
+                passive: false,
             };
             (entry, waiters)
         };
@@ -591,17 +954,20 @@ where
                 let _ = waiter.send(GenericCacheEntry {
                     cache: self.clone(),
                     ptr: entry.ptr,
+                    passive: false,
                 });
             }
         }
 
-        // Do not deallocate data within the lock section.
-        if let Some(listener) = self.context.event_listener.as_ref() {
-            for (k, v, _c, _w) in to_release {
-                listener.on_memory_release(k, v);
+        if let Some(mrc) = self.mrc.as_ref() {
+            if !entry.is_rejected() {
+                mrc.insert(hash, weight);
             }
         }
 
+        // Do not deallocate data within the lock section.
+        self.release(to_release);
+
         entry
     }
 
@@ -612,12 +978,19 @@ where
         Q: Hash + Eq + ?Sized,
     {
         let hash = self.hash_builder.hash_one(key);
+        self.remove_with_hash(hash, key)
+    }
 
+    pub(crate) fn remove_with_hash<Q>(self: &Arc<Self>, hash: u64, key: &Q) -> Option<GenericCacheEntry<K, V, E, I, S>>
+    where
+        Q: Equivalent<K> + ?Sized,
+    {
         unsafe {
             let mut shard = self.shard(hash as usize % self.shards.len());
             shard.remove(hash, key).map(|ptr| GenericCacheEntry {
                 cache: self.clone(),
                 ptr,
+                passive: false,
             })
         }
     }
@@ -629,14 +1002,27 @@ where
         Q: Hash + Eq + ?Sized,
     {
         let hash = self.hash_builder.hash_one(key);
+        self.get_with_hash(hash, key)
+    }
 
-        unsafe {
+    pub(crate) fn get_with_hash<Q>(self: &Arc<Self>, hash: u64, key: &Q) -> Option<GenericCacheEntry<K, V, E, I, S>>
+    where
+        Q: Equivalent<K> + ?Sized,
+    {
+        let entry = unsafe {
             let mut shard = self.shard(hash as usize % self.shards.len());
             shard.get(hash, key).map(|ptr| GenericCacheEntry {
                 cache: self.clone(),
                 ptr,
+                passive: false,
             })
+        };
+
+        if let Some(mrc) = self.mrc.as_ref() {
+            mrc.access(hash, entry.as_ref().map(|entry| entry.weight()));
         }
+
+        entry
     }
 
     pub fn contains<Q>(self: &Arc<Self>, key: &Q) -> bool
@@ -645,7 +1031,13 @@ where
         Q: Hash + Eq + ?Sized,
     {
         let hash = self.hash_builder.hash_one(key);
+        self.contains_with_hash(hash, key)
+    }
 
+    pub(crate) fn contains_with_hash<Q>(&self, hash: u64, key: &Q) -> bool
+    where
+        Q: Equivalent<K> + ?Sized,
+    {
         unsafe {
             let mut shard = self.shard(hash as usize % self.shards.len());
             shard.contains(hash, key)
@@ -658,13 +1050,57 @@ where
         Q: Hash + Eq + ?Sized,
     {
         let hash = self.hash_builder.hash_one(key);
+        self.touch_with_hash(hash, key)
+    }
 
+    pub(crate) fn touch_with_hash<Q>(&self, hash: u64, key: &Q) -> bool
+    where
+        Q: Equivalent<K> + ?Sized,
+    {
         unsafe {
             let mut shard = self.shard(hash as usize % self.shards.len());
             shard.touch(hash, key)
         }
     }
 
+    pub fn pin<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let hash = self.hash_builder.hash_one(key);
+        self.pin_with_hash(hash, key)
+    }
+
+    pub(crate) fn pin_with_hash<Q>(&self, hash: u64, key: &Q) -> bool
+    where
+        Q: Equivalent<K> + ?Sized,
+    {
+        unsafe {
+            let mut shard = self.shard(hash as usize % self.shards.len());
+            shard.pin(hash, key)
+        }
+    }
+
+    pub fn unpin<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let hash = self.hash_builder.hash_one(key);
+        self.unpin_with_hash(hash, key)
+    }
+
+    pub(crate) fn unpin_with_hash<Q>(&self, hash: u64, key: &Q) -> bool
+    where
+        Q: Equivalent<K> + ?Sized,
+    {
+        unsafe {
+            let mut shard = self.shard(hash as usize % self.shards.len());
+            shard.unpin(hash, key)
+        }
+    }
+
     #[fastrace::trace(name = "foyer::memory::generic::clear")]
     pub fn clear(&self) {
         let mut to_release = vec![];
@@ -674,21 +1110,184 @@ where
         }
 
         // Do not deallocate data within the lock section.
-        if let Some(listener) = self.context.event_listener.as_ref() {
-            for (k, v, _c, _w) in to_release {
-                listener.on_memory_release(k, v);
+        self.release(to_release);
+    }
+
+    /// Recompute the weight of the entry with the given key with the weighter, e.g. after the value is mutated through
+    /// interior mutability.
+    ///
+    /// Return `false` if the entry is not found.
+    #[fastrace::trace(name = "foyer::memory::generic::update_weight")]
+    pub fn update_weight<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let hash = self.hash_builder.hash_one(key);
+        self.update_weight_with_hash(hash, key)
+    }
+
+    pub(crate) fn update_weight_with_hash<Q>(&self, hash: u64, key: &Q) -> bool
+    where
+        Q: Equivalent<K> + ?Sized,
+    {
+        let mut to_release = vec![];
+
+        let found = unsafe {
+            let mut shard = self.shard(hash as usize % self.shards.len());
+            match shard.indexer.get(hash, key) {
+                Some(ptr) => {
+                    shard.reweigh(ptr, self.weighter.as_ref(), &mut to_release);
+                    true
+                }
+                None => false,
             }
+        };
+
+        // Do not deallocate data within the lock section.
+        self.release(to_release);
+
+        found
+    }
+
+    pub(crate) unsafe fn reweigh_handle(&self, ptr: NonNull<E::Handle>) -> usize {
+        let mut to_release = vec![];
+
+        let weight = {
+            let mut shard = self.shard(ptr.as_ref().base().hash() as usize % self.shards.len());
+            shard.reweigh(ptr, self.weighter.as_ref(), &mut to_release)
+        };
+
+        // Do not deallocate data within the lock section.
+        self.release(to_release);
+
+        weight
+    }
+
+    /// Iterate over the entries of the cache, one shard at a time.
+    ///
+    /// A shard is locked only while the references of its entries are taken, and the entries are yielded after the
+    /// lock is released.
+    pub fn iter(self: &Arc<Self>) -> impl Iterator<Item = GenericCacheEntry<K, V, E, I, S>> + '_ {
+        (0..self.shards.len()).flat_map(|shard| self.shard_entries(shard))
+    }
+
+    /// Take references of the entries of the given shard.
+    pub(crate) fn shard_entries(self: &Arc<Self>, shard: usize) -> Vec<GenericCacheEntry<K, V, E, I, S>> {
+        let ptrs = unsafe { self.shard(shard).entries() };
+        ptrs.into_iter()
+            .map(|ptr| GenericCacheEntry {
+                cache: self.clone(),
+                ptr,
+                passive: true,
+            })
+            .collect()
+    }
+
+    /// Iterate over the keys of the cache, one shard at a time.
+    ///
+    /// A shard is locked only while its keys are cloned.
+    pub fn keys(&self) -> impl Iterator<Item = K> + '_
+    where
+        K: Clone,
+    {
+        (0..self.shards.len()).flat_map(|shard| {
+            let mut keys = vec![];
+            self.visit_shard_keys(shard, &mut |key: &K| keys.push(key.clone()));
+            keys
+        })
+    }
+
+    /// Visit the keys of the given shard with the shard locked.
+    pub(crate) fn visit_shard_keys(&self, shard: usize, f: &mut dyn FnMut(&K)) {
+        let shard = self.shard(shard);
+        unsafe { shard.indexer.iter().for_each(|ptr| f(ptr.as_ref().key())) };
+    }
+
+    /// Retain only the entries that the predicate returns `true`, one shard at a time.
+    ///
+    /// The predicate is called with the shard locked, so it must not access the cache.
+    #[fastrace::trace(name = "foyer::memory::generic::retain")]
+    pub fn retain(&self, mut f: impl FnMut(&K, &V) -> bool) {
+        let mut to_release = vec![];
+        for shard in self.shards.iter() {
+            let mut shard = shard.lock();
+            unsafe { shard.retain(&mut f, &mut to_release) };
+            drop(shard);
+
+            // Do not deallocate data within the lock section.
+            self.release(std::mem::take(&mut to_release));
+        }
+    }
+
+    /// Resize the capacity of the cache.
+    ///
+    /// The capacity is redistributed evenly among the shards. Entries are evicted if the usage exceeds the new
+    /// capacity.
+    #[fastrace::trace(name = "foyer::memory::generic::resize")]
+    pub fn resize(&self, capacity: usize) {
+        let eviction_config = self.eviction_config.lock();
+        self.update(capacity, &eviction_config);
+        self.capacity.store(capacity, Ordering::Relaxed);
+    }
+
+    /// Update the eviction config of the cache.
+    ///
+    /// Returns the config back if it is invalid. The cache is left unchanged in that case.
+    #[fastrace::trace(name = "foyer::memory::generic::update_eviction_config")]
+    pub fn update_eviction_config(&self, config: E::Config) -> std::result::Result<(), E::Config> {
+        if let Err(e) = E::validate(&config) {
+            tracing::warn!("[memory]: Reject invalid eviction config: {e}");
+            return Err(config);
+        }
+
+        let mut eviction_config = self.eviction_config.lock();
+        self.update(self.capacity(), &config);
+        *eviction_config = config;
+        Ok(())
+    }
+
+    /// The current eviction config of the cache.
+    pub fn eviction_config(&self) -> E::Config {
+        self.eviction_config.lock().clone()
+    }
+
+    fn update(&self, capacity: usize, eviction_config: &E::Config) {
+        let shard_capacity = capacity / self.shards.len();
+
+        let mut to_release = vec![];
+        for shard in self.shards.iter() {
+            let mut shard = shard.lock();
+            unsafe { shard.update(shard_capacity, eviction_config, &mut to_release) };
         }
+
+        // Do not deallocate data within the lock section.
+        self.release(to_release);
     }
 
     pub fn capacity(&self) -> usize {
-        self.capacity
+        self.capacity.load(Ordering::Relaxed)
     }
 
     pub fn usage(&self) -> usize {
         self.usages.iter().map(|usage| usage.load(Ordering::Relaxed)).sum()
     }
 
+    pub fn miss_ratio_curve(&self) -> Option<MissRatioCurve> {
+        self.mrc.as_ref().map(|mrc| mrc.curve())
+    }
+
+    pub fn pinned_capacity(&self) -> usize {
+        self.pinned_capacity
+    }
+
+    pub fn pinned_usage(&self) -> usize {
+        self.pinned_usages
+            .iter()
+            .map(|usage| usage.load(Ordering::Relaxed))
+            .sum()
+    }
+
     pub fn metrics(&self) -> &Metrics {
         &self.context.metrics
     }
@@ -701,22 +1300,42 @@ where
         self.shards.len()
     }
 
-    unsafe fn try_release_external_handle(&self, ptr: NonNull<E::Handle>) {
+    pub(crate) unsafe fn try_release_external_handle(&self, ptr: NonNull<E::Handle>, passive: bool) {
         let entry = {
             let base = ptr.as_ref().base();
             let mut shard = self.shard(base.hash() as usize % self.shards.len());
-            shard.try_release_external_handle(ptr)
+            match passive {
+                false => shard.try_release_external_handle(ptr),
+                true => shard.try_release_passive_handle(ptr),
+            }
         };
 
         // Do not deallocate data within the lock section.
-        if let Some(listener) = self.context.event_listener.as_ref() {
-            if let Some((k, v, _c, _w)) = entry {
-                listener.on_memory_release(k, v);
+        if let Some(entry) = entry {
+            self.release(vec![entry]);
+        }
+    }
+
+    /// Release the entries released by the shards, with the reclaimer if enabled.
+    ///
+    /// Must be called outside of the lock section.
+    fn release(&self, to_release: Vec<(K, V, <E::Handle as Handle>::Context, usize)>) {
+        if to_release.is_empty() {
+            return;
+        }
+        match self.context.reclaimer.as_ref() {
+            Some(reclaimer) => reclaimer.reclaim(to_release.into_iter().map(|(k, v, _c, _w)| (k, v)).collect()),
+            None => {
+                if let Some(listener) = self.context.event_listener.as_ref() {
+                    for (k, v, _c, _w) in to_release {
+                        listener.on_memory_release(k, v);
+                    }
+                }
             }
         }
     }
 
-    unsafe fn inc_refs(&self, mut ptr: NonNull<E::Handle>) {
+    pub(crate) unsafe fn inc_refs(&self, mut ptr: NonNull<E::Handle>) {
         let shard = self.shard(ptr.as_ref().base().hash() as usize % self.shards.len());
         ptr.as_mut().base_mut().inc_refs();
         drop(shard);
@@ -781,24 +1400,61 @@ where
         ID: Into<Diversion<std::result::Result<V, ER>, FetchMark>>,
     {
         let hash = self.hash_builder.hash_one(&key);
+        GenericFetch::new(self.fetch_with_hash(hash, key, K::clone, context, fetch, runtime))
+    }
+}
 
+impl<K, V, E, I, S> GenericCache<K, V, E, I, S>
+where
+    K: Key,
+    V: Value,
+    E: Eviction,
+    E::Handle: KeyedHandle<Key = K, Data = (K, V)>,
+    I: Indexer<Key = K, Handle = E::Handle>,
+    S: HashBuilder,
+{
+    /// Fetch an entry whose key hash has already been calculated with the hash builder of the cache.
+    ///
+    /// `clone_key` is only called on cache miss, so `K` is not required to be [`Clone`] here.
+    pub(crate) fn fetch_with_hash<F, FU, ER, ID>(
+        self: &Arc<Self>,
+        hash: u64,
+        key: K,
+        clone_key: impl FnOnce(&K) -> K,
+        context: CacheContext,
+        fetch: F,
+        runtime: &SingletonHandle,
+    ) -> GenericFetchInner<K, V, E, I, S, ER>
+    where
+        F: FnOnce() -> FU,
+        FU: Future<Output = ID> + Send + 'static,
+        ER: Send + 'static + Debug,
+        ID: Into<Diversion<std::result::Result<V, ER>, FetchMark>>,
+    {
         {
             let mut shard = self.shard(hash as usize % self.shards.len());
 
             if let Some(ptr) = unsafe { shard.get(hash, &key) } {
-                return GenericFetch::new(GenericFetchInner::Hit(Some(GenericCacheEntry {
+                if let Some(mrc) = self.mrc.as_ref() {
+                    mrc.access(hash, Some(unsafe { ptr.as_ref().base().weight() }));
+                }
+                return GenericFetchInner::Hit(Some(GenericCacheEntry {
                     cache: self.clone(),
                     ptr,
-                })));
+                    passive: false,
+                }));
+            }
+            if let Some(mrc) = self.mrc.as_ref() {
+                mrc.access(hash, None);
             }
-            match shard.waiters.entry(key.clone()) {
+            match shard.waiters.entry(clone_key(&key)) {
                 HashMapEntry::Occupied(mut o) => {
                     let (tx, rx) = oneshot::channel();
                     o.get_mut().push(tx);
                     shard.state.metrics.memory_queue.increment(1);
-                    return GenericFetch::new(GenericFetchInner::Wait(rx.in_span(Span::enter_with_local_parent(
+                    return GenericFetchInner::Wait(rx.in_span(Span::enter_with_local_parent(
                         "foyer::memory::generic::fetch_with_runtime::wait",
-                    ))));
+                    )));
                 }
                 HashMapEntry::Vacant(v) => {
                     v.insert(vec![]);
@@ -826,7 +1482,7 @@ where
                         return Diversion { target: Err(e), store };
                     }
                 };
-                let entry = cache.insert_with_context(key, value, context);
+                let entry = cache.emplace(hash, key, value, context, false);
                 Diversion {
                     target: Ok(entry),
                     store,
@@ -836,7 +1492,7 @@ where
                 "foyer::memory::generic::fetch_with_runtime::spawn",
             )),
         );
-        GenericFetch::new(GenericFetchInner::Miss(join))
+        GenericFetchInner::Miss(join)
     }
 }
 
@@ -865,6 +1521,9 @@ where
 {
     cache: Arc<GenericCache<K, V, E, I, S>>,
     ptr: NonNull<E::Handle>,
+    /// The reference is taken without notifying the eviction container (e.g. by iteration), so it is released
+    /// without notifying the eviction container either.
+    passive: bool,
 }
 
 impl<K, V, E, I, S> Debug for GenericCacheEntry<K, V, E, I, S>
@@ -877,7 +1536,10 @@ where
     S: HashBuilder,
 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("GenericCacheEntry").finish()
+        f.debug_struct("GenericCacheEntry")
+            .field("hash", &self.hash())
+            .field("weight", &self.weight())
+            .finish()
     }
 }
 
@@ -917,6 +1579,27 @@ where
     pub fn is_outdated(&self) -> bool {
         unsafe { !self.ptr.as_ref().base().is_in_indexer() }
     }
+
+    pub fn is_rejected(&self) -> bool {
+        unsafe { self.ptr.as_ref().base().is_rejected() }
+    }
+
+    pub fn metadata(&self) -> Option<EntryMetadata> {
+        unsafe { self.ptr.as_ref().base().metadata().map(|metadata| metadata.snapshot()) }
+    }
+
+    pub fn reweigh(&self) -> usize {
+        unsafe { self.cache.reweigh_handle(self.ptr) }
+    }
+
+    /// Take the cache, the handle and the passive flag out of the entry without releasing its reference.
+    #[expect(clippy::type_complexity)]
+    pub(crate) fn into_raw(self) -> (Arc<GenericCache<K, V, E, I, S>>, NonNull<E::Handle>, bool) {
+        let this = ManuallyDrop::new(self);
+        // The entry is never dropped, so the cache is moved out exactly once.
+        let cache = unsafe { std::ptr::read(&this.cache) };
+        (cache, this.ptr, this.passive)
+    }
 }
 
 impl<K, V, E, I, S> Clone for GenericCacheEntry<K, V, E, I, S>
@@ -933,6 +1616,7 @@ where
         Self {
             cache: self.cache.clone(),
             ptr: self.ptr,
+            passive: self.passive,
         }
     }
 }
@@ -947,7 +1631,7 @@ where
     S: HashBuilder,
 {
     fn drop(&mut self) {
-        unsafe { self.cache.try_release_external_handle(self.ptr) };
+        unsafe { self.cache.try_release_external_handle(self.ptr, self.passive) };
     }
 }
 
@@ -1000,7 +1684,7 @@ mod tests {
         cache::{FifoCache, FifoCacheEntry, LfuCache, LruCache, LruCacheEntry, S3FifoCache},
         eviction::{
             fifo::{FifoConfig, FifoHandle},
-            lru::LruConfig,
+            lru::{Lru, LruConfig, LruHandle},
             test_utils::TestEviction,
         },
         indexer::{hash_table::HashTableIndexer, sanity::SanityIndexer},
@@ -1015,11 +1699,11 @@ mod tests {
         is_send_sync_static::<LruCache<(), ()>>();
     }
 
-    #[expect(clippy::type_complexity)]
-    fn fuzzy<E>(cache: Arc<GenericCache<u64, u64, E, SanityIndexer<HashTableIndexer<u64, E::Handle>>>>)
+    fn fuzzy<E, I>(cache: Arc<GenericCache<u64, u64, E, I>>)
     where
         E: Eviction,
         E::Handle: KeyedHandle<Key = u64, Data = (u64, u64)>,
+        I: Indexer<Key = u64, Handle = E::Handle>,
     {
         let handles = (0..8)
             .map(|i| {
@@ -1057,12 +1741,17 @@ mod tests {
         fuzzy(Arc::new(FifoCache::<u64, u64>::new(GenericCacheConfig {
             name: "test".to_string(),
             capacity: 256,
+            pinned_capacity: 0,
             shards: 4,
             eviction_config: FifoConfig::default(),
             object_pool_capacity: 16,
+            entry_metadata: true,
             hash_builder: RandomState::default(),
             weighter: Arc::new(|_, _| 1),
             event_listener: None,
+            reclaim_queue_capacity: None,
+            admission_config: Default::default(),
+            miss_ratio_curve_config: None,
         })))
     }
 
@@ -1071,12 +1760,17 @@ mod tests {
         fuzzy(Arc::new(LruCache::<u64, u64>::new(GenericCacheConfig {
             name: "test".to_string(),
             capacity: 256,
+            pinned_capacity: 0,
             shards: 4,
             eviction_config: LruConfig::default(),
             object_pool_capacity: 16,
+            entry_metadata: true,
             hash_builder: RandomState::default(),
             weighter: Arc::new(|_, _| 1),
             event_listener: None,
+            reclaim_queue_capacity: None,
+            admission_config: Default::default(),
+            miss_ratio_curve_config: None,
         })))
     }
 
@@ -1085,12 +1779,68 @@ mod tests {
         fuzzy(Arc::new(LfuCache::<u64, u64>::new(GenericCacheConfig {
             name: "test".to_string(),
             capacity: 256,
+            pinned_capacity: 0,
             shards: 4,
             eviction_config: LfuConfig::default(),
             object_pool_capacity: 16,
+            entry_metadata: true,
+            hash_builder: RandomState::default(),
+            weighter: Arc::new(|_, _| 1),
+            event_listener: None,
+            reclaim_queue_capacity: None,
+            admission_config: Default::default(),
+            miss_ratio_curve_config: None,
+        })))
+    }
+
+    #[test]
+    fn test_hash_table_indexer_fuzzy() {
+        fuzzy(Arc::new(GenericCache::<
+            u64,
+            u64,
+            Lru<(u64, u64)>,
+            SanityIndexer<HashTableIndexer<u64, LruHandle<(u64, u64)>>>,
+        >::new(GenericCacheConfig {
+            name: "test".to_string(),
+            capacity: 256,
+            pinned_capacity: 0,
+            shards: 4,
+            eviction_config: LruConfig::default(),
+            object_pool_capacity: 16,
+            entry_metadata: true,
+            hash_builder: RandomState::default(),
+            weighter: Arc::new(|_, _| 1),
+            event_listener: None,
+            reclaim_queue_capacity: None,
+            admission_config: Default::default(),
+            miss_ratio_curve_config: None,
+        })))
+    }
+
+    #[cfg(feature = "intrusive_indexer")]
+    #[test]
+    fn test_intrusive_indexer_fuzzy() {
+        use crate::indexer::intrusive::IntrusiveIndexer;
+
+        fuzzy(Arc::new(GenericCache::<
+            u64,
+            u64,
+            Lru<(u64, u64)>,
+            SanityIndexer<IntrusiveIndexer<u64, LruHandle<(u64, u64)>>>,
+        >::new(GenericCacheConfig {
+            name: "test".to_string(),
+            capacity: 256,
+            pinned_capacity: 0,
+            shards: 4,
+            eviction_config: LruConfig::default(),
+            object_pool_capacity: 16,
+            entry_metadata: true,
             hash_builder: RandomState::default(),
             weighter: Arc::new(|_, _| 1),
             event_listener: None,
+            reclaim_queue_capacity: None,
+            admission_config: Default::default(),
+            miss_ratio_curve_config: None,
         })))
     }
 
@@ -1099,12 +1849,17 @@ mod tests {
         fuzzy(Arc::new(S3FifoCache::<u64, u64>::new(GenericCacheConfig {
             name: "test".to_string(),
             capacity: 256,
+            pinned_capacity: 0,
             shards: 4,
             eviction_config: S3FifoConfig::default(),
             object_pool_capacity: 16,
+            entry_metadata: true,
             hash_builder: RandomState::default(),
             weighter: Arc::new(|_, _| 1),
             event_listener: None,
+            reclaim_queue_capacity: None,
+            admission_config: Default::default(),
+            miss_ratio_curve_config: None,
         })))
     }
 
@@ -1112,12 +1867,17 @@ mod tests {
         let config = GenericCacheConfig {
             name: "test".to_string(),
             capacity,
+            pinned_capacity: 0,
             shards: 1,
-            eviction_config: FifoConfig {},
+            eviction_config: FifoConfig::default(),
             object_pool_capacity: 1,
+            entry_metadata: true,
             hash_builder: RandomState::default(),
             weighter: Arc::new(|_, v: &String| v.len()),
             event_listener: None,
+            reclaim_queue_capacity: None,
+            admission_config: Default::default(),
+            miss_ratio_curve_config: None,
         };
         Arc::new(FifoCache::<u64, String>::new(config))
     }
@@ -1126,14 +1886,17 @@ mod tests {
         let config = GenericCacheConfig {
             name: "test".to_string(),
             capacity,
+            pinned_capacity: 0,
             shards: 1,
-            eviction_config: LruConfig {
-                high_priority_pool_ratio: 0.0,
-            },
+            eviction_config: LruConfig::default(),
             object_pool_capacity: 1,
+            entry_metadata: true,
             hash_builder: RandomState::default(),
             weighter: Arc::new(|_, v: &String| v.len()),
             event_listener: None,
+            reclaim_queue_capacity: None,
+            admission_config: Default::default(),
+            miss_ratio_curve_config: None,
         };
         Arc::new(LruCache::<u64, String>::new(config))
     }
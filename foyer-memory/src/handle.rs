@@ -18,8 +18,10 @@ use foyer_common::{
     code::{Key, Value},
     strict_assert,
 };
+#[cfg(feature = "intrusive_indexer")]
+use foyer_intrusive::hash_table::HashTableLink;
 
-use crate::context::Context;
+use crate::{context::Context, metadata::Metadata};
 
 bitflags! {
     #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -27,14 +29,27 @@ bitflags! {
         const IN_INDEXER = 0b00000001;
         const IN_EVICTION = 0b00000010;
         const IS_DEPOSIT= 0b00000100;
+        const IS_PINNED = 0b00001000;
+        const IS_REJECTED = 0b00010000;
     }
 }
 
+/// The handle of a cache entry, shared by the indexer and the eviction container.
+///
+/// A handle of a custom eviction algorithm usually embeds a [`BaseHandle`] together with the intrusive links or the
+/// per-entry states that the eviction algorithm needs.
+///
+/// The handle is created with [`Default`] and recycled by the cache, so [`Default`] must return an uninitialized
+/// handle.
 pub trait Handle: Send + Sync + 'static + Default {
+    /// The data carried by the handle. It is always `(K, V)` for the in-memory cache.
     type Data;
+    /// The context carried by the handle, converted from and into [`CacheContext`](crate::CacheContext).
     type Context: Context;
 
+    /// Get the reference of the base handle.
     fn base(&self) -> &BaseHandle<Self::Data, Self::Context>;
+    /// Get the mutable reference of the base handle.
     fn base_mut(&mut self) -> &mut BaseHandle<Self::Data, Self::Context>;
 }
 
@@ -64,6 +79,8 @@ where
     }
 }
 
+/// The common part of all handles, including the entry, the key hash, the weight, the external reference count and the
+/// flags used by the cache.
 #[derive(Debug)]
 pub struct BaseHandle<T, C> {
     /// key, value, context
@@ -76,6 +93,11 @@ pub struct BaseHandle<T, C> {
     refs: usize,
     /// flags that used by the general cache abstraction
     flags: BaseHandleFlags,
+    /// insertion time, last access time and hit count, allocated only if enabled
+    metadata: Option<Box<Metadata>>,
+    /// link of the intrusive indexer
+    #[cfg(feature = "intrusive_indexer")]
+    hash_link: HashTableLink,
 }
 
 impl<T, C> Default for BaseHandle<T, C> {
@@ -94,6 +116,9 @@ impl<T, C> BaseHandle<T, C> {
             weight: 0,
             refs: 0,
             flags: BaseHandleFlags::empty(),
+            metadata: None,
+            #[cfg(feature = "intrusive_indexer")]
+            hash_link: HashTableLink::default(),
         }
     }
 
@@ -107,6 +132,7 @@ impl<T, C> BaseHandle<T, C> {
         self.weight = weight;
         self.refs = 0;
         self.flags = BaseHandleFlags::empty();
+        // Keep the allocated metadata for the recycled handle, it is reset by the cache if enabled.
     }
 
     /// Take key and value from the handle and reset it to the uninitialized state.
@@ -165,6 +191,38 @@ impl<T, C> BaseHandle<T, C> {
         self.weight
     }
 
+    /// Set the weight of the handle.
+    ///
+    /// Only used by the cache, or by the eviction container in [`Eviction::reweigh`](crate::Eviction::reweigh).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the weight is `0`.
+    #[inline(always)]
+    pub fn set_weight(&mut self, weight: usize) {
+        assert_ne!(weight, 0);
+        self.weight = weight;
+    }
+
+    /// Get the metadata of the handle, `None` if the metadata is disabled. Only used by the cache.
+    #[inline(always)]
+    pub(crate) fn metadata(&self) -> Option<&Metadata> {
+        self.metadata.as_deref()
+    }
+
+    /// Allocate the metadata of the handle if not yet, and reset it. Only used by the cache.
+    #[inline(always)]
+    pub(crate) fn init_metadata(&mut self) {
+        self.metadata.get_or_insert_with(Default::default).init();
+    }
+
+    /// Get the link of the intrusive indexer. Only used by the cache.
+    #[cfg(feature = "intrusive_indexer")]
+    #[inline(always)]
+    pub(crate) fn hash_link(&self) -> &HashTableLink {
+        &self.hash_link
+    }
+
     /// Increase the external reference count of the handle, returns the new reference count.
     #[inline(always)]
     pub fn inc_refs(&mut self) -> usize {
@@ -197,6 +255,7 @@ impl<T, C> BaseHandle<T, C> {
         self.refs() > 0
     }
 
+    /// Set if the handle is in the indexer. Only used by the cache.
     #[inline(always)]
     pub fn set_in_indexer(&mut self, in_cache: bool) {
         if in_cache {
@@ -206,11 +265,15 @@ impl<T, C> BaseHandle<T, C> {
         }
     }
 
+    /// Return `true` if the handle is in the indexer.
     #[inline(always)]
     pub fn is_in_indexer(&self) -> bool {
         self.flags.contains(BaseHandleFlags::IN_INDEXER)
     }
 
+    /// Set if the handle is in the eviction container.
+    ///
+    /// It must be maintained by the eviction container. See [`Eviction`](crate::Eviction).
     #[inline(always)]
     pub fn set_in_eviction(&mut self, in_eviction: bool) {
         if in_eviction {
@@ -220,11 +283,13 @@ impl<T, C> BaseHandle<T, C> {
         }
     }
 
+    /// Return `true` if the handle is in the eviction container.
     #[inline(always)]
     pub fn is_in_eviction(&self) -> bool {
         self.flags.contains(BaseHandleFlags::IN_EVICTION)
     }
 
+    /// Set if the handle is inserted by deposit and never accessed. Only used by the cache.
     #[inline(always)]
     pub fn set_deposit(&mut self, deposit: bool) {
         if deposit {
@@ -234,10 +299,45 @@ impl<T, C> BaseHandle<T, C> {
         }
     }
 
+    /// Return `true` if the handle is inserted by deposit and never accessed.
     #[inline(always)]
     pub fn is_deposit(&self) -> bool {
         self.flags.contains(BaseHandleFlags::IS_DEPOSIT)
     }
+
+    /// Set if the handle is pinned. Only used by the cache.
+    ///
+    /// A pinned handle is kept out of the eviction container, so it is never evicted.
+    #[inline(always)]
+    pub fn set_pinned(&mut self, pinned: bool) {
+        if pinned {
+            self.flags |= BaseHandleFlags::IS_PINNED;
+        } else {
+            self.flags -= BaseHandleFlags::IS_PINNED;
+        }
+    }
+
+    /// Return `true` if the handle is pinned.
+    #[inline(always)]
+    pub fn is_pinned(&self) -> bool {
+        self.flags.contains(BaseHandleFlags::IS_PINNED)
+    }
+
+    /// Set if the handle is rejected by the admission filter. Only used by the cache.
+    #[inline(always)]
+    pub fn set_rejected(&mut self, rejected: bool) {
+        if rejected {
+            self.flags |= BaseHandleFlags::IS_REJECTED;
+        } else {
+            self.flags -= BaseHandleFlags::IS_REJECTED;
+        }
+    }
+
+    /// Return `true` if the handle is rejected by the admission filter.
+    #[inline(always)]
+    pub fn is_rejected(&self) -> bool {
+        self.flags.contains(BaseHandleFlags::IS_REJECTED)
+    }
 }
 
 #[cfg(test)]
@@ -12,14 +12,19 @@
 //  See the License for the specific language governing permissions and
 //  limitations under the License.
 
-use std::{borrow::Borrow, hash::Hash, ptr::NonNull};
+use std::ptr::NonNull;
 
 use foyer_common::{code::Key, strict_assert};
-use hashbrown::hash_table::{Entry as HashTableEntry, HashTable};
+use hashbrown::{
+    hash_table::{Entry as HashTableEntry, HashTable},
+    Equivalent,
+};
 
 use super::Indexer;
 use crate::handle::KeyedHandle;
 
+/// The hashbrown-based indexer, which is the default indexer.
+#[cfg_attr(all(feature = "intrusive_indexer", not(test)), expect(dead_code))]
 pub struct HashTableIndexer<K, H>
 where
     K: Key,
@@ -83,20 +88,18 @@ where
 
     unsafe fn get<Q>(&self, hash: u64, key: &Q) -> Option<NonNull<Self::Handle>>
     where
-        Self::Key: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: Equivalent<Self::Key> + ?Sized,
     {
-        self.table.find(hash, |p| p.as_ref().key().borrow() == key).copied()
+        self.table.find(hash, |p| key.equivalent(p.as_ref().key())).copied()
     }
 
     unsafe fn remove<Q>(&mut self, hash: u64, key: &Q) -> Option<NonNull<Self::Handle>>
     where
-        Self::Key: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: Equivalent<Self::Key> + ?Sized,
     {
         match self
             .table
-            .entry(hash, |p| p.as_ref().key().borrow() == key, |p| p.as_ref().base().hash())
+            .entry(hash, |p| key.equivalent(p.as_ref().key()), |p| p.as_ref().base().hash())
         {
             HashTableEntry::Occupied(o) => {
                 let (mut p, _) = o.remove();
@@ -115,4 +118,8 @@ where
             ptr
         })
     }
+
+    unsafe fn iter(&self) -> impl Iterator<Item = NonNull<Self::Handle>> {
+        self.table.iter().copied()
+    }
 }
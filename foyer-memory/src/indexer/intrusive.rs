@@ -0,0 +1,144 @@
+//  Copyright 2024 foyer Project Authors
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::{fmt::Debug, marker::PhantomData, ptr::NonNull};
+
+use foyer_common::{code::Key, strict_assert};
+use foyer_intrusive::{
+    adapter::Adapter,
+    hash_table::{HashTable, HashTableLink},
+};
+use hashbrown::Equivalent;
+
+use super::Indexer;
+use crate::handle::KeyedHandle;
+
+/// The adapter for the link in the base handle of any handle type.
+///
+/// The offset of the link can't be resolved with `offset_of!` for an arbitrary handle type, so it is measured from
+/// a default handle. The offset is the same for all instances of the same type.
+pub struct HandleHashTableAdapter<H> {
+    offset: usize,
+    _marker: PhantomData<H>,
+}
+
+unsafe impl<H> Send for HandleHashTableAdapter<H> {}
+unsafe impl<H> Sync for HandleHashTableAdapter<H> {}
+
+impl<H> Debug for HandleHashTableAdapter<H> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HandleHashTableAdapter")
+            .field("offset", &self.offset)
+            .finish()
+    }
+}
+
+unsafe impl<H> Adapter for HandleHashTableAdapter<H>
+where
+    H: KeyedHandle,
+{
+    type Item = H;
+    type Link = HashTableLink;
+
+    fn new() -> Self {
+        let handle = H::default();
+        let offset = handle.base().hash_link() as *const _ as usize - &handle as *const _ as usize;
+        Self {
+            offset,
+            _marker: PhantomData,
+        }
+    }
+
+    unsafe fn link2ptr(&self, link: NonNull<Self::Link>) -> NonNull<Self::Item> {
+        NonNull::new_unchecked((link.as_ptr() as *mut u8).sub(self.offset) as *mut H)
+    }
+
+    unsafe fn ptr2link(&self, item: NonNull<Self::Item>) -> NonNull<Self::Link> {
+        NonNull::new_unchecked((item.as_ptr() as *mut u8).add(self.offset) as *mut HashTableLink)
+    }
+}
+
+/// The indexer that links the handles through the link in the base handle.
+///
+/// Compared to [`HashTableIndexer`](super::hash_table::HashTableIndexer), it takes no extra allocation per entry and
+/// resizes incrementally without a rehash pause.
+pub struct IntrusiveIndexer<K, H>
+where
+    K: Key,
+    H: KeyedHandle<Key = K>,
+{
+    table: HashTable<HandleHashTableAdapter<H>>,
+}
+
+impl<K, H> Indexer for IntrusiveIndexer<K, H>
+where
+    K: Key,
+    H: KeyedHandle<Key = K>,
+{
+    type Key = K;
+    type Handle = H;
+
+    fn new() -> Self {
+        Self {
+            table: HashTable::new(),
+        }
+    }
+
+    unsafe fn insert(&mut self, mut ptr: NonNull<Self::Handle>) -> Option<NonNull<Self::Handle>> {
+        let handle = ptr.as_mut();
+
+        strict_assert!(!handle.base().is_in_indexer());
+        handle.base_mut().set_in_indexer(true);
+
+        let key = handle.key();
+        self.table
+            .insert(handle.base().hash(), ptr, |h| h.key() == key)
+            .map(|mut old| {
+                let b = old.as_mut().base_mut();
+                strict_assert!(b.is_in_indexer());
+                b.set_in_indexer(false);
+                old
+            })
+    }
+
+    unsafe fn get<Q>(&self, hash: u64, key: &Q) -> Option<NonNull<Self::Handle>>
+    where
+        Q: Equivalent<Self::Key> + ?Sized,
+    {
+        self.table.find(hash, |h| key.equivalent(h.key()))
+    }
+
+    unsafe fn remove<Q>(&mut self, hash: u64, key: &Q) -> Option<NonNull<Self::Handle>>
+    where
+        Q: Equivalent<Self::Key> + ?Sized,
+    {
+        self.table.remove(hash, |h| key.equivalent(h.key())).map(|mut ptr| {
+            let b = ptr.as_mut().base_mut();
+            strict_assert!(b.is_in_indexer());
+            b.set_in_indexer(false);
+            ptr
+        })
+    }
+
+    unsafe fn drain(&mut self) -> impl Iterator<Item = NonNull<Self::Handle>> {
+        self.table.drain().map(|mut ptr| {
+            ptr.as_mut().base_mut().set_in_indexer(false);
+            ptr
+        })
+    }
+
+    unsafe fn iter(&self) -> impl Iterator<Item = NonNull<Self::Handle>> {
+        self.table.iter().map(NonNull::from)
+    }
+}
@@ -12,9 +12,10 @@
 //  See the License for the specific language governing permissions and
 //  limitations under the License.
 
-use std::{borrow::Borrow, hash::Hash, ptr::NonNull};
+use std::ptr::NonNull;
 
 use foyer_common::code::Key;
+use hashbrown::Equivalent;
 
 use crate::handle::KeyedHandle;
 
@@ -26,14 +27,28 @@ pub trait Indexer: Send + Sync + 'static {
     unsafe fn insert(&mut self, ptr: NonNull<Self::Handle>) -> Option<NonNull<Self::Handle>>;
     unsafe fn get<Q>(&self, hash: u64, key: &Q) -> Option<NonNull<Self::Handle>>
     where
-        Self::Key: Borrow<Q>,
-        Q: Hash + Eq + ?Sized;
+        Q: Equivalent<Self::Key> + ?Sized;
     unsafe fn remove<Q>(&mut self, hash: u64, key: &Q) -> Option<NonNull<Self::Handle>>
     where
-        Self::Key: Borrow<Q>,
-        Q: Hash + Eq + ?Sized;
+        Q: Equivalent<Self::Key> + ?Sized;
     unsafe fn drain(&mut self) -> impl Iterator<Item = NonNull<Self::Handle>>;
+    unsafe fn iter(&self) -> impl Iterator<Item = NonNull<Self::Handle>>;
 }
 
 pub mod hash_table;
+#[cfg(feature = "intrusive_indexer")]
+pub mod intrusive;
 pub mod sanity;
+
+/// The indexer used by the built-in caches.
+///
+/// It is [`HashTableIndexer`](hash_table::HashTableIndexer) by default, and `IntrusiveIndexer` with the
+/// `intrusive_indexer` feature enabled, which links the handles through a field in the base handle instead of a
+/// separate table. The field is only present with the feature enabled, so the users who don't want the intrusive table
+/// don't pay for it.
+#[cfg(not(feature = "intrusive_indexer"))]
+pub type DefaultIndexer<K, H> = hash_table::HashTableIndexer<K, H>;
+/// The indexer used by the built-in caches, which is [`IntrusiveIndexer`](intrusive::IntrusiveIndexer) with the
+/// `intrusive_indexer` feature enabled.
+#[cfg(feature = "intrusive_indexer")]
+pub type DefaultIndexer<K, H> = intrusive::IntrusiveIndexer<K, H>;
@@ -14,6 +14,8 @@
 
 use std::ptr::NonNull;
 
+use hashbrown::Equivalent;
+
 use super::Indexer;
 #[cfg(feature = "sanity")]
 use crate::handle::Handle;
@@ -49,8 +51,7 @@ where
 
     unsafe fn get<Q>(&self, hash: u64, key: &Q) -> Option<NonNull<Self::Handle>>
     where
-        Self::Key: std::borrow::Borrow<Q>,
-        Q: std::hash::Hash + Eq + ?Sized,
+        Q: Equivalent<Self::Key> + ?Sized,
     {
         self.indexer
             .get(hash, key)
@@ -59,8 +60,7 @@ where
 
     unsafe fn remove<Q>(&mut self, hash: u64, key: &Q) -> Option<NonNull<Self::Handle>>
     where
-        Self::Key: std::borrow::Borrow<Q>,
-        Q: std::hash::Hash + Eq + ?Sized,
+        Q: Equivalent<Self::Key> + ?Sized,
     {
         self.indexer
             .remove(hash, key)
@@ -70,6 +70,12 @@ where
     unsafe fn drain(&mut self) -> impl Iterator<Item = NonNull<Self::Handle>> {
         self.indexer.drain()
     }
+
+    unsafe fn iter(&self) -> impl Iterator<Item = NonNull<Self::Handle>> {
+        self.indexer
+            .iter()
+            .inspect(|ptr| assert!(ptr.as_ref().base().is_in_indexer()))
+    }
 }
 
 #[cfg(not(feature = "sanity"))]
@@ -90,16 +96,14 @@ where
 
     unsafe fn get<Q>(&self, hash: u64, key: &Q) -> Option<NonNull<Self::Handle>>
     where
-        Self::Key: std::borrow::Borrow<Q>,
-        Q: std::hash::Hash + Eq + ?Sized,
+        Q: Equivalent<Self::Key> + ?Sized,
     {
         self.indexer.get(hash, key)
     }
 
     unsafe fn remove<Q>(&mut self, hash: u64, key: &Q) -> Option<NonNull<Self::Handle>>
     where
-        Self::Key: std::borrow::Borrow<Q>,
-        Q: std::hash::Hash + Eq + ?Sized,
+        Q: Equivalent<Self::Key> + ?Sized,
     {
         self.indexer.remove(hash, key)
     }
@@ -107,4 +111,8 @@ where
     unsafe fn drain(&mut self) -> impl Iterator<Item = NonNull<Self::Handle>> {
         self.indexer.drain()
     }
+
+    unsafe fn iter(&self) -> impl Iterator<Item = NonNull<Self::Handle>> {
+        self.indexer.iter()
+    }
 }
@@ -65,12 +65,19 @@
 #![warn(missing_docs)]
 #![warn(clippy::allow_attributes)]
 
+mod admission;
 mod cache;
 mod context;
+mod custom;
 mod eviction;
 mod generic;
 mod handle;
 mod indexer;
+mod metadata;
 mod prelude;
+mod pressure;
+mod reclaimer;
+#[cfg(target_os = "linux")]
+mod shared;
 
 pub use prelude::*;
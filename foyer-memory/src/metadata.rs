@@ -0,0 +1,135 @@
+//  Copyright 2024 foyer Project Authors
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        LazyLock,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+static ANCHOR: LazyLock<fastant::Anchor> = LazyLock::new(fastant::Anchor::new);
+
+/// Get the current unix timestamp in nanoseconds with a cheap clock (TSC if available).
+///
+/// The clock is less accurate than [`SystemTime::now`], which is good enough for the entry metadata.
+#[inline(always)]
+fn now_unix_nanos() -> u64 {
+    fastant::Instant::now().as_unix_nanos(&ANCHOR)
+}
+
+/// Snapshot of the metadata of a cache entry.
+///
+/// The metadata is maintained by the in-memory cache only if it is enabled with
+/// [`CacheBuilder::with_entry_metadata`](crate::CacheBuilder::with_entry_metadata).
+///
+/// The timestamps are taken with a cheap clock that may drift from the system clock by a few milliseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EntryMetadata {
+    inserted_at: u64,
+    last_accessed_at: u64,
+    hits: u64,
+}
+
+impl EntryMetadata {
+    /// The time when the entry is inserted into the in-memory cache.
+    pub fn inserted_at(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_nanos(self.inserted_at)
+    }
+
+    /// The time when the entry is accessed last time, or the insertion time if it has never been accessed.
+    pub fn last_accessed_at(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_nanos(self.last_accessed_at)
+    }
+
+    /// The approximate count of the accesses to the entry since it is inserted.
+    ///
+    /// Only `get`, `fetch` hits and `touch` count as accesses.
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// The duration since the entry is inserted.
+    pub fn age(&self) -> Duration {
+        Duration::from_nanos(now_unix_nanos().saturating_sub(self.inserted_at))
+    }
+
+    /// The duration since the entry is accessed last time.
+    pub fn idle(&self) -> Duration {
+        Duration::from_nanos(now_unix_nanos().saturating_sub(self.last_accessed_at))
+    }
+}
+
+/// The metadata stored out of the handle.
+///
+/// The fields are atomic because they can be read by the entry holders without the shard lock.
+#[derive(Debug, Default)]
+pub(crate) struct Metadata {
+    inserted_at: AtomicU64,
+    last_accessed_at: AtomicU64,
+    hits: AtomicU64,
+}
+
+impl Metadata {
+    #[inline(always)]
+    pub(crate) fn init(&self) {
+        let now = now_unix_nanos();
+        self.inserted_at.store(now, Ordering::Relaxed);
+        self.last_accessed_at.store(now, Ordering::Relaxed);
+        self.hits.store(0, Ordering::Relaxed);
+    }
+
+    #[inline(always)]
+    pub(crate) fn record_access(&self) {
+        self.last_accessed_at.store(now_unix_nanos(), Ordering::Relaxed);
+        self.hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    #[inline(always)]
+    pub(crate) fn snapshot(&self) -> EntryMetadata {
+        EntryMetadata {
+            inserted_at: self.inserted_at.load(Ordering::Relaxed),
+            last_accessed_at: self.last_accessed_at.load(Ordering::Relaxed),
+            hits: self.hits.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metadata() {
+        let metadata = Metadata::default();
+
+        let before = SystemTime::now() - Duration::from_millis(100);
+        metadata.init();
+        let snapshot = metadata.snapshot();
+        assert_eq!(snapshot.hits(), 0);
+        assert_eq!(snapshot.inserted_at(), snapshot.last_accessed_at());
+        assert!(snapshot.inserted_at() > before);
+        assert!(snapshot.inserted_at() < SystemTime::now() + Duration::from_millis(100));
+
+        std::thread::sleep(Duration::from_millis(10));
+        metadata.record_access();
+        metadata.record_access();
+        let accessed = metadata.snapshot();
+        assert_eq!(accessed.hits(), 2);
+        assert_eq!(accessed.inserted_at(), snapshot.inserted_at());
+        assert!(accessed.last_accessed_at() > snapshot.last_accessed_at());
+        assert!(accessed.age() >= accessed.idle());
+    }
+}
@@ -15,8 +15,19 @@
 pub use ahash::RandomState;
 
 pub use crate::{
+    admission::{AdmissionConfig, DoorkeeperConfig},
     cache::{Cache, CacheBuilder, CacheEntry, EvictionConfig, Fetch},
     context::CacheContext,
-    eviction::{fifo::FifoConfig, lfu::LfuConfig, lru::LruConfig, s3fifo::S3FifoConfig},
+    custom::{CustomCache, CustomCacheEntry, CustomFetch, CustomFetchInner},
+    eviction::{fifo::FifoConfig, lfu::LfuConfig, lru::LruConfig, s3fifo::S3FifoConfig, Eviction},
     generic::{FetchMark, FetchState, Weighter},
+    handle::{BaseHandle, Handle},
+    metadata::EntryMetadata,
+    pressure::{MemoryPressureConfig, MemoryPressureMonitor, MemoryPressureMonitorHandle},
 };
+
+#[cfg(feature = "test-utils")]
+pub use crate::eviction::test_utils;
+
+#[cfg(target_os = "linux")]
+pub use crate::shared::{SharedMemoryCache, SharedMemoryCacheBuilder};
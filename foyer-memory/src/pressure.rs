@@ -0,0 +1,439 @@
+//  Copyright 2024 foyer Project Authors
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::{fmt::Debug, path::PathBuf, time::Duration};
+
+use foyer_common::{
+    code::{HashBuilder, Key, Value},
+    metrics::MemoryPressureMetrics,
+};
+use serde::{Deserialize, Serialize};
+use tokio::{runtime::Handle, sync::oneshot};
+
+use crate::Cache;
+
+/// Memory pressure monitor config.
+///
+/// The monitor reads the cgroup v2 memory interface files and decides whether the host is under memory pressure by:
+///
+/// 1. the ratio of `memory.current` to `memory.max`;
+/// 2. the `some avg10` value of `memory.pressure`, if the file exists.
+///
+/// The monitor starts shrinking the effective capacity when the usage ratio reaches `high_watermark` or the pressure
+/// reaches `pressure_threshold`, and only starts restoring it after the usage ratio falls below `low_watermark` and
+/// the pressure falls below `pressure_threshold`. The effective capacity is kept between the two watermarks.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryPressureConfig {
+    /// Name of the monitor, used as the metrics label.
+    ///
+    /// Should be the same as the name of the monitored cache.
+    pub name: String,
+    /// Path of the cgroup v2 `memory.current` file.
+    pub memory_current_path: PathBuf,
+    /// Path of the cgroup v2 `memory.max` file.
+    pub memory_max_path: PathBuf,
+    /// Path of the cgroup v2 `memory.pressure` file.
+    ///
+    /// The pressure is regarded as 0 if the file cannot be read.
+    pub memory_pressure_path: PathBuf,
+    /// The usage ratio of the cgroup to start shrinking the effective capacity.
+    ///
+    /// Must be in (0, 1].
+    pub high_watermark: f64,
+    /// The usage ratio of the cgroup to start restoring the effective capacity.
+    ///
+    /// Must be in [0, `high_watermark`].
+    pub low_watermark: f64,
+    /// The `some avg10` value of `memory.pressure` (in percentage) to start shrinking the effective capacity.
+    pub pressure_threshold: f64,
+    /// The ratio applied to the effective capacity on each shrinking step. Restoring step uses its reciprocal.
+    ///
+    /// Must be in (0, 1).
+    pub shrink_ratio: f64,
+    /// The minimum ratio of the effective capacity to the original capacity.
+    ///
+    /// Must be in (0, 1].
+    pub min_capacity_ratio: f64,
+    /// The interval between two checks.
+    pub interval: Duration,
+}
+
+impl Default for MemoryPressureConfig {
+    fn default() -> Self {
+        Self {
+            name: "foyer".to_string(),
+            memory_current_path: PathBuf::from("/sys/fs/cgroup/memory.current"),
+            memory_max_path: PathBuf::from("/sys/fs/cgroup/memory.max"),
+            memory_pressure_path: PathBuf::from("/sys/fs/cgroup/memory.pressure"),
+            high_watermark: 0.9,
+            low_watermark: 0.8,
+            pressure_threshold: 10.0,
+            shrink_ratio: 0.8,
+            min_capacity_ratio: 0.25,
+            interval: Duration::from_secs(1),
+        }
+    }
+}
+
+impl MemoryPressureConfig {
+    fn validate(&self) {
+        assert!(
+            self.high_watermark > 0.0 && self.high_watermark <= 1.0,
+            "high_watermark must be in (0, 1], given: {}",
+            self.high_watermark
+        );
+        assert!(
+            self.low_watermark >= 0.0 && self.low_watermark <= self.high_watermark,
+            "low_watermark must be in [0, high_watermark], given: {}",
+            self.low_watermark
+        );
+        assert!(
+            self.shrink_ratio > 0.0 && self.shrink_ratio < 1.0,
+            "shrink_ratio must be in (0, 1), given: {}",
+            self.shrink_ratio
+        );
+        assert!(
+            self.min_capacity_ratio > 0.0 && self.min_capacity_ratio <= 1.0,
+            "min_capacity_ratio must be in (0, 1], given: {}",
+            self.min_capacity_ratio
+        );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Pressured,
+    Relaxed,
+    Steady,
+}
+
+/// Memory pressure monitor that temporarily lowers the effective capacity of the in-memory cache under memory
+/// pressure, and restores it after the pressure subsides.
+///
+/// The monitor takes the capacity of the cache on creation as the original capacity, and adjusts the effective
+/// capacity with [`Cache::resize`]. Resizing the cache by others while the monitor is running is not supported.
+///
+/// Call [`MemoryPressureMonitor::check`] periodically, or use [`MemoryPressureMonitor::spawn`] to run it in the
+/// background.
+pub struct MemoryPressureMonitor<K, V, S>
+where
+    K: Key,
+    V: Value,
+    S: HashBuilder,
+{
+    cache: Cache<K, V, S>,
+    config: MemoryPressureConfig,
+
+    capacity: usize,
+    effective_capacity: usize,
+
+    metrics: MemoryPressureMetrics,
+}
+
+impl<K, V, S> Debug for MemoryPressureMonitor<K, V, S>
+where
+    K: Key,
+    V: Value,
+    S: HashBuilder,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MemoryPressureMonitor")
+            .field("config", &self.config)
+            .field("capacity", &self.capacity)
+            .field("effective_capacity", &self.effective_capacity)
+            .finish()
+    }
+}
+
+impl<K, V, S> MemoryPressureMonitor<K, V, S>
+where
+    K: Key,
+    V: Value,
+    S: HashBuilder,
+{
+    /// Create a memory pressure monitor for the given in-memory cache.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the config is invalid.
+    pub fn new(cache: Cache<K, V, S>, config: MemoryPressureConfig) -> Self {
+        config.validate();
+
+        let capacity = cache.capacity();
+        let metrics = MemoryPressureMetrics::new(&config.name);
+        metrics.effective_capacity.set(capacity as f64);
+
+        Self {
+            cache,
+            config,
+            capacity,
+            effective_capacity: capacity,
+            metrics,
+        }
+    }
+
+    /// Get the original capacity of the in-memory cache.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Get the effective capacity of the in-memory cache.
+    pub fn effective_capacity(&self) -> usize {
+        self.effective_capacity
+    }
+
+    /// Check the memory pressure and adjust the effective capacity of the in-memory cache by one step if needed.
+    ///
+    /// Returns the effective capacity after the check.
+    pub fn check(&mut self) -> usize {
+        let state = match self.state() {
+            Ok(state) => state,
+            Err(e) => {
+                tracing::warn!("[memory pressure]: Failed to read cgroup memory stats, skip: {e}");
+                return self.effective_capacity;
+            }
+        };
+
+        let min_capacity = (self.capacity as f64 * self.config.min_capacity_ratio) as usize;
+        let effective_capacity = match state {
+            State::Pressured if self.effective_capacity > min_capacity => {
+                self.metrics.shrink.increment(1);
+                ((self.effective_capacity as f64 * self.config.shrink_ratio) as usize).max(min_capacity)
+            }
+            State::Relaxed if self.effective_capacity < self.capacity => {
+                self.metrics.restore.increment(1);
+                ((self.effective_capacity as f64 / self.config.shrink_ratio).ceil() as usize).min(self.capacity)
+            }
+            _ => return self.effective_capacity,
+        };
+
+        tracing::debug!(
+            "[memory pressure]: Adjust effective capacity from {} to {} ({state:?}).",
+            self.effective_capacity,
+            effective_capacity
+        );
+        self.resize(effective_capacity);
+        self.effective_capacity
+    }
+
+    /// Run the monitor in the background with the given runtime, checking with the configured interval.
+    ///
+    /// The original capacity is restored after the returned handle is dropped.
+    pub fn spawn(mut self, runtime: &Handle) -> MemoryPressureMonitorHandle {
+        let (stop_tx, mut stop_rx) = oneshot::channel();
+        runtime.spawn(async move {
+            loop {
+                tokio::select! {
+                    biased;
+                    _ = &mut stop_rx => {
+                        self.resize(self.capacity);
+                        tracing::info!("[memory pressure]: Memory pressure monitor exits.");
+                        return;
+                    }
+                    _ = tokio::time::sleep(self.config.interval) => {
+                        self.check();
+                    }
+                }
+            }
+        });
+        MemoryPressureMonitorHandle { _stop_tx: stop_tx }
+    }
+
+    fn resize(&mut self, capacity: usize) {
+        self.cache.resize(capacity);
+        self.effective_capacity = capacity;
+        self.metrics.effective_capacity.set(capacity as f64);
+    }
+
+    fn state(&self) -> std::io::Result<State> {
+        let current = read_u64(&self.config.memory_current_path)?;
+        let usage_ratio = match read_max(&self.config.memory_max_path)? {
+            Some(max) if max > 0 => current as f64 / max as f64,
+            _ => 0.0,
+        };
+        let pressure = read_pressure(&self.config.memory_pressure_path).unwrap_or_default();
+
+        let state = if usage_ratio >= self.config.high_watermark || pressure >= self.config.pressure_threshold {
+            State::Pressured
+        } else if usage_ratio < self.config.low_watermark {
+            State::Relaxed
+        } else {
+            State::Steady
+        };
+        Ok(state)
+    }
+}
+
+/// Handle of the memory pressure monitor running in the background.
+///
+/// The monitor stops and restores the original capacity of the in-memory cache after the handle is dropped.
+#[derive(Debug)]
+pub struct MemoryPressureMonitorHandle {
+    _stop_tx: oneshot::Sender<()>,
+}
+
+fn invalid_data(e: impl std::error::Error + Send + Sync + 'static) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, e)
+}
+
+fn read_u64(path: &PathBuf) -> std::io::Result<u64> {
+    std::fs::read_to_string(path)?.trim().parse().map_err(invalid_data)
+}
+
+/// Returns `None` if there is no limit.
+fn read_max(path: &PathBuf) -> std::io::Result<Option<u64>> {
+    let content = std::fs::read_to_string(path)?;
+    match content.trim() {
+        "max" => Ok(None),
+        v => v.parse().map(Some).map_err(invalid_data),
+    }
+}
+
+/// Read the `some avg10` value of the pressure stall information.
+///
+/// e.g.
+///
+/// ```plain
+/// some avg10=0.00 avg60=0.00 avg300=0.00 total=0
+/// full avg10=0.00 avg60=0.00 avg300=0.00 total=0
+/// ```
+fn read_pressure(path: &PathBuf) -> std::io::Result<f64> {
+    let content = std::fs::read_to_string(path)?;
+    content
+        .lines()
+        .find_map(|line| line.strip_prefix("some "))
+        .and_then(|line| line.split_whitespace().find_map(|kv| kv.strip_prefix("avg10=")))
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidData, "no `some avg10` in pressure file"))?
+        .parse()
+        .map_err(invalid_data)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::{CacheBuilder, LruConfig};
+
+    fn write(dir: &Path, current: u64, max: &str, avg10: f64) {
+        std::fs::write(dir.join("memory.current"), format!("{current}\n")).unwrap();
+        std::fs::write(dir.join("memory.max"), format!("{max}\n")).unwrap();
+        std::fs::write(
+            dir.join("memory.pressure"),
+            format!(
+                "some avg10={avg10:.2} avg60=0.00 avg300=0.00 total=0\nfull avg10=0.00 avg60=0.00 avg300=0.00 total=0\n"
+            ),
+        )
+        .unwrap();
+    }
+
+    fn config(dir: &Path) -> MemoryPressureConfig {
+        MemoryPressureConfig {
+            memory_current_path: dir.join("memory.current"),
+            memory_max_path: dir.join("memory.max"),
+            memory_pressure_path: dir.join("memory.pressure"),
+            high_watermark: 0.9,
+            low_watermark: 0.8,
+            pressure_threshold: 10.0,
+            shrink_ratio: 0.5,
+            min_capacity_ratio: 0.25,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_read_cgroup_files() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), 42, "max", 1.5);
+        assert_eq!(read_u64(&dir.path().join("memory.current")).unwrap(), 42);
+        assert_eq!(read_max(&dir.path().join("memory.max")).unwrap(), None);
+        assert_eq!(read_pressure(&dir.path().join("memory.pressure")).unwrap(), 1.5);
+        write(dir.path(), 42, "100", 0.0);
+        assert_eq!(read_max(&dir.path().join("memory.max")).unwrap(), Some(100));
+        assert!(read_u64(&dir.path().join("memory.nonexistent")).is_err());
+    }
+
+    #[test]
+    fn test_memory_pressure_monitor() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache: Cache<u64, u64> = CacheBuilder::new(100)
+            .with_shards(1)
+            .with_eviction_config(LruConfig::default())
+            .build();
+        for i in 0..100 {
+            cache.insert(i, i);
+        }
+        let mut monitor = MemoryPressureMonitor::new(cache.clone(), config(dir.path()));
+
+        // Files not found, skip.
+        assert_eq!(monitor.check(), 100);
+
+        // Relaxed, nothing to restore.
+        write(dir.path(), 50, "100", 0.0);
+        assert_eq!(monitor.check(), 100);
+
+        // Shrink by usage ratio until the minimum capacity.
+        write(dir.path(), 95, "100", 0.0);
+        assert_eq!(monitor.check(), 50);
+        assert_eq!(cache.capacity(), 50);
+        assert!(cache.usage() <= 50);
+        assert_eq!(monitor.check(), 25);
+        assert_eq!(monitor.check(), 25);
+        assert_eq!(cache.usage(), 25);
+
+        // Between the watermarks, hold.
+        write(dir.path(), 85, "100", 0.0);
+        assert_eq!(monitor.check(), 25);
+
+        // Restore step by step.
+        write(dir.path(), 70, "100", 0.0);
+        assert_eq!(monitor.check(), 50);
+        assert_eq!(cache.capacity(), 50);
+
+        // Shrink by pressure stall information.
+        write(dir.path(), 70, "100", 20.0);
+        assert_eq!(monitor.check(), 25);
+
+        // No limit.
+        write(dir.path(), 70, "max", 0.0);
+        assert_eq!(monitor.check(), 50);
+        assert_eq!(monitor.check(), 100);
+        assert_eq!(monitor.check(), 100);
+        assert_eq!(cache.capacity(), 100);
+    }
+
+    #[tokio::test]
+    async fn test_memory_pressure_monitor_spawn() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache: Cache<u64, u64> = CacheBuilder::new(100).with_shards(1).build();
+        write(dir.path(), 95, "100", 0.0);
+
+        let monitor = MemoryPressureMonitor::new(
+            cache.clone(),
+            MemoryPressureConfig {
+                interval: Duration::from_millis(1),
+                ..config(dir.path())
+            },
+        );
+        let handle = monitor.spawn(&Handle::current());
+        while cache.capacity() > 25 {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+
+        drop(handle);
+        while cache.capacity() < 100 {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+    }
+}
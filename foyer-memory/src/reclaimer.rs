@@ -0,0 +1,177 @@
+//  Copyright 2024 foyer Project Authors
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    mpsc, Arc,
+};
+
+use foyer_common::{
+    code::{Key, Value},
+    event::EventListener,
+    metrics::Metrics,
+};
+
+/// Release the released entries: notify the event listener and drop them.
+fn release<K, V>(listener: Option<&Arc<dyn EventListener<Key = K, Value = V>>>, entries: Vec<(K, V)>)
+where
+    K: Key,
+    V: Value,
+{
+    match listener {
+        Some(listener) => {
+            for (k, v) in entries {
+                listener.on_memory_release(k, v);
+            }
+        }
+        None => drop(entries),
+    }
+}
+
+/// The background reclaimer that drops the released entries and notifies the event listener on a dedicated thread,
+/// to keep the slow destruction of the values off the hot path.
+///
+/// The count of the pending entries is bounded. If the queue is full, the entries are released inline.
+pub(crate) struct Reclaimer<K, V> {
+    tx: mpsc::Sender<Vec<(K, V)>>,
+    pending: Arc<AtomicUsize>,
+    capacity: usize,
+    event_listener: Option<Arc<dyn EventListener<Key = K, Value = V>>>,
+    metrics: Arc<Metrics>,
+}
+
+impl<K, V> Reclaimer<K, V>
+where
+    K: Key,
+    V: Value,
+{
+    /// Spawn the reclaimer thread.
+    ///
+    /// The thread exits after the reclaimer is dropped and all pending entries are released.
+    pub(crate) fn new(
+        name: &str,
+        capacity: usize,
+        event_listener: Option<Arc<dyn EventListener<Key = K, Value = V>>>,
+        metrics: Arc<Metrics>,
+    ) -> Self {
+        let (tx, rx) = mpsc::channel::<Vec<(K, V)>>();
+        let pending = Arc::new(AtomicUsize::new(0));
+
+        std::thread::Builder::new()
+            .name(format!("{name}-reclaimer"))
+            .spawn({
+                let pending = pending.clone();
+                let event_listener = event_listener.clone();
+                let metrics = metrics.clone();
+                move || {
+                    while let Ok(entries) = rx.recv() {
+                        let count = entries.len();
+                        release(event_listener.as_ref(), entries);
+                        pending.fetch_sub(count, Ordering::Relaxed);
+                        metrics.memory_reclaim_pending.decrement(count as f64);
+                    }
+                    tracing::debug!("[reclaimer]: Reclaimer exits.");
+                }
+            })
+            .expect("failed to spawn the reclaimer thread");
+
+        Self {
+            tx,
+            pending,
+            capacity,
+            event_listener,
+            metrics,
+        }
+    }
+
+    /// Release the entries on the reclaimer thread, or inline if the queue is full.
+    pub(crate) fn reclaim(&self, entries: Vec<(K, V)>) {
+        let count = entries.len();
+        if count == 0 {
+            return;
+        }
+
+        if self.pending.fetch_add(count, Ordering::Relaxed) + count > self.capacity {
+            self.pending.fetch_sub(count, Ordering::Relaxed);
+            self.metrics.memory_reclaim_overflow.increment(count as u64);
+            release(self.event_listener.as_ref(), entries);
+            return;
+        }
+
+        self.metrics.memory_reclaim_defer.increment(count as u64);
+        self.metrics.memory_reclaim_pending.increment(count as f64);
+        if let Err(mpsc::SendError(entries)) = self.tx.send(entries) {
+            // Unreachable unless the reclaimer thread panicked in the event listener.
+            self.pending.fetch_sub(count, Ordering::Relaxed);
+            self.metrics.memory_reclaim_pending.decrement(count as f64);
+            release(self.event_listener.as_ref(), entries);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use parking_lot::Mutex;
+
+    use super::*;
+
+    struct Recorder(Mutex<Vec<(u64, String)>>);
+
+    impl EventListener for Recorder {
+        type Key = u64;
+        type Value = u64;
+
+        fn on_memory_release(&self, key: Self::Key, value: Self::Value)
+        where
+            Self::Key: Key,
+            Self::Value: Value,
+        {
+            let thread = std::thread::current().name().unwrap_or_default().to_string();
+            assert_eq!(key, value);
+            self.0.lock().push((key, thread));
+        }
+    }
+
+    fn wait(f: impl Fn() -> bool) {
+        let start = Instant::now();
+        while !f() {
+            assert!(start.elapsed() < Duration::from_secs(10));
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn test_reclaimer() {
+        let recorder = Arc::new(Recorder(Mutex::new(vec![])));
+        let reclaimer = Reclaimer::new(
+            "test",
+            4,
+            Some(recorder.clone() as Arc<dyn EventListener<Key = u64, Value = u64>>),
+            Arc::new(Metrics::new("test")),
+        );
+
+        reclaimer.reclaim(vec![(1, 1), (2, 2)]);
+        wait(|| recorder.0.lock().len() == 2);
+        wait(|| reclaimer.pending.load(Ordering::Relaxed) == 0);
+        assert!(recorder.0.lock().iter().all(|(_, thread)| thread == "test-reclaimer"));
+
+        // Exceeds the bound, released inline.
+        reclaimer.reclaim(vec![(3, 3), (4, 4), (5, 5), (6, 6), (7, 7)]);
+        let current = std::thread::current().name().unwrap_or_default().to_string();
+        assert_eq!(recorder.0.lock().len(), 7);
+        assert!(recorder.0.lock()[2..].iter().all(|(_, thread)| *thread == current));
+    }
+}
@@ -0,0 +1,932 @@
+//  Copyright 2024 foyer Project Authors
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+//! A byte-valued in-memory cache backed by a shared memory segment, which can be shared by the processes on one host.
+//!
+//! Layout of the segment:
+//!
+//! ```plain
+//! | header (64B) | mutex (64B) | buckets (u32 * buckets, aligned to 8B) | slot 0 | slot 1 | ... | slot N-1 |
+//!
+//! slot: | slot header (40B) | key | value | (padding to slot size) |
+//! ```
+//!
+//! The index is a chained hash table whose chains are linked through the slots. Unused slots are linked as the free
+//! list. Eviction follows the CLOCK algorithm with the referenced bit in the slot header.
+//!
+//! All operations are serialized by a process-shared robust pthread mutex in the segment. If the owner dies while
+//! holding the mutex, the kernel releases it and the next owner is notified with `EOWNERDEAD`, which doesn't rely on
+//! pids, so the processes are not required to share a pid namespace. The dirty flag in the header is set for the
+//! duration of each update, so if the owner dies or panics in the middle, the next owner rebuilds the index from the
+//! slots and drops the slots that fail the checksum.
+
+use std::{
+    fmt::Debug,
+    fs::OpenOptions,
+    hash::Hasher,
+    io,
+    mem::MaybeUninit,
+    os::fd::AsRawFd,
+    path::{Path, PathBuf},
+    ptr::NonNull,
+    sync::{
+        atomic::{AtomicU32, AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+use memmap2::MmapMut;
+use twox_hash::XxHash64;
+
+const MAGIC: u64 = 0x666f_7965_725f_7368;
+const VERSION: u32 = 2;
+
+const HEADER_SIZE: usize = 64;
+const MUTEX_SIZE: usize = 64;
+const SLOT_HEADER_SIZE: usize = 40;
+
+const NIL: u32 = u32::MAX;
+
+const FREE: u32 = 0;
+const OCCUPIED: u32 = 1;
+
+#[repr(C)]
+struct Header {
+    magic: AtomicU64,
+    version: AtomicU32,
+    slot_size: AtomicU32,
+    slots: AtomicU32,
+    buckets: AtomicU32,
+    /// Non-zero if the index is being updated.
+    dirty: AtomicU32,
+    free: AtomicU32,
+    hand: AtomicU32,
+    len: AtomicU32,
+    recoveries: AtomicU64,
+}
+
+#[repr(C)]
+struct SlotHeader {
+    state: AtomicU32,
+    referenced: AtomicU32,
+    next: AtomicU32,
+    key_len: AtomicU32,
+    value_len: AtomicU32,
+    _padding: u32,
+    hash: AtomicU64,
+    checksum: AtomicU64,
+}
+
+const _: () = assert!(std::mem::size_of::<Header>() <= HEADER_SIZE);
+const _: () = assert!(std::mem::size_of::<libc::pthread_mutex_t>() <= MUTEX_SIZE);
+const _: () = assert!(std::mem::size_of::<SlotHeader>() == SLOT_HEADER_SIZE);
+
+fn hash(key: &[u8]) -> u64 {
+    let mut hasher = XxHash64::with_seed(0);
+    hasher.write(key);
+    hasher.finish()
+}
+
+fn checksum(hash: u64, key: &[u8], value: &[u8]) -> u64 {
+    let mut hasher = XxHash64::with_seed(0);
+    hasher.write_u64(hash);
+    hasher.write_u32(key.len() as u32);
+    hasher.write_u32(value.len() as u32);
+    hasher.write(key);
+    hasher.write(value);
+    hasher.finish()
+}
+
+fn check(res: libc::c_int) -> io::Result<()> {
+    match res {
+        0 => Ok(()),
+        e => Err(io::Error::from_raw_os_error(e)),
+    }
+}
+
+/// Shared memory in-memory cache builder.
+#[derive(Debug)]
+pub struct SharedMemoryCacheBuilder {
+    path: PathBuf,
+    slot_size: usize,
+    slots: usize,
+}
+
+impl SharedMemoryCacheBuilder {
+    /// Create a shared memory in-memory cache builder with the path of the segment file.
+    ///
+    /// The path is supposed to be on a memory-backed file system, e.g. `/dev/shm`. The processes that open the same
+    /// path share the same cache.
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().into(),
+            slot_size: 4096,
+            slots: 1024,
+        }
+    }
+
+    /// Set the size of each slot. Each entry takes one slot, so an entry whose key and value are larger than
+    /// `slot_size - 40` cannot be inserted.
+    ///
+    /// The slot size must be a multiple of 8.
+    ///
+    /// Default: 4 KiB.
+    pub fn with_slot_size(mut self, slot_size: usize) -> Self {
+        self.slot_size = slot_size;
+        self
+    }
+
+    /// Set the count of the slots, which is the max entry count of the cache.
+    ///
+    /// Default: 1024.
+    pub fn with_slots(mut self, slots: usize) -> Self {
+        self.slots = slots;
+        self
+    }
+
+    /// Open the shared memory segment, or create and initialize it if it doesn't exist.
+    ///
+    /// All processes sharing the segment must use the same slot size and slot count.
+    pub fn build(self) -> io::Result<SharedMemoryCache> {
+        if self.slot_size % 8 != 0 || self.slot_size <= SLOT_HEADER_SIZE || self.slot_size > u32::MAX as usize {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid slot size: {}", self.slot_size),
+            ));
+        }
+        if self.slots == 0 || self.slots >= NIL as usize {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid slot count: {}", self.slots),
+            ));
+        }
+
+        let buckets = self.slots.next_power_of_two();
+        let slots_offset = (HEADER_SIZE + MUTEX_SIZE + buckets * std::mem::size_of::<u32>()).next_multiple_of(8);
+        let size = slots_offset + self.slots * self.slot_size;
+
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&self.path)?;
+
+        // Serialize the initialization among the processes.
+        if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let len = file.metadata()?.len() as usize;
+        if len == 0 {
+            file.set_len(size as u64)?;
+        } else if len != size {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("shared memory segment size mismatch, expected: {size}, get: {len}"),
+            ));
+        }
+
+        let mut mmap = unsafe { MmapMut::map_mut(&file)? };
+        let base = NonNull::new(mmap.as_mut_ptr()).unwrap();
+
+        let inner = SharedMemoryCacheInner {
+            _mmap: mmap,
+            base,
+            path: self.path,
+            slot_size: self.slot_size,
+            slots: self.slots as u32,
+            buckets: buckets as u32,
+            slots_offset,
+        };
+
+        let header = inner.header();
+        match header.magic.load(Ordering::Acquire) {
+            // The segment is newly created, or the creator died before the initialization finished.
+            0 => inner.init()?,
+            MAGIC => {
+                let version = header.version.load(Ordering::Relaxed);
+                let slot_size = header.slot_size.load(Ordering::Relaxed) as usize;
+                let slots = header.slots.load(Ordering::Relaxed);
+                if version != VERSION || slot_size != inner.slot_size || slots != inner.slots {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "shared memory segment config mismatch, expected: (version: {VERSION}, slot size: {}, slots: {}), get: (version: {version}, slot size: {slot_size}, slots: {slots})",
+                            inner.slot_size, inner.slots
+                        ),
+                    ));
+                }
+            }
+            magic => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("shared memory segment magic mismatch, expected: {MAGIC}, get: {magic}"),
+                ))
+            }
+        }
+
+        if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_UN) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(SharedMemoryCache { inner: Arc::new(inner) })
+    }
+}
+
+struct SharedMemoryCacheInner {
+    _mmap: MmapMut,
+    base: NonNull<u8>,
+    path: PathBuf,
+    slot_size: usize,
+    slots: u32,
+    buckets: u32,
+    slots_offset: usize,
+}
+
+// The shared fields in the segment are accessed either with atomics or with the segment lock held.
+unsafe impl Send for SharedMemoryCacheInner {}
+unsafe impl Sync for SharedMemoryCacheInner {}
+
+impl SharedMemoryCacheInner {
+    fn header(&self) -> &Header {
+        unsafe { self.base.cast().as_ref() }
+    }
+
+    fn bucket(&self, hash: u64) -> &AtomicU32 {
+        let index = (hash & (self.buckets as u64 - 1)) as usize;
+        unsafe {
+            self.base
+                .add(HEADER_SIZE + MUTEX_SIZE + index * std::mem::size_of::<u32>())
+                .cast()
+                .as_ref()
+        }
+    }
+
+    fn mutex(&self) -> *mut libc::pthread_mutex_t {
+        unsafe { self.base.as_ptr().add(HEADER_SIZE).cast() }
+    }
+
+    fn slot(&self, index: u32) -> &SlotHeader {
+        debug_assert!(index < self.slots);
+        unsafe {
+            self.base
+                .add(self.slots_offset + index as usize * self.slot_size)
+                .cast()
+                .as_ref()
+        }
+    }
+
+    fn payload(&self) -> usize {
+        self.slot_size - SLOT_HEADER_SIZE
+    }
+
+    /// # Safety
+    ///
+    /// The segment lock must be held.
+    unsafe fn data(&self, index: u32) -> *mut u8 {
+        self.base
+            .as_ptr()
+            .add(self.slots_offset + index as usize * self.slot_size + SLOT_HEADER_SIZE)
+    }
+
+    /// # Safety
+    ///
+    /// The segment lock must be held, and the slot must be occupied.
+    unsafe fn key(&self, index: u32) -> &[u8] {
+        let len = self.slot(index).key_len.load(Ordering::Relaxed) as usize;
+        std::slice::from_raw_parts(self.data(index), len)
+    }
+
+    /// # Safety
+    ///
+    /// The segment lock must be held, and the slot must be occupied.
+    unsafe fn value(&self, index: u32) -> &[u8] {
+        let slot = self.slot(index);
+        let offset = slot.key_len.load(Ordering::Relaxed) as usize;
+        let len = slot.value_len.load(Ordering::Relaxed) as usize;
+        std::slice::from_raw_parts(self.data(index).add(offset), len)
+    }
+
+    /// Initialize the newly created segment, with the file lock held.
+    fn init(&self) -> io::Result<()> {
+        let header = self.header();
+        header.version.store(VERSION, Ordering::Relaxed);
+        header.slot_size.store(self.slot_size as u32, Ordering::Relaxed);
+        header.slots.store(self.slots, Ordering::Relaxed);
+        header.buckets.store(self.buckets, Ordering::Relaxed);
+        header.dirty.store(0, Ordering::Relaxed);
+        header.recoveries.store(0, Ordering::Relaxed);
+        self.init_mutex()?;
+        self.reset();
+        header.magic.store(MAGIC, Ordering::Release);
+        Ok(())
+    }
+
+    /// Initialize the process-shared robust mutex, so the mutex is released by the kernel if its owner dies.
+    fn init_mutex(&self) -> io::Result<()> {
+        unsafe {
+            let mut attr = MaybeUninit::<libc::pthread_mutexattr_t>::uninit();
+            check(libc::pthread_mutexattr_init(attr.as_mut_ptr()))?;
+            let res = check(libc::pthread_mutexattr_setpshared(
+                attr.as_mut_ptr(),
+                libc::PTHREAD_PROCESS_SHARED,
+            ))
+            .and_then(|_| {
+                check(libc::pthread_mutexattr_setrobust(
+                    attr.as_mut_ptr(),
+                    libc::PTHREAD_MUTEX_ROBUST,
+                ))
+            })
+            .and_then(|_| check(libc::pthread_mutex_init(self.mutex(), attr.as_ptr())));
+            libc::pthread_mutexattr_destroy(attr.as_mut_ptr());
+            res
+        }
+    }
+
+    /// Reset the index and free all slots.
+    fn reset(&self) {
+        let header = self.header();
+        for index in 0..self.buckets {
+            self.bucket(index as u64).store(NIL, Ordering::Relaxed);
+        }
+        for index in 0..self.slots {
+            let slot = self.slot(index);
+            slot.state.store(FREE, Ordering::Relaxed);
+            slot.referenced.store(0, Ordering::Relaxed);
+            slot.next
+                .store(if index + 1 < self.slots { index + 1 } else { NIL }, Ordering::Relaxed);
+        }
+        header.free.store(0, Ordering::Relaxed);
+        header.hand.store(0, Ordering::Relaxed);
+        header.len.store(0, Ordering::Relaxed);
+    }
+
+    fn lock(&self) -> SegmentGuard<'_> {
+        let res = unsafe { libc::pthread_mutex_lock(self.mutex()) };
+        self.acquired(res)
+    }
+
+    /// Take the segment lock only if it is not held by others.
+    fn try_lock(&self) -> Option<SegmentGuard<'_>> {
+        match unsafe { libc::pthread_mutex_trylock(self.mutex()) } {
+            libc::EBUSY => None,
+            res => Some(self.acquired(res)),
+        }
+    }
+
+    /// Finish taking the segment lock with the result of the pthread mutex lock call.
+    fn acquired(&self, res: i32) -> SegmentGuard<'_> {
+        match res {
+            0 => {}
+            libc::EOWNERDEAD => {
+                tracing::warn!(
+                    "[shared memory cache]: Take over the lock of {:?} from a dead owner.",
+                    self.path
+                );
+                // The owner always marks the mutex consistent before releasing it, so the mutex never becomes
+                // unrecoverable.
+                let res = unsafe { libc::pthread_mutex_consistent(self.mutex()) };
+                assert_eq!(
+                    res,
+                    0,
+                    "[shared memory cache]: make mutex consistent error: {}",
+                    io::Error::from_raw_os_error(res)
+                );
+            }
+            res => panic!(
+                "[shared memory cache]: lock mutex error: {}",
+                io::Error::from_raw_os_error(res)
+            ),
+        }
+
+        if self.header().dirty.load(Ordering::Relaxed) != 0 {
+            self.recover();
+        }
+
+        SegmentGuard { inner: self }
+    }
+
+    /// Rebuild the index from the slots after an interrupted update. Slots that fail the validation are freed.
+    fn recover(&self) {
+        let header = self.header();
+        tracing::warn!("[shared memory cache]: Recover interrupted update of {:?}.", self.path);
+
+        for index in 0..self.buckets {
+            self.bucket(index as u64).store(NIL, Ordering::Relaxed);
+        }
+        let mut free = NIL;
+        let mut len = 0;
+        for index in (0..self.slots).rev() {
+            let slot = self.slot(index);
+            if unsafe { self.validate(index) } {
+                let bucket = self.bucket(slot.hash.load(Ordering::Relaxed));
+                slot.next.store(bucket.load(Ordering::Relaxed), Ordering::Relaxed);
+                bucket.store(index, Ordering::Relaxed);
+                len += 1;
+            } else {
+                slot.state.store(FREE, Ordering::Relaxed);
+                slot.next.store(free, Ordering::Relaxed);
+                free = index;
+            }
+        }
+        header.free.store(free, Ordering::Relaxed);
+        header.len.store(len, Ordering::Relaxed);
+        if header.hand.load(Ordering::Relaxed) >= self.slots {
+            header.hand.store(0, Ordering::Relaxed);
+        }
+
+        header.recoveries.fetch_add(1, Ordering::Relaxed);
+        header.dirty.store(0, Ordering::Release);
+    }
+
+    /// # Safety
+    ///
+    /// The segment lock must be held.
+    unsafe fn validate(&self, index: u32) -> bool {
+        let slot = self.slot(index);
+        if slot.state.load(Ordering::Relaxed) != OCCUPIED {
+            return false;
+        }
+        let key_len = slot.key_len.load(Ordering::Relaxed) as usize;
+        let value_len = slot.value_len.load(Ordering::Relaxed) as usize;
+        if key_len + value_len > self.payload() {
+            return false;
+        }
+        let (key, value) = (self.key(index), self.value(index));
+        slot.checksum.load(Ordering::Relaxed) == checksum(slot.hash.load(Ordering::Relaxed), key, value)
+    }
+}
+
+/// The guard of the segment lock. Operations that modify the index are only available with the guard.
+struct SegmentGuard<'a> {
+    inner: &'a SharedMemoryCacheInner,
+}
+
+impl Drop for SegmentGuard<'_> {
+    fn drop(&mut self) {
+        unsafe { libc::pthread_mutex_unlock(self.inner.mutex()) };
+    }
+}
+
+impl SegmentGuard<'_> {
+    fn begin(&self) {
+        self.inner.header().dirty.store(1, Ordering::Release);
+    }
+
+    fn commit(&self) {
+        self.inner.header().dirty.store(0, Ordering::Release);
+    }
+
+    fn find(&self, hash: u64, eq: impl Fn(&[u8]) -> bool) -> Option<u32> {
+        let mut index = self.inner.bucket(hash).load(Ordering::Relaxed);
+        while index != NIL {
+            let slot = self.inner.slot(index);
+            if slot.hash.load(Ordering::Relaxed) == hash && eq(unsafe { self.inner.key(index) }) {
+                return Some(index);
+            }
+            index = slot.next.load(Ordering::Relaxed);
+        }
+        None
+    }
+
+    /// Unlink the occupied slot from the index and push it to the free list.
+    fn unlink(&self, index: u32) {
+        let header = self.inner.header();
+        let slot = self.inner.slot(index);
+        let bucket = self.inner.bucket(slot.hash.load(Ordering::Relaxed));
+        let next = slot.next.load(Ordering::Relaxed);
+
+        let mut cursor = bucket.load(Ordering::Relaxed);
+        if cursor == index {
+            bucket.store(next, Ordering::Relaxed);
+        } else {
+            while cursor != NIL {
+                let prev = self.inner.slot(cursor);
+                if prev.next.load(Ordering::Relaxed) == index {
+                    prev.next.store(next, Ordering::Relaxed);
+                    break;
+                }
+                cursor = prev.next.load(Ordering::Relaxed);
+            }
+        }
+
+        slot.state.store(FREE, Ordering::Relaxed);
+        slot.next.store(header.free.load(Ordering::Relaxed), Ordering::Relaxed);
+        header.free.store(index, Ordering::Relaxed);
+        header.len.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    /// Pop a free slot, or evict one with the CLOCK algorithm if there is no free slot.
+    fn alloc(&self) -> u32 {
+        let header = self.inner.header();
+        if header.free.load(Ordering::Relaxed) == NIL {
+            loop {
+                let hand = header.hand.load(Ordering::Relaxed);
+                header.hand.store((hand + 1) % self.inner.slots, Ordering::Relaxed);
+                let slot = self.inner.slot(hand);
+                if slot.state.load(Ordering::Relaxed) != OCCUPIED {
+                    continue;
+                }
+                if slot.referenced.swap(0, Ordering::Relaxed) == 0 {
+                    self.unlink(hand);
+                    break;
+                }
+            }
+        }
+        let index = header.free.load(Ordering::Relaxed);
+        header
+            .free
+            .store(self.inner.slot(index).next.load(Ordering::Relaxed), Ordering::Relaxed);
+        index
+    }
+}
+
+/// A byte-valued in-memory cache backed by a shared memory segment, which can be shared by the processes on one host.
+///
+/// Each entry takes a fixed-size slot in the segment. The entries are evicted with the CLOCK algorithm when all slots
+/// are occupied.
+///
+/// All operations on the segment are serialized by a process-shared robust mutex in the segment, which is held only
+/// for the index update and the copy of the entry. If a process dies while holding the mutex, the kernel releases it,
+/// and the next owner recovers the segment.
+///
+/// The shared memory cache can also be used as a tier of the hybrid cache, which is enabled with
+/// `HybridCacheBuilderPhaseMemory::with_shared_memory_cache` of the `foyer` crate.
+#[derive(Clone)]
+pub struct SharedMemoryCache {
+    inner: Arc<SharedMemoryCacheInner>,
+}
+
+impl Debug for SharedMemoryCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SharedMemoryCache")
+            .field("path", &self.inner.path)
+            .field("slot_size", &self.inner.slot_size)
+            .field("slots", &self.inner.slots)
+            .finish()
+    }
+}
+
+impl SharedMemoryCache {
+    /// Insert an entry into the shared memory cache, replacing the old one with the same key.
+    ///
+    /// Returns `false` if the entry is larger than [`SharedMemoryCache::max_entry_size`].
+    pub fn insert(&self, key: &[u8], value: &[u8]) -> bool {
+        self.insert_with_hash(hash(key), key, value)
+    }
+
+    /// Insert an entry with the given hash into the shared memory cache, replacing the old one with the same hash and
+    /// key.
+    ///
+    /// The hash is used instead of the built-in hash of the key bytes, so the entry can be looked up by a typed key
+    /// that is not serialized. All processes must hash the same key to the same value, and the entries inserted with
+    /// [`SharedMemoryCache::insert`] must be looked up with the `_with_hash` methods with the hash of
+    /// [`SharedMemoryCache::hash`].
+    ///
+    /// Returns `false` if the entry is larger than [`SharedMemoryCache::max_entry_size`].
+    pub fn insert_with_hash(&self, hash: u64, key: &[u8], value: &[u8]) -> bool {
+        if key.len() + value.len() > self.inner.payload() {
+            return false;
+        }
+        self.insert_locked(self.inner.lock(), hash, key, value);
+        true
+    }
+
+    /// Insert an entry with the given hash like [`SharedMemoryCache::insert_with_hash`], but only if the segment lock
+    /// is not held by others, so the caller never waits for the other processes.
+    ///
+    /// Returns `false` if the entry is larger than [`SharedMemoryCache::max_entry_size`] or the segment lock is
+    /// contended, in which case the entry is not inserted.
+    pub fn try_insert_with_hash(&self, hash: u64, key: &[u8], value: &[u8]) -> bool {
+        if key.len() + value.len() > self.inner.payload() {
+            return false;
+        }
+        let Some(guard) = self.inner.try_lock() else {
+            return false;
+        };
+        self.insert_locked(guard, hash, key, value);
+        true
+    }
+
+    fn insert_locked(&self, guard: SegmentGuard<'_>, hash: u64, key: &[u8], value: &[u8]) {
+        let checksum = checksum(hash, key, value);
+
+        guard.begin();
+
+        if let Some(index) = guard.find(hash, |k| k == key) {
+            guard.unlink(index);
+        }
+        let index = guard.alloc();
+
+        let slot = self.inner.slot(index);
+        slot.key_len.store(key.len() as u32, Ordering::Relaxed);
+        slot.value_len.store(value.len() as u32, Ordering::Relaxed);
+        slot.hash.store(hash, Ordering::Relaxed);
+        slot.checksum.store(checksum, Ordering::Relaxed);
+        slot.referenced.store(0, Ordering::Relaxed);
+        unsafe {
+            let data = self.inner.data(index);
+            std::ptr::copy_nonoverlapping(key.as_ptr(), data, key.len());
+            std::ptr::copy_nonoverlapping(value.as_ptr(), data.add(key.len()), value.len());
+        }
+        slot.state.store(OCCUPIED, Ordering::Relaxed);
+
+        let bucket = self.inner.bucket(hash);
+        slot.next.store(bucket.load(Ordering::Relaxed), Ordering::Relaxed);
+        bucket.store(index, Ordering::Relaxed);
+        self.inner.header().len.fetch_add(1, Ordering::Relaxed);
+
+        guard.commit();
+    }
+
+    /// Get a copy of the value with the given key.
+    pub fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.get_with_hash(hash(key), |k| k == key).map(|(_, value)| value)
+    }
+
+    /// Get a copy of the key and the value of the entry with the given hash whose key bytes satisfy `eq`.
+    ///
+    /// `eq` is called with the segment lock held.
+    pub fn get_with_hash(&self, hash: u64, eq: impl Fn(&[u8]) -> bool) -> Option<(Vec<u8>, Vec<u8>)> {
+        let guard = self.inner.lock();
+        let index = guard.find(hash, eq)?;
+        self.inner.slot(index).referenced.store(1, Ordering::Relaxed);
+        Some(unsafe { (self.inner.key(index).to_vec(), self.inner.value(index).to_vec()) })
+    }
+
+    /// Check if the shared memory cache contains an entry with the given key.
+    pub fn contains(&self, key: &[u8]) -> bool {
+        self.contains_with_hash(hash(key), |k| k == key)
+    }
+
+    /// Check if the shared memory cache contains an entry with the given hash whose key bytes satisfy `eq`.
+    pub fn contains_with_hash(&self, hash: u64, eq: impl Fn(&[u8]) -> bool) -> bool {
+        self.inner.lock().find(hash, eq).is_some()
+    }
+
+    /// Remove the entry with the given key. Returns `true` if the entry exists before removal.
+    pub fn remove(&self, key: &[u8]) -> bool {
+        self.remove_with_hash(hash(key), |k| k == key)
+    }
+
+    /// Remove the entry with the given hash whose key bytes satisfy `eq`. Returns `true` if the entry exists before
+    /// removal.
+    pub fn remove_with_hash(&self, hash: u64, eq: impl Fn(&[u8]) -> bool) -> bool {
+        let guard = self.inner.lock();
+        let Some(index) = guard.find(hash, eq) else {
+            return false;
+        };
+        guard.begin();
+        guard.unlink(index);
+        guard.commit();
+        true
+    }
+
+    /// The built-in hash of the key bytes, which is used by the methods without the `_with_hash` suffix.
+    pub fn hash(key: &[u8]) -> u64 {
+        hash(key)
+    }
+
+    /// Remove all entries.
+    pub fn clear(&self) {
+        let guard = self.inner.lock();
+        guard.begin();
+        self.inner.reset();
+        guard.commit();
+    }
+
+    /// Get the count of the entries.
+    pub fn len(&self) -> usize {
+        self.inner.header().len.load(Ordering::Relaxed) as usize
+    }
+
+    /// Check if the shared memory cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get the max count of the entries, which is the count of the slots.
+    pub fn capacity(&self) -> usize {
+        self.inner.slots as usize
+    }
+
+    /// Get the max total size of the key and the value of an entry.
+    pub fn max_entry_size(&self) -> usize {
+        self.inner.payload()
+    }
+
+    /// Get the count of the recoveries after interrupted updates since the segment is created.
+    pub fn recoveries(&self) -> u64 {
+        self.inner.header().recoveries.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fork a child process that takes the segment lock, marks the segment dirty and exits while holding the lock.
+    fn die_with_lock(cache: &SharedMemoryCache) {
+        match unsafe { libc::fork() } {
+            0 => {
+                let guard = cache.inner.lock();
+                guard.begin();
+                std::mem::forget(guard);
+                unsafe { libc::_exit(0) };
+            }
+            -1 => panic!("fork error: {}", io::Error::last_os_error()),
+            pid => {
+                let mut status = 0;
+                assert_eq!(unsafe { libc::waitpid(pid, &mut status, 0) }, pid);
+                assert!(libc::WIFEXITED(status));
+            }
+        }
+    }
+
+    #[test]
+    fn test_shared_memory_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("shm");
+
+        let a = SharedMemoryCacheBuilder::new(&path)
+            .with_slot_size(128)
+            .with_slots(16)
+            .build()
+            .unwrap();
+        let b = SharedMemoryCacheBuilder::new(&path)
+            .with_slot_size(128)
+            .with_slots(16)
+            .build()
+            .unwrap();
+
+        assert!(a.insert(b"k1", b"v1"));
+        assert_eq!(b.get(b"k1"), Some(b"v1".to_vec()));
+        assert!(b.insert(b"k1", b"v2"));
+        assert_eq!(a.get(b"k1"), Some(b"v2".to_vec()));
+        assert_eq!(a.len(), 1);
+
+        assert_eq!(a.max_entry_size(), 88);
+        assert!(!a.insert(b"k2", &[0; 87]));
+        assert!(a.insert(b"k2", &[0; 86]));
+        assert_eq!(b.get(b"k2").unwrap().len(), 86);
+
+        assert!(b.remove(b"k1"));
+        assert!(!a.remove(b"k1"));
+        assert!(!a.contains(b"k1"));
+        assert_eq!(b.len(), 1);
+
+        a.clear();
+        assert!(b.is_empty());
+
+        assert!(SharedMemoryCacheBuilder::new(&path)
+            .with_slot_size(128)
+            .with_slots(32)
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn test_shared_memory_cache_eviction() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = SharedMemoryCacheBuilder::new(dir.path().join("shm"))
+            .with_slot_size(64)
+            .with_slots(4)
+            .build()
+            .unwrap();
+
+        for i in 0u64..4 {
+            assert!(cache.insert(&i.to_le_bytes(), &i.to_le_bytes()));
+        }
+        assert!(cache.get(&0u64.to_le_bytes()).is_some());
+
+        assert!(cache.insert(&4u64.to_le_bytes(), &4u64.to_le_bytes()));
+        assert_eq!(cache.len(), 4);
+        assert!(cache.contains(&0u64.to_le_bytes()));
+        assert!(!cache.contains(&1u64.to_le_bytes()));
+
+        for i in 5u64..100 {
+            assert!(cache.insert(&i.to_le_bytes(), &i.to_le_bytes()));
+        }
+        assert_eq!(cache.len(), 4);
+        assert_eq!(cache.get(&99u64.to_le_bytes()), Some(99u64.to_le_bytes().to_vec()));
+    }
+
+    #[test]
+    fn test_shared_memory_cache_recovery() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = SharedMemoryCacheBuilder::new(dir.path().join("shm"))
+            .with_slot_size(64)
+            .with_slots(16)
+            .build()
+            .unwrap();
+
+        for i in 0u64..10 {
+            assert!(cache.insert(&i.to_le_bytes(), &i.to_le_bytes()));
+        }
+
+        // Simulate a process that died in the middle of an update: the index is lost, the entry being written is torn,
+        // and the lock is held by the dead process.
+        let inner = &cache.inner;
+        for index in 0..inner.buckets {
+            inner.bucket(index as u64).store(NIL, Ordering::Relaxed);
+        }
+        let torn = guard_find(&cache, &3u64.to_le_bytes());
+        inner.slot(torn).checksum.fetch_add(1, Ordering::Relaxed);
+        die_with_lock(&cache);
+
+        assert!(cache.get(&3u64.to_le_bytes()).is_none());
+        assert_eq!(cache.recoveries(), 1);
+        assert_eq!(cache.len(), 9);
+        for i in (0u64..10).filter(|i| *i != 3) {
+            assert_eq!(cache.get(&i.to_le_bytes()), Some(i.to_le_bytes().to_vec()));
+        }
+
+        for i in 10u64..30 {
+            assert!(cache.insert(&i.to_le_bytes(), &i.to_le_bytes()));
+        }
+        assert_eq!(cache.len(), 16);
+        assert_eq!(cache.recoveries(), 1);
+    }
+
+    #[test]
+    fn test_shared_memory_cache_try_insert() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = SharedMemoryCacheBuilder::new(dir.path().join("shm"))
+            .with_slot_size(64)
+            .with_slots(16)
+            .build()
+            .unwrap();
+        let hash = SharedMemoryCache::hash(b"k1");
+
+        let guard = cache.inner.lock();
+        assert!(!cache.try_insert_with_hash(hash, b"k1", b"v1"));
+        drop(guard);
+        assert!(!cache.contains(b"k1"));
+
+        assert!(cache.try_insert_with_hash(hash, b"k1", b"v1"));
+        assert_eq!(cache.get(b"k1"), Some(b"v1".to_vec()));
+        assert!(!cache.try_insert_with_hash(hash, b"k1", &[0; 64]));
+
+        die_with_lock(&cache);
+        assert!(cache.try_insert_with_hash(hash, b"k1", b"v2"));
+        assert_eq!(cache.recoveries(), 1);
+        assert_eq!(cache.get(b"k1"), Some(b"v2".to_vec()));
+    }
+
+    #[test]
+    fn test_shared_memory_cache_concurrent() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("shm");
+        let caches = (0..4)
+            .map(|_| {
+                SharedMemoryCacheBuilder::new(&path)
+                    .with_slot_size(64)
+                    .with_slots(64)
+                    .build()
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        std::thread::scope(|s| {
+            for (t, cache) in caches.iter().enumerate() {
+                s.spawn(move || {
+                    for i in 0u64..10000 {
+                        let key = (i % 128).to_le_bytes();
+                        match i % 3 {
+                            0 => assert!(cache.insert(&key, &(t as u64).to_le_bytes())),
+                            1 => {
+                                if let Some(value) = cache.get(&key) {
+                                    assert!(u64::from_le_bytes(value.try_into().unwrap()) < 4);
+                                }
+                            }
+                            _ => {
+                                cache.remove(&key);
+                            }
+                        }
+                    }
+                });
+            }
+        });
+
+        assert!(caches[0].len() <= 64);
+        assert_eq!(caches[0].recoveries(), 0);
+    }
+
+    fn guard_find(cache: &SharedMemoryCache, key: &[u8]) -> u32 {
+        (0..cache.inner.slots)
+            .find(|index| unsafe { cache.inner.validate(*index) && cache.inner.key(*index) == key })
+            .unwrap()
+    }
+}
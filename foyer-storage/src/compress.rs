@@ -14,10 +14,14 @@
 
 // TODO(MrCroxx): unify compress interface?
 
+use std::{fmt::Debug, sync::Arc};
+
 use clap::ValueEnum;
+use foyer_common::code::StorageValue;
 use serde::{Deserialize, Serialize};
+use zstd::dict::{DecoderDictionary, EncoderDictionary};
 
-use crate::error::Error;
+use crate::error::{Error, Result};
 
 /// The compression algorithm of the disk cache.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
@@ -54,7 +58,7 @@ impl From<Compression> for u8 {
 impl TryFrom<u8> for Compression {
     type Error = Error;
 
-    fn try_from(value: u8) -> Result<Self, Self::Error> {
+    fn try_from(value: u8) -> std::result::Result<Self, Self::Error> {
         match value {
             0 => Ok(Self::None),
             1 => Ok(Self::Zstd),
@@ -63,3 +67,224 @@ impl TryFrom<u8> for Compression {
         }
     }
 }
+
+/// The codec of a serialized value, recorded in the entry header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Codec {
+    /// The compression algorithm of the value.
+    pub compression: Compression,
+    /// The id of the zstd dictionary of the value, or `0` if the value is not compressed with a dictionary.
+    pub dictionary: u32,
+}
+
+impl Codec {
+    /// The codec of the value that is stored raw.
+    pub const RAW: Self = Self {
+        compression: Compression::None,
+        dictionary: 0,
+    };
+}
+
+/// A zstd dictionary to compress the values of the disk cache.
+///
+/// A dictionary improves the compression ratio of the small values with the similar contents, which are hard to compress
+/// on their own. The id of the dictionary is recorded in the header of each entry compressed with it, the entries
+/// compressed with another dictionary are treated as misses.
+#[derive(Clone)]
+pub struct ZstdDictionary {
+    inner: Arc<ZstdDictionaryInner>,
+}
+
+struct ZstdDictionaryInner {
+    id: u32,
+    bytes: Vec<u8>,
+    decoder: DecoderDictionary<'static>,
+}
+
+impl Debug for ZstdDictionary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ZstdDictionary")
+            .field("id", &self.inner.id)
+            .field("size", &self.inner.bytes.len())
+            .finish()
+    }
+}
+
+impl ZstdDictionary {
+    /// Load a zstd dictionary, e.g. the one trained by `zstd --train` or [`ZstdDictionary::train`].
+    ///
+    /// The dictionary must have a non-zero id in its header, raw content dictionaries are not supported.
+    pub fn new(bytes: Vec<u8>) -> Result<Self> {
+        let id = zstd::zstd_safe::get_dict_id_from_dict(&bytes)
+            .ok_or_else(|| anyhow::anyhow!("zstd dictionary must have a non-zero id"))?
+            .get();
+        let decoder = DecoderDictionary::copy(&bytes);
+        Ok(Self {
+            inner: Arc::new(ZstdDictionaryInner { id, bytes, decoder }),
+        })
+    }
+
+    /// Train a zstd dictionary with at most `max_size` bytes from a sample of the cached values.
+    ///
+    /// The sample should be large enough, usually hundreds of values with a total size of about 100 times of
+    /// `max_size`, otherwise the training may fail.
+    pub fn train<'a, V>(values: impl IntoIterator<Item = &'a V>, max_size: usize) -> Result<Self>
+    where
+        V: StorageValue + 'a,
+    {
+        let samples = values
+            .into_iter()
+            .map(|value| bincode::serialize(value))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        let bytes = zstd::dict::from_samples(&samples, max_size)?;
+        Self::new(bytes)
+    }
+
+    /// Get the id of the dictionary.
+    pub fn id(&self) -> u32 {
+        self.inner.id
+    }
+
+    /// Get the content of the dictionary, which can be saved and loaded later with [`ZstdDictionary::new`].
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.inner.bytes
+    }
+
+    pub(crate) fn decoder(&self) -> &DecoderDictionary<'static> {
+        &self.inner.decoder
+    }
+}
+
+/// The compression config of the disk cache.
+///
+/// A value is compressed only if its serialized size is at least [`CompressionConfig::with_min_size`], and the
+/// compressed value is stored only if its size is at most [`CompressionConfig::with_fallback_ratio`] of the raw one.
+/// Otherwise, the value is stored raw. The codec of each value is recorded in its entry header.
+#[derive(Clone)]
+pub struct CompressionConfig {
+    algorithm: Compression,
+    level: i32,
+    min_size: usize,
+    fallback_ratio: f64,
+    dictionary: Option<ZstdDictionary>,
+    /// The dictionary prepared with the level for compression.
+    encoder: Option<Arc<EncoderDictionary<'static>>>,
+}
+
+impl Debug for CompressionConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CompressionConfig")
+            .field("algorithm", &self.algorithm)
+            .field("level", &self.level)
+            .field("min_size", &self.min_size)
+            .field("fallback_ratio", &self.fallback_ratio)
+            .field("dictionary", &self.dictionary)
+            .finish()
+    }
+}
+
+impl Default for CompressionConfig {
+    fn default() -> Self {
+        Self::new(Compression::None)
+    }
+}
+
+impl From<Compression> for CompressionConfig {
+    fn from(algorithm: Compression) -> Self {
+        Self::new(algorithm)
+    }
+}
+
+impl CompressionConfig {
+    /// Create a compression config with the given algorithm and the default options.
+    pub fn new(algorithm: Compression) -> Self {
+        Self {
+            algorithm,
+            level: 0,
+            min_size: 0,
+            fallback_ratio: 1.0,
+            dictionary: None,
+            encoder: None,
+        }
+    }
+
+    /// Set the compression level.
+    ///
+    /// For zstd, the level ranges from `1` to `22`, `0` means the zstd default level `3`, and the negative levels are
+    /// faster. For lz4, the level ranges from `0` to `16`, the negative levels are treated as `0`.
+    ///
+    /// Default: `0`.
+    pub fn with_level(mut self, level: i32) -> Self {
+        self.level = level;
+        self.prepare();
+        self
+    }
+
+    /// Set the minimal serialized size of the values to compress, the smaller values are stored raw.
+    ///
+    /// Default: `0`.
+    pub fn with_min_size(mut self, min_size: usize) -> Self {
+        self.min_size = min_size;
+        self
+    }
+
+    /// Set the ratio of the compressed size to the raw size, above which the value is stored raw.
+    ///
+    /// e.g. With ratio `0.9`, the compressed value is stored only if it saves at least 10% of the space.
+    ///
+    /// Range: (0 ~ 1]
+    ///
+    /// Default: `1.0`.
+    pub fn with_fallback_ratio(mut self, ratio: f64) -> Self {
+        assert!(
+            ratio > 0.0 && ratio <= 1.0,
+            "compression fallback ratio must be a f64 in range (0.0, 1.0], given: {ratio}"
+        );
+        self.fallback_ratio = ratio;
+        self
+    }
+
+    /// Set the zstd dictionary to compress the values with.
+    ///
+    /// The dictionary is only used with [`Compression::Zstd`].
+    ///
+    /// Default: no dictionary.
+    pub fn with_zstd_dictionary(mut self, dictionary: ZstdDictionary) -> Self {
+        self.dictionary = Some(dictionary);
+        self.prepare();
+        self
+    }
+
+    /// Get the compression algorithm.
+    pub fn algorithm(&self) -> Compression {
+        self.algorithm
+    }
+
+    /// Get the zstd dictionary.
+    pub fn zstd_dictionary(&self) -> Option<&ZstdDictionary> {
+        self.dictionary.as_ref()
+    }
+
+    pub(crate) fn level(&self) -> i32 {
+        self.level
+    }
+
+    pub(crate) fn min_size(&self) -> usize {
+        self.min_size
+    }
+
+    pub(crate) fn fallback_ratio(&self) -> f64 {
+        self.fallback_ratio
+    }
+
+    pub(crate) fn zstd_encoder_dictionary(&self) -> Option<&EncoderDictionary<'static>> {
+        self.encoder.as_deref()
+    }
+
+    fn prepare(&mut self) {
+        self.encoder = self
+            .dictionary
+            .as_ref()
+            .map(|dictionary| Arc::new(EncoderDictionary::copy(dictionary.as_bytes(), self.level)));
+    }
+}
@@ -0,0 +1,524 @@
+//  Copyright 2024 foyer Project Authors
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::{
+    borrow::Borrow,
+    fmt::Debug,
+    hash::Hash,
+    marker::PhantomData,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+use foyer_common::{
+    code::{StorageKey, StorageValue},
+    event::EventListener,
+};
+use foyer_memory::{Cache, CacheBuilder, EvictionConfig, LruConfig};
+
+use crate::{
+    compress::Compression,
+    error::{Error, Result},
+    serde::{EntryDeserializer, EntrySerializer},
+};
+
+/// The serialized and compressed value stored in the compressed in-memory cache.
+#[derive(Debug)]
+struct CompressedValue {
+    /// The version is unique for each insertion, to tell if a decompressed value in the hot cache is stale.
+    version: u64,
+    /// The serialized size of the entry before compression, used to weigh the decompressed value in the hot cache.
+    size: usize,
+    /// The serialized key, to restore the owned key on access, for the key is not required to be `Clone`.
+    key: Box<[u8]>,
+    buf: Box<[u8]>,
+}
+
+#[derive(Debug)]
+struct HotValue<V> {
+    version: u64,
+    size: usize,
+    value: Arc<V>,
+}
+
+/// Invalidate the decompressed value in the hot cache when the compressed value is released, so the hot cache never
+/// keeps the values that have been evicted from the compressed in-memory cache.
+struct HotInvalidator<K, V>
+where
+    K: StorageKey,
+    V: StorageValue,
+{
+    hot: Cache<K, HotValue<V>>,
+}
+
+impl<K, V> EventListener for HotInvalidator<K, V>
+where
+    K: StorageKey,
+    V: StorageValue,
+{
+    type Key = K;
+    type Value = CompressedValue;
+
+    fn on_memory_release(&self, key: Self::Key, value: Self::Value) {
+        // The key may have been inserted again, only invalidate the hot value of the released version.
+        if self.hot.get(&key).is_some_and(|hot| hot.version == value.version) {
+            self.hot.remove(&key);
+        }
+    }
+}
+
+/// Compressed in-memory cache builder.
+pub struct CompressedCacheBuilder<K, V> {
+    name: String,
+    capacity: usize,
+    shards: usize,
+    eviction_config: Option<EvictionConfig>,
+    compression: Compression,
+    hot_capacity: Option<usize>,
+    _marker: PhantomData<(K, V)>,
+}
+
+impl<K, V> CompressedCacheBuilder<K, V>
+where
+    K: StorageKey,
+    V: StorageValue,
+{
+    /// Create a new compressed in-memory cache builder.
+    ///
+    /// The capacity is the max total size of the compressed values.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            name: "foyer".to_string(),
+            capacity,
+            shards: 8,
+            eviction_config: None,
+            compression: Compression::Lz4,
+            hot_capacity: None,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Set the name of the compressed in-memory cache instance.
+    ///
+    /// foyer will use the name as the prefix of the metric names. The hot cache uses `{name}-hot`.
+    ///
+    /// Default: `foyer`.
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    /// Set the sharding count of the compressed in-memory cache and the hot cache.
+    ///
+    /// Default: 8.
+    pub fn with_shards(mut self, shards: usize) -> Self {
+        self.shards = shards;
+        self
+    }
+
+    /// Set the eviction algorithm of the compressed in-memory cache.
+    ///
+    /// Default: the same as [`CacheBuilder`].
+    pub fn with_eviction_config(mut self, eviction_config: impl Into<EvictionConfig>) -> Self {
+        self.eviction_config = Some(eviction_config.into());
+        self
+    }
+
+    /// Set the compression algorithm of the values. The codecs are the same as the disk cache.
+    ///
+    /// Default: [`Compression::Lz4`].
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Enable the hot cache in front of the compressed in-memory cache, which keeps the decompressed values to save the
+    /// decompression of the frequently accessed entries.
+    ///
+    /// The capacity is the max total serialized size of the decompressed entries.
+    ///
+    /// Default: No hot cache.
+    pub fn with_hot_cache(mut self, capacity: usize) -> Self {
+        self.hot_capacity = Some(capacity);
+        self
+    }
+
+    /// Build the compressed in-memory cache with the given configuration.
+    pub fn build(self) -> CompressedCache<K, V> {
+        let hot = self.hot_capacity.map(|capacity| {
+            CacheBuilder::new(capacity)
+                .with_name(&format!("{}-hot", self.name))
+                .with_shards(self.shards)
+                .with_eviction_config(LruConfig::default())
+                .with_weighter(|_, value: &HotValue<V>| value.size)
+                .build()
+        });
+
+        let mut builder = CacheBuilder::new(self.capacity)
+            .with_name(&self.name)
+            .with_shards(self.shards)
+            .with_weighter(|_, value: &CompressedValue| std::mem::size_of::<K>() + value.key.len() + value.buf.len());
+        if let Some(eviction_config) = self.eviction_config {
+            builder = builder.with_eviction_config(eviction_config);
+        }
+        if let Some(hot) = hot.as_ref() {
+            builder = builder.with_event_listener(Arc::new(HotInvalidator { hot: hot.clone() }));
+        }
+        let compressed = builder.build();
+
+        CompressedCache {
+            inner: Arc::new(CompressedCacheInner {
+                compressed,
+                hot,
+                compression: self.compression,
+                version: AtomicU64::new(0),
+            }),
+        }
+    }
+}
+
+struct CompressedCacheInner<K, V>
+where
+    K: StorageKey,
+    V: StorageValue,
+{
+    compressed: Cache<K, CompressedValue>,
+    hot: Option<Cache<K, HotValue<V>>>,
+    compression: Compression,
+    version: AtomicU64,
+}
+
+/// In-memory cache that keeps the values serialized and compressed.
+///
+/// The compressed in-memory cache trades CPU for memory. It holds more entries than [`Cache`] with the same memory if
+/// the values are highly compressible. The entries are weighted by the compressed size of the values, and the values
+/// are decompressed on access.
+///
+/// An optional hot cache of the decompressed values can be enabled in front of the compressed in-memory cache with
+/// [`CompressedCacheBuilder::with_hot_cache`]. The hot cache never serves or keeps a value that has been replaced,
+/// removed or evicted from the compressed in-memory cache.
+///
+/// The compressed in-memory cache can also be used as the compressed tier of the hybrid cache, which is enabled with
+/// `HybridCacheBuilderPhaseMemory::with_compressed_cache` of the `foyer` crate.
+pub struct CompressedCache<K, V>
+where
+    K: StorageKey,
+    V: StorageValue,
+{
+    inner: Arc<CompressedCacheInner<K, V>>,
+}
+
+impl<K, V> Debug for CompressedCache<K, V>
+where
+    K: StorageKey,
+    V: StorageValue,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CompressedCache")
+            .field("compression", &self.inner.compression)
+            .field("hot", &self.inner.hot.is_some())
+            .finish()
+    }
+}
+
+impl<K, V> Clone for CompressedCache<K, V>
+where
+    K: StorageKey,
+    V: StorageValue,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<K, V> CompressedCache<K, V>
+where
+    K: StorageKey,
+    V: StorageValue,
+{
+    /// Serialize and compress the entry, then insert it into the compressed in-memory cache.
+    ///
+    /// The entry is taken by reference, for the compressed in-memory cache keeps its own serialized copy.
+    ///
+    /// Returns the compressed size of the value.
+    pub fn insert(&self, key: &K, value: &V) -> Result<usize> {
+        let mut buf = vec![];
+        EntrySerializer::serialize_value(value, &self.inner.compression, &mut buf)?;
+        let len = buf.len();
+        let size = EntrySerializer::estimated_size(key, value);
+        let key = bincode::serialize(key).map_err(Error::from)?;
+
+        let version = self.inner.version.fetch_add(1, Ordering::Relaxed);
+        self.inner.compressed.insert(
+            EntryDeserializer::deserialize_key(&key)?,
+            CompressedValue {
+                version,
+                size,
+                key: key.into_boxed_slice(),
+                buf: buf.into_boxed_slice(),
+            },
+        );
+
+        Ok(len)
+    }
+
+    /// Get the value with the given key from the compressed in-memory cache.
+    ///
+    /// The value is served by the hot cache if it is enabled and the value is still fresh, otherwise the value is
+    /// decompressed and then admitted to the hot cache.
+    pub fn get<Q>(&self, key: &Q) -> Result<Option<Arc<V>>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let Some(entry) = self.inner.compressed.get(key) else {
+            return Ok(None);
+        };
+
+        if let Some(hot) = self.inner.hot.as_ref() {
+            if let Some(hot) = hot.get(key) {
+                if hot.version == entry.version {
+                    return Ok(Some(hot.value.clone()));
+                }
+            }
+        }
+
+        let value = Arc::new(EntryDeserializer::deserialize_value::<V>(
+            &entry.buf,
+            self.inner.compression,
+        )?);
+
+        if let Some(hot) = self.inner.hot.as_ref() {
+            hot.insert(
+                EntryDeserializer::deserialize_key(&entry.key)?,
+                HotValue {
+                    version: entry.version,
+                    size: entry.size,
+                    value: value.clone(),
+                },
+            );
+        }
+
+        Ok(Some(value))
+    }
+
+    /// Decompress the entry with the given key from the compressed in-memory cache, bypassing the hot cache.
+    ///
+    /// It is used when the caller keeps the decompressed value by itself, e.g. the hybrid cache.
+    pub fn load<Q>(&self, key: &Q) -> Result<Option<(K, V)>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let Some(entry) = self.inner.compressed.get(key) else {
+            return Ok(None);
+        };
+        let key = EntryDeserializer::deserialize_key(&entry.key)?;
+        let value = EntryDeserializer::deserialize_value::<V>(&entry.buf, self.inner.compression)?;
+        Ok(Some((key, value)))
+    }
+
+    /// Remove the entry with the given key from the compressed in-memory cache and the hot cache.
+    ///
+    /// Returns `true` if the compressed in-memory cache contains the entry before removal.
+    pub fn remove<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if let Some(hot) = self.inner.hot.as_ref() {
+            hot.remove(key);
+        }
+        self.inner.compressed.remove(key).is_some()
+    }
+
+    /// Check if the compressed in-memory cache contains an entry with the given key.
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.inner.compressed.contains(key)
+    }
+
+    /// Clear the compressed in-memory cache and the hot cache.
+    pub fn clear(&self) {
+        if let Some(hot) = self.inner.hot.as_ref() {
+            hot.clear();
+        }
+        self.inner.compressed.clear();
+    }
+
+    /// Get the compression algorithm of the compressed in-memory cache.
+    pub fn compression(&self) -> Compression {
+        self.inner.compression
+    }
+
+    /// Get the capacity of the compressed in-memory cache.
+    pub fn capacity(&self) -> usize {
+        self.inner.compressed.capacity()
+    }
+
+    /// Get the total compressed size of the cached entries.
+    pub fn usage(&self) -> usize {
+        self.inner.compressed.usage()
+    }
+
+    /// Get the total serialized size of the decompressed entries in the hot cache.
+    pub fn hot_usage(&self) -> usize {
+        self.inner.hot.as_ref().map(|hot| hot.usage()).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn json(i: u64) -> String {
+        (0..64)
+            .map(|n| format!(r#"{{"id":{i},"seq":{n},"status":"ok","tags":["alpha","beta"]}}"#))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    fn case(compression: Compression) {
+        let cache: CompressedCache<u64, String> = CompressedCacheBuilder::new(usize::MAX)
+            .with_shards(1)
+            .with_compression(compression)
+            .build();
+
+        let mut raw = 0;
+        for i in 0..16 {
+            raw += json(i).len();
+            cache.insert(&i, &json(i)).unwrap();
+        }
+        if compression != Compression::None {
+            assert!(cache.usage() * 4 < raw, "usage: {}, raw: {}", cache.usage(), raw);
+        }
+
+        for i in 0..16 {
+            assert_eq!(cache.get(&i).unwrap().unwrap().as_str(), json(i));
+        }
+        assert!(cache.get(&16).unwrap().is_none());
+
+        assert!(cache.remove(&0));
+        assert!(!cache.remove(&0));
+        assert!(!cache.contains(&0));
+        cache.clear();
+        assert_eq!(cache.usage(), 0);
+    }
+
+    #[test]
+    fn test_compressed_cache() {
+        case(Compression::None);
+        case(Compression::Lz4);
+        case(Compression::Zstd);
+    }
+
+    fn entry_size(i: u64) -> usize {
+        EntrySerializer::estimated_size(&i, &json(i))
+    }
+
+    #[test]
+    fn test_compressed_cache_hot() {
+        let cache: CompressedCache<u64, String> = CompressedCacheBuilder::new(usize::MAX)
+            .with_shards(1)
+            .with_hot_cache(entry_size(0) * 4)
+            .build();
+
+        cache.insert(&1, &json(1)).unwrap();
+        assert_eq!(cache.hot_usage(), 0);
+
+        let v1 = cache.get(&1).unwrap().unwrap();
+        assert_eq!(cache.hot_usage(), entry_size(1));
+        let v2 = cache.get(&1).unwrap().unwrap();
+        assert!(Arc::ptr_eq(&v1, &v2));
+
+        // The stale value in the hot cache must not be served.
+        cache.insert(&1, &json(2)).unwrap();
+        let v3 = cache.get(&1).unwrap().unwrap();
+        assert_eq!(v3.as_str(), json(2));
+
+        assert!(cache.remove(&1));
+        assert!(cache.get(&1).unwrap().is_none());
+        assert_eq!(cache.hot_usage(), 0);
+
+        // The hot cache is weighed by the serialized size of the decompressed entries.
+        for i in 0..10 {
+            cache.insert(&i, &json(i)).unwrap();
+            cache.get(&i).unwrap().unwrap();
+        }
+        assert_eq!(cache.hot_usage(), (6..10).map(entry_size).sum::<usize>());
+    }
+
+    #[test]
+    fn test_compressed_cache_hot_invalidation() {
+        let cache: CompressedCache<u64, String> = CompressedCacheBuilder::new(16 * 1024)
+            .with_shards(1)
+            .with_compression(Compression::None)
+            .with_hot_cache(usize::MAX)
+            .build();
+
+        cache.insert(&0, &json(0)).unwrap();
+        let v0 = cache.get(&0).unwrap().unwrap();
+        assert_eq!(cache.hot_usage(), entry_size(0));
+
+        // Evict key `0` from the compressed in-memory cache, the hot copy must be invalidated with it.
+        for i in 1..16 {
+            cache.insert(&i, &json(i)).unwrap();
+        }
+        assert!(!cache.contains(&0));
+        assert!(cache.get(&0).unwrap().is_none());
+        assert_eq!(cache.hot_usage(), 0);
+        assert_eq!(v0.as_str(), json(0));
+
+        // Replacing a key must not invalidate the hot copy of the new version.
+        cache.insert(&15, &json(16)).unwrap();
+        assert_eq!(cache.get(&15).unwrap().unwrap().as_str(), json(16));
+        cache.insert(&15, &json(17)).unwrap();
+        assert_eq!(cache.get(&15).unwrap().unwrap().as_str(), json(17));
+        assert_eq!(cache.hot_usage(), entry_size(17));
+    }
+
+    #[test]
+    fn test_compressed_cache_load() {
+        let cache: CompressedCache<u64, String> = CompressedCacheBuilder::new(usize::MAX)
+            .with_hot_cache(usize::MAX)
+            .build();
+
+        cache.insert(&1, &json(1)).unwrap();
+        assert_eq!(cache.load(&1).unwrap(), Some((1, json(1))));
+        assert!(cache.load(&2).unwrap().is_none());
+        assert_eq!(cache.hot_usage(), 0);
+    }
+
+    #[test]
+    fn test_compressed_cache_capacity() {
+        let cache: CompressedCache<u64, String> = CompressedCacheBuilder::new(16 * 1024)
+            .with_shards(1)
+            .with_compression(Compression::None)
+            .build();
+
+        for i in 0..16 {
+            cache.insert(&i, &json(i)).unwrap();
+        }
+        assert!(cache.usage() <= 16 * 1024);
+        assert!(cache.contains(&15));
+        assert!(!cache.contains(&0));
+    }
+}
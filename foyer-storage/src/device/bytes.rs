@@ -49,6 +49,15 @@ impl IoBuffer {
     }
 }
 
+impl From<VecA<u8, &'static AlignedAllocator<ALIGN>>> for IoBuffer {
+    fn from(value: VecA<u8, &'static AlignedAllocator<ALIGN>>) -> Self {
+        bits::assert_aligned(ALIGN, value.len());
+        Self {
+            inner: value.into_boxed_slice(),
+        }
+    }
+}
+
 impl Deref for IoBuffer {
     type Target = BoxA<[u8], &'static AlignedAllocator<ALIGN>>;
 
@@ -15,7 +15,10 @@
 use std::{
     fs::{create_dir_all, File, OpenOptions},
     path::{Path, PathBuf},
-    sync::Arc,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
 };
 
 use foyer_common::{asyncify::asyncify_with_runtime, bits};
@@ -37,6 +40,25 @@ pub struct DirectFileDeviceConfig {
 }
 
 impl DirectFileDeviceConfig {
+    /// The manifest of the disk cache is placed beside the file of the device.
+    pub(crate) fn manifest_path(&self) -> PathBuf {
+        let mut path = self.path.clone();
+        path.as_mut_os_string().push(".manifest");
+        path
+    }
+
+    /// The capacity and the region size of the device opened with the config.
+    pub(crate) fn layout(&self) -> (usize, usize) {
+        (self.capacity, self.region_size)
+    }
+
+    /// Check if the file of the device already holds data.
+    ///
+    /// A raw block device is never considered occupied, for it cannot be told apart from a fresh one.
+    pub(crate) fn occupied(&self) -> bool {
+        std::fs::metadata(&self.path).is_ok_and(|metadata| metadata.is_file() && metadata.len() > 0)
+    }
+
     fn verify(&self) -> Result<()> {
         if self.region_size == 0 || self.region_size % ALIGN != 0 {
             return Err(anyhow::anyhow!(
@@ -64,7 +86,7 @@ impl DirectFileDeviceConfig {
 pub struct DirectFileDevice {
     file: Arc<File>,
 
-    capacity: usize,
+    capacity: Arc<AtomicUsize>,
     region_size: usize,
 
     runtime: Runtime,
@@ -80,7 +102,7 @@ impl DirectFileDevice {
             offset as usize + aligned <= self.capacity(),
             "offset ({offset}) + aligned ({aligned}) = total ({total}) <= capacity ({capacity})",
             total = offset as usize + aligned,
-            capacity = self.capacity,
+            capacity = self.capacity(),
         );
 
         let file = self.file.clone();
@@ -118,7 +140,7 @@ impl DirectFileDevice {
             offset as usize + aligned <= self.capacity(),
             "offset ({offset}) + aligned ({aligned}) = total ({total}) <= capacity ({capacity})",
             total = offset as usize + aligned,
-            capacity = self.capacity,
+            capacity = self.capacity(),
         );
 
         let mut buf = IoBytesMut::with_capacity(aligned);
@@ -153,13 +175,48 @@ impl DirectFileDevice {
 
         Ok(buffer)
     }
+
+    /// Check if the device can be resized, which requires the device to be on a regular file.
+    pub(crate) fn resizable(&self) -> bool {
+        self.file.metadata().map(|metadata| metadata.is_file()).unwrap_or(false)
+    }
+
+    /// Resize the device to the given capacity by extending or truncating the file.
+    ///
+    /// The caller must make sure the truncated regions are no longer used.
+    pub(crate) async fn resize(&self, capacity: usize) -> Result<()> {
+        if capacity == 0 || capacity % self.region_size != 0 {
+            return Err(anyhow::anyhow!(
+                "capacity ({capacity}) must be a multiplier of region size ({region_size})",
+                region_size = self.region_size,
+            )
+            .into());
+        }
+        if !self.resizable() {
+            return Err(anyhow::anyhow!("direct file device on a raw block device cannot be resized").into());
+        }
+
+        // Shrink the capacity before truncating the file, and grow it after extending the file, so the capacity never
+        // exceeds the file.
+        if capacity < self.capacity() {
+            self.capacity.store(capacity, Ordering::Relaxed);
+        }
+        let file = self.file.clone();
+        asyncify_with_runtime(self.runtime.write(), move || {
+            file.set_len(capacity as _).map_err(Error::from)
+        })
+        .await?;
+        self.capacity.store(capacity, Ordering::Relaxed);
+
+        Ok(())
+    }
 }
 
 impl Dev for DirectFileDevice {
     type Config = DirectFileDeviceConfig;
 
     fn capacity(&self) -> usize {
-        self.capacity
+        self.capacity.load(Ordering::Relaxed)
     }
 
     fn region_size(&self) -> usize {
@@ -205,7 +262,7 @@ impl Dev for DirectFileDevice {
 
         Ok(Self {
             file,
-            capacity: options.capacity,
+            capacity: Arc::new(AtomicUsize::new(options.capacity)),
             region_size: options.region_size,
             runtime,
         })
@@ -239,6 +296,15 @@ impl Dev for DirectFileDevice {
             region_size = self.region_size(),
         );
 
+        // The index may still refer to a region that is removed by shrinking the device.
+        if region as usize >= self.regions() {
+            return Err(anyhow::anyhow!(
+                "region {region} is out of the device with {regions} regions",
+                regions = self.regions()
+            )
+            .into());
+        }
+
         let poffset = offset + region as u64 * self.region_size as u64;
         self.pread(poffset, len).await
     }
@@ -22,6 +22,7 @@ use foyer_common::{asyncify::asyncify_with_runtime, bits};
 use fs4::free_space;
 use futures::future::try_join_all;
 use itertools::Itertools;
+use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 
 use super::{Dev, DevExt, RegionId};
@@ -39,6 +40,25 @@ pub struct DirectFsDeviceConfig {
 }
 
 impl DirectFsDeviceConfig {
+    /// The manifest of the disk cache is placed in the directory of the device.
+    pub(crate) fn manifest_path(&self) -> PathBuf {
+        self.dir.join("foyer-storage-manifest")
+    }
+
+    /// The capacity and the region size of the device opened with the config.
+    pub(crate) fn layout(&self) -> (usize, usize) {
+        (self.capacity, self.file_size)
+    }
+
+    /// Check if the directory of the device already holds any region file.
+    pub(crate) fn occupied(&self) -> bool {
+        std::fs::read_dir(&self.dir).is_ok_and(|entries| {
+            entries
+                .flatten()
+                .any(|entry| entry.file_name().to_string_lossy().starts_with(DirectFsDevice::PREFIX))
+        })
+    }
+
     fn verify(&self) -> Result<()> {
         if self.file_size == 0 || self.file_size % ALIGN != 0 {
             return Err(anyhow::anyhow!(
@@ -69,9 +89,9 @@ pub struct DirectFsDevice {
 
 #[derive(Debug)]
 struct DirectFsDeviceInner {
-    files: Vec<Arc<File>>,
+    dir: PathBuf,
+    files: RwLock<Vec<Arc<File>>>,
 
-    capacity: usize,
     file_size: usize,
 
     runtime: Runtime,
@@ -84,8 +104,70 @@ impl DirectFsDevice {
         format!("{}{:08}", Self::PREFIX, region)
     }
 
-    fn file(&self, region: RegionId) -> &Arc<File> {
-        &self.inner.files[region as usize]
+    fn file(&self, region: RegionId) -> Result<Arc<File>> {
+        // The index may still refer to a region that is removed by shrinking the device.
+        self.inner.files.read().get(region as usize).cloned().ok_or_else(|| {
+            anyhow::anyhow!(
+                "region {region} is out of the device with {regions} regions",
+                regions = self.regions()
+            )
+            .into()
+        })
+    }
+
+    fn open_file(path: PathBuf, file_size: usize) -> Result<Arc<File>> {
+        let mut opts = OpenOptions::new();
+
+        opts.create(true).write(true).read(true);
+
+        #[cfg(target_os = "linux")]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            opts.custom_flags(libc::O_DIRECT | libc::O_NOATIME);
+        }
+
+        let file = opts.open(path)?;
+        file.set_len(file_size as _)?;
+
+        Ok(Arc::new(file))
+    }
+
+    /// Resize the device to the given capacity by creating or removing the files of the regions.
+    ///
+    /// The caller must make sure the removed regions are no longer used.
+    pub(crate) async fn resize(&self, capacity: usize) -> Result<()> {
+        let file_size = self.inner.file_size;
+        if capacity == 0 || capacity % file_size != 0 {
+            return Err(
+                anyhow::anyhow!("capacity ({capacity}) must be a multiplier of file size ({file_size})").into(),
+            );
+        }
+
+        let regions = capacity / file_size;
+        let current = self.regions();
+        let dir = self.inner.dir.clone();
+
+        if regions > current {
+            let files = asyncify_with_runtime(self.inner.runtime.write(), move || {
+                (current..regions)
+                    .map(|i| Self::open_file(dir.join(Self::filename(i as RegionId)), file_size))
+                    .collect::<Result<Vec<_>>>()
+            })
+            .await?;
+            self.inner.files.write().extend(files);
+        } else if regions < current {
+            // The in-flight i/o on the removed files holds the file handles, so it is safe to remove them here.
+            self.inner.files.write().truncate(regions);
+            asyncify_with_runtime(self.inner.runtime.write(), move || {
+                for i in regions..current {
+                    std::fs::remove_file(dir.join(Self::filename(i as RegionId)))?;
+                }
+                Ok::<_, Error>(())
+            })
+            .await?;
+        }
+
+        Ok(())
     }
 }
 
@@ -93,7 +175,7 @@ impl Dev for DirectFsDevice {
     type Config = DirectFsDeviceConfig;
 
     fn capacity(&self) -> usize {
-        self.inner.capacity
+        self.inner.files.read().len() * self.inner.file_size
     }
 
     fn region_size(&self) -> usize {
@@ -104,8 +186,6 @@ impl Dev for DirectFsDevice {
     async fn open(options: Self::Config, runtime: Runtime) -> Result<Self> {
         options.verify()?;
 
-        // TODO(MrCroxx): write and read options to a manifest file for pinning
-
         let regions = options.capacity / options.file_size;
 
         if !options.dir.exists() {
@@ -115,31 +195,15 @@ impl Dev for DirectFsDevice {
         let futures = (0..regions)
             .map(|i| {
                 let path = options.dir.clone().join(Self::filename(i as RegionId));
-                async {
-                    let mut opts = OpenOptions::new();
-
-                    opts.create(true).write(true).read(true);
-
-                    #[cfg(target_os = "linux")]
-                    {
-                        use std::os::unix::fs::OpenOptionsExt;
-                        opts.custom_flags(libc::O_DIRECT | libc::O_NOATIME);
-                    }
-
-                    let file = opts.open(path)?;
-                    file.set_len(options.file_size as _)?;
-                    let file = Arc::new(file);
-
-                    Ok::<_, Error>(file)
-                }
+                async { Self::open_file(path, options.file_size) }
             })
             .collect_vec();
         let files = try_join_all(futures).await?;
 
         Ok(Self {
             inner: Arc::new(DirectFsDeviceInner {
-                files,
-                capacity: options.capacity,
+                dir: options.dir,
+                files: RwLock::new(files),
                 file_size: options.file_size,
                 runtime,
             }),
@@ -157,7 +221,7 @@ impl Dev for DirectFsDevice {
             region_size = self.region_size(),
         );
 
-        let file = self.file(region).clone();
+        let file = self.file(region)?;
 
         asyncify_with_runtime(self.inner.runtime.write(), move || {
             #[cfg(target_family = "windows")]
@@ -199,7 +263,7 @@ impl Dev for DirectFsDevice {
             buf.set_len(aligned);
         }
 
-        let file = self.file(region).clone();
+        let file = self.file(region)?;
 
         let mut buffer = asyncify_with_runtime(self.inner.runtime.read(), move || {
             #[cfg(target_family = "unix")]
@@ -230,8 +294,11 @@ impl Dev for DirectFsDevice {
     #[fastrace::trace(name = "foyer::storage::device::direct_fs::flush")]
     async fn flush(&self, region: Option<super::RegionId>) -> Result<()> {
         let flush = |region: RegionId| {
-            let file = self.file(region).clone();
-            asyncify_with_runtime(self.inner.runtime.write(), move || file.sync_all().map_err(Error::from))
+            let file = self.file(region);
+            async move {
+                let file = file?;
+                asyncify_with_runtime(self.inner.runtime.write(), move || file.sync_all().map_err(Error::from)).await
+            }
         };
 
         if let Some(region) = region {
@@ -0,0 +1,412 @@
+//  Copyright 2024 foyer Project Authors
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::{
+    collections::HashSet,
+    fmt::Debug,
+    ops::RangeInclusive,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use foyer_common::bits;
+use futures::future::BoxFuture;
+use parking_lot::RwLock;
+use rand::Rng;
+
+use super::{Dev, DevExt, Device, DeviceConfig, RegionId, ALIGN};
+use crate::{
+    error::{Error, Result},
+    IoBytes, IoBytesMut, Runtime,
+};
+
+/// The faults to inject into the i/o of a [`FaultInjected`] device.
+///
+/// The rates are probabilities in `[0, 1]`, rolled independently for each i/o.
+#[derive(Debug, Clone, Default)]
+pub struct FaultPolicy {
+    /// The probability that a read fails with `EIO`.
+    pub read_error_rate: f64,
+    /// The probability that a write fails with `EIO`.
+    pub write_error_rate: f64,
+    /// The probability that a flush fails with `EIO`.
+    pub flush_error_rate: f64,
+    /// The probability that a write is torn.
+    ///
+    /// A torn write only persists a 4K-aligned prefix (maybe empty) of the buffer, but still reports success.
+    pub torn_write_rate: f64,
+    /// The latency injected before each i/o, uniformly distributed in the range.
+    pub latency: Option<RangeInclusive<Duration>>,
+    /// All i/o on the regions fails with `EIO`.
+    pub failed_regions: HashSet<RegionId>,
+    /// Acknowledge the flushes without passing them to the underlying device.
+    pub drop_flush: bool,
+}
+
+/// The statistics of the injected faults.
+#[derive(Debug, Default)]
+pub struct FaultStats {
+    /// The count of the injected errors.
+    pub errors: AtomicUsize,
+    /// The count of the torn writes.
+    pub torn_writes: AtomicUsize,
+    /// The count of the dropped flushes.
+    pub dropped_flushes: AtomicUsize,
+}
+
+/// A handle to program the fault policy of the [`FaultInjected`] devices.
+///
+/// The clones of the handle share the same policy and statistics, so the policy can be changed at any time after the
+/// device is opened.
+#[derive(Debug, Clone, Default)]
+pub struct FaultInjector {
+    policy: Arc<RwLock<FaultPolicy>>,
+    stats: Arc<FaultStats>,
+}
+
+impl FaultInjector {
+    /// Create a new fault injector with the given policy.
+    pub fn new(policy: FaultPolicy) -> Self {
+        Self {
+            policy: Arc::new(RwLock::new(policy)),
+            stats: Arc::default(),
+        }
+    }
+
+    /// Get the current fault policy.
+    pub fn policy(&self) -> FaultPolicy {
+        self.policy.read().clone()
+    }
+
+    /// Replace the fault policy.
+    pub fn set_policy(&self, policy: FaultPolicy) {
+        *self.policy.write() = policy;
+    }
+
+    /// Update the fault policy in place.
+    pub fn update(&self, f: impl FnOnce(&mut FaultPolicy)) {
+        f(&mut self.policy.write());
+    }
+
+    /// Get the statistics of the injected faults.
+    pub fn stats(&self) -> &Arc<FaultStats> {
+        &self.stats
+    }
+
+    fn latency(&self) -> Option<Duration> {
+        let policy = self.policy.read();
+        let range = policy.latency.as_ref()?;
+        if range.start() >= range.end() {
+            return Some(*range.start());
+        }
+        Some(rand::thread_rng().gen_range(range.clone()))
+    }
+
+    async fn delay(&self) {
+        if let Some(latency) = self.latency() {
+            tokio::time::sleep(latency).await;
+        }
+    }
+
+    fn roll(rate: f64) -> bool {
+        rate > 0.0 && (rate >= 1.0 || rand::thread_rng().gen_bool(rate))
+    }
+
+    fn error(&self, rate: impl FnOnce(&FaultPolicy) -> f64, region: Option<RegionId>) -> Result<()> {
+        let fail = {
+            let policy = self.policy.read();
+            region.is_some_and(|region| policy.failed_regions.contains(&region)) || Self::roll(rate(&policy))
+        };
+        if fail {
+            self.stats.errors.fetch_add(1, Ordering::Relaxed);
+            return Err(Error::Io(std::io::Error::from_raw_os_error(libc::EIO)));
+        }
+        Ok(())
+    }
+
+    /// Return the length of the prefix to persist if the write is torn.
+    ///
+    /// A write shorter than a page cannot be torn at a page boundary, so it is never torn.
+    fn tear(&self, aligned: usize) -> Option<usize> {
+        let pages = aligned / ALIGN;
+        if pages == 0 || !Self::roll(self.policy.read().torn_write_rate) {
+            return None;
+        }
+        self.stats.torn_writes.fetch_add(1, Ordering::Relaxed);
+        Some(rand::thread_rng().gen_range(0..pages) * ALIGN)
+    }
+
+    fn drop_flush(&self) -> bool {
+        if !self.policy.read().drop_flush {
+            return false;
+        }
+        self.stats.dropped_flushes.fetch_add(1, Ordering::Relaxed);
+        true
+    }
+}
+
+#[derive(Clone)]
+pub struct FaultInjectedConfig<D>
+where
+    D: Dev,
+{
+    pub config: D::Config,
+    pub injector: FaultInjector,
+}
+
+impl<D> Debug for FaultInjectedConfig<D>
+where
+    D: Dev,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FaultInjectedConfig")
+            .field("config", &self.config)
+            .field("injector", &self.injector)
+            .finish()
+    }
+}
+
+/// A device wrapper that injects faults into the i/o of the underlying device with a programmable [`FaultPolicy`].
+///
+/// It is supposed to be used for resilience testing only, and is available with the `fault_injection` feature.
+#[derive(Debug, Clone)]
+pub struct FaultInjected<D>
+where
+    D: Dev,
+{
+    device: D,
+    injector: FaultInjector,
+}
+
+impl<D> FaultInjected<D>
+where
+    D: Dev,
+{
+    /// Get the fault injector of the device.
+    pub fn injector(&self) -> &FaultInjector {
+        &self.injector
+    }
+}
+
+impl<D> Dev for FaultInjected<D>
+where
+    D: Dev,
+{
+    type Config = FaultInjectedConfig<D>;
+
+    fn capacity(&self) -> usize {
+        self.device.capacity()
+    }
+
+    fn region_size(&self) -> usize {
+        self.device.region_size()
+    }
+
+    async fn open(config: Self::Config, runtime: Runtime) -> Result<Self> {
+        let device = D::open(config.config, runtime).await?;
+        Ok(Self {
+            device,
+            injector: config.injector,
+        })
+    }
+
+    #[fastrace::trace(name = "foyer::storage::device::fault::write")]
+    async fn write(&self, buf: IoBytes, region: RegionId, offset: u64) -> Result<()> {
+        self.injector.delay().await;
+        self.injector.error(|policy| policy.write_error_rate, Some(region))?;
+        match self.injector.tear(buf.as_aligned().len()) {
+            None => self.device.write(buf, region, offset).await,
+            Some(0) => Ok(()),
+            Some(len) => self.device.write(buf.slice(..len), region, offset).await,
+        }
+    }
+
+    #[fastrace::trace(name = "foyer::storage::device::fault::read")]
+    async fn read(&self, region: RegionId, offset: u64, len: usize) -> Result<IoBytesMut> {
+        bits::assert_aligned(self.align() as u64, offset);
+
+        self.injector.delay().await;
+        self.injector.error(|policy| policy.read_error_rate, Some(region))?;
+        self.device.read(region, offset, len).await
+    }
+
+    #[fastrace::trace(name = "foyer::storage::device::fault::flush")]
+    async fn flush(&self, region: Option<RegionId>) -> Result<()> {
+        self.injector.delay().await;
+        self.injector.error(|policy| policy.flush_error_rate, region)?;
+        if self.injector.drop_flush() {
+            return Ok(());
+        }
+        self.device.flush(region).await
+    }
+}
+
+// The underlying device of the fault injected device in the device enum is the device enum itself. The futures are
+// boxed outside the async fns of the device to break the recursive future types.
+impl FaultInjected<Device> {
+    pub(crate) fn open_boxed(
+        config: FaultInjectedConfig<Device>,
+        runtime: Runtime,
+    ) -> BoxFuture<'static, Result<Self>> {
+        Box::pin(<Self as Dev>::open(config, runtime))
+    }
+
+    pub(crate) fn write_boxed(&self, buf: IoBytes, region: RegionId, offset: u64) -> BoxFuture<'_, Result<()>> {
+        Box::pin(<Self as Dev>::write(self, buf, region, offset))
+    }
+
+    pub(crate) fn read_boxed(&self, region: RegionId, offset: u64, len: usize) -> BoxFuture<'_, Result<IoBytesMut>> {
+        Box::pin(<Self as Dev>::read(self, region, offset, len))
+    }
+
+    pub(crate) fn flush_boxed(&self, region: Option<RegionId>) -> BoxFuture<'_, Result<()>> {
+        Box::pin(<Self as Dev>::flush(self, region))
+    }
+}
+
+/// [`FaultInjectedDeviceOptions`] is used to build the options for the fault injected device.
+///
+/// The fault injected device wraps the device built with the given options, and injects faults into its i/o with the
+/// policy programmed by the given [`FaultInjector`]. See [`FaultPolicy`] for details.
+#[derive(Debug)]
+pub struct FaultInjectedDeviceOptions {
+    device: DeviceConfig,
+    injector: FaultInjector,
+}
+
+impl FaultInjectedDeviceOptions {
+    /// Wrap the device built with the given options, and inject faults with the given injector.
+    pub fn new(options: impl Into<DeviceConfig>, injector: FaultInjector) -> Self {
+        Self {
+            device: options.into(),
+            injector,
+        }
+    }
+}
+
+impl From<FaultInjectedDeviceOptions> for FaultInjectedConfig<Device> {
+    fn from(options: FaultInjectedDeviceOptions) -> Self {
+        Self {
+            config: options.device,
+            injector: options.injector,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use itertools::repeat_n;
+
+    use super::*;
+    use crate::MemoryDeviceOptions;
+
+    const MB: usize = 1024 * 1024;
+
+    async fn device_for_test(injector: FaultInjector) -> Device {
+        let config: DeviceConfig = FaultInjectedDeviceOptions::new(
+            MemoryDeviceOptions::new().with_capacity(4 * MB).with_region_size(MB),
+            injector,
+        )
+        .into();
+        Device::open(config, Runtime::current()).await.unwrap()
+    }
+
+    fn buffer(byte: u8, len: usize) -> IoBytes {
+        let mut buf = IoBytesMut::with_capacity(len);
+        buf.extend(repeat_n(byte, len));
+        buf.freeze()
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn test_fault_injected_errors() {
+        let injector = FaultInjector::default();
+        let device = device_for_test(injector.clone()).await;
+
+        device.write(buffer(1, 4096), 0, 0).await.unwrap();
+        device.write(buffer(2, 4096), 1, 0).await.unwrap();
+
+        injector.update(|policy| policy.read_error_rate = 1.0);
+        assert!(matches!(device.read(0, 0, 4096).await, Err(Error::Io(_))));
+        device.write(buffer(1, 4096), 0, 0).await.unwrap();
+        device.flush(None).await.unwrap();
+
+        injector.set_policy(FaultPolicy {
+            write_error_rate: 1.0,
+            flush_error_rate: 1.0,
+            ..Default::default()
+        });
+        assert!(device.write(buffer(3, 4096), 0, 0).await.is_err());
+        assert!(device.flush(None).await.is_err());
+        assert_eq!(device.read(0, 0, 4096).await.unwrap().freeze(), buffer(1, 4096));
+
+        injector.set_policy(FaultPolicy {
+            failed_regions: HashSet::from_iter([1]),
+            ..Default::default()
+        });
+        assert_eq!(device.read(0, 0, 4096).await.unwrap().freeze(), buffer(1, 4096));
+        assert!(device.read(1, 0, 4096).await.is_err());
+        assert!(device.write(buffer(3, 4096), 1, 0).await.is_err());
+        assert!(device.flush(Some(1)).await.is_err());
+        device.flush(Some(0)).await.unwrap();
+
+        assert_eq!(injector.stats().errors.load(Ordering::Relaxed), 6);
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn test_fault_injected_torn_write() {
+        let injector = FaultInjector::default();
+        let device = device_for_test(injector.clone()).await;
+
+        device.write(buffer(2, 16 * 4096), 0, 0).await.unwrap();
+
+        injector.set_policy(FaultPolicy {
+            torn_write_rate: 1.0,
+            drop_flush: true,
+            ..Default::default()
+        });
+        device.write(buffer(1, 16 * 4096), 0, 0).await.unwrap();
+        device.flush(None).await.unwrap();
+
+        let read = device.read(0, 0, 16 * 4096).await.unwrap();
+        let persisted = read.iter().take_while(|b| **b == 1).count();
+        assert_eq!(persisted % ALIGN, 0);
+        assert!(persisted < 16 * 4096);
+        assert!(read[persisted..].iter().all(|b| *b == 2));
+
+        // The write shorter than a page is never torn.
+        device.write(buffer(1, 0), 0, 0).await.unwrap();
+
+        assert_eq!(injector.stats().torn_writes.load(Ordering::Relaxed), 1);
+        assert_eq!(injector.stats().dropped_flushes.load(Ordering::Relaxed), 1);
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn test_fault_injected_latency() {
+        let injector = FaultInjector::new(FaultPolicy {
+            latency: Some(Duration::from_millis(10)..=Duration::from_millis(20)),
+            ..Default::default()
+        });
+        let device = device_for_test(injector).await;
+
+        let now = Instant::now();
+        device.write(buffer(1, 4096), 0, 0).await.unwrap();
+        device.read(0, 0, 4096).await.unwrap();
+        assert!(now.elapsed() >= Duration::from_millis(20));
+    }
+}
@@ -0,0 +1,320 @@
+//  Copyright 2024 foyer Project Authors
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::{fmt::Debug, sync::Arc};
+
+use foyer_common::bits;
+use parking_lot::{Mutex, RwLock};
+
+use super::{Dev, DevExt, RegionId};
+use crate::{
+    device::{ALIGN, IO_BUFFER_ALLOCATOR},
+    error::Result,
+    IoBuffer, IoBytes, IoBytesMut, Runtime,
+};
+
+type Regions = Arc<Vec<RwLock<IoBuffer>>>;
+
+/// The memory of the memory device, shared by the clones of the options and the config.
+#[derive(Clone, Default)]
+struct MemoryDeviceHandle {
+    regions: Arc<Mutex<Option<Regions>>>,
+}
+
+impl Debug for MemoryDeviceHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MemoryDeviceHandle")
+            .field("allocated", &self.regions.lock().is_some())
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MemoryDeviceConfig {
+    capacity: usize,
+    region_size: usize,
+    handle: MemoryDeviceHandle,
+}
+
+impl MemoryDeviceConfig {
+    /// The capacity and the region size of the device opened with the config.
+    pub(crate) fn layout(&self) -> (usize, usize) {
+        (self.capacity, self.region_size)
+    }
+
+    fn verify(&self) -> Result<()> {
+        if self.region_size == 0 || self.region_size % ALIGN != 0 {
+            return Err(anyhow::anyhow!(
+                "region size ({region_size}) must be a multiplier of ALIGN ({ALIGN})",
+                region_size = self.region_size,
+            )
+            .into());
+        }
+
+        if self.capacity == 0 || self.capacity % self.region_size != 0 {
+            return Err(anyhow::anyhow!(
+                "capacity ({capacity}) must be a multiplier of region size ({region_size})",
+                capacity = self.capacity,
+                region_size = self.region_size,
+            )
+            .into());
+        }
+
+        Ok(())
+    }
+}
+
+/// A device that keeps the data in the memory.
+///
+/// Each region is backed by an aligned [`IoBuffer`], and the device honors the same alignment requirements as the disk
+/// devices. So the disk cache engines can run without a file system that supports direct i/o, e.g. for tests or tmpfs
+/// deployments.
+///
+/// The data is lost after all the clones of the [`MemoryDeviceOptions`] that opens the device and the device itself are
+/// dropped.
+#[derive(Debug, Clone)]
+pub struct MemoryDevice {
+    regions: Regions,
+
+    capacity: usize,
+    region_size: usize,
+}
+
+impl Dev for MemoryDevice {
+    type Config = MemoryDeviceConfig;
+
+    fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    fn region_size(&self) -> usize {
+        self.region_size
+    }
+
+    #[fastrace::trace(name = "foyer::storage::device::memory::open")]
+    async fn open(options: Self::Config, _: Runtime) -> Result<Self> {
+        options.verify()?;
+
+        let mut guard = options.handle.regions.lock();
+        let regions = match guard.as_ref() {
+            Some(regions) => {
+                let capacity = regions.len() * regions[0].read().len();
+                if capacity != options.capacity || regions[0].read().len() != options.region_size {
+                    return Err(anyhow::anyhow!(
+                        "memory device is reopened with a different layout, capacity: {capacity} -> {}, region size: {} -> {}",
+                        options.capacity,
+                        regions[0].read().len(),
+                        options.region_size,
+                    )
+                    .into());
+                }
+                regions.clone()
+            }
+            None => {
+                let regions = (0..options.capacity / options.region_size)
+                    .map(|_| {
+                        RwLock::new(
+                            allocator_api2::vec::from_elem_in(0, options.region_size, &IO_BUFFER_ALLOCATOR).into(),
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                guard.insert(Arc::new(regions)).clone()
+            }
+        };
+        drop(guard);
+
+        Ok(Self {
+            regions,
+            capacity: options.capacity,
+            region_size: options.region_size,
+        })
+    }
+
+    #[fastrace::trace(name = "foyer::storage::device::memory::write")]
+    async fn write(&self, buf: IoBytes, region: RegionId, offset: u64) -> Result<()> {
+        bits::assert_aligned(self.align() as u64, offset);
+
+        let aligned = buf.as_aligned().len();
+
+        assert!(
+            offset as usize + aligned <= self.region_size(),
+            "offset ({offset}) + aligned ({aligned}) = total ({total}) <= region size ({region_size})",
+            total = offset as usize + aligned,
+            region_size = self.region_size(),
+        );
+
+        let offset = offset as usize;
+        self.regions[region as usize].write()[offset..offset + aligned].copy_from_slice(buf.as_aligned());
+
+        Ok(())
+    }
+
+    #[fastrace::trace(name = "foyer::storage::device::memory::read")]
+    async fn read(&self, region: RegionId, offset: u64, len: usize) -> Result<IoBytesMut> {
+        bits::assert_aligned(self.align() as u64, offset);
+
+        let aligned = bits::align_up(self.align(), len);
+
+        assert!(
+            offset as usize + aligned <= self.region_size(),
+            "offset ({offset}) + aligned ({aligned}) = total ({total}) <= region size ({region_size})",
+            total = offset as usize + aligned,
+            region_size = self.region_size(),
+        );
+
+        let mut buf = IoBytesMut::with_capacity(aligned);
+        unsafe {
+            buf.set_len(aligned);
+        }
+
+        let offset = offset as usize;
+        buf.copy_from_slice(&self.regions[region as usize].read()[offset..offset + aligned]);
+
+        buf.truncate(len);
+
+        Ok(buf)
+    }
+
+    #[fastrace::trace(name = "foyer::storage::device::memory::flush")]
+    async fn flush(&self, _: Option<RegionId>) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// [`MemoryDeviceOptions`] is used to build the options for the memory device.
+///
+/// The memory device keeps the data in the memory, see [`MemoryDevice`] for details.
+///
+/// The memory is allocated when the device is opened for the first time, and is shared by all the clones of the
+/// options. Opening the device again with a clone of the options reopens the device with the data written before, which
+/// can be used to test the recovery of the disk cache.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryDeviceOptions {
+    capacity: Option<usize>,
+    region_size: Option<usize>,
+    handle: MemoryDeviceHandle,
+}
+
+impl MemoryDeviceOptions {
+    const DEFAULT_CAPACITY: usize = 64 * 1024 * 1024;
+    const DEFAULT_REGION_SIZE: usize = 4 * 1024 * 1024;
+
+    /// Create a new memory device options with a new piece of memory.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the capacity of the memory device.
+    ///
+    /// The given capacity may be modified on build for alignment.
+    ///
+    /// Default: 64 MiB.
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = Some(capacity);
+        self
+    }
+
+    /// Set the region size of the memory device.
+    ///
+    /// The given region size may be modified on build for alignment.
+    ///
+    /// Default: 4 MiB.
+    pub fn with_region_size(mut self, region_size: usize) -> Self {
+        self.region_size = Some(region_size);
+        self
+    }
+}
+
+impl From<MemoryDeviceOptions> for MemoryDeviceConfig {
+    fn from(options: MemoryDeviceOptions) -> Self {
+        let align_v = |value: usize, align: usize| value - value % align;
+
+        let capacity = options.capacity.unwrap_or(MemoryDeviceOptions::DEFAULT_CAPACITY);
+        let capacity = align_v(capacity, ALIGN);
+
+        let region_size = options
+            .region_size
+            .unwrap_or(MemoryDeviceOptions::DEFAULT_REGION_SIZE)
+            .min(capacity);
+        let region_size = align_v(region_size, ALIGN);
+
+        let capacity = align_v(capacity, region_size);
+
+        MemoryDeviceConfig {
+            capacity,
+            region_size,
+            handle: options.handle,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::repeat_n;
+
+    use super::*;
+
+    #[test_log::test(tokio::test)]
+    async fn test_memory_device_io() {
+        let runtime = Runtime::current();
+
+        let options = MemoryDeviceOptions::new()
+            .with_capacity(4 * 1024 * 1024 + 100)
+            .with_region_size(1024 * 1024);
+
+        let device = MemoryDevice::open(options.clone().into(), runtime.clone())
+            .await
+            .unwrap();
+        assert_eq!(device.capacity(), 4 * 1024 * 1024);
+        assert_eq!(device.regions(), 4);
+
+        let mut buf = IoBytesMut::with_capacity(64 * 1024);
+        buf.extend(repeat_n(b'x', 64 * 1024 - 100));
+        let buf = buf.freeze();
+
+        device.write(buf.clone(), 1, 4096).await.unwrap();
+
+        let b = device.read(1, 4096, 64 * 1024 - 100).await.unwrap().freeze();
+        assert_eq!(buf, b);
+
+        drop(device);
+
+        // Reopen with a clone of the options.
+        let device = MemoryDevice::open(options.clone().into(), runtime.clone())
+            .await
+            .unwrap();
+        let b = device.read(1, 4096, 64 * 1024 - 100).await.unwrap().freeze();
+        assert_eq!(buf, b);
+
+        // Reopen with a different layout.
+        assert!(
+            MemoryDevice::open(options.with_region_size(2 * 1024 * 1024).into(), runtime.clone())
+                .await
+                .is_err()
+        );
+
+        // Open a new piece of memory.
+        let device = MemoryDevice::open(
+            MemoryDeviceOptions::new()
+                .with_capacity(4 * 1024 * 1024)
+                .with_region_size(1024 * 1024)
+                .into(),
+            runtime,
+        )
+        .await
+        .unwrap();
+        let b = device.read(1, 4096, 64 * 1024 - 100).await.unwrap();
+        assert!(b.iter().all(|&v| v == 0));
+    }
+}
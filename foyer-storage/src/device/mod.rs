@@ -16,14 +16,28 @@ pub mod allocator;
 pub mod bytes;
 pub mod direct_file;
 pub mod direct_fs;
+#[cfg(any(test, feature = "fault_injection"))]
+pub mod fault;
+pub mod memory;
 pub mod monitor;
+pub mod scheduler;
+pub mod striped;
+pub mod throttle;
+#[cfg(all(feature = "io_uring", target_os = "linux"))]
+pub mod uring;
 
-use std::{fmt::Debug, future::Future};
+use std::{fmt::Debug, future::Future, path::PathBuf};
 
 use allocator::AlignedAllocator;
 use direct_file::DirectFileDeviceConfig;
 use direct_fs::DirectFsDeviceConfig;
+#[cfg(any(test, feature = "fault_injection"))]
+use fault::{FaultInjected, FaultInjectedConfig, FaultInjectedDeviceOptions};
+use memory::{MemoryDevice, MemoryDeviceConfig, MemoryDeviceOptions};
 use monitor::Monitored;
+use striped::{StripedDevice, StripedDeviceConfig, StripedDeviceOptions};
+#[cfg(all(feature = "io_uring", target_os = "linux"))]
+use uring::{IoUringDevice, IoUringDeviceConfig, IoUringDeviceOptions};
 
 use crate::{
     error::Result, DirectFileDevice, DirectFileDeviceOptions, DirectFsDevice, DirectFsDeviceOptions, IoBytes,
@@ -89,6 +103,56 @@ impl<T> DevExt for T where T: Dev {}
 pub enum DeviceConfig {
     DirectFile(DirectFileDeviceConfig),
     DirectFs(DirectFsDeviceConfig),
+    Memory(MemoryDeviceConfig),
+    #[cfg(any(test, feature = "fault_injection"))]
+    FaultInjected(Box<FaultInjectedConfig<Device>>),
+    Striped(StripedDeviceConfig),
+    #[cfg(all(feature = "io_uring", target_os = "linux"))]
+    IoUring(IoUringDeviceConfig),
+}
+
+impl DeviceConfig {
+    /// The path of the manifest of the disk cache on the device, or `None` if the device is not persistent.
+    pub(crate) fn manifest_path(&self) -> Option<PathBuf> {
+        match self {
+            DeviceConfig::DirectFile(config) => Some(config.manifest_path()),
+            DeviceConfig::DirectFs(config) => Some(config.manifest_path()),
+            DeviceConfig::Memory(_) => None,
+            #[cfg(any(test, feature = "fault_injection"))]
+            DeviceConfig::FaultInjected(config) => config.config.manifest_path(),
+            DeviceConfig::Striped(config) => config.manifest_path(),
+            #[cfg(all(feature = "io_uring", target_os = "linux"))]
+            DeviceConfig::IoUring(config) => Some(config.manifest_path()),
+        }
+    }
+
+    /// The capacity and the region size of the device opened with the config.
+    pub(crate) fn layout(&self) -> (usize, usize) {
+        match self {
+            DeviceConfig::DirectFile(config) => config.layout(),
+            DeviceConfig::DirectFs(config) => config.layout(),
+            DeviceConfig::Memory(config) => config.layout(),
+            #[cfg(any(test, feature = "fault_injection"))]
+            DeviceConfig::FaultInjected(config) => config.config.layout(),
+            DeviceConfig::Striped(config) => config.layout(),
+            #[cfg(all(feature = "io_uring", target_os = "linux"))]
+            DeviceConfig::IoUring(config) => config.layout(),
+        }
+    }
+
+    /// Check if the device already holds data, before it is opened.
+    pub(crate) fn occupied(&self) -> bool {
+        match self {
+            DeviceConfig::DirectFile(config) => config.occupied(),
+            DeviceConfig::DirectFs(config) => config.occupied(),
+            DeviceConfig::Memory(_) => false,
+            #[cfg(any(test, feature = "fault_injection"))]
+            DeviceConfig::FaultInjected(config) => config.config.occupied(),
+            DeviceConfig::Striped(config) => config.occupied(),
+            #[cfg(all(feature = "io_uring", target_os = "linux"))]
+            DeviceConfig::IoUring(config) => config.occupied(),
+        }
+    }
 }
 
 impl From<DirectFileDeviceOptions> for DeviceConfig {
@@ -103,10 +167,42 @@ impl From<DirectFsDeviceOptions> for DeviceConfig {
     }
 }
 
+impl From<MemoryDeviceOptions> for DeviceConfig {
+    fn from(options: MemoryDeviceOptions) -> Self {
+        Self::Memory(options.into())
+    }
+}
+
+#[cfg(any(test, feature = "fault_injection"))]
+impl From<FaultInjectedDeviceOptions> for DeviceConfig {
+    fn from(options: FaultInjectedDeviceOptions) -> Self {
+        Self::FaultInjected(Box::new(options.into()))
+    }
+}
+
+impl From<StripedDeviceOptions> for DeviceConfig {
+    fn from(options: StripedDeviceOptions) -> Self {
+        Self::Striped(options.into())
+    }
+}
+
+#[cfg(all(feature = "io_uring", target_os = "linux"))]
+impl From<IoUringDeviceOptions> for DeviceConfig {
+    fn from(options: IoUringDeviceOptions) -> Self {
+        Self::IoUring(options.into())
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum Device {
     DirectFile(DirectFileDevice),
     DirectFs(DirectFsDevice),
+    Memory(MemoryDevice),
+    #[cfg(any(test, feature = "fault_injection"))]
+    FaultInjected(Box<FaultInjected<Device>>),
+    Striped(StripedDevice),
+    #[cfg(all(feature = "io_uring", target_os = "linux"))]
+    IoUring(IoUringDevice),
 }
 
 impl Dev for Device {
@@ -116,6 +212,12 @@ impl Dev for Device {
         match self {
             Device::DirectFile(dev) => dev.capacity(),
             Device::DirectFs(dev) => dev.capacity(),
+            Device::Memory(dev) => dev.capacity(),
+            #[cfg(any(test, feature = "fault_injection"))]
+            Device::FaultInjected(dev) => dev.capacity(),
+            Device::Striped(dev) => dev.capacity(),
+            #[cfg(all(feature = "io_uring", target_os = "linux"))]
+            Device::IoUring(dev) => dev.capacity(),
         }
     }
 
@@ -123,6 +225,12 @@ impl Dev for Device {
         match self {
             Device::DirectFile(dev) => dev.region_size(),
             Device::DirectFs(dev) => dev.region_size(),
+            Device::Memory(dev) => dev.region_size(),
+            #[cfg(any(test, feature = "fault_injection"))]
+            Device::FaultInjected(dev) => dev.region_size(),
+            Device::Striped(dev) => dev.region_size(),
+            #[cfg(all(feature = "io_uring", target_os = "linux"))]
+            Device::IoUring(dev) => dev.region_size(),
         }
     }
 
@@ -130,6 +238,25 @@ impl Dev for Device {
         match options {
             DeviceConfig::DirectFile(opts) => Ok(Self::DirectFile(DirectFileDevice::open(opts, runtime).await?)),
             DeviceConfig::DirectFs(opts) => Ok(Self::DirectFs(DirectFsDevice::open(opts, runtime).await?)),
+            DeviceConfig::Memory(opts) => Ok(Self::Memory(MemoryDevice::open(opts, runtime).await?)),
+            #[cfg(any(test, feature = "fault_injection"))]
+            DeviceConfig::FaultInjected(opts) => Ok(Self::FaultInjected(Box::new(
+                FaultInjected::open_boxed(*opts, runtime).await?,
+            ))),
+            DeviceConfig::Striped(opts) => Ok(Self::Striped(StripedDevice::open(opts, runtime).await?)),
+            #[cfg(all(feature = "io_uring", target_os = "linux"))]
+            DeviceConfig::IoUring(opts) => {
+                if uring::is_supported() {
+                    Ok(Self::IoUring(IoUringDevice::open(opts, runtime).await?))
+                } else {
+                    tracing::warn!(
+                        "[device]: io_uring is not supported by the kernel, fall back to `DirectFileDevice`."
+                    );
+                    Ok(Self::DirectFile(
+                        DirectFileDevice::open(opts.fallback().into(), runtime).await?,
+                    ))
+                }
+            }
         }
     }
 
@@ -137,6 +264,12 @@ impl Dev for Device {
         match self {
             Device::DirectFile(dev) => dev.write(buf, region, offset).await,
             Device::DirectFs(dev) => dev.write(buf, region, offset).await,
+            Device::Memory(dev) => dev.write(buf, region, offset).await,
+            #[cfg(any(test, feature = "fault_injection"))]
+            Device::FaultInjected(dev) => dev.write_boxed(buf, region, offset).await,
+            Device::Striped(dev) => dev.write(buf, region, offset).await,
+            #[cfg(all(feature = "io_uring", target_os = "linux"))]
+            Device::IoUring(dev) => dev.write(buf, region, offset).await,
         }
     }
 
@@ -144,6 +277,12 @@ impl Dev for Device {
         match self {
             Device::DirectFile(dev) => dev.read(region, offset, len).await,
             Device::DirectFs(dev) => dev.read(region, offset, len).await,
+            Device::Memory(dev) => dev.read(region, offset, len).await,
+            #[cfg(any(test, feature = "fault_injection"))]
+            Device::FaultInjected(dev) => dev.read_boxed(region, offset, len).await,
+            Device::Striped(dev) => dev.read(region, offset, len).await,
+            #[cfg(all(feature = "io_uring", target_os = "linux"))]
+            Device::IoUring(dev) => dev.read(region, offset, len).await,
         }
     }
 
@@ -151,6 +290,36 @@ impl Dev for Device {
         match self {
             Device::DirectFile(dev) => dev.flush(region).await,
             Device::DirectFs(dev) => dev.flush(region).await,
+            Device::Memory(dev) => dev.flush(region).await,
+            #[cfg(any(test, feature = "fault_injection"))]
+            Device::FaultInjected(dev) => dev.flush_boxed(region).await,
+            Device::Striped(dev) => dev.flush(region).await,
+            #[cfg(all(feature = "io_uring", target_os = "linux"))]
+            Device::IoUring(dev) => dev.flush(region).await,
+        }
+    }
+}
+
+impl Device {
+    /// Check if the device can be resized online.
+    ///
+    /// Only [`DirectFileDevice`] on a regular file and [`DirectFsDevice`] can be resized.
+    pub(crate) fn resizable(&self) -> bool {
+        match self {
+            Device::DirectFile(dev) => dev.resizable(),
+            Device::DirectFs(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Resize the device to the given capacity.
+    ///
+    /// The caller must make sure the removed regions are no longer used on shrinking.
+    pub(crate) async fn resize(&self, capacity: usize) -> Result<()> {
+        match self {
+            Device::DirectFile(dev) => dev.resize(capacity).await,
+            Device::DirectFs(dev) => dev.resize(capacity).await,
+            _ => Err(anyhow::anyhow!("the device cannot be resized, only a direct file device on a regular file or a direct fs device can be resized").into()),
         }
     }
 }
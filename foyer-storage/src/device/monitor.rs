@@ -18,12 +18,16 @@ use std::{
         atomic::{AtomicUsize, Ordering},
         Arc,
     },
-    time::Instant,
+    time::{Duration, Instant},
 };
 
 use foyer_common::{bits, metrics::Metrics};
 
-use super::RegionId;
+use super::{
+    scheduler::{IoClass, IoPermit, IoScheduler, IoSchedulerConfig},
+    throttle::{IoPriority, Throttle, Throttler},
+    Device, RegionId,
+};
 use crate::{error::Result, Dev, DevExt, DirectFileDevice, IoBytes, IoBytesMut, Runtime};
 
 /// The statistics information of the device.
@@ -50,6 +54,8 @@ where
 {
     pub config: D::Config,
     pub metrics: Arc<Metrics>,
+    pub throttle: Throttle,
+    pub scheduler: IoSchedulerConfig,
 }
 
 impl<D> Debug for MonitoredConfig<D>
@@ -60,6 +66,8 @@ where
         f.debug_struct("MonitoredOptions")
             .field("options", &self.config)
             .field("metrics", &self.metrics)
+            .field("throttle", &self.throttle)
+            .field("scheduler", &self.scheduler)
             .finish()
     }
 }
@@ -72,6 +80,8 @@ where
     device: D,
     stats: Arc<DeviceStats>,
     metrics: Arc<Metrics>,
+    throttler: Arc<Throttler>,
+    scheduler: Arc<IoScheduler>,
 }
 
 impl<D> Monitored<D>
@@ -84,14 +94,82 @@ where
             device,
             stats: Arc::default(),
             metrics: options.metrics,
+            throttler: Arc::new(Throttler::new(&options.throttle)),
+            scheduler: Arc::new(IoScheduler::new(&options.scheduler)),
         })
     }
 
-    #[fastrace::trace(name = "foyer::storage::device::monitor::write")]
-    async fn write(&self, buf: IoBytes, region: RegionId, offset: u64) -> Result<()> {
-        let now = Instant::now();
+    /// Get the throttler of the device.
+    pub(crate) fn throttler(&self) -> &Arc<Throttler> {
+        &self.throttler
+    }
 
+    /// Throttle the i/o of the device with the given throttler, so it shares the budgets with other devices.
+    pub(crate) fn with_throttler(mut self, throttler: Arc<Throttler>) -> Self {
+        self.throttler = throttler;
+        self
+    }
+
+    async fn throttle_write(&self, bytes: usize) {
+        if !self.throttler.is_enabled() {
+            return;
+        }
+        let wait = self.throttler.write(bytes, IoPriority::Background).await;
+        if wait > Duration::ZERO {
+            self.metrics.storage_disk_write_throttle_duration.record(wait);
+        }
+    }
+
+    async fn throttle_read(&self, bytes: usize, priority: IoPriority) {
+        if !self.throttler.is_enabled() {
+            return;
+        }
+        let wait = self.throttler.read(bytes, priority).await;
+        if wait > Duration::ZERO {
+            self.metrics.storage_disk_read_throttle_duration.record(wait);
+        }
+    }
+
+    /// Write to the device as the i/o of the given class.
+    ///
+    /// The writes via [`Dev::write`] are not scheduled.
+    pub(crate) async fn write_with_class(
+        &self,
+        buf: IoBytes,
+        region: RegionId,
+        offset: u64,
+        class: IoClass,
+    ) -> Result<()> {
+        self.write(buf, region, offset, Some(class)).await
+    }
+
+    /// Read from the device as the i/o of the given class.
+    ///
+    /// The reads via [`Dev::read`] are not scheduled, and are throttled as the background i/o.
+    pub(crate) async fn read_with_class(
+        &self,
+        region: RegionId,
+        offset: u64,
+        len: usize,
+        class: IoClass,
+    ) -> Result<IoBytesMut> {
+        self.read(region, offset, len, Some(class)).await
+    }
+
+    async fn schedule(&self, class: Option<IoClass>) -> Option<IoPermit<'_>> {
+        match class {
+            Some(class) if self.scheduler.is_enabled() => Some(self.scheduler.acquire(class).await),
+            _ => None,
+        }
+    }
+
+    #[fastrace::trace(name = "foyer::storage::device::monitor::write")]
+    async fn write(&self, buf: IoBytes, region: RegionId, offset: u64, class: Option<IoClass>) -> Result<()> {
         let bytes = bits::align_up(self.align(), buf.len());
+        let _permit = self.schedule(class).await;
+        self.throttle_write(bytes).await;
+
+        let now = Instant::now();
         self.stats.write_ios.fetch_add(1, Ordering::Relaxed);
         self.stats.write_bytes.fetch_add(bytes, Ordering::Relaxed);
 
@@ -105,10 +183,13 @@ where
     }
 
     #[fastrace::trace(name = "foyer::storage::device::monitor::read")]
-    async fn read(&self, region: RegionId, offset: u64, len: usize) -> Result<IoBytesMut> {
-        let now = Instant::now();
-
+    async fn read(&self, region: RegionId, offset: u64, len: usize, class: Option<IoClass>) -> Result<IoBytesMut> {
         let bytes = bits::align_up(self.align(), len);
+        let _permit = self.schedule(class).await;
+        let priority = class.map(|class| class.priority()).unwrap_or(IoPriority::Background);
+        self.throttle_read(bytes, priority).await;
+
+        let now = Instant::now();
         self.stats.read_ios.fetch_add(1, Ordering::Relaxed);
         self.stats.read_bytes.fetch_add(bytes, Ordering::Relaxed);
 
@@ -155,11 +236,11 @@ where
     }
 
     async fn write(&self, buf: IoBytes, region: RegionId, offset: u64) -> Result<()> {
-        self.write(buf, region, offset).await
+        self.write(buf, region, offset, None).await
     }
 
     async fn read(&self, region: RegionId, offset: u64, len: usize) -> Result<IoBytesMut> {
-        self.read(region, offset, len).await
+        self.read(region, offset, len, None).await
     }
 
     async fn flush(&self, region: Option<RegionId>) -> Result<()> {
@@ -170,9 +251,10 @@ where
 impl Monitored<DirectFileDevice> {
     #[fastrace::trace(name = "foyer::storage::device::monitor::pwrite")]
     pub async fn pwrite(&self, buf: IoBytes, offset: u64) -> Result<()> {
-        let now = Instant::now();
-
         let bytes = bits::align_up(self.align(), buf.len());
+        self.throttle_write(bytes).await;
+
+        let now = Instant::now();
         self.stats.write_ios.fetch_add(1, Ordering::Relaxed);
         self.stats.write_bytes.fetch_add(bytes, Ordering::Relaxed);
 
@@ -187,9 +269,10 @@ impl Monitored<DirectFileDevice> {
 
     #[fastrace::trace(name = "foyer::storage::device::monitor::pread")]
     pub async fn pread(&self, offset: u64, len: usize) -> Result<IoBytesMut> {
-        let now = Instant::now();
-
         let bytes = bits::align_up(self.align(), len);
+        self.throttle_read(bytes, IoPriority::Background).await;
+
+        let now = Instant::now();
         self.stats.read_ios.fetch_add(1, Ordering::Relaxed);
         self.stats.read_bytes.fetch_add(bytes, Ordering::Relaxed);
 
@@ -203,6 +286,16 @@ impl Monitored<DirectFileDevice> {
     }
 }
 
+impl Monitored<Device> {
+    pub(crate) fn resizable(&self) -> bool {
+        self.device.resizable()
+    }
+
+    pub(crate) async fn resize(&self, capacity: usize) -> Result<()> {
+        self.device.resize(capacity).await
+    }
+}
+
 impl<D> Monitored<D>
 where
     D: Dev,
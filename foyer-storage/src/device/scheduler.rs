@@ -0,0 +1,301 @@
+//  Copyright 2024 foyer Project Authors
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::{
+    pin::pin,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use tokio::{sync::Notify, time::Instant};
+
+use super::throttle::IoPriority;
+
+/// The class of the device i/o, by the task that issues it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IoClass {
+    /// The reads of the lookups.
+    Foreground,
+    /// The writes of the flushers, and the reads and writes of the sets updated by the small object disk cache.
+    Flush,
+    /// The reads of the reclaimers scanning the regions, and the writes to clean the regions.
+    Reclaim,
+    /// The writes of the flushers that only carry the entries reinserted by the reclaimers.
+    Reinsertion,
+    /// The reads of the recovery on open.
+    Recovery,
+}
+
+impl IoClass {
+    const COUNT: usize = 5;
+
+    /// The priority of the i/o of the class under throttling.
+    pub fn priority(&self) -> IoPriority {
+        match self {
+            IoClass::Foreground => IoPriority::Foreground,
+            _ => IoPriority::Background,
+        }
+    }
+}
+
+/// The config of the i/o scheduler of the device.
+///
+/// The i/o scheduler puts the foreground reads, which serve the lookups, ahead of the background i/o. While any
+/// foreground read is queued or in flight, the in-flight i/o of each background class is limited to the depth of the
+/// class. The exceeding i/o waits until the foreground reads are done, the in-flight i/o of its class drops below the
+/// depth, or it has waited for the max wait. Otherwise, the background i/o is not limited.
+///
+/// The i/o of the classes without depth is never limited. No class has depth by default.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IoSchedulerConfig {
+    depths: [Option<usize>; IoClass::COUNT],
+    max_wait: Duration,
+}
+
+impl Default for IoSchedulerConfig {
+    fn default() -> Self {
+        Self {
+            depths: Default::default(),
+            max_wait: Self::DEFAULT_MAX_WAIT,
+        }
+    }
+}
+
+impl IoSchedulerConfig {
+    /// The default max wait of the background i/o.
+    pub const DEFAULT_MAX_WAIT: Duration = Duration::from_millis(100);
+
+    /// Create an i/o scheduler config without any depth.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the depth of the in-flight i/o of the background class while the foreground reads are queued.
+    ///
+    /// Depth `0` pauses the i/o of the class until the foreground reads are done or the max wait is reached.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the class is [`IoClass::Foreground`], the foreground reads are never limited.
+    pub fn with_depth(mut self, class: IoClass, depth: usize) -> Self {
+        assert_ne!(
+            class,
+            IoClass::Foreground,
+            "the foreground reads are never limited by the i/o scheduler"
+        );
+        self.depths[class as usize] = Some(depth);
+        self
+    }
+
+    /// Set the max time the background i/o waits for the foreground reads.
+    ///
+    /// The background i/o that has waited for the max wait is issued regardless of the depth of its class, so the
+    /// flushers and the reclaimers are not starved by the steady foreground reads.
+    ///
+    /// Default: 100 ms.
+    pub fn with_max_wait(mut self, max_wait: Duration) -> Self {
+        self.max_wait = max_wait;
+        self
+    }
+}
+
+/// Schedules the device i/o with the depths of [`IoSchedulerConfig`].
+#[derive(Debug)]
+pub struct IoScheduler {
+    depths: [Option<usize>; IoClass::COUNT],
+    max_wait: Duration,
+    inflights: [AtomicUsize; IoClass::COUNT],
+    /// Count of the queued and in-flight foreground reads.
+    foreground: AtomicUsize,
+    notify: Notify,
+}
+
+impl IoScheduler {
+    pub fn new(config: &IoSchedulerConfig) -> Self {
+        Self {
+            depths: config.depths,
+            max_wait: config.max_wait,
+            inflights: Default::default(),
+            foreground: AtomicUsize::new(0),
+            notify: Notify::new(),
+        }
+    }
+
+    /// Check if any class has depth.
+    pub fn is_enabled(&self) -> bool {
+        self.depths.iter().any(|depth| depth.is_some())
+    }
+
+    /// Wait until the i/o of the class can be issued.
+    ///
+    /// The i/o is tracked by the scheduler until the returned permit is dropped.
+    pub async fn acquire(&self, class: IoClass) -> IoPermit<'_> {
+        if !self.is_enabled() {
+            return IoPermit {
+                scheduler: self,
+                class: None,
+            };
+        }
+
+        if class == IoClass::Foreground {
+            self.foreground.fetch_add(1, Ordering::AcqRel);
+            return IoPermit {
+                scheduler: self,
+                class: Some(class),
+            };
+        }
+
+        let Some(depth) = self.depths[class as usize] else {
+            return IoPermit {
+                scheduler: self,
+                class: None,
+            };
+        };
+
+        let inflight = &self.inflights[class as usize];
+        let deadline = Instant::now() + self.max_wait;
+        loop {
+            // Register for the notification before checking, so the wakeups between the check and the wait are not
+            // missed.
+            let mut notified = pin!(self.notify.notified());
+            notified.as_mut().enable();
+
+            if self.foreground.load(Ordering::Acquire) == 0 {
+                inflight.fetch_add(1, Ordering::AcqRel);
+                break;
+            }
+            if inflight
+                .fetch_update(Ordering::AcqRel, Ordering::Acquire, |c| (c < depth).then_some(c + 1))
+                .is_ok()
+            {
+                break;
+            }
+
+            if tokio::time::timeout_at(deadline, notified).await.is_err() {
+                // Issue the i/o that has waited for the max wait regardless of the depth to bound the starvation.
+                inflight.fetch_add(1, Ordering::AcqRel);
+                break;
+            }
+        }
+
+        IoPermit {
+            scheduler: self,
+            class: Some(class),
+        }
+    }
+}
+
+/// The permit of the device i/o tracked by the [`IoScheduler`].
+#[derive(Debug)]
+pub struct IoPermit<'a> {
+    scheduler: &'a IoScheduler,
+    class: Option<IoClass>,
+}
+
+impl Drop for IoPermit<'_> {
+    fn drop(&mut self) {
+        let Some(class) = self.class else { return };
+        // Wake the waiting background i/o if the last foreground read is done or an in-flight slot is released.
+        let wake = match class {
+            IoClass::Foreground => self.scheduler.foreground.fetch_sub(1, Ordering::AcqRel) == 1,
+            class => {
+                self.scheduler.inflights[class as usize].fetch_sub(1, Ordering::AcqRel);
+                true
+            }
+        };
+        if wake {
+            self.scheduler.notify.notify_waiters();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use futures::FutureExt;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_io_scheduler() {
+        let scheduler = Arc::new(IoScheduler::new(
+            &IoSchedulerConfig::new()
+                .with_depth(IoClass::Flush, 1)
+                .with_depth(IoClass::Reclaim, 0),
+        ));
+
+        // The background i/o is not limited without foreground reads.
+        let f1 = scheduler.acquire(IoClass::Flush).now_or_never().unwrap();
+        let f2 = scheduler.acquire(IoClass::Flush).now_or_never().unwrap();
+        let r1 = scheduler.acquire(IoClass::Reclaim).now_or_never().unwrap();
+
+        let fg = scheduler.acquire(IoClass::Foreground).now_or_never().unwrap();
+        assert!(scheduler.acquire(IoClass::Flush).now_or_never().is_none());
+        assert!(scheduler.acquire(IoClass::Reclaim).now_or_never().is_none());
+        // The classes without depth are not limited.
+        drop(scheduler.acquire(IoClass::Recovery).now_or_never().unwrap());
+        drop(scheduler.acquire(IoClass::Foreground).now_or_never().unwrap());
+
+        // The in-flight flushes drop below the depth.
+        let s = scheduler.clone();
+        let flush = tokio::spawn(async move {
+            let _permit = s.acquire(IoClass::Flush).await;
+        });
+        drop(f1);
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(!flush.is_finished());
+        drop(f2);
+        tokio::time::timeout(Duration::from_secs(1), flush)
+            .await
+            .unwrap()
+            .unwrap();
+
+        // The foreground reads are done.
+        let s = scheduler.clone();
+        let reclaim = tokio::spawn(async move {
+            let _permit = s.acquire(IoClass::Reclaim).await;
+        });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(!reclaim.is_finished());
+        drop(fg);
+        tokio::time::timeout(Duration::from_secs(1), reclaim)
+            .await
+            .unwrap()
+            .unwrap();
+        drop(r1);
+
+        assert!(!IoScheduler::new(&IoSchedulerConfig::new()).is_enabled());
+    }
+
+    #[tokio::test]
+    async fn test_io_scheduler_max_wait() {
+        let scheduler = IoScheduler::new(
+            &IoSchedulerConfig::new()
+                .with_depth(IoClass::Flush, 0)
+                .with_max_wait(Duration::from_millis(50)),
+        );
+
+        // The flush is issued after the max wait even if the foreground reads never end.
+        let _fg = scheduler.acquire(IoClass::Foreground).await;
+        let now = std::time::Instant::now();
+        let permit = tokio::time::timeout(Duration::from_secs(1), scheduler.acquire(IoClass::Flush))
+            .await
+            .unwrap();
+        assert!(now.elapsed() >= Duration::from_millis(50));
+        assert_eq!(scheduler.inflights[IoClass::Flush as usize].load(Ordering::Acquire), 1);
+        drop(permit);
+        assert_eq!(scheduler.inflights[IoClass::Flush as usize].load(Ordering::Acquire), 0);
+    }
+}
@@ -0,0 +1,396 @@
+//  Copyright 2024 foyer Project Authors
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::{path::PathBuf, sync::Arc};
+
+use foyer_common::metrics::Metrics;
+use futures::future::{try_join_all, BoxFuture};
+use itertools::Itertools;
+
+use super::{
+    monitor::{DeviceStats, Monitored, MonitoredConfig},
+    scheduler::IoSchedulerConfig,
+    throttle::Throttle,
+    Dev, Device, DeviceConfig, RegionId,
+};
+use crate::{error::Result, IoBytes, IoBytesMut, Runtime};
+
+#[derive(Debug, Clone)]
+pub struct StripedDeviceConfig {
+    name: Option<String>,
+    devices: Vec<DeviceConfig>,
+}
+
+impl StripedDeviceConfig {
+    fn verify(&self) -> Result<()> {
+        if self.devices.is_empty() {
+            return Err(anyhow::anyhow!("striped device must have at least one child device").into());
+        }
+        Ok(())
+    }
+
+    /// The manifest of the disk cache is placed with the first child device that supports it.
+    pub(crate) fn manifest_path(&self) -> Option<PathBuf> {
+        self.devices.iter().find_map(|device| device.manifest_path())
+    }
+
+    /// The capacity and the region size of the device opened with the config.
+    ///
+    /// Only the regions that all child devices have are used.
+    pub(crate) fn layout(&self) -> (usize, usize) {
+        let layouts = self.devices.iter().map(|device| device.layout()).collect_vec();
+        let region_size = layouts.first().map(|(_, region_size)| *region_size).unwrap_or_default();
+        let regions = layouts
+            .iter()
+            .map(|(capacity, _)| capacity.checked_div(region_size).unwrap_or_default())
+            .min()
+            .unwrap_or_default();
+        (regions * layouts.len() * region_size, region_size)
+    }
+
+    /// Check if any child device already holds data.
+    pub(crate) fn occupied(&self) -> bool {
+        self.devices.iter().any(|device| device.occupied())
+    }
+
+    /// Set the name used by the metrics of the child devices if it is not set by the user.
+    pub(crate) fn set_default_name(&mut self, name: &str) {
+        self.name.get_or_insert_with(|| name.to_string());
+        for device in self.devices.iter_mut() {
+            if let DeviceConfig::Striped(config) = device {
+                config.set_default_name(name);
+            }
+        }
+    }
+}
+
+/// A device that stripes the regions across several child devices.
+///
+/// Region `i` of the striped device is region `i / N` of the child device `i % N`, where `N` is the count of the child
+/// devices. So the flushers and the reclaimers of the large object disk cache, which work on different regions, spread
+/// the load across all the child devices.
+///
+/// All the child devices must have the same region size. If the child devices have different region counts, only the
+/// minimal region count of each child device is used.
+///
+/// Each child device is monitored separately, its i/o is recorded in the metrics with the name `{name}-{index}`.
+#[derive(Debug, Clone)]
+pub struct StripedDevice {
+    inner: Arc<StripedDeviceInner>,
+}
+
+#[derive(Debug)]
+struct StripedDeviceInner {
+    devices: Vec<Monitored<Device>>,
+
+    capacity: usize,
+    region_size: usize,
+}
+
+impl StripedDevice {
+    /// Get the child devices.
+    pub fn devices(&self) -> &[Monitored<Device>] {
+        &self.inner.devices
+    }
+
+    /// Get the statistics of each child device.
+    pub fn stats(&self) -> Vec<Arc<DeviceStats>> {
+        self.inner.devices.iter().map(|device| device.stat().clone()).collect()
+    }
+
+    fn locate(&self, region: RegionId) -> (&Monitored<Device>, RegionId) {
+        let devices = &self.inner.devices;
+        let n = devices.len() as RegionId;
+        (&devices[(region % n) as usize], region / n)
+    }
+}
+
+// The child device may be a striped device itself, so the futures of the child devices are boxed to break the
+// recursive future types. The boxing must be done outside the async fns of the device.
+
+fn open_child(config: MonitoredConfig<Device>, runtime: Runtime) -> BoxFuture<'static, Result<Monitored<Device>>> {
+    Box::pin(Monitored::open(config, runtime))
+}
+
+fn write_child(device: &Monitored<Device>, buf: IoBytes, region: RegionId, offset: u64) -> BoxFuture<'_, Result<()>> {
+    Box::pin(device.write(buf, region, offset))
+}
+
+fn read_child(
+    device: &Monitored<Device>,
+    region: RegionId,
+    offset: u64,
+    len: usize,
+) -> BoxFuture<'_, Result<IoBytesMut>> {
+    Box::pin(device.read(region, offset, len))
+}
+
+fn flush_child(device: &Monitored<Device>, region: Option<RegionId>) -> BoxFuture<'_, Result<()>> {
+    Box::pin(device.flush(region))
+}
+
+impl Dev for StripedDevice {
+    type Config = StripedDeviceConfig;
+
+    fn capacity(&self) -> usize {
+        self.inner.capacity
+    }
+
+    fn region_size(&self) -> usize {
+        self.inner.region_size
+    }
+
+    #[fastrace::trace(name = "foyer::storage::device::striped::open")]
+    async fn open(options: Self::Config, runtime: Runtime) -> Result<Self> {
+        options.verify()?;
+
+        let name = options.name.as_deref().unwrap_or("striped");
+
+        let futures = options
+            .devices
+            .into_iter()
+            .enumerate()
+            .map(|(i, config)| {
+                let config = MonitoredConfig {
+                    config,
+                    metrics: Arc::new(Metrics::new(&format!("{name}-{i}"))),
+                    throttle: Throttle::default(),
+                    scheduler: IoSchedulerConfig::default(),
+                };
+                open_child(config, runtime.clone())
+            })
+            .collect_vec();
+        let devices = try_join_all(futures).await?;
+
+        let region_size = devices[0].region_size();
+        if let Some(device) = devices.iter().find(|device| device.region_size() != region_size) {
+            return Err(anyhow::anyhow!(
+                "all child devices of the striped device must have the same region size, expected: {region_size}, get: {}",
+                device.region_size()
+            )
+            .into());
+        }
+
+        let regions = devices
+            .iter()
+            .map(|device| device.capacity() / region_size)
+            .min()
+            .unwrap();
+        if devices.iter().any(|device| device.capacity() / region_size != regions) {
+            tracing::warn!(
+                "[striped device]: child devices have different region counts, only {regions} regions of each child device are used."
+            );
+        }
+        let capacity = regions * devices.len() * region_size;
+
+        Ok(Self {
+            inner: Arc::new(StripedDeviceInner {
+                devices,
+                capacity,
+                region_size,
+            }),
+        })
+    }
+
+    #[fastrace::trace(name = "foyer::storage::device::striped::write")]
+    async fn write(&self, buf: IoBytes, region: RegionId, offset: u64) -> Result<()> {
+        let (device, region) = self.locate(region);
+        write_child(device, buf, region, offset).await
+    }
+
+    #[fastrace::trace(name = "foyer::storage::device::striped::read")]
+    async fn read(&self, region: RegionId, offset: u64, len: usize) -> Result<IoBytesMut> {
+        let (device, region) = self.locate(region);
+        read_child(device, region, offset, len).await
+    }
+
+    #[fastrace::trace(name = "foyer::storage::device::striped::flush")]
+    async fn flush(&self, region: Option<RegionId>) -> Result<()> {
+        match region {
+            Some(region) => {
+                let (device, region) = self.locate(region);
+                flush_child(device, Some(region)).await
+            }
+            None => try_join_all(self.inner.devices.iter().map(|device| flush_child(device, None)))
+                .await
+                .map(|_| ()),
+        }
+    }
+}
+
+/// [`StripedDeviceOptions`] is used to build the options for the striped device.
+///
+/// The striped device stripes the regions across several child devices, e.g. the direct fs devices on different
+/// drives. See [`StripedDevice`] for details.
+#[derive(Debug, Default)]
+pub struct StripedDeviceOptions {
+    name: Option<String>,
+    devices: Vec<DeviceConfig>,
+}
+
+impl StripedDeviceOptions {
+    /// Create an empty striped device options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a child device with the given options.
+    ///
+    /// The region of the striped device is mapped to the child devices in the order they are added.
+    pub fn with_device(mut self, options: impl Into<DeviceConfig>) -> Self {
+        self.devices.push(options.into());
+        self
+    }
+
+    /// Set the name used by the metrics of the child devices, whose i/o is recorded with the name `{name}-{index}`.
+    ///
+    /// Default: The name of the disk cache.
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+}
+
+impl From<StripedDeviceOptions> for StripedDeviceConfig {
+    fn from(options: StripedDeviceOptions) -> Self {
+        Self {
+            name: options.name,
+            devices: options.devices,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::Ordering;
+
+    use itertools::repeat_n;
+
+    use super::*;
+    use crate::{DevExt, DirectFileDeviceOptions, DirectFsDeviceOptions};
+
+    const MB: usize = 1024 * 1024;
+
+    fn options(dir: &std::path::Path) -> StripedDeviceOptions {
+        StripedDeviceOptions::new()
+            .with_name("test")
+            .with_device(
+                DirectFsDeviceOptions::new(dir.join("0"))
+                    .with_capacity(4 * MB)
+                    .with_file_size(MB),
+            )
+            .with_device(
+                DirectFileDeviceOptions::new(dir.join("1"))
+                    .with_capacity(4 * MB)
+                    .with_region_size(MB),
+            )
+            .with_device(
+                DirectFsDeviceOptions::new(dir.join("2"))
+                    .with_capacity(5 * MB)
+                    .with_file_size(MB),
+            )
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn test_striped_device_io() {
+        let dir = tempfile::tempdir().unwrap();
+        let runtime = Runtime::current();
+
+        let config: StripedDeviceConfig = options(dir.path()).into();
+        let device = StripedDevice::open(config.clone(), runtime.clone()).await.unwrap();
+
+        assert_eq!(device.region_size(), MB);
+        assert_eq!(device.regions(), 12);
+        assert_eq!(device.capacity(), 12 * MB);
+
+        let bufs = (0..12u8)
+            .map(|i| {
+                let mut buf = IoBytesMut::with_capacity(64 * 1024);
+                buf.extend(repeat_n(i, 64 * 1024 - 100));
+                buf.freeze()
+            })
+            .collect_vec();
+        for (region, buf) in bufs.iter().enumerate() {
+            device.write(buf.clone(), region as RegionId, 4096).await.unwrap();
+        }
+        device.flush(None).await.unwrap();
+
+        for stats in device.stats() {
+            assert_eq!(stats.write_ios.load(Ordering::Relaxed), 4);
+            assert_eq!(stats.flush_ios.load(Ordering::Relaxed), 1);
+        }
+
+        // Region 4 is region 1 of the second child device.
+        let b = device.devices()[1]
+            .read(1, 4096, 64 * 1024 - 100)
+            .await
+            .unwrap()
+            .freeze();
+        assert_eq!(bufs[4], b);
+
+        drop(device);
+
+        let device = StripedDevice::open(config, runtime).await.unwrap();
+        for (region, buf) in bufs.iter().enumerate() {
+            let b = device
+                .read(region as RegionId, 4096, 64 * 1024 - 100)
+                .await
+                .unwrap()
+                .freeze();
+            assert_eq!(buf, &b);
+        }
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn test_striped_device_nested() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let config: DeviceConfig = StripedDeviceOptions::new()
+            .with_device(options(&dir.path().join("a")))
+            .with_device(
+                DirectFsDeviceOptions::new(dir.path().join("b"))
+                    .with_capacity(4 * MB)
+                    .with_file_size(MB),
+            )
+            .into();
+
+        let device = Device::open(config, Runtime::current()).await.unwrap();
+        assert_eq!(device.regions(), 8);
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn test_striped_device_region_size_mismatch() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let config: StripedDeviceConfig = StripedDeviceOptions::new()
+            .with_device(
+                DirectFsDeviceOptions::new(dir.path().join("0"))
+                    .with_capacity(4 * MB)
+                    .with_file_size(MB),
+            )
+            .with_device(
+                DirectFsDeviceOptions::new(dir.path().join("1"))
+                    .with_capacity(4 * MB)
+                    .with_file_size(2 * MB),
+            )
+            .into();
+
+        assert!(StripedDevice::open(config, Runtime::current()).await.is_err());
+        assert!(
+            StripedDevice::open(StripedDeviceOptions::new().into(), Runtime::current())
+                .await
+                .is_err()
+        );
+    }
+}
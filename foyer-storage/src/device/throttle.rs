@@ -0,0 +1,159 @@
+//  Copyright 2024 foyer Project Authors
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::{num::NonZeroUsize, time::Duration};
+
+use foyer_common::rate::RateLimiter;
+
+/// The i/o budgets of the device.
+///
+/// Each budget is a token bucket that refills at the given rate and holds at most one second of the rate, so the disk
+/// traffic is bounded to the rate with a burst of one second. All the i/o on the cache device is throttled, including
+/// the flushes, reclamations, reinsertions and recoveries. The child devices of a striped device and the tombstone log
+/// share the budgets.
+///
+/// The foreground reads, which serve the lookups, are throttled last. They may overdraw the budgets by up to
+/// [`Throttle::FOREGROUND_OVERDRAFT`] of the rates, while the background i/o waits until the budgets are refilled.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Throttle {
+    read_throughput: Option<NonZeroUsize>,
+    write_throughput: Option<NonZeroUsize>,
+    iops: Option<NonZeroUsize>,
+}
+
+impl Throttle {
+    /// The budgets the foreground reads can overdraw, in the time to refill them.
+    pub const FOREGROUND_OVERDRAFT: Duration = Duration::from_secs(1);
+
+    /// Create a throttle without any budget.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the read throughput budget in bytes per second.
+    ///
+    /// Default: unlimited.
+    pub fn with_read_throughput(mut self, read_throughput: usize) -> Self {
+        self.read_throughput = NonZeroUsize::new(read_throughput);
+        self
+    }
+
+    /// Set the write throughput budget in bytes per second.
+    ///
+    /// Default: unlimited.
+    pub fn with_write_throughput(mut self, write_throughput: usize) -> Self {
+        self.write_throughput = NonZeroUsize::new(write_throughput);
+        self
+    }
+
+    /// Set the budget of the read and write operations per second.
+    ///
+    /// Default: unlimited.
+    pub fn with_iops(mut self, iops: usize) -> Self {
+        self.iops = NonZeroUsize::new(iops);
+        self
+    }
+}
+
+/// The priority of the device i/o under throttling.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IoPriority {
+    /// The i/o on the critical path of the queries, e.g. the reads of the lookups.
+    Foreground,
+    /// The i/o of the background tasks, e.g. the flushes, reclamations and recoveries.
+    Background,
+}
+
+/// Throttles the device i/o with the budgets of [`Throttle`].
+#[derive(Debug)]
+pub struct Throttler {
+    read: Option<RateLimiter>,
+    write: Option<RateLimiter>,
+    iops: Option<RateLimiter>,
+}
+
+impl Throttler {
+    pub fn new(throttle: &Throttle) -> Self {
+        let limiter = |rate: Option<NonZeroUsize>| rate.map(|rate| RateLimiter::new(rate.get() as f64));
+        Self {
+            read: limiter(throttle.read_throughput),
+            write: limiter(throttle.write_throughput),
+            iops: limiter(throttle.iops),
+        }
+    }
+
+    /// Check if any budget is set.
+    pub fn is_enabled(&self) -> bool {
+        self.read.is_some() || self.write.is_some() || self.iops.is_some()
+    }
+
+    /// Wait for the budgets to read `bytes`, returns the duration throttled.
+    pub async fn read(&self, bytes: usize, priority: IoPriority) -> Duration {
+        self.throttle(self.read.as_ref(), bytes, priority).await
+    }
+
+    /// Wait for the budgets to write `bytes`, returns the duration throttled.
+    pub async fn write(&self, bytes: usize, priority: IoPriority) -> Duration {
+        self.throttle(self.write.as_ref(), bytes, priority).await
+    }
+
+    async fn throttle(&self, throughput: Option<&RateLimiter>, bytes: usize, priority: IoPriority) -> Duration {
+        let wait = [
+            throughput.and_then(|limiter| limiter.consume(bytes as f64)),
+            self.iops.as_ref().and_then(|limiter| limiter.consume(1.0)),
+        ]
+        .into_iter()
+        .flatten()
+        .max()
+        .unwrap_or_default();
+
+        let wait = match priority {
+            IoPriority::Foreground => wait.saturating_sub(Throttle::FOREGROUND_OVERDRAFT),
+            IoPriority::Background => wait,
+        };
+
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+        wait
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MS: Duration = Duration::from_millis(1);
+
+    #[tokio::test]
+    async fn test_throttler() {
+        assert!(!Throttler::new(&Throttle::new()).is_enabled());
+
+        let throttler = Throttler::new(&Throttle::new().with_write_throughput(10_000).with_iops(100));
+        assert!(throttler.is_enabled());
+
+        // The buckets start empty.
+        let wait = throttler.write(1_000, IoPriority::Background).await;
+        assert!(wait > 90 * MS && wait <= 100 * MS, "wait: {wait:?}");
+
+        // The reads are not limited by the write throughput, and the i/o count budget is refilled.
+        assert_eq!(throttler.read(1 << 20, IoPriority::Background).await, Duration::ZERO);
+
+        // The foreground reads overdraw the budgets before being throttled.
+        let throttler = Throttler::new(&Throttle::new().with_read_throughput(100_000));
+        assert_eq!(throttler.read(100_000, IoPriority::Foreground).await, Duration::ZERO);
+        let wait = throttler.read(5_000, IoPriority::Foreground).await;
+        assert!(wait > 40 * MS && wait <= 50 * MS, "wait: {wait:?}");
+    }
+}
@@ -0,0 +1,1008 @@
+//  Copyright 2024 foyer Project Authors
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::{
+    collections::VecDeque,
+    fmt::Debug,
+    fs::{create_dir_all, File, OpenOptions},
+    os::fd::{AsRawFd, FromRawFd, OwnedFd},
+    path::{Path, PathBuf},
+    sync::{Arc, OnceLock},
+};
+
+use foyer_common::bits;
+use fs4::free_space;
+use io_uring::{opcode, squeue, types, EnterFlags, IoUring, Probe};
+use serde::{Deserialize, Serialize};
+use tokio::sync::oneshot;
+
+use super::{Dev, DevExt, RegionId};
+use crate::{device::ALIGN, error::Result, DirectFileDeviceOptions, IoBytes, IoBytesMut, Runtime};
+
+/// Return `true` if the running kernel supports the io_uring operations used by [`IoUringDevice`].
+///
+/// The result is probed once and cached.
+pub fn is_supported() -> bool {
+    static SUPPORTED: OnceLock<bool> = OnceLock::new();
+
+    *SUPPORTED.get_or_init(|| {
+        let probe = || -> std::io::Result<bool> {
+            let ring = IoUring::new(2)?;
+            let mut probe = Probe::new();
+            ring.submitter().register_probe(&mut probe)?;
+            Ok(probe.is_supported(opcode::Read::CODE)
+                && probe.is_supported(opcode::Write::CODE)
+                && probe.is_supported(opcode::Fsync::CODE))
+        };
+        match probe() {
+            Ok(supported) => supported,
+            Err(e) => {
+                tracing::debug!("[io_uring]: probe io_uring failed: {e}");
+                false
+            }
+        }
+    })
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IoUringDeviceConfig {
+    path: PathBuf,
+    capacity: usize,
+    region_size: usize,
+    queue_depth: usize,
+}
+
+impl IoUringDeviceConfig {
+    /// The manifest of the disk cache is placed beside the file of the device.
+    pub(crate) fn manifest_path(&self) -> PathBuf {
+        let mut path = self.path.clone();
+        path.as_mut_os_string().push(".manifest");
+        path
+    }
+
+    /// The capacity and the region size of the device opened with the config.
+    pub(crate) fn layout(&self) -> (usize, usize) {
+        (self.capacity, self.region_size)
+    }
+
+    /// Check if the file of the device already holds data.
+    ///
+    /// A raw block device is never considered occupied, for it cannot be told apart from a fresh one.
+    pub(crate) fn occupied(&self) -> bool {
+        std::fs::metadata(&self.path).is_ok_and(|metadata| metadata.is_file() && metadata.len() > 0)
+    }
+
+    fn verify(&self) -> Result<()> {
+        if self.region_size == 0 || self.region_size % ALIGN != 0 {
+            return Err(anyhow::anyhow!(
+                "region size ({region_size}) must be a multiplier of ALIGN ({ALIGN})",
+                region_size = self.region_size,
+            )
+            .into());
+        }
+
+        if self.capacity == 0 || self.capacity % self.region_size != 0 {
+            return Err(anyhow::anyhow!(
+                "capacity ({capacity}) must be a multiplier of region size ({region_size})",
+                capacity = self.capacity,
+                region_size = self.region_size,
+            )
+            .into());
+        }
+
+        if self.queue_depth == 0 || self.queue_depth > u32::MAX as usize {
+            return Err(anyhow::anyhow!(
+                "queue depth ({queue_depth}) must be positive and fit in u32",
+                queue_depth = self.queue_depth,
+            )
+            .into());
+        }
+
+        Ok(())
+    }
+
+    /// Convert the config into the options of the equivalent direct file device, used as the fallback if io_uring is
+    /// not supported.
+    pub(crate) fn fallback(&self) -> DirectFileDeviceOptions {
+        DirectFileDeviceOptions::new(&self.path)
+            .with_capacity(self.capacity)
+            .with_region_size(self.region_size)
+    }
+}
+
+enum IoRequest {
+    Write {
+        buf: IoBytes,
+        offset: u64,
+        done: usize,
+        tx: oneshot::Sender<Result<()>>,
+    },
+    Read {
+        buf: IoBytesMut,
+        offset: u64,
+        done: usize,
+        tx: oneshot::Sender<Result<IoBytesMut>>,
+    },
+    Fsync {
+        tx: oneshot::Sender<Result<()>>,
+    },
+}
+
+impl Debug for IoRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Write { offset, done, .. } => f
+                .debug_struct("Write")
+                .field("offset", offset)
+                .field("done", done)
+                .finish(),
+            Self::Read { offset, done, .. } => f
+                .debug_struct("Read")
+                .field("offset", offset)
+                .field("done", done)
+                .finish(),
+            Self::Fsync { .. } => f.debug_struct("Fsync").finish(),
+        }
+    }
+}
+
+impl IoRequest {
+    /// Build the submission queue entry of the remainder of the request.
+    ///
+    /// The fsync entry is drained, so it starts after all entries submitted before it are completed.
+    ///
+    /// The buffer of the request must be kept alive and untouched until the request is completed.
+    fn entry(&mut self, fd: types::Fd) -> squeue::Entry {
+        match self {
+            IoRequest::Write { buf, offset, done, .. } => {
+                let buf = &buf.as_aligned()[*done..];
+                opcode::Write::new(fd, buf.as_ptr(), buf.len() as _)
+                    .offset(*offset + *done as u64)
+                    .build()
+            }
+            IoRequest::Read { buf, offset, done, .. } => {
+                let buf = &mut buf[*done..];
+                opcode::Read::new(fd, buf.as_mut_ptr(), buf.len() as _)
+                    .offset(*offset + *done as u64)
+                    .build()
+            }
+            IoRequest::Fsync { .. } => opcode::Fsync::new(fd).build().flags(squeue::Flags::IO_DRAIN),
+        }
+    }
+
+    /// Advance the request with the result of a completion.
+    ///
+    /// Return `true` if the request is fully transferred, or `false` if the remainder needs to be resubmitted.
+    fn advance(&mut self, res: i32) -> Result<bool> {
+        if res < 0 {
+            return Err(std::io::Error::from_raw_os_error(-res).into());
+        }
+
+        let (len, done) = match self {
+            IoRequest::Write { buf, done, .. } => (buf.as_aligned().len(), done),
+            IoRequest::Read { buf, done, .. } => (buf.len(), done),
+            IoRequest::Fsync { .. } => return Ok(true),
+        };
+
+        let remaining = len - *done;
+        if res == 0 && remaining > 0 {
+            return Err(anyhow::anyhow!("transferred nothing, remaining: {remaining}").into());
+        }
+        if res as usize > remaining {
+            return Err(anyhow::anyhow!("transferred {res}, remaining: {remaining}").into());
+        }
+        *done += res as usize;
+        Ok(*done == len)
+    }
+
+    fn complete(self, result: Result<()>) {
+        match self {
+            IoRequest::Write { tx, .. } | IoRequest::Fsync { tx } => {
+                let _ = tx.send(result);
+            }
+            IoRequest::Read { buf, tx, .. } => {
+                let _ = tx.send(result.map(|_| buf));
+            }
+        }
+    }
+
+    /// Fail the request and leak its buffer, for the kernel may still reference it.
+    fn leak(self, err: std::io::Error) {
+        match self {
+            IoRequest::Write { buf, tx, .. } => {
+                std::mem::forget(buf);
+                let _ = tx.send(Err(err.into()));
+            }
+            IoRequest::Read { buf, tx, .. } => {
+                std::mem::forget(buf);
+                let _ = tx.send(Err(err.into()));
+            }
+            IoRequest::Fsync { tx } => {
+                let _ = tx.send(Err(err.into()));
+            }
+        }
+    }
+}
+
+/// An eventfd that wakes up the submitter when a new request is sent.
+///
+/// The submitter keeps a read of the eventfd in the io_uring while any request is in flight, so that it does not wait
+/// for the completion of a slow request (e.g. a large write or a fsync) before submitting the newly sent ones.
+#[derive(Debug)]
+struct Waker(OwnedFd);
+
+impl Waker {
+    fn new() -> std::io::Result<Self> {
+        // SAFETY: The returned fd is checked and owned by the waker.
+        let fd = unsafe { libc::eventfd(0, libc::EFD_CLOEXEC) };
+        if fd < 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        Ok(Self(unsafe { OwnedFd::from_raw_fd(fd) }))
+    }
+
+    fn wake(&self) {
+        let buf = 1u64.to_ne_bytes();
+        // SAFETY: The buffer is valid for 8 bytes. The counter of the eventfd never overflows in practice.
+        let res = unsafe { libc::write(self.0.as_raw_fd(), buf.as_ptr() as *const _, buf.len()) };
+        if res < 0 {
+            tracing::warn!(
+                "[io_uring]: wake up submitter failed: {}",
+                std::io::Error::last_os_error()
+            );
+        }
+    }
+}
+
+/// A request held by a slot of the submitter until it is completed.
+#[derive(Debug)]
+struct Inflight {
+    request: IoRequest,
+    /// The order in which the request is received.
+    seq: u64,
+}
+
+/// The submitter thread that owns the ring.
+///
+/// It keeps at most `queue_depth` requests in flight, and exits after all device handles are dropped and all in-flight
+/// requests are completed.
+///
+/// A short read or write is resubmitted with its remainder. A fsync is drained after the entries submitted before it,
+/// and is resubmitted if the remainder of an earlier write is resubmitted after it.
+///
+/// While any request is in flight, the submitter waits for either a completion or the [`Waker`].
+struct Submitter {
+    ring: IoUring,
+    file: Arc<File>,
+    rx: flume::Receiver<IoRequest>,
+    slots: Vec<Option<Inflight>>,
+    free: Vec<usize>,
+    /// The slots whose entries are pushed to the submission queue but not consumed by the kernel yet, in order.
+    queued: VecDeque<usize>,
+    seq: u64,
+
+    waker: Arc<Waker>,
+    /// The buffer of the read of the waker, which must outlive the ring.
+    waker_buf: Box<[u8; 8]>,
+    waker_armed: bool,
+    /// Set if reading the waker fails, then the submitter only wakes up on completions.
+    waker_broken: bool,
+}
+
+impl Submitter {
+    /// The user data of the read of the waker, which never collides with a slot.
+    const WAKER: usize = usize::MAX;
+
+    fn new(file: Arc<File>, queue_depth: usize, rx: flume::Receiver<IoRequest>, waker: Arc<Waker>) -> Result<Self> {
+        // Reserve an entry for the read of the waker.
+        let ring = IoUring::new(queue_depth as u32 + 1)?;
+        Ok(Self {
+            ring,
+            file,
+            rx,
+            slots: (0..queue_depth).map(|_| None).collect(),
+            free: (0..queue_depth).rev().collect(),
+            queued: VecDeque::with_capacity(queue_depth + 1),
+            seq: 0,
+            waker,
+            waker_buf: Box::new([0; 8]),
+            waker_armed: false,
+            waker_broken: false,
+        })
+    }
+
+    fn run(mut self) {
+        let fd = types::Fd(self.file.as_raw_fd());
+        let mut disconnected = false;
+
+        loop {
+            // Fill the submission queue with the pending requests.
+            while !self.free.is_empty() {
+                let inflight = self.free.len() < self.slots.len();
+                let request = if !inflight {
+                    if disconnected {
+                        return;
+                    }
+                    match self.rx.recv() {
+                        Ok(request) => request,
+                        Err(_) => return,
+                    }
+                } else if disconnected {
+                    break;
+                } else {
+                    match self.rx.try_recv() {
+                        Ok(request) => request,
+                        Err(flume::TryRecvError::Empty) => break,
+                        Err(flume::TryRecvError::Disconnected) => {
+                            disconnected = true;
+                            break;
+                        }
+                    }
+                };
+
+                let slot = self.free.pop().unwrap();
+                self.seq += 1;
+                self.slots[slot] = Some(Inflight { request, seq: self.seq });
+                self.push(fd, slot);
+            }
+
+            if !disconnected && !self.waker_armed && !self.waker_broken {
+                self.arm_waker();
+            }
+
+            match self.ring.submit_and_wait(1) {
+                Ok(submitted) => {
+                    self.queued.drain(..submitted.min(self.queued.len()));
+                }
+                Err(e) => match e.raw_os_error() {
+                    Some(libc::EINTR) | Some(libc::EAGAIN) | Some(libc::EBUSY) => {}
+                    _ => return self.abort(e),
+                },
+            }
+
+            for (slot, res) in self.completions() {
+                if slot == Self::WAKER {
+                    self.waker_armed = false;
+                    if res < 0 {
+                        tracing::warn!(
+                            "[io_uring]: read waker failed, only wake up on completions: {}",
+                            std::io::Error::from_raw_os_error(-res)
+                        );
+                        self.waker_broken = true;
+                    }
+                    continue;
+                }
+                self.complete(fd, slot, res);
+            }
+        }
+    }
+
+    fn arm_waker(&mut self) {
+        let fd = types::Fd(self.waker.0.as_raw_fd());
+        let entry = opcode::Read::new(fd, self.waker_buf.as_mut_ptr(), self.waker_buf.len() as _)
+            .build()
+            .user_data(Self::WAKER as u64);
+        // SAFETY: The buffer of the read is held by the submitter, and outlives the ring.
+        unsafe {
+            self.ring
+                .submission()
+                .push(&entry)
+                .expect("the submission queue must be sized to the queue depth and the waker");
+        }
+        self.queued.push_back(Self::WAKER);
+        self.waker_armed = true;
+    }
+
+    fn push(&mut self, fd: types::Fd, slot: usize) {
+        let inflight = self.slots[slot].as_mut().unwrap();
+        let entry = inflight.request.entry(fd).user_data(slot as u64);
+        // SAFETY: The buffer of the request is held by the slot until the request is completed.
+        unsafe {
+            self.ring
+                .submission()
+                .push(&entry)
+                .expect("the submission queue must be sized to the queue depth");
+        }
+        self.queued.push_back(slot);
+    }
+
+    fn completions(&mut self) -> Vec<(usize, i32)> {
+        self.ring
+            .completion()
+            .map(|cqe| (cqe.user_data() as usize, cqe.result()))
+            .collect()
+    }
+
+    fn complete(&mut self, fd: types::Fd, slot: usize, res: i32) {
+        let inflight = self.slots[slot].as_mut().unwrap();
+        let seq = inflight.seq;
+        let is_fsync = matches!(inflight.request, IoRequest::Fsync { .. });
+        let result = inflight.request.advance(res);
+
+        let resubmit = match result {
+            Ok(done) => {
+                !done
+                    || (is_fsync
+                        && self
+                            .slots
+                            .iter()
+                            .flatten()
+                            .any(|other| other.seq < seq && matches!(other.request, IoRequest::Write { .. })))
+            }
+            Err(_) => false,
+        };
+        if resubmit {
+            self.push(fd, slot);
+            return;
+        }
+
+        let inflight = self.slots[slot].take().unwrap();
+        self.free.push(slot);
+        inflight.request.complete(result.map(|_| ()));
+    }
+
+    /// Tear down the submitter after submitting to the io_uring failed.
+    ///
+    /// The requests not consumed by the kernel are failed at once. The requests in flight are failed after they are
+    /// completed, for the kernel still references their buffers.
+    fn abort(mut self, e: std::io::Error) {
+        tracing::error!("[io_uring]: submit to io_uring failed: {e}");
+        let err = || std::io::Error::new(e.kind(), format!("submit to io_uring failed: {e}"));
+
+        for slot in std::mem::take(&mut self.queued) {
+            if slot == Self::WAKER {
+                self.waker_armed = false;
+                continue;
+            }
+            let inflight = self.slots[slot].take().unwrap();
+            self.free.push(slot);
+            inflight.request.complete(Err(err().into()));
+        }
+
+        while self.free.len() < self.slots.len() {
+            // SAFETY: Only wait for the completions, the entries left in the submission queue are never submitted.
+            let res = unsafe {
+                self.ring
+                    .submitter()
+                    .enter::<libc::sigset_t>(0, 1, EnterFlags::GETEVENTS.bits(), None)
+            };
+            if let Err(e) = res {
+                if !matches!(
+                    e.raw_os_error(),
+                    Some(libc::EINTR) | Some(libc::EAGAIN) | Some(libc::EBUSY)
+                ) {
+                    tracing::error!("[io_uring]: wait for io_uring failed, leak in-flight buffers: {e}");
+                    for inflight in self.slots.iter_mut().filter_map(|slot| slot.take()) {
+                        inflight.request.leak(err());
+                    }
+                    break;
+                }
+            }
+
+            for (slot, res) in self.completions() {
+                if slot == Self::WAKER {
+                    continue;
+                }
+                let mut inflight = self.slots[slot].take().unwrap();
+                self.free.push(slot);
+                let result = match inflight.request.advance(res) {
+                    Ok(true) => Ok(()),
+                    Ok(false) => Err(err().into()),
+                    Err(e) => Err(e),
+                };
+                inflight.request.complete(result);
+            }
+        }
+
+        for request in self.rx.drain() {
+            request.complete(Err(err().into()));
+        }
+    }
+}
+
+/// A device that uses a single direct i/o file, and submits the i/o requests with io_uring.
+///
+/// Different from [`DirectFileDevice`](crate::DirectFileDevice), which pushes each positioned read or write to a
+/// blocking thread, [`IoUringDevice`] submits the requests to an io_uring owned by a dedicated submitter thread, and
+/// keeps at most the configured queue depth of requests in flight.
+#[derive(Debug, Clone)]
+pub struct IoUringDevice {
+    inner: Arc<IoUringDeviceInner>,
+}
+
+#[derive(Debug)]
+struct IoUringDeviceInner {
+    tx: flume::Sender<IoRequest>,
+    waker: Arc<Waker>,
+
+    capacity: usize,
+    region_size: usize,
+    queue_depth: usize,
+}
+
+impl IoUringDevice {
+    /// Get the queue depth of the io_uring.
+    pub fn queue_depth(&self) -> usize {
+        self.inner.queue_depth
+    }
+
+    async fn submit<T>(&self, request: IoRequest, rx: oneshot::Receiver<Result<T>>) -> Result<T> {
+        self.inner
+            .tx
+            .send(request)
+            .map_err(|_| anyhow::anyhow!("io_uring submitter has exited"))?;
+        self.inner.waker.wake();
+        rx.await
+            .map_err(|_| anyhow::anyhow!("io_uring submitter has exited before the request completes"))?
+    }
+
+    /// Positioned write API for the io_uring device.
+    #[fastrace::trace(name = "foyer::storage::device::uring::pwrite")]
+    pub async fn pwrite(&self, buf: IoBytes, offset: u64) -> Result<()> {
+        bits::assert_aligned(self.align() as u64, offset);
+
+        let aligned = buf.as_aligned().len();
+
+        assert!(
+            offset as usize + aligned <= self.capacity(),
+            "offset ({offset}) + aligned ({aligned}) = total ({total}) <= capacity ({capacity})",
+            total = offset as usize + aligned,
+            capacity = self.inner.capacity,
+        );
+
+        let (tx, rx) = oneshot::channel();
+        self.submit(
+            IoRequest::Write {
+                buf,
+                offset,
+                done: 0,
+                tx,
+            },
+            rx,
+        )
+        .await
+    }
+
+    /// Positioned read API for the io_uring device.
+    #[fastrace::trace(name = "foyer::storage::device::uring::pread")]
+    pub async fn pread(&self, offset: u64, len: usize) -> Result<IoBytesMut> {
+        bits::assert_aligned(self.align() as u64, offset);
+
+        let aligned = bits::align_up(self.align(), len);
+
+        assert!(
+            offset as usize + aligned <= self.capacity(),
+            "offset ({offset}) + aligned ({aligned}) = total ({total}) <= capacity ({capacity})",
+            total = offset as usize + aligned,
+            capacity = self.inner.capacity,
+        );
+
+        let mut buf = IoBytesMut::with_capacity(aligned);
+        unsafe {
+            buf.set_len(aligned);
+        }
+
+        let (tx, rx) = oneshot::channel();
+        let mut buffer = self
+            .submit(
+                IoRequest::Read {
+                    buf,
+                    offset,
+                    done: 0,
+                    tx,
+                },
+                rx,
+            )
+            .await?;
+
+        buffer.truncate(len);
+
+        Ok(buffer)
+    }
+}
+
+impl Dev for IoUringDevice {
+    type Config = IoUringDeviceConfig;
+
+    fn capacity(&self) -> usize {
+        self.inner.capacity
+    }
+
+    fn region_size(&self) -> usize {
+        self.inner.region_size
+    }
+
+    #[fastrace::trace(name = "foyer::storage::device::uring::open")]
+    async fn open(options: Self::Config, _: Runtime) -> Result<Self> {
+        options.verify()?;
+
+        let dir = options
+            .path
+            .parent()
+            .expect("path must not be the root directory")
+            .to_path_buf();
+        if !dir.exists() {
+            create_dir_all(dir)?;
+        }
+
+        let mut opts = OpenOptions::new();
+
+        opts.create(true).write(true).read(true);
+
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            opts.custom_flags(libc::O_DIRECT | libc::O_NOATIME);
+        }
+
+        let file = opts.open(&options.path)?;
+
+        if file.metadata().unwrap().is_file() {
+            file.set_len(options.capacity as _)?;
+        }
+
+        let file = Arc::new(file);
+
+        let (tx, rx) = flume::unbounded();
+        let waker = Arc::new(Waker::new()?);
+        let submitter = Submitter::new(file, options.queue_depth, rx, waker.clone())?;
+        std::thread::Builder::new()
+            .name("foyer-io-uring".to_string())
+            .spawn(move || submitter.run())?;
+
+        let inner = IoUringDeviceInner {
+            tx,
+            waker,
+            capacity: options.capacity,
+            region_size: options.region_size,
+            queue_depth: options.queue_depth,
+        };
+
+        Ok(Self { inner: Arc::new(inner) })
+    }
+
+    #[fastrace::trace(name = "foyer::storage::device::uring::write")]
+    async fn write(&self, buf: IoBytes, region: RegionId, offset: u64) -> Result<()> {
+        let aligned = buf.as_aligned().len();
+
+        assert!(
+            offset as usize + aligned <= self.region_size(),
+            "offset ({offset}) + aligned ({aligned}) = total ({total}) <= region size ({region_size})",
+            total = offset as usize + aligned,
+            region_size = self.region_size(),
+        );
+
+        let poffset = offset + region as u64 * self.inner.region_size as u64;
+        self.pwrite(buf, poffset).await
+    }
+
+    #[fastrace::trace(name = "foyer::storage::device::uring::read")]
+    async fn read(&self, region: RegionId, offset: u64, len: usize) -> Result<IoBytesMut> {
+        bits::assert_aligned(self.align() as u64, offset);
+
+        let aligned = bits::align_up(self.align(), len);
+
+        assert!(
+            offset as usize + aligned <= self.region_size(),
+            "offset ({offset}) + aligned ({aligned}) = total ({total}) <= region size ({region_size})",
+            total = offset as usize + aligned,
+            region_size = self.region_size(),
+        );
+
+        let poffset = offset + region as u64 * self.inner.region_size as u64;
+        self.pread(poffset, len).await
+    }
+
+    #[fastrace::trace(name = "foyer::storage::device::uring::flush")]
+    async fn flush(&self, _: Option<RegionId>) -> Result<()> {
+        let (tx, rx) = oneshot::channel();
+        self.submit(IoRequest::Fsync { tx }, rx).await
+    }
+}
+
+/// [`IoUringDeviceOptions`] is used to build the options for the io_uring device.
+///
+/// The io_uring device uses a single direct i/o file, which is either a raw block device or a file in a file system,
+/// and submits the i/o requests with io_uring.
+///
+/// If the running kernel does not support io_uring, the device falls back to the
+/// [`DirectFileDevice`](crate::DirectFileDevice) with the same path, capacity and region size when it is opened.
+#[derive(Debug)]
+pub struct IoUringDeviceOptions {
+    path: PathBuf,
+    capacity: Option<usize>,
+    region_size: Option<usize>,
+    queue_depth: Option<usize>,
+}
+
+impl IoUringDeviceOptions {
+    const DEFAULT_FILE_SIZE: usize = 64 * 1024 * 1024;
+    const DEFAULT_QUEUE_DEPTH: usize = 128;
+
+    /// Use the given file path as the io_uring device path.
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().into(),
+            capacity: None,
+            region_size: None,
+            queue_depth: None,
+        }
+    }
+
+    /// Set the capacity of the io_uring device.
+    ///
+    /// The given capacity may be modified on build for alignment.
+    ///
+    /// The io_uring device uses 80% of the current free disk space by default.
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = Some(capacity);
+        self
+    }
+
+    /// Set the region size of the io_uring device.
+    ///
+    /// The given region size may be modified on build for alignment.
+    ///
+    /// The serialized entry size (with extra metadata) must be equal to or smaller than the region size.
+    pub fn with_region_size(mut self, region_size: usize) -> Self {
+        self.region_size = Some(region_size);
+        self
+    }
+
+    /// Set the queue depth of the io_uring, which is also the max count of the in-flight i/o requests.
+    ///
+    /// Default: 128.
+    pub fn with_queue_depth(mut self, queue_depth: usize) -> Self {
+        self.queue_depth = Some(queue_depth);
+        self
+    }
+}
+
+impl From<IoUringDeviceOptions> for IoUringDeviceConfig {
+    fn from(options: IoUringDeviceOptions) -> Self {
+        let path = options.path;
+
+        let align_v = |value: usize, align: usize| value - value % align;
+
+        let capacity = options.capacity.unwrap_or({
+            // Create an empty directory before to get free space.
+            let dir = path.parent().expect("path must point to a file").to_path_buf();
+            create_dir_all(&dir).unwrap();
+            free_space(&dir).unwrap() as usize / 10 * 8
+        });
+        let capacity = align_v(capacity, ALIGN);
+
+        let region_size = options
+            .region_size
+            .unwrap_or(IoUringDeviceOptions::DEFAULT_FILE_SIZE)
+            .min(capacity);
+        let region_size = align_v(region_size, ALIGN);
+
+        let capacity = align_v(capacity, region_size);
+
+        let queue_depth = options.queue_depth.unwrap_or(IoUringDeviceOptions::DEFAULT_QUEUE_DEPTH);
+
+        IoUringDeviceConfig {
+            path,
+            capacity,
+            region_size,
+            queue_depth,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::future::try_join_all;
+    use itertools::repeat_n;
+
+    use super::*;
+    use crate::device::{Device, DeviceConfig};
+
+    #[test_log::test]
+    fn test_options_builder() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let config: IoUringDeviceConfig = IoUringDeviceOptions::new(dir.path().join("test-io-uring"))
+            .with_queue_depth(8)
+            .into();
+
+        tracing::debug!("{config:?}");
+
+        config.verify().unwrap();
+        assert_eq!(config.queue_depth, 8);
+    }
+
+    #[test]
+    fn test_io_request_advance() {
+        let (tx, _rx) = oneshot::channel();
+        let mut buf = IoBytesMut::with_capacity(3 * ALIGN);
+        unsafe { buf.set_len(3 * ALIGN) };
+        let mut request = IoRequest::Read {
+            buf,
+            offset: 0,
+            done: 0,
+            tx,
+        };
+
+        // Short reads are resubmitted with the remainder.
+        assert!(!request.advance(ALIGN as _).unwrap());
+        assert!(!request.advance(ALIGN as _).unwrap());
+        assert!(matches!(request, IoRequest::Read { done, .. } if done == 2 * ALIGN));
+        assert!(request.advance(0).is_err());
+        assert!(request.advance(-libc::EIO).is_err());
+        assert!(request.advance(2 * ALIGN as i32).is_err());
+        assert!(request.advance(ALIGN as _).unwrap());
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn test_io_uring_device_io() {
+        if !is_supported() {
+            tracing::warn!("io_uring is not supported by the kernel, skip");
+            return;
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let runtime = Runtime::current();
+
+        let config: IoUringDeviceConfig = IoUringDeviceOptions::new(dir.path().join("test-io-uring"))
+            .with_capacity(4 * 1024 * 1024)
+            .with_region_size(1024 * 1024)
+            .with_queue_depth(4)
+            .into();
+
+        let device = IoUringDevice::open(config.clone(), runtime.clone()).await.unwrap();
+
+        // Submit more requests than the queue depth.
+        let bufs = (0..16u8)
+            .map(|i| {
+                let mut buf = IoBytesMut::with_capacity(16 * 1024);
+                buf.extend(repeat_n(i, 16 * 1024 - 100));
+                buf.freeze()
+            })
+            .collect::<Vec<_>>();
+        try_join_all(
+            bufs.iter()
+                .enumerate()
+                .map(|(i, buf)| device.write(buf.clone(), (i / 4) as RegionId, (i % 4) as u64 * 16 * 1024)),
+        )
+        .await
+        .unwrap();
+
+        device.flush(None).await.unwrap();
+
+        let reads = try_join_all(
+            (0..16).map(|i| device.read((i / 4) as RegionId, (i % 4) as u64 * 16 * 1024, 16 * 1024 - 100)),
+        )
+        .await
+        .unwrap();
+        for (buf, read) in bufs.iter().zip(reads) {
+            assert_eq!(buf, &read.freeze());
+        }
+
+        drop(device);
+
+        let device = IoUringDevice::open(config, runtime).await.unwrap();
+
+        let b = device.read(3, 3 * 16 * 1024, 16 * 1024 - 100).await.unwrap().freeze();
+        assert_eq!(bufs[15], b);
+    }
+
+    #[test_log::test]
+    fn test_io_uring_submitter_wake_up() {
+        use std::{
+            io::{Read, Write},
+            os::unix::net::UnixStream,
+            time::{Duration, Instant},
+        };
+
+        if !is_supported() {
+            tracing::warn!("io_uring is not supported by the kernel, skip");
+            return;
+        }
+
+        // A write to a socket stays in flight while the socket buffer is full. The offset of a socket must be -1.
+        let (mut stream, mut peer) = UnixStream::pair().unwrap();
+        stream.set_nonblocking(true).unwrap();
+        let mut filled = 0;
+        loop {
+            match stream.write(&[b'f'; ALIGN]) {
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => break,
+                Err(e) => panic!("fill socket error: {e}"),
+            }
+        }
+        stream.set_nonblocking(false).unwrap();
+
+        let file = Arc::new(File::from(OwnedFd::from(stream)));
+        let (tx, rx) = flume::unbounded();
+        let waker = Arc::new(Waker::new().unwrap());
+        let submitter = Submitter::new(file, 4, rx, waker.clone()).unwrap();
+        let handle = std::thread::spawn(move || submitter.run());
+
+        let send = |request| {
+            tx.send(request).unwrap();
+            waker.wake();
+        };
+
+        let mut buf = IoBytesMut::with_capacity(ALIGN);
+        buf.extend(repeat_n(b'w', ALIGN));
+        let (wtx, mut wrx) = oneshot::channel();
+        send(IoRequest::Write {
+            buf: buf.freeze(),
+            offset: u64::MAX,
+            done: 0,
+            tx: wtx,
+        });
+        std::thread::sleep(Duration::from_millis(100));
+        assert!(matches!(wrx.try_recv(), Err(oneshot::error::TryRecvError::Empty)));
+
+        // The read completes while the slow write is still outstanding.
+        peer.write_all(&[b'r'; ALIGN]).unwrap();
+        let mut buf = IoBytesMut::with_capacity(ALIGN);
+        unsafe { buf.set_len(ALIGN) };
+        let (rtx, mut rrx) = oneshot::channel();
+        send(IoRequest::Read {
+            buf,
+            offset: u64::MAX,
+            done: 0,
+            tx: rtx,
+        });
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let read = loop {
+            match rrx.try_recv() {
+                Ok(res) => break res.unwrap(),
+                Err(oneshot::error::TryRecvError::Empty) if Instant::now() < deadline => {
+                    std::thread::sleep(Duration::from_millis(1))
+                }
+                Err(e) => panic!("the read is not completed while the write is in flight: {e}"),
+            }
+        };
+        assert_eq!(&read[..], &[b'r'; ALIGN]);
+        assert!(matches!(wrx.try_recv(), Err(oneshot::error::TryRecvError::Empty)));
+
+        // Consume the socket buffer to complete the write.
+        let mut remaining = filled + ALIGN;
+        let mut chunk = vec![0; 1024 * 1024];
+        while remaining > 0 {
+            remaining -= peer.read(&mut chunk).unwrap();
+        }
+        wrx.blocking_recv().unwrap().unwrap();
+
+        drop(tx);
+        handle.join().unwrap();
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn test_io_uring_device_fallback() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let config: DeviceConfig = IoUringDeviceOptions::new(dir.path().join("test-io-uring"))
+            .with_capacity(4 * 1024 * 1024)
+            .with_region_size(1024 * 1024)
+            .into();
+
+        let device = Device::open(config, Runtime::current()).await.unwrap();
+        match device {
+            Device::IoUring(_) => assert!(is_supported()),
+            Device::DirectFile(_) => assert!(!is_supported()),
+            _ => unreachable!(),
+        }
+
+        let mut buf = IoBytesMut::with_capacity(4096);
+        buf.extend(repeat_n(b'x', 4096));
+        let buf = buf.freeze();
+        device.write(buf.clone(), 1, 4096).await.unwrap();
+        assert_eq!(buf, device.read(1, 4096, 4096).await.unwrap().freeze());
+    }
+}
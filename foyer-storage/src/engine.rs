@@ -169,6 +169,25 @@ where
     }
 }
 
+impl<K, V, S> EngineEnum<K, V, S>
+where
+    K: StorageKey,
+    V: StorageValue,
+    S: HashBuilder + Debug,
+{
+    /// Resize the disk cache to the given capacity online.
+    ///
+    /// Only the large object disk cache engine supports resizing.
+    pub async fn resize(&self, capacity: usize) -> Result<()> {
+        match self {
+            EngineEnum::Large(storage) => storage.resize(capacity).await,
+            EngineEnum::Noop(_) | EngineEnum::Small(_) | EngineEnum::Mixed(_) => {
+                Err(anyhow::anyhow!("resizing is only supported by the large object disk cache engine").into())
+            }
+        }
+    }
+}
+
 impl<K, V, S> Storage for EngineEnum<K, V, S>
 where
     K: StorageKey,
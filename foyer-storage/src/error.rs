@@ -53,9 +53,27 @@ pub enum Error {
         /// Gotten range.
         get: Range<usize>,
     },
+    /// The layout pinned by the manifest mismatches the given one.
+    #[error("manifest mismatch on {field}, pinned: {pinned}, given: {given}")]
+    ManifestMismatch {
+        /// The mismatched field.
+        field: &'static str,
+        /// The pinned value.
+        pinned: String,
+        /// The given value.
+        given: String,
+    },
     /// Compression algorithm not supported.
     #[error("compression algorithm not supported: {0}")]
     CompressionAlgorithmNotSupported(u8),
+    /// The value is compressed with another zstd dictionary.
+    #[error("zstd dictionary mismatch, expected: {expected}, get: {get:?}")]
+    ZstdDictionaryMismatch {
+        /// The id of the dictionary of the value.
+        expected: u32,
+        /// The id of the given dictionary.
+        get: Option<u32>,
+    },
     /// Other error.
     #[error(transparent)]
     Other(#[from] anyhow::Error),
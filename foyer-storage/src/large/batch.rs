@@ -37,7 +37,7 @@ use crate::{
     large::{indexer::HashedEntryAddress, serde::EntryHeader},
     region::{GetCleanRegionHandle, RegionManager},
     serde::{Checksummer, EntrySerializer},
-    Compression, Dev, DevExt, IoBuffer,
+    CompressionConfig, Dev, DevExt, IoBuffer,
 };
 
 pub struct BatchMut<K, V, S>
@@ -110,7 +110,7 @@ where
         batch
     }
 
-    pub fn entry(&mut self, entry: CacheEntry<K, V, S>, compression: &Compression, sequence: Sequence) -> bool {
+    pub fn entry(&mut self, entry: CacheEntry<K, V, S>, compression: &CompressionConfig, sequence: Sequence) -> bool {
         tracing::trace!("[batch]: append entry with sequence: {sequence}");
 
         self.may_init();
@@ -149,7 +149,8 @@ where
                 &self.buffer[pos + EntryHeader::serialized_len()
                     ..pos + EntryHeader::serialized_len() + info.key_len + info.value_len],
             ),
-            compression: *compression,
+            compression: info.codec.compression,
+            dictionary: info.codec.dictionary,
         };
         header.write(&mut self.buffer[pos..pos + EntryHeader::serialized_len()]);
 
@@ -241,8 +242,17 @@ where
 
         let waiters = std::mem::take(&mut self.waiters);
 
-        let next = self.groups.last().map(|last| {
-            assert!(!last.region.is_full);
+        // Seal the last region if it is retiring while shrinking, so it can be drained by the reclaimers.
+        if let Some(last) = self.groups.last_mut() {
+            if let Some(region) = last.region.handle.peek() {
+                if self.region_manager.is_retiring(region.id()) {
+                    tracing::debug!("[batch]: seal retiring region {id}", id = region.id());
+                    last.region.is_full = true;
+                }
+            }
+        }
+
+        let next = self.groups.last().filter(|last| !last.region.is_full).map(|last| {
             let next = GroupMut {
                 region: RegionHandle {
                     handle: last.region.handle.clone(),
@@ -26,7 +26,7 @@ use foyer_common::{
     metrics::Metrics,
 };
 use foyer_memory::CacheEntry;
-use futures::future::{try_join, try_join_all};
+use futures::future::{join, join_all};
 use tokio::sync::{oneshot, OwnedSemaphorePermit, Semaphore};
 
 use super::{
@@ -38,11 +38,11 @@ use super::{
     tombstone::{Tombstone, TombstoneLog},
 };
 use crate::{
-    device::MonitoredDevice,
+    device::{scheduler::IoClass, MonitoredDevice},
     error::{Error, Result},
     region::RegionManager,
     runtime::Runtime,
-    Compression, Statistics,
+    CompressionConfig, Statistics,
 };
 
 pub enum Submission<K, V, S>
@@ -163,7 +163,7 @@ where
             region_manager,
             indexer,
             tombstone_log,
-            compression: config.compression,
+            compression: config.compression.clone(),
             flush: config.flush,
             stats,
             metrics: metrics.clone(),
@@ -216,7 +216,7 @@ where
     indexer: Indexer,
     tombstone_log: Option<TombstoneLog>,
 
-    compression: Compression,
+    compression: CompressionConfig,
     flush: bool,
 
     stats: Arc<Statistics>,
@@ -294,26 +294,47 @@ where
 
                 // Write buffer to device.
                 let size: usize = group.bytes.len();
-                if size > 0 {
-                    region.write(group.bytes, group.region.offset).await?;
-                    if flush {
-                        region.flush().await?;
+                let class = if group.entries.is_empty() {
+                    IoClass::Reinsertion
+                } else {
+                    IoClass::Flush
+                };
+                let res = async {
+                    if size > 0 {
+                        region.write(group.bytes, group.region.offset, class).await?;
+                        if flush {
+                            region.flush().await?;
+                        }
+                        stats.cache_write_bytes.fetch_add(size, Ordering::Relaxed);
                     }
-                    stats.cache_write_bytes.fetch_add(size, Ordering::Relaxed);
+                    Ok::<_, Error>(())
                 }
+                .await;
+
                 let mut indices = group.indices;
-                for haddr in indices.iter_mut() {
-                    haddr.address.region = region.id();
+                match res {
+                    Ok(()) => {
+                        for haddr in indices.iter_mut() {
+                            haddr.address.region = region.id();
+                        }
+                        indexer.insert_batch(indices);
+                    }
+                    Err(_) => {
+                        // The entries are lost. Remove the indices of the older versions of the entries as well, so the
+                        // queries return misses instead of the stale entries.
+                        let hashes = indices.iter().map(|haddr| haddr.hash).collect::<Vec<_>>();
+                        indexer.remove_batch(&hashes);
+                    }
                 }
-                indexer.insert_batch(indices);
 
+                // The region must be reclaimed even if the write fails, or its space leaks.
                 if group.region.is_full {
                     region_manager.mark_evictable(region.id());
                 }
                 // Make sure entries are dropped after written.
                 drop(group.entries);
                 tracing::trace!("[flusher]: write region {id} finish.", id = region.id());
-                Ok::<_, Error>(())
+                res
             }
         });
         let future = {
@@ -326,17 +347,22 @@ where
                 }
                 for TombstoneInfo { tombstone: _, stats } in tombstones {
                     if let Some(stats) = stats {
-                        region_manager
-                            .region(stats.region)
-                            .stats()
-                            .invalid
-                            .fetch_add(stats.size, Ordering::Relaxed);
+                        // The region may be removed by shrinking.
+                        if let Some(region) = region_manager.try_region(stats.region) {
+                            region.stats().invalid.fetch_add(stats.size, Ordering::Relaxed);
+                        }
                     }
                 }
                 Ok::<_, Error>(())
             }
         };
-        if let Err(e) = try_join(try_join_all(futures), future).await {
+        // Wait for all the groups even if some fail, so the regions of the groups are always marked evictable.
+        let (results, res) = join(join_all(futures), future).await;
+        for e in results
+            .into_iter()
+            .chain(std::iter::once(res))
+            .filter_map(|res| res.err())
+        {
             tracing::error!("[flusher]: error raised when committing batch, error: {e}");
         }
 
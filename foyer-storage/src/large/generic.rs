@@ -13,6 +13,7 @@
 //  limitations under the License.
 
 use std::{
+    cmp::Ordering as CmpOrdering,
     fmt::Debug,
     future::Future,
     marker::PhantomData,
@@ -21,7 +22,7 @@ use std::{
         atomic::{AtomicBool, AtomicUsize, Ordering},
         Arc,
     },
-    time::Instant,
+    time::{Duration, Instant},
 };
 
 use fastrace::prelude::*;
@@ -32,7 +33,7 @@ use foyer_common::{
 };
 use foyer_memory::CacheEntry;
 use futures::future::{join_all, try_join_all};
-use tokio::sync::Semaphore;
+use tokio::sync::{Mutex, Semaphore};
 
 use super::{
     batch::InvalidStats,
@@ -42,8 +43,8 @@ use super::{
     recover::{RecoverMode, RecoverRunner},
 };
 use crate::{
-    compress::Compression,
-    device::{monitor::DeviceStats, Dev, DevExt, MonitoredDevice, RegionId},
+    compress::CompressionConfig,
+    device::{monitor::DeviceStats, scheduler::IoClass, Dev, DevExt, MonitoredDevice, RegionId},
     error::{Error, Result},
     large::{
         reclaimer::RegionCleaner,
@@ -67,7 +68,7 @@ where
     pub name: String,
     pub device: MonitoredDevice,
     pub regions: Range<RegionId>,
-    pub compression: Compression,
+    pub compression: CompressionConfig,
     pub flush: bool,
     pub indexer_shards: usize,
     pub recover_mode: RecoverMode,
@@ -150,10 +151,16 @@ where
     submit_queue_size: Arc<AtomicUsize>,
     submit_queue_size_threshold: usize,
 
+    clean_region_threshold: usize,
+
     statistics: Arc<Statistics>,
 
+    compression: CompressionConfig,
     flush: bool,
 
+    /// Serializes resizing and destroying, which touch all the regions.
+    resize_lock: Mutex<()>,
+
     sequence: AtomicSequence,
 
     runtime: Runtime,
@@ -182,6 +189,8 @@ where
     V: StorageValue,
     S: HashBuilder + Debug,
 {
+    const RETIRE_INTERVAL: Duration = Duration::from_millis(10);
+
     async fn open(mut config: GenericLargeStorageConfig<K, V, S>) -> Result<Self> {
         let stats = config.statistics.clone();
 
@@ -198,6 +207,7 @@ where
                     tombstone_log_config.flush,
                     &mut tombstones,
                     metrics.clone(),
+                    device.throttler().clone(),
                     config.runtime.clone(),
                 )
                 .await?;
@@ -271,8 +281,11 @@ where
                 reclaimers,
                 submit_queue_size,
                 submit_queue_size_threshold: config.submit_queue_size_threshold,
+                clean_region_threshold: config.clean_region_threshold,
                 statistics: stats,
+                compression: config.compression.clone(),
                 flush: config.flush,
+                resize_lock: Mutex::new(()),
                 sequence,
                 runtime: config.runtime,
                 active: AtomicBool::new(true),
@@ -328,6 +341,7 @@ where
         let indexer = self.inner.indexer.clone();
         let stats = self.inner.statistics.clone();
         let metrics = self.inner.metrics.clone();
+        let compression = self.inner.compression.clone();
 
         async move {
             let addr = match indexer.get(hash) {
@@ -341,7 +355,18 @@ where
 
             tracing::trace!("{addr:#?}");
 
-            let buffer = device.read(addr.region, addr.offset as _, addr.len as _).await?;
+            let buffer = match device
+                .read_with_class(addr.region, addr.offset as _, addr.len as _, IoClass::Foreground)
+                .await
+            {
+                Ok(buffer) => buffer,
+                Err(e) => {
+                    tracing::warn!("[lodc]: read entry {hash} at {addr:?} error: {e}, treat it as a miss");
+                    metrics.storage_miss.increment(1);
+                    metrics.storage_miss_duration.record(now.elapsed());
+                    return Ok(None);
+                }
+            };
 
             stats
                 .cache_read_bytes
@@ -361,23 +386,34 @@ where
                 Err(e) => return Err(e),
             };
 
+            // The header may be a stale one left by a torn write, whose lengths don't match the indexed entry.
+            if EntryHeader::serialized_len() + header.key_len as usize + header.value_len as usize > buffer.len() {
+                tracing::trace!(
+                    "entry header {header:?} mismatches the indexed address {addr:?}, remove this entry and skip"
+                );
+                indexer.remove(hash);
+                metrics.storage_miss.increment(1);
+                metrics.storage_miss_duration.record(now.elapsed());
+                return Ok(None);
+            }
+
             let (k, v) = match EntryDeserializer::deserialize::<K, V>(
                 &buffer[EntryHeader::serialized_len()..],
                 header.key_len as _,
                 header.value_len as _,
-                header.compression,
+                header.codec(),
+                &compression,
                 Some(header.checksum),
                 &metrics,
             ) {
                 Ok(res) => res,
-                Err(e @ Error::MagicMismatch { .. }) | Err(e @ Error::ChecksumMismatch { .. }) => {
+                Err(e) => {
                     tracing::trace!("deserialize read buffer raise error: {e}, remove this entry and skip");
                     indexer.remove(hash);
                     metrics.storage_miss.increment(1);
                     metrics.storage_miss_duration.record(now.elapsed());
                     return Ok(None);
                 }
-                Err(e) => return Err(e),
             };
 
             metrics.storage_hit.increment(1);
@@ -423,6 +459,8 @@ where
             return Err(anyhow::anyhow!("cannot delete entry after closed").into());
         }
 
+        let _guard = self.inner.resize_lock.lock().await;
+
         // Write an tombstone to clear tombstone log by increase the max sequence.
         let sequence = self.inner.sequence.fetch_add(1, Ordering::Relaxed);
 
@@ -440,7 +478,7 @@ where
 
         // Clean regions.
         try_join_all((0..self.inner.region_manager.regions() as RegionId).map(|id| {
-            let region = self.inner.region_manager.region(id).clone();
+            let region = self.inner.region_manager.region(id);
             async move {
                 let res = RegionCleaner::clean(&region, self.inner.flush).await;
                 region.stats().reset();
@@ -451,6 +489,105 @@ where
 
         Ok(())
     }
+
+    /// Resize the disk cache to the given capacity online.
+    ///
+    /// On growing, the device is extended and the new regions are marked clean.
+    ///
+    /// On shrinking, the regions with the highest ids are retired. The retiring regions that are being written are
+    /// sealed, then all retiring regions are drained by the reclaimers, which reinsert the entries picked by the
+    /// reinsertion picker into the other regions. At last, the device is truncated.
+    pub async fn resize(&self, capacity: usize) -> Result<()> {
+        let _guard = self.inner.resize_lock.lock().await;
+
+        if !self.inner.active.load(Ordering::Relaxed) {
+            return Err(anyhow::anyhow!("cannot resize after closed").into());
+        }
+        if !self.inner.device.resizable() {
+            return Err(anyhow::anyhow!("the device of the disk cache cannot be resized").into());
+        }
+
+        let region_size = self.inner.device.region_size();
+        if capacity % region_size != 0 {
+            return Err(
+                anyhow::anyhow!("capacity ({capacity}) must be a multiplier of region size ({region_size})").into(),
+            );
+        }
+
+        // Each flusher holds a region to write, and the reclaimers keep the clean regions, at least one more region is
+        // required to be evictable.
+        let regions = capacity / region_size;
+        let min = self.inner.flushers.len() + self.inner.clean_region_threshold + 1;
+        if regions < min {
+            return Err(anyhow::anyhow!(
+                "capacity ({capacity}) must hold at least {min} regions for {flushers} flushers and {clean} clean regions",
+                flushers = self.inner.flushers.len(),
+                clean = self.inner.clean_region_threshold,
+            )
+            .into());
+        }
+
+        let current = self.inner.region_manager.regions();
+        match regions.cmp(&current) {
+            CmpOrdering::Equal => return Ok(()),
+            CmpOrdering::Greater => self.grow(capacity, regions).await?,
+            CmpOrdering::Less => self.shrink(capacity, regions).await?,
+        }
+
+        tracing::info!("[lodc]: Resized from {current} regions to {regions} regions.");
+
+        Ok(())
+    }
+
+    async fn grow(&self, capacity: usize, regions: usize) -> Result<()> {
+        let region_manager = &self.inner.region_manager;
+
+        self.inner.device.resize(capacity).await?;
+
+        let new = region_manager.grow(regions);
+        try_join_all(new.iter().map(|region| RegionCleaner::clean(region, self.inner.flush))).await?;
+
+        // The new clean regions are not produced by the reclaimers, so offset the reclaim permits the same way as
+        // `RecoverRunner::run()`.
+        let mut skips = 0;
+        for _ in 0..new.len() {
+            match region_manager.reclaim_semaphore().try_acquire() {
+                Ok(permit) => permit.forget(),
+                Err(_) => skips += 1,
+            }
+        }
+        region_manager.reclaim_semaphore_countdown().extend(skips);
+
+        for region in new {
+            region_manager.mark_clean(region.id()).await;
+        }
+
+        Ok(())
+    }
+
+    async fn shrink(&self, capacity: usize, regions: usize) -> Result<()> {
+        let region_manager = &self.inner.region_manager;
+        let wait_flushers = || join_all(self.inner.flushers.iter().map(|flusher| flusher.wait()));
+
+        region_manager.retire(regions);
+
+        // Keep the flushers rotating, so the retiring regions being written are sealed and drained.
+        loop {
+            wait_flushers().await;
+            if region_manager.is_retired() {
+                break;
+            }
+            tokio::time::sleep(Self::RETIRE_INTERVAL).await;
+        }
+        // Wait for the reinsertions from the retired regions to finish.
+        wait_flushers().await;
+
+        // Remove the indices that still refer to the retired regions, e.g. the entries failed to reinsert.
+        self.inner.indexer.retain(|addr| (addr.region as usize) < regions);
+
+        region_manager.shrink();
+        self.inner.device.resize(capacity).await
+    }
 }
 
 impl<K, V, S> Storage for GenericLargeStorage<K, V, S>
@@ -514,11 +651,16 @@ mod tests {
 
     use super::*;
     use crate::{
-        device::monitor::{Monitored, MonitoredConfig},
+        device::{
+            monitor::{Monitored, MonitoredConfig},
+            scheduler::IoSchedulerConfig,
+            throttle::Throttle,
+        },
         picker::utils::{FifoPicker, RejectAllPicker},
         serde::EntrySerializer,
         test_utils::BiasedPicker,
-        DirectFsDeviceOptions, TombstoneLogConfigBuilder,
+        DirectFsDeviceOptions, FaultInjectedDeviceOptions, FaultInjector, FaultPolicy, MemoryDeviceOptions,
+        TombstoneLogConfigBuilder,
     };
 
     const KB: usize = 1024;
@@ -538,6 +680,8 @@ mod tests {
                     .with_file_size(ByteSize::kib(16).as_u64() as _)
                     .into(),
                 metrics: Arc::new(Metrics::new("test")),
+                throttle: Throttle::default(),
+                scheduler: IoSchedulerConfig::default(),
             },
             runtime,
         )
@@ -560,7 +704,7 @@ mod tests {
             name: "test".to_string(),
             device,
             regions,
-            compression: Compression::None,
+            compression: CompressionConfig::default(),
             flush: true,
             indexer_shards: 4,
             recover_mode: RecoverMode::Strict,
@@ -590,7 +734,7 @@ mod tests {
             name: "test".to_string(),
             device,
             regions,
-            compression: Compression::None,
+            compression: CompressionConfig::default(),
             flush: true,
             indexer_shards: 4,
             recover_mode: RecoverMode::Strict,
@@ -610,6 +754,106 @@ mod tests {
         GenericLargeStorage::open(config).await.unwrap()
     }
 
+    /// 4 files, fifo eviction, 16 KiB region, 64 KiB capacity, only the given regions are used.
+    async fn store_for_test_with_regions(
+        dir: impl AsRef<Path>,
+        regions: Range<RegionId>,
+    ) -> GenericLargeStorage<u64, Vec<u8>, RandomState> {
+        let device = device_for_test(dir).await;
+        let config = GenericLargeStorageConfig {
+            name: "test".to_string(),
+            device,
+            regions,
+            compression: CompressionConfig::default(),
+            flush: true,
+            indexer_shards: 4,
+            recover_mode: RecoverMode::Strict,
+            recover_concurrency: 2,
+            flushers: 1,
+            reclaimers: 1,
+            clean_region_threshold: 1,
+            eviction_pickers: vec![Box::<FifoPicker>::default()],
+            reinsertion_picker: Arc::<RejectAllPicker<u64>>::default(),
+            tombstone_log_config: None,
+            buffer_pool_size: 16 * 1024 * 1024,
+            submit_queue_size_threshold: 16 * 1024 * 1024 * 2,
+            statistics: Arc::<Statistics>::default(),
+            runtime: Runtime::new(None, None, Handle::current()),
+            marker: PhantomData,
+        };
+        GenericLargeStorage::open(config).await.unwrap()
+    }
+
+    /// 4 regions, fifo eviction, 16 KiB region, 64 KiB capacity, with the faults injected and quiet recovery.
+    async fn store_for_test_with_fault(
+        memory: MemoryDeviceOptions,
+        injector: FaultInjector,
+        tombstone_log: Option<&Path>,
+    ) -> GenericLargeStorage<u64, Vec<u8>, RandomState> {
+        let device = Monitored::open(
+            MonitoredConfig {
+                config: FaultInjectedDeviceOptions::new(
+                    memory
+                        .with_capacity(ByteSize::kib(64).as_u64() as _)
+                        .with_region_size(ByteSize::kib(16).as_u64() as _),
+                    injector,
+                )
+                .into(),
+                metrics: Arc::new(Metrics::new("test")),
+                throttle: Throttle::default(),
+                scheduler: IoSchedulerConfig::default(),
+            },
+            Runtime::current(),
+        )
+        .await
+        .unwrap();
+        let regions = 0..device.regions() as RegionId;
+        let config = GenericLargeStorageConfig {
+            name: "test".to_string(),
+            device,
+            regions,
+            compression: CompressionConfig::default(),
+            flush: true,
+            indexer_shards: 4,
+            recover_mode: RecoverMode::Quiet,
+            recover_concurrency: 2,
+            flushers: 1,
+            reclaimers: 1,
+            clean_region_threshold: 1,
+            eviction_pickers: vec![Box::<FifoPicker>::default()],
+            reinsertion_picker: Arc::<RejectAllPicker<u64>>::default(),
+            tombstone_log_config: tombstone_log
+                .map(|path| TombstoneLogConfigBuilder::new(path).with_flush(true).build()),
+            buffer_pool_size: 16 * 1024 * 1024,
+            submit_queue_size_threshold: 16 * 1024 * 1024 * 2,
+            statistics: Arc::<Statistics>::default(),
+            runtime: Runtime::new(None, None, Handle::current()),
+            marker: PhantomData,
+        };
+        GenericLargeStorage::open(config).await.unwrap()
+    }
+
+    /// Assert the entry is either a miss or the right one.
+    async fn assert_miss_or_match(
+        store: &GenericLargeStorage<u64, Vec<u8>, RandomState>,
+        entry: &CacheEntry<u64, Vec<u8>, RandomState>,
+    ) {
+        if let Some(res) = store.load(entry.hash()).await.unwrap() {
+            assert_eq!(res, (*entry.key(), entry.value().clone()));
+        }
+    }
+
+    /// Assert the entry is a hit with the right value.
+    async fn assert_hit(
+        store: &GenericLargeStorage<u64, Vec<u8>, RandomState>,
+        entry: &CacheEntry<u64, Vec<u8>, RandomState>,
+    ) {
+        assert_eq!(
+            store.load(entry.hash()).await.unwrap(),
+            Some((*entry.key(), entry.value().clone()))
+        );
+    }
+
     fn enqueue(store: &GenericLargeStorage<u64, Vec<u8>, RandomState>, entry: CacheEntry<u64, Vec<u8>, RandomState>) {
         let estimated_size = EntrySerializer::estimated_size(entry.key(), entry.value());
         store.enqueue(entry, estimated_size);
@@ -706,6 +950,34 @@ mod tests {
         assert_eq!(r6, (6, vec![6; 7 * KB]));
     }
 
+    #[test_log::test(tokio::test)]
+    async fn test_store_regions_not_from_zero() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let memory = cache_for_test();
+        let store = store_for_test_with_regions(dir.path(), 2..4).await;
+
+        let e1 = memory.insert(1, vec![1; 7 * KB]);
+        enqueue(&store, e1.clone());
+        store.wait().await;
+
+        // The clean regions are recovered by their ids, the regions out of the range are never written.
+        let addr = store.inner.indexer.get(e1.hash()).unwrap();
+        assert!((2..4).contains(&addr.region), "addr: {addr:?}");
+        let r1 = store.load(e1.hash()).await.unwrap().unwrap();
+        assert_eq!(r1, (1, vec![1; 7 * KB]));
+
+        store.close().await.unwrap();
+        drop(store);
+
+        let store = store_for_test_with_regions(dir.path(), 2..4).await;
+
+        let addr = store.inner.indexer.get(e1.hash()).unwrap();
+        assert!((2..4).contains(&addr.region), "addr: {addr:?}");
+        let r1 = store.load(e1.hash()).await.unwrap().unwrap();
+        assert_eq!(r1, (1, vec![1; 7 * KB]));
+    }
+
     #[test_log::test(tokio::test)]
     async fn test_store_delete_recovery() {
         let dir = tempfile::tempdir().unwrap();
@@ -923,6 +1195,65 @@ mod tests {
         );
     }
 
+    #[test_log::test(tokio::test)]
+    async fn test_store_resize() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = |region: usize| dir.path().join(format!("foyer-storage-direct-fs-{region:08}"));
+
+        let memory = cache_for_test();
+        let store = store_for_test_with_reinsertion_picker(dir.path(), Arc::new(BiasedPicker::new(vec![8, 9]))).await;
+
+        let es = (0..14).map(|i| memory.insert(i, vec![i as u8; 7 * KB])).collect_vec();
+
+        // Unaligned capacity, or too few regions for the flusher and the clean region.
+        assert!(store.resize(100 * KB).await.is_err());
+        assert!(store.resize(32 * KB).await.is_err());
+
+        // [ [], [], [], [] ] => [ [], [], [], [], [], [], [], [] ]
+        store.resize(128 * KB).await.unwrap();
+        assert_eq!(store.inner.region_manager.regions(), 8);
+        assert_eq!(store.inner.device.capacity(), 128 * KB);
+        assert!(file(7).exists());
+
+        // [ [e0, e1], [e2, e3], [e4, e5], [e6, e7], [e8, e9], [e10, e11], [], [] ]
+        for e in es.iter().take(12).cloned() {
+            enqueue(&store, e);
+            store.wait().await;
+        }
+        for i in 0..12 {
+            let r = store.load(memory.hash(&i)).await.unwrap().unwrap();
+            assert_eq!(r, (i, vec![i as u8; 7 * KB]));
+        }
+
+        // Regions 4..8 are drained, e8 and e9 are reinserted and the others in them are dropped.
+        store.resize(64 * KB).await.unwrap();
+        assert_eq!(store.inner.region_manager.regions(), 4);
+        assert_eq!(store.inner.device.capacity(), 64 * KB);
+        assert!(file(3).exists());
+        assert!(!file(4).exists());
+
+        for e in es.iter().take(8) {
+            assert_miss_or_match(&store, e).await;
+        }
+        for i in 8..10 {
+            let r = store.load(memory.hash(&i)).await.unwrap().unwrap();
+            assert_eq!(r, (i, vec![i as u8; 7 * KB]));
+        }
+        for i in 10..12 {
+            assert!(store.load(memory.hash(&i)).await.unwrap().is_none());
+        }
+
+        // The store keeps working after shrinking.
+        for e in es.iter().skip(12).cloned() {
+            enqueue(&store, e);
+            store.wait().await;
+        }
+        for i in 12..14 {
+            let r = store.load(memory.hash(&i)).await.unwrap().unwrap();
+            assert_eq!(r, (i, vec![i as u8; 7 * KB]));
+        }
+    }
+
     #[test_log::test(tokio::test)]
     async fn test_store_magic_checksum_mismatch() {
         let dir = tempfile::tempdir().unwrap();
@@ -961,4 +1292,158 @@ mod tests {
 
         assert!(store.load(memory.hash(&1)).await.unwrap().is_none());
     }
+
+    #[test_log::test(tokio::test)]
+    async fn test_store_fault_read_error() {
+        let memory = cache_for_test();
+        let injector = FaultInjector::default();
+        let store = store_for_test_with_fault(MemoryDeviceOptions::new(), injector.clone(), None).await;
+
+        let e1 = memory.insert(1, vec![1; 7 * KB]);
+        enqueue(&store, e1.clone());
+        store.wait().await;
+
+        injector.update(|policy| policy.read_error_rate = 1.0);
+        assert!(store.load(e1.hash()).await.unwrap().is_none());
+
+        // The entry is still there after the device recovers.
+        injector.set_policy(FaultPolicy::default());
+        assert_eq!(store.load(e1.hash()).await.unwrap(), Some((1, vec![1; 7 * KB])));
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn test_store_fault_write_error() {
+        let memory = cache_for_test();
+        let injector = FaultInjector::default();
+        let store = store_for_test_with_fault(MemoryDeviceOptions::new(), injector.clone(), None).await;
+
+        let e1 = memory.insert(1, vec![1; 7 * KB]);
+        enqueue(&store, e1);
+        store.wait().await;
+
+        // The failed write of the new version must not leave the stale version visible.
+        injector.update(|policy| policy.write_error_rate = 1.0);
+        let e1v2 = memory.insert(1, vec![!1; 7 * KB]);
+        let e2 = memory.insert(2, vec![2; 7 * KB]);
+        enqueue(&store, e1v2.clone());
+        enqueue(&store, e2.clone());
+        store.wait().await;
+        assert!(store.load(e1v2.hash()).await.unwrap().is_none());
+        assert!(store.load(e2.hash()).await.unwrap().is_none());
+
+        // The regions of the failed writes can still be reclaimed and reused.
+        injector.set_policy(FaultPolicy::default());
+        for i in 0..16 {
+            let e = memory.insert(i, vec![i as u8; 7 * KB]);
+            enqueue(&store, e.clone());
+            store.wait().await;
+            assert_eq!(store.load(e.hash()).await.unwrap(), Some((i, vec![i as u8; 7 * KB])));
+        }
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn test_store_fault_torn_write_recovery() {
+        let memory = cache_for_test();
+        let options = MemoryDeviceOptions::new();
+        let injector = FaultInjector::default();
+        let store = store_for_test_with_fault(options.clone(), injector.clone(), None).await;
+
+        // e0 ~ e7 are written intact in two regions, e8 ~ e11 are torn.
+        let es = (0..12).map(|i| memory.insert(i, vec![i as u8; 3 * KB])).collect_vec();
+        for e in es.iter().take(8) {
+            enqueue(&store, e.clone());
+            store.wait().await;
+        }
+        injector.set_policy(FaultPolicy {
+            torn_write_rate: 1.0,
+            drop_flush: true,
+            ..Default::default()
+        });
+        for e in es.iter().skip(8) {
+            enqueue(&store, e.clone());
+            store.wait().await;
+        }
+        assert!(injector.stats().torn_writes.load(Ordering::Relaxed) > 0);
+        for e in es.iter().take(8) {
+            assert_hit(&store, e).await;
+        }
+        // Each torn write of a single page persists nothing.
+        for e in es.iter().skip(8) {
+            assert!(store.load(e.hash()).await.unwrap().is_none());
+        }
+
+        store.close().await.unwrap();
+        drop(store);
+
+        // Recover with the torn regions.
+        injector.set_policy(FaultPolicy::default());
+        let store = store_for_test_with_fault(options.clone(), injector.clone(), None).await;
+        for e in es.iter().take(8) {
+            assert_hit(&store, e).await;
+        }
+        for e in es.iter().skip(8) {
+            assert!(store.load(e.hash()).await.unwrap().is_none());
+        }
+
+        let regions = es
+            .iter()
+            .take(8)
+            .map(|e| store.inner.indexer.get(e.hash()).unwrap().region)
+            .collect_vec();
+        let failed = regions[0];
+
+        store.close().await.unwrap();
+        drop(store);
+
+        // Recover with the read errors on the region of e0, the intact entries in the other regions are still hits.
+        injector.set_policy(FaultPolicy {
+            failed_regions: [failed].into_iter().collect(),
+            ..Default::default()
+        });
+        let store = store_for_test_with_fault(options, injector.clone(), None).await;
+        injector.set_policy(FaultPolicy::default());
+        for (e, region) in es.iter().zip(regions) {
+            if region == failed {
+                assert!(store.load(e.hash()).await.unwrap().is_none());
+            } else {
+                assert_hit(&store, e).await;
+            }
+        }
+        for e in es.iter().skip(8) {
+            assert!(store.load(e.hash()).await.unwrap().is_none());
+        }
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn test_store_fault_tombstone_log_corruption() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test-tombstone-log");
+
+        let memory = cache_for_test();
+        let options = MemoryDeviceOptions::new();
+        let injector = FaultInjector::default();
+        let store = store_for_test_with_fault(options.clone(), injector.clone(), Some(&path)).await;
+
+        let es = (0..6).map(|i| memory.insert(i, vec![i as u8; 7 * KB])).collect_vec();
+        for e in es.iter() {
+            enqueue(&store, e.clone());
+        }
+        store.wait().await;
+        store.delete(es[3].hash());
+        store.wait().await;
+
+        store.close().await.unwrap();
+        drop(store);
+
+        // Overwrite the tombstone log with garbage.
+        let len = std::fs::metadata(&path).unwrap().len() as usize;
+        let garbage = (0..len).map(|i| (i * 131 % 251) as u8).collect_vec();
+        std::fs::write(&path, garbage).unwrap();
+
+        // Only the deletion of e3 is lost, the other entries are still hits.
+        let store = store_for_test_with_fault(options, injector, Some(&path)).await;
+        for e in es.iter() {
+            assert_hit(&store, e).await;
+        }
+    }
 }
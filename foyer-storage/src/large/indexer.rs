@@ -104,6 +104,14 @@ impl Indexer {
         self.shards.iter().for_each(|shard| shard.write().clear());
     }
 
+    /// Retain only the indices whose address satisfies the predicate.
+    #[fastrace::trace(name = "foyer::storage::large::indexer::retain")]
+    pub fn retain(&self, f: impl Fn(&EntryAddress) -> bool) {
+        self.shards
+            .iter()
+            .for_each(|shard| shard.write().retain(|_, addr| f(addr)));
+    }
+
     #[inline(always)]
     fn shard(&self, hash: u64) -> usize {
         hash as usize % self.shards.len()
@@ -23,7 +23,7 @@ use itertools::Itertools;
 use tokio::sync::{mpsc, oneshot, Semaphore, SemaphorePermit};
 
 use crate::{
-    device::IO_BUFFER_ALLOCATOR,
+    device::{scheduler::IoClass, IO_BUFFER_ALLOCATOR},
     error::Result,
     large::{
         flusher::{Flusher, Submission},
@@ -141,6 +141,7 @@ where
                     }
 
                 }
+                region = self.region_manager.retiring_region() => self.retire(region).await,
                 permit = self.reclaim_semaphore.acquire() => {
                     match permit {
                         Err(_) => {
@@ -169,9 +170,35 @@ where
 
         let id = region.id();
 
+        self.reclaim(&region).await;
+
+        self.region_manager.mark_clean(id).await;
+        // These operations should be atomic:
+        //
+        // 1. Reclaim runner releases 1 permit on finish. (+1)
+        // 2. There is a new clean region. (-1)
+        //
+        // Because the total permits to modify is 0 and to avoid concurrent corner case, just forget the permit.
+        //
+        // The permit only increase when the a clean region is taken to write.
+        permit.forget();
+    }
+
+    /// Drain a retiring region while shrinking.
+    ///
+    /// The retiring region is not counted by the permits, so no permit is required.
+    async fn retire(&self, region: Region) {
+        self.reclaim(&region).await;
+        self.region_manager.mark_retired(region.id());
+    }
+
+    /// Reinsert the picked entries of the region, remove the indices of the others and clean the region.
+    async fn reclaim(&self, region: &Region) {
+        let id = region.id();
+
         tracing::debug!("[reclaimer]: Start reclaiming region {id}.");
 
-        let mut scanner = RegionScanner::new(region.clone(), self.metrics.clone());
+        let mut scanner = RegionScanner::new(region.clone(), IoClass::Reclaim, self.metrics.clone());
         let mut picked_count = 0;
         let mut unpicked = vec![];
         // The loop will ends when:
@@ -194,7 +221,10 @@ where
                 Ok(Some((info, key))) => (info, key),
             };
             if self.reinsertion_picker.pick(&self.stats, &key) {
-                let buffer = match region.read(info.addr.offset as _, info.addr.len as _).await {
+                let buffer = match region
+                    .read(info.addr.offset as _, info.addr.len as _, IoClass::Reclaim)
+                    .await
+                {
                     Err(e) => {
                         tracing::warn!(
                             "[reclaimer]: error raised when reclaiming region {id}, skip the subsequent entries, err: {e}",
@@ -226,7 +256,7 @@ where
         });
         self.indexer.remove_batch(&unpicked);
 
-        if let Err(e) = RegionCleaner::clean(&region, self.flush).await {
+        if let Err(e) = RegionCleaner::clean(region, self.flush).await {
             tracing::warn!("reclaimer]: mark region {id} clean error: {e}", id = region.id());
         }
 
@@ -235,17 +265,6 @@ where
         );
 
         region.stats().reset();
-
-        self.region_manager.mark_clean(id).await;
-        // These operations should be atomic:
-        //
-        // 1. Reclaim runner releases 1 permit on finish. (+1)
-        // 2. There is a new clean region. (-1)
-        //
-        // Because the total permits to modify is 0 and to avoid concurrent corner case, just forget the permit.
-        //
-        // The permit only increase when the a clean region is taken to write.
-        permit.forget();
     }
 }
 
@@ -255,7 +274,7 @@ pub struct RegionCleaner;
 impl RegionCleaner {
     pub async fn clean(region: &Region, flush: bool) -> Result<()> {
         let buf = allocator_api2::vec::from_elem_in(0, region.align(), &IO_BUFFER_ALLOCATOR).into();
-        region.write(buf, 0).await?;
+        region.write(buf, 0, IoClass::Reclaim).await?;
         if flush {
             region.flush().await?;
         }
@@ -34,7 +34,7 @@ use super::{
     indexer::{EntryAddress, Indexer},
 };
 use crate::{
-    device::RegionId,
+    device::{scheduler::IoClass, RegionId},
     error::{Error, Result},
     large::{
         indexer::HashedEntryAddress,
@@ -83,9 +83,9 @@ impl RecoverRunner {
         // Recover regions concurrently.
         let semaphore = Arc::new(Semaphore::new(config.recover_concurrency));
         let mode = config.recover_mode;
-        let handles = regions.map(|id| {
+        let handles = regions.clone().map(|id| {
             let semaphore = semaphore.clone();
-            let region = region_manager.region(id).clone();
+            let region = region_manager.region(id);
             let metrics = metrics.clone();
             runtime.user().spawn(async move {
                 let permit = semaphore.acquire().await;
@@ -114,9 +114,8 @@ impl RecoverRunner {
         let mut indices: HashMap<u64, Vec<(Sequence, EntryAddressOrTombstone)>> = HashMap::new();
         let mut clean_regions = vec![];
         let mut evictable_regions = vec![];
-        for (region, infos) in total.into_iter().map(|r| r.unwrap()).enumerate() {
-            let region = region as RegionId;
-
+        // The regions of the large object disk cache don't start from 0 with the mixed engine.
+        for (region, infos) in regions.zip(total.into_iter().map(|r| r.unwrap())) {
             if infos.is_empty() {
                 clean_regions.push(region);
             } else {
@@ -208,7 +207,7 @@ impl RegionRecoverRunner {
         let mut infos = vec![];
 
         let id = region.id();
-        let mut iter = RegionScanner::new(region, metrics);
+        let mut iter = RegionScanner::new(region, IoClass::Recovery, metrics);
         loop {
             let r = iter.next().await;
             match r {
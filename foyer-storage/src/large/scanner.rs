@@ -23,7 +23,8 @@ use foyer_common::{
 
 use super::indexer::EntryAddress;
 use crate::{
-    device::bytes::IoBytes,
+    compress::CompressionConfig,
+    device::{bytes::IoBytes, scheduler::IoClass},
     error::{Error, Result},
     large::serde::{EntryHeader, Sequence},
     region::Region,
@@ -40,6 +41,7 @@ pub struct EntryInfo {
 #[derive(Debug)]
 struct CachedRegionReader {
     region: Region,
+    class: IoClass,
     offset: u64,
     buffer: IoBytes,
 }
@@ -47,9 +49,10 @@ struct CachedRegionReader {
 impl CachedRegionReader {
     const IO_SIZE_HINT: usize = 16 * 1024;
 
-    fn new(region: Region) -> Self {
+    fn new(region: Region, class: IoClass) -> Self {
         Self {
             region,
+            class,
             offset: 0,
             buffer: IoBytes::new(),
         }
@@ -81,7 +84,7 @@ impl CachedRegionReader {
         assert!(bits::is_aligned(self.region.align(), read_len));
         assert!(read_len >= len);
 
-        let buffer = self.region.read(self.offset, read_len).await?.freeze();
+        let buffer = self.region.read(self.offset, read_len, self.class).await?.freeze();
         self.buffer = buffer;
 
         let start = (offset - self.offset) as usize;
@@ -99,8 +102,8 @@ pub struct RegionScanner {
 }
 
 impl RegionScanner {
-    pub fn new(region: Region, metrics: Arc<Metrics>) -> Self {
-        let cache = CachedRegionReader::new(region.clone());
+    pub fn new(region: Region, class: IoClass, metrics: Arc<Metrics>) -> Self {
+        let cache = CachedRegionReader::new(region.clone(), class);
         Self {
             region,
             offset: 0,
@@ -184,7 +187,7 @@ impl RegionScanner {
     }
 
     #[expect(dead_code)]
-    pub async fn next_value<V>(&mut self) -> Result<Option<(EntryInfo, V)>>
+    pub async fn next_value<V>(&mut self, compression: &CompressionConfig) -> Result<Option<(EntryInfo, V)>>
     where
         V: StorageValue,
     {
@@ -199,7 +202,7 @@ impl RegionScanner {
         let offset = info.addr.offset as u64 + EntryHeader::serialized_len() as u64;
         let len = header.value_len as usize;
         let buf = self.cache.read(offset, len).await?;
-        let value = EntryDeserializer::deserialize_value(buf, header.compression)?;
+        let value = EntryDeserializer::decompress_value(buf, header.codec(), compression)?;
 
         self.step(&header).await;
 
@@ -207,7 +210,7 @@ impl RegionScanner {
     }
 
     #[expect(dead_code)]
-    pub async fn next_kv<K, V>(&mut self) -> Result<Option<(EntryInfo, K, V)>>
+    pub async fn next_kv<K, V>(&mut self, compression: &CompressionConfig) -> Result<Option<(EntryInfo, K, V)>>
     where
         K: StorageKey,
         V: StorageValue,
@@ -226,7 +229,8 @@ impl RegionScanner {
             &buf[EntryHeader::serialized_len()..],
             header.key_len as _,
             header.value_len as _,
-            header.compression,
+            header.codec(),
+            compression,
             Some(header.checksum),
             &self.metrics,
         )?;
@@ -247,6 +251,8 @@ mod tests {
     use crate::{
         device::{
             monitor::{Monitored, MonitoredConfig},
+            scheduler::IoSchedulerConfig,
+            throttle::Throttle,
             Dev, MonitoredDevice,
         },
         region::RegionStats,
@@ -262,6 +268,8 @@ mod tests {
                     .with_file_size(ByteSize::kib(16).as_u64() as _)
                     .into(),
                 metrics: Arc::new(Metrics::new("test")),
+                throttle: Throttle::default(),
+                scheduler: IoSchedulerConfig::default(),
             },
             runtime,
         )
@@ -276,7 +284,7 @@ mod tests {
         let device = device_for_test(dir.path()).await;
         let region = Region::new_for_test(0, device, Arc::<RegionStats>::default());
 
-        let mut cached = CachedRegionReader::new(region.clone());
+        let mut cached = CachedRegionReader::new(region.clone(), IoClass::Recovery);
 
         cached.read(0, region.size() / 2).await.unwrap();
         let res = cached.read(region.size() as u64 / 2, region.size()).await;
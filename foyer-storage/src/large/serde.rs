@@ -17,7 +17,7 @@ use std::sync::atomic::AtomicU64;
 use bytes::{Buf, BufMut};
 
 use crate::{
-    compress::Compression,
+    compress::{Codec, Compression},
     error::{Error, Result},
 };
 
@@ -35,11 +35,20 @@ pub struct EntryHeader {
     pub sequence: Sequence,
     pub checksum: u64,
     pub compression: Compression,
+    /// The id of the zstd dictionary of the value, or `0` if the value is not compressed with a dictionary.
+    pub dictionary: u32,
 }
 
 impl EntryHeader {
     pub const fn serialized_len() -> usize {
-        4 + 4 + 8 + 8 + 8 + 4 /* magic & compression */
+        4 + 4 + 8 + 8 + 8 + 4 /* magic & compression */ + 4 /* dictionary */
+    }
+
+    pub fn codec(&self) -> Codec {
+        Codec {
+            compression: self.compression,
+            dictionary: self.dictionary,
+        }
     }
 
     pub fn entry_len(&self) -> usize {
@@ -55,6 +64,7 @@ impl EntryHeader {
 
         let v = ENTRY_MAGIC | self.compression.to_u8() as u32;
         buf.put_u32(v);
+        buf.put_u32(self.dictionary);
     }
 
     pub fn read(mut buf: impl Buf) -> Result<Self> {
@@ -76,6 +86,7 @@ impl EntryHeader {
             });
         }
         let compression = Compression::try_from(v as u8)?;
+        let dictionary = buf.get_u32();
 
         Ok(Self {
             key_len,
@@ -84,6 +95,7 @@ impl EntryHeader {
             sequence,
             checksum,
             compression,
+            dictionary,
         })
     }
 }
@@ -27,6 +27,8 @@ use crate::{
     device::{
         direct_file::DirectFileDevice,
         monitor::{Monitored, MonitoredConfig},
+        scheduler::IoSchedulerConfig,
+        throttle::{Throttle, Throttler},
         Dev, DevExt, RegionId,
     },
     error::{Error, Result},
@@ -121,6 +123,7 @@ impl TombstoneLog {
         flush: bool,
         tombstones: &mut Vec<Tombstone>,
         metrics: Arc<Metrics>,
+        throttler: Arc<Throttler>,
         runtime: Runtime,
     ) -> Result<Self>
     where
@@ -142,10 +145,14 @@ impl TombstoneLog {
                     .with_capacity(capacity)
                     .into(),
                 metrics,
+                throttle: Throttle::default(),
+                scheduler: IoSchedulerConfig::default(),
             },
             runtime,
         )
-        .await?;
+        .await?
+        // The tombstone log shares the budgets with the cache device.
+        .with_throttler(throttler);
 
         let tasks = bits::align_up(Self::RECOVER_IO_SIZE, capacity) / Self::RECOVER_IO_SIZE;
         tracing::trace!("[tombstone log]: recover task count: {tasks}");
@@ -308,11 +315,16 @@ where
 
 #[cfg(test)]
 mod tests {
+    use std::time::{Duration, Instant};
+
     use itertools::Itertools;
     use tempfile::tempdir;
 
     use super::*;
-    use crate::device::direct_fs::{DirectFsDevice, DirectFsDeviceOptions};
+    use crate::device::{
+        direct_fs::{DirectFsDevice, DirectFsDeviceOptions},
+        throttle::IoPriority,
+    };
 
     #[test_log::test(tokio::test)]
     async fn test_tombstone_log() {
@@ -336,6 +348,7 @@ mod tests {
             true,
             &mut vec![],
             Arc::new(Metrics::new("test")),
+            Arc::new(Throttler::new(&Throttle::default())),
             runtime.clone(),
         )
         .await
@@ -366,6 +379,7 @@ mod tests {
             true,
             &mut vec![],
             Arc::new(Metrics::new("test")),
+            Arc::new(Throttler::new(&Throttle::default())),
             runtime,
         )
         .await
@@ -379,4 +393,47 @@ mod tests {
             )
         }
     }
+
+    #[test_log::test(tokio::test)]
+    async fn test_tombstone_log_throttle() {
+        let runtime = Runtime::current();
+
+        let dir = tempdir().unwrap();
+
+        let device = DirectFsDevice::open(
+            DirectFsDeviceOptions::new(dir.path())
+                .with_capacity(4 * 1024 * 1024)
+                .into(),
+            runtime.clone(),
+        )
+        .await
+        .unwrap();
+
+        // 10 pages per second.
+        let throttler = Arc::new(Throttler::new(&Throttle::new().with_write_throughput(40 * 1024)));
+        let log = TombstoneLog::open(
+            dir.path().join("test-tombstone-log"),
+            device,
+            true,
+            &mut vec![],
+            Arc::new(Metrics::new("test")),
+            throttler.clone(),
+            runtime,
+        )
+        .await
+        .unwrap();
+
+        let now = Instant::now();
+        for i in 0..4 {
+            log.append([Tombstone { hash: i, sequence: i }].iter()).await.unwrap();
+        }
+        assert!(
+            now.elapsed() >= Duration::from_millis(300),
+            "elapsed: {:?}",
+            now.elapsed()
+        );
+
+        // The budgets drained by the tombstone log are shared with the cache device.
+        assert!(throttler.write(4 * 1024, IoPriority::Background).await > Duration::ZERO);
+    }
 }
@@ -20,11 +20,13 @@
 #![warn(clippy::allow_attributes)]
 
 mod compress;
+mod compressed;
 mod device;
 mod engine;
 mod error;
 mod io_buffer_pool;
 mod large;
+mod manifest;
 mod picker;
 mod region;
 mod runtime;
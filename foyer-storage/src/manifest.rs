@@ -0,0 +1,225 @@
+//  Copyright 2024 foyer Project Authors
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::{
+    fmt::Debug,
+    fs::File,
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+};
+
+use bytes::{Buf, BufMut};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{Error, Result},
+    Compression, Engine,
+};
+
+/// The layout of the disk cache, pinned by the manifest on the first open.
+///
+/// The disk cache cannot recover from the data written with another layout, so the layout is verified on every open.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Manifest {
+    /// Capacity of the device.
+    pub capacity: usize,
+    /// Region size of the device.
+    pub region_size: usize,
+    /// The disk cache engine.
+    pub engine: Engine,
+    /// Set size of the small object disk cache, if it is used.
+    pub set_size: Option<usize>,
+    /// Compression algorithm of the disk cache.
+    pub compression: Compression,
+    /// Type name of the hasher of the keys.
+    pub hasher: String,
+}
+
+/// The result of [`Manifest::check`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pinned {
+    /// The disk cache is opened for the first time. The manifest must be stored.
+    Created,
+    /// The manifest matches the layout.
+    Matched,
+    /// The manifest mismatches the layout. The data on the device must be wiped before the manifest is stored.
+    Replaced,
+}
+
+impl Manifest {
+    /// The magic of the manifest file, "FOYM".
+    const MAGIC: u32 = 0x464f594d;
+    /// The format version of the disk cache.
+    ///
+    /// Bump it when the on-disk format of any engine changes.
+    pub const VERSION: u32 = 3;
+
+    /// Check the layout against the manifest at the given path, before the device is opened.
+    ///
+    /// The manifest is never written here, for opening the device or wiping it must happen first. The caller stores
+    /// the manifest with [`Manifest::store`] unless it is matched.
+    ///
+    /// If the manifest does not exist but the device is `occupied`, the data is written with an unknown layout and it
+    /// is treated as a mismatch.
+    ///
+    /// On mismatch, [`Pinned::Replaced`] is returned if `wipe` is set, or an [`Error::ManifestMismatch`] otherwise.
+    pub fn check(&self, path: impl AsRef<Path>, occupied: bool, wipe: bool) -> Result<Pinned> {
+        let path = path.as_ref();
+
+        let e = match Self::load(path) {
+            Ok(Some(pinned)) => match pinned.verify(self) {
+                Ok(()) => return Ok(Pinned::Matched),
+                Err(e) => e,
+            },
+            Ok(None) if !occupied => return Ok(Pinned::Created),
+            Ok(None) => Error::ManifestMismatch {
+                field: "manifest",
+                pinned: "missing".to_string(),
+                given: "existing data".to_string(),
+            },
+            Err(e) => e,
+        };
+
+        if !wipe {
+            return Err(e);
+        }
+        tracing::warn!("[manifest]: {e}, wipe the disk cache and reinitialize it with the given layout");
+        Ok(Pinned::Replaced)
+    }
+
+    /// Verify the given layout against the pinned one.
+    fn verify(&self, given: &Self) -> Result<()> {
+        fn check<T: PartialEq + Debug>(field: &'static str, pinned: &T, given: &T) -> Result<()> {
+            if pinned == given {
+                return Ok(());
+            }
+            Err(Error::ManifestMismatch {
+                field,
+                pinned: format!("{pinned:?}"),
+                given: format!("{given:?}"),
+            })
+        }
+
+        check("capacity", &self.capacity, &given.capacity)?;
+        check("region size", &self.region_size, &given.region_size)?;
+        check("engine", &self.engine, &given.engine)?;
+        check("set size", &self.set_size, &given.set_size)?;
+        check("compression", &self.compression, &given.compression)?;
+        check("hasher", &self.hasher, &given.hasher)?;
+        Ok(())
+    }
+
+    /// Load the manifest, or return `None` if it does not exist.
+    fn load(path: &Path) -> Result<Option<Self>> {
+        let buf = match std::fs::read(path) {
+            Ok(buf) => buf,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        if buf.len() < 8 {
+            return Err(anyhow::anyhow!("manifest {path:?} is truncated").into());
+        }
+        let mut cursor = &buf[..];
+        let magic = cursor.get_u32();
+        if magic != Self::MAGIC {
+            return Err(Error::MagicMismatch {
+                expected: Self::MAGIC,
+                get: magic,
+            });
+        }
+        let version = cursor.get_u32();
+        if version != Self::VERSION {
+            return Err(Error::ManifestMismatch {
+                field: "version",
+                pinned: version.to_string(),
+                given: Self::VERSION.to_string(),
+            });
+        }
+
+        let manifest = bincode::deserialize(cursor)?;
+        Ok(Some(manifest))
+    }
+
+    /// Store the manifest atomically.
+    pub(crate) fn store(&self, path: &Path) -> Result<()> {
+        let mut buf = vec![];
+        buf.put_u32(Self::MAGIC);
+        buf.put_u32(Self::VERSION);
+        bincode::serialize_into(&mut buf, self)?;
+
+        let mut tmp = PathBuf::from(path);
+        tmp.as_mut_os_string().push(".tmp");
+
+        let mut file = File::create(&tmp)?;
+        file.write_all(&buf)?;
+        file.sync_all()?;
+        drop(file);
+        std::fs::rename(&tmp, path)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest() -> Manifest {
+        Manifest {
+            capacity: 64 * 1024 * 1024,
+            region_size: 4 * 1024 * 1024,
+            engine: Engine::Mixed(0.1),
+            set_size: Some(16 * 1024),
+            compression: Compression::Lz4,
+            hasher: "ahash::random_state::RandomState".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_manifest_check() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("manifest");
+
+        // The data without a manifest is written with an unknown layout.
+        assert!(matches!(
+            manifest().check(&path, true, false),
+            Err(Error::ManifestMismatch { field: "manifest", .. })
+        ));
+        assert_eq!(manifest().check(&path, true, true).unwrap(), Pinned::Replaced);
+
+        assert_eq!(manifest().check(&path, false, false).unwrap(), Pinned::Created);
+        // Nothing is written until the manifest is stored.
+        assert!(!path.exists());
+        manifest().store(&path).unwrap();
+        assert_eq!(manifest().check(&path, true, false).unwrap(), Pinned::Matched);
+
+        let mismatch = Manifest {
+            engine: Engine::Mixed(0.2),
+            ..manifest()
+        };
+        assert!(matches!(
+            mismatch.check(&path, true, false),
+            Err(Error::ManifestMismatch { field: "engine", .. })
+        ));
+        assert_eq!(mismatch.check(&path, true, true).unwrap(), Pinned::Replaced);
+        assert_eq!(manifest().check(&path, true, false).unwrap(), Pinned::Matched);
+        mismatch.store(&path).unwrap();
+        assert_eq!(mismatch.check(&path, true, false).unwrap(), Pinned::Matched);
+
+        std::fs::write(&path, b"garbage").unwrap();
+        assert!(manifest().check(&path, true, false).is_err());
+        assert_eq!(manifest().check(&path, true, true).unwrap(), Pinned::Replaced);
+    }
+}
@@ -13,12 +13,17 @@
 //  limitations under the License.
 
 pub use crate::{
-    compress::Compression,
+    compress::{Compression, CompressionConfig, ZstdDictionary},
+    compressed::{CompressedCache, CompressedCacheBuilder},
     device::{
         bytes::{IoBuffer, IoBytes, IoBytesMut},
         direct_file::{DirectFileDevice, DirectFileDeviceOptions},
         direct_fs::{DirectFsDevice, DirectFsDeviceOptions},
+        memory::{MemoryDevice, MemoryDeviceOptions},
         monitor::DeviceStats,
+        scheduler::{IoClass, IoSchedulerConfig},
+        striped::{StripedDevice, StripedDeviceOptions},
+        throttle::{IoPriority, Throttle},
         Dev, DevConfig, DevExt,
     },
     error::{Error, Result},
@@ -38,3 +43,9 @@ pub use crate::{
         TokioRuntimeOptions,
     },
 };
+
+#[cfg(any(test, feature = "fault_injection"))]
+pub use crate::device::fault::{FaultInjected, FaultInjectedDeviceOptions, FaultInjector, FaultPolicy, FaultStats};
+
+#[cfg(all(feature = "io_uring", target_os = "linux"))]
+pub use crate::device::uring::{IoUringDevice, IoUringDeviceOptions};
@@ -31,13 +31,13 @@ use futures::{
     FutureExt,
 };
 use itertools::Itertools;
-use parking_lot::Mutex;
+use parking_lot::{Mutex, RwLock};
 use pin_project::pin_project;
 use rand::seq::IteratorRandom;
 use tokio::sync::Semaphore;
 
 use crate::{
-    device::{Dev, DevExt, MonitoredDevice, RegionId},
+    device::{scheduler::IoClass, Dev, DevExt, MonitoredDevice, RegionId},
     error::Result,
     picker::EvictionPicker,
     IoBytes, IoBytesMut,
@@ -83,13 +83,13 @@ impl Region {
         &self.stats
     }
 
-    pub async fn write(&self, buf: IoBytes, offset: u64) -> Result<()> {
-        self.device.write(buf, self.id, offset).await
+    pub async fn write(&self, buf: IoBytes, offset: u64, class: IoClass) -> Result<()> {
+        self.device.write_with_class(buf, self.id, offset, class).await
     }
 
-    pub async fn read(&self, offset: u64, len: usize) -> Result<IoBytesMut> {
+    pub async fn read(&self, offset: u64, len: usize, class: IoClass) -> Result<IoBytesMut> {
         self.stats.access.fetch_add(1, Ordering::Relaxed);
-        self.device.read(self.id, offset, len).await
+        self.device.read_with_class(self.id, offset, len, class).await
     }
 
     pub async fn flush(&self) -> Result<()> {
@@ -123,13 +123,21 @@ struct Eviction {
 }
 
 struct RegionManagerInner {
-    regions: Vec<Region>,
+    device: MonitoredDevice,
+    regions: RwLock<Vec<Region>>,
 
     eviction: Mutex<Eviction>,
 
     clean_region_tx: Sender<Region>,
     clean_region_rx: Receiver<Region>,
 
+    /// The regions with ids not less than the limit are retiring while shrinking, or `usize::MAX` if not shrinking.
+    retire_limit: AtomicUsize,
+    /// Count of the retired regions while shrinking.
+    retired: AtomicUsize,
+    retiring_region_tx: Sender<Region>,
+    retiring_region_rx: Receiver<Region>,
+
     reclaim_semaphore: Arc<Semaphore>,
     reclaim_semaphore_countdown: Arc<Countdown>,
 
@@ -157,19 +165,25 @@ impl RegionManager {
             })
             .collect_vec();
         let (clean_region_tx, clean_region_rx) = async_channel::unbounded();
+        let (retiring_region_tx, retiring_region_rx) = async_channel::unbounded();
 
         metrics.storage_region_total.set(device.regions() as f64);
         metrics.storage_region_size_bytes.set(device.region_size() as f64);
 
         Self {
             inner: Arc::new(RegionManagerInner {
-                regions,
+                device,
+                regions: RwLock::new(regions),
                 eviction: Mutex::new(Eviction {
                     evictable: HashMap::new(),
                     eviction_pickers,
                 }),
                 clean_region_tx,
                 clean_region_rx,
+                retire_limit: AtomicUsize::new(usize::MAX),
+                retired: AtomicUsize::new(0),
+                retiring_region_tx,
+                retiring_region_rx,
                 reclaim_semaphore,
                 reclaim_semaphore_countdown: Arc::new(Countdown::new(0)),
                 metrics,
@@ -180,10 +194,15 @@ impl RegionManager {
     pub fn mark_evictable(&self, region: RegionId) {
         let mut eviction = self.inner.eviction.lock();
 
+        // A retiring region is drained by the reclaimers instead of being evicted.
+        if self.is_retiring(region) {
+            self.inner.retiring_region_tx.try_send(self.region(region)).unwrap();
+            tracing::debug!("[region manager]: Region {region} is marked retiring.");
+            return;
+        }
+
         // Update evictable map.
-        let res = eviction
-            .evictable
-            .insert(region, self.inner.regions[region as usize].stats.clone());
+        let res = eviction.evictable.insert(region, self.region(region).stats.clone());
         assert!(res.is_none());
 
         // Temporarily take pickers to make borrow checker happy.
@@ -248,44 +267,150 @@ impl RegionManager {
         std::mem::swap(&mut eviction.eviction_pickers, &mut pickers);
         assert!(pickers.is_empty());
 
-        let region = self.region(picked).clone();
+        let region = self.region(picked);
         tracing::debug!("[region manager]: Region {picked} is evicted.");
 
         Some(region)
     }
 
     pub async fn mark_clean(&self, region: RegionId) {
-        self.inner
-            .clean_region_tx
-            .send(self.region(region).clone())
-            .await
-            .unwrap();
+        // Check and send with the eviction lock, so a retiring region never slips into the clean regions.
+        let eviction = self.inner.eviction.lock();
+        if self.is_retiring(region) {
+            // The region is retired instead of being clean, release the permit as it is taken.
+            self.inner.release_reclaim_permit();
+            self.inner.retire(region);
+            return;
+        }
+        self.inner.clean_region_tx.try_send(self.region(region)).unwrap();
+        drop(eviction);
         self.inner.metrics.storage_region_clean.increment(1);
     }
 
     pub fn get_clean_region(&self) -> GetCleanRegionHandle {
-        let clean_region_rx = self.inner.clean_region_rx.clone();
-        let reclaim_semaphore = self.inner.reclaim_semaphore.clone();
-        let reclaim_semaphore_countdown = self.inner.reclaim_semaphore_countdown.clone();
-        let metrics = self.inner.metrics.clone();
+        let inner = self.inner.clone();
         GetCleanRegionHandle::new(
             async move {
-                let region = clean_region_rx.recv().await.unwrap();
-                // The only place to increase the permit.
-                //
-                // See comments in `ReclaimRunner::handle()` and `RecoverRunner::run()`.
-                if reclaim_semaphore_countdown.countdown() {
-                    reclaim_semaphore.add_permits(1);
+                loop {
+                    let region = inner.clean_region_rx.recv().await.unwrap();
+                    inner.release_reclaim_permit();
+                    inner.metrics.storage_region_clean.decrement(1);
+                    // Skip the clean regions that are retiring while shrinking.
+                    if inner.is_retiring(region.id()) {
+                        inner.retire(region.id());
+                        continue;
+                    }
+                    return region;
                 }
-                metrics.storage_region_clean.decrement(1);
-                region
             }
             .boxed(),
         )
     }
 
+    /// Start retiring the regions with ids not less than `limit` to shrink the regions to `limit`.
+    ///
+    /// The clean retiring regions are retired at once. The evictable retiring regions and the retiring regions that
+    /// become evictable later are sent to the reclaimers to drain, see [`RegionManager::retiring_region`]. The retiring
+    /// regions that are being written must be sealed by the flushers.
+    pub fn retire(&self, limit: usize) {
+        let mut eviction = self.inner.eviction.lock();
+
+        self.inner.retire_limit.store(limit, Ordering::Relaxed);
+        self.inner.retired.store(0, Ordering::Relaxed);
+
+        // Temporarily take pickers to make borrow checker happy.
+        let mut pickers = std::mem::take(&mut eviction.eviction_pickers);
+
+        let retiring = eviction
+            .evictable
+            .keys()
+            .filter(|id| **id as usize >= limit)
+            .copied()
+            .collect_vec();
+        for id in retiring {
+            eviction.evictable.remove(&id).unwrap();
+            self.inner.metrics.storage_region_evictable.decrement(1);
+            for picker in pickers.iter_mut() {
+                picker.on_region_evict(&eviction.evictable, id);
+            }
+            self.inner.retiring_region_tx.try_send(self.region(id)).unwrap();
+        }
+
+        // Restore taken pickers after operations.
+        std::mem::swap(&mut eviction.eviction_pickers, &mut pickers);
+        assert!(pickers.is_empty());
+
+        for _ in 0..self.inner.clean_region_rx.len() {
+            let Ok(region) = self.inner.clean_region_rx.try_recv() else {
+                break;
+            };
+            if self.is_retiring(region.id()) {
+                self.inner.release_reclaim_permit();
+                self.inner.metrics.storage_region_clean.decrement(1);
+                self.inner.retire(region.id());
+            } else {
+                self.inner.clean_region_tx.try_send(region).unwrap();
+            }
+        }
+
+        tracing::info!("[region manager]: Start retiring regions {limit}..{}.", self.regions());
+    }
+
+    /// Mark a retiring region retired after it is drained by a reclaimer.
+    pub fn mark_retired(&self, region: RegionId) {
+        self.inner.retire(region);
+    }
+
+    /// Check if all the retiring regions are retired.
+    pub fn is_retired(&self) -> bool {
+        let limit = self.inner.retire_limit.load(Ordering::Relaxed);
+        self.inner.retired.load(Ordering::Relaxed) >= self.regions().saturating_sub(limit)
+    }
+
+    /// Check if the region is retiring while shrinking.
+    pub fn is_retiring(&self, region: RegionId) -> bool {
+        self.inner.is_retiring(region)
+    }
+
+    /// Get the next retiring region to drain.
+    pub async fn retiring_region(&self) -> Region {
+        self.inner.retiring_region_rx.recv().await.unwrap()
+    }
+
+    /// Remove the retired regions and finish shrinking.
+    pub fn shrink(&self) {
+        let limit = self.inner.retire_limit.load(Ordering::Relaxed);
+        assert!(self.is_retired());
+
+        self.inner.regions.write().truncate(limit);
+        self.inner.retire_limit.store(usize::MAX, Ordering::Relaxed);
+        self.inner.retired.store(0, Ordering::Relaxed);
+
+        self.inner.metrics.storage_region_total.set(limit as f64);
+    }
+
+    /// Add regions after the device grows to the given region count, returns the new regions.
+    ///
+    /// The new regions must be cleaned before marked clean.
+    pub fn grow(&self, regions: usize) -> Vec<Region> {
+        let mut guard = self.inner.regions.write();
+        let new = (guard.len()..regions)
+            .map(|id| Region {
+                id: id as RegionId,
+                device: self.inner.device.clone(),
+                stats: Arc::new(RegionStats::default()),
+            })
+            .collect_vec();
+        guard.extend(new.iter().cloned());
+        drop(guard);
+
+        self.inner.metrics.storage_region_total.set(regions as f64);
+
+        new
+    }
+
     pub fn regions(&self) -> usize {
-        self.inner.regions.len()
+        self.inner.regions.read().len()
     }
 
     #[expect(dead_code)]
@@ -298,8 +423,13 @@ impl RegionManager {
         self.inner.clean_region_rx.len()
     }
 
-    pub fn region(&self, id: RegionId) -> &Region {
-        &self.inner.regions[id as usize]
+    pub fn region(&self, id: RegionId) -> Region {
+        self.inner.regions.read()[id as usize].clone()
+    }
+
+    /// Get the region, or `None` if it is removed by shrinking.
+    pub fn try_region(&self, id: RegionId) -> Option<Region> {
+        self.inner.regions.read().get(id as usize).cloned()
     }
 
     pub fn reclaim_semaphore(&self) -> &Arc<Semaphore> {
@@ -311,6 +441,26 @@ impl RegionManager {
     }
 }
 
+impl RegionManagerInner {
+    fn is_retiring(&self, region: RegionId) -> bool {
+        region as usize >= self.retire_limit.load(Ordering::Relaxed)
+    }
+
+    fn retire(&self, region: RegionId) {
+        self.retired.fetch_add(1, Ordering::Relaxed);
+        tracing::debug!("[region manager]: Region {region} is retired.");
+    }
+
+    /// Release a reclaim permit when a clean region is taken, or a region is retired instead of being clean.
+    ///
+    /// See comments in `ReclaimRunner::handle()` and `RecoverRunner::run()`.
+    fn release_reclaim_permit(&self) {
+        if self.reclaim_semaphore_countdown.countdown() {
+            self.reclaim_semaphore.add_permits(1);
+        }
+    }
+}
+
 #[derive(Debug)]
 #[pin_project]
 pub struct GetCleanRegionHandle {
@@ -332,6 +482,11 @@ impl GetCleanRegionHandle {
             future: future.shared(),
         }
     }
+
+    /// Get the region if it is already taken.
+    pub fn peek(&self) -> Option<&Region> {
+        self.future.peek()
+    }
 }
 
 impl Future for GetCleanRegionHandle {
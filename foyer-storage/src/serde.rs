@@ -21,7 +21,7 @@ use foyer_common::{
 use twox_hash::{XxHash32, XxHash64};
 
 use crate::{
-    compress::Compression,
+    compress::{Codec, Compression, CompressionConfig},
     error::{Error, Result},
 };
 
@@ -46,6 +46,7 @@ impl Checksummer {
 pub struct KvInfo {
     pub key_len: usize,
     pub value_len: usize,
+    pub codec: Codec,
 }
 
 #[derive(Debug)]
@@ -104,7 +105,7 @@ impl EntrySerializer {
     pub fn serialize<'a, K, V, W>(
         key: &'a K,
         value: &'a V,
-        compression: &'a Compression,
+        compression: &'a CompressionConfig,
         writer: W,
         metrics: &Metrics,
     ) -> Result<KvInfo>
@@ -118,6 +119,91 @@ impl EntrySerializer {
         let mut writer = TrackedWriter::new(writer);
 
         // serialize value
+        let codec = Self::compress_value(value, compression, &mut writer)?;
+
+        let value_len = writer.written();
+        writer.recount();
+
+        // serialize key
+        bincode::serialize_into(&mut writer, &key).map_err(Error::from)?;
+        let key_len = writer.written();
+
+        metrics.storage_entry_serialize_duration.record(now.elapsed());
+
+        Ok(KvInfo {
+            key_len,
+            value_len,
+            codec,
+        })
+    }
+
+    /// Serialize and compress the value with the compression config, returns the codec of the written value.
+    ///
+    /// The value is written raw if it is smaller than the minimal size to compress, or if the compressed one is not
+    /// small enough.
+    #[fastrace::trace(name = "foyer::storage::serde::compress_value")]
+    pub fn compress_value<V, W>(value: &V, compression: &CompressionConfig, mut writer: W) -> Result<Codec>
+    where
+        V: StorageValue,
+        W: Write,
+    {
+        if compression.algorithm() == Compression::None {
+            bincode::serialize_into(&mut writer, &value).map_err(Error::from)?;
+            return Ok(Codec::RAW);
+        }
+
+        let raw = bincode::serialize(value).map_err(Error::from)?;
+        if raw.len() < compression.min_size() {
+            writer.write_all(&raw).map_err(Error::from)?;
+            return Ok(Codec::RAW);
+        }
+
+        let mut buf = Vec::with_capacity(raw.len());
+        let mut dictionary = 0;
+        match compression.algorithm() {
+            Compression::None => unreachable!(),
+            Compression::Zstd => {
+                let mut encoder = match compression.zstd_encoder_dictionary() {
+                    Some(prepared) => {
+                        dictionary = compression.zstd_dictionary().unwrap().id();
+                        zstd::Encoder::with_prepared_dictionary(&mut buf, prepared)
+                    }
+                    None => zstd::Encoder::new(&mut buf, compression.level()),
+                }
+                .map_err(Error::from)?;
+                encoder.write_all(&raw).map_err(Error::from)?;
+                encoder.finish().map_err(Error::from)?;
+            }
+            Compression::Lz4 => {
+                let mut encoder = lz4::EncoderBuilder::new()
+                    .checksum(lz4::ContentChecksum::NoChecksum)
+                    .level(compression.level().max(0) as u32)
+                    .build(&mut buf)
+                    .map_err(Error::from)?;
+                encoder.write_all(&raw).map_err(Error::from)?;
+                let (_, res) = encoder.finish();
+                res.map_err(Error::from)?;
+            }
+        }
+
+        if buf.len() as f64 > raw.len() as f64 * compression.fallback_ratio() {
+            writer.write_all(&raw).map_err(Error::from)?;
+            return Ok(Codec::RAW);
+        }
+
+        writer.write_all(&buf).map_err(Error::from)?;
+        Ok(Codec {
+            compression: compression.algorithm(),
+            dictionary,
+        })
+    }
+
+    #[fastrace::trace(name = "foyer::storage::serde::serialize_value")]
+    pub fn serialize_value<V, W>(value: &V, compression: &Compression, mut writer: W) -> Result<()>
+    where
+        V: StorageValue,
+        W: Write,
+    {
         match compression {
             Compression::None => {
                 bincode::serialize_into(&mut writer, &value).map_err(Error::from)?;
@@ -137,17 +223,7 @@ impl EntrySerializer {
                 bincode::serialize_into(encoder, &value).map_err(Error::from)?;
             }
         }
-
-        let value_len = writer.written();
-        writer.recount();
-
-        // serialize key
-        bincode::serialize_into(&mut writer, &key).map_err(Error::from)?;
-        let key_len = writer.written();
-
-        metrics.storage_entry_serialize_duration.record(now.elapsed());
-
-        Ok(KvInfo { key_len, value_len })
+        Ok(())
     }
 
     pub fn estimated_size<'a, K, V>(key: &'a K, value: &'a V) -> usize
@@ -169,7 +245,8 @@ impl EntryDeserializer {
         buffer: &[u8],
         ken_len: usize,
         value_len: usize,
-        compression: Compression,
+        codec: Codec,
+        compression: &CompressionConfig,
         checksum: Option<u64>,
         metrics: &Metrics,
     ) -> Result<(K, V)>
@@ -181,7 +258,7 @@ impl EntryDeserializer {
 
         // deserialize value
         let buf = &buffer[..value_len];
-        let value = Self::deserialize_value(buf, compression)?;
+        let value = Self::decompress_value(buf, codec, compression)?;
 
         // deserialize key
         let buf = &buffer[value_len..value_len + ken_len];
@@ -208,6 +285,27 @@ impl EntryDeserializer {
         bincode::deserialize_from(buf).map_err(Error::from)
     }
 
+    /// Decompress and deserialize the value written by [`EntrySerializer::compress_value`] with the given codec.
+    #[fastrace::trace(name = "foyer::storage::serde::decompress_value")]
+    pub fn decompress_value<V>(buf: &[u8], codec: Codec, compression: &CompressionConfig) -> Result<V>
+    where
+        V: StorageValue,
+    {
+        if codec.dictionary == 0 {
+            return Self::deserialize_value(buf, codec.compression);
+        }
+
+        let dictionary = compression
+            .zstd_dictionary()
+            .filter(|dictionary| dictionary.id() == codec.dictionary)
+            .ok_or_else(|| Error::ZstdDictionaryMismatch {
+                expected: codec.dictionary,
+                get: compression.zstd_dictionary().map(|dictionary| dictionary.id()),
+            })?;
+        let decoder = zstd::Decoder::with_prepared_dictionary(buf, dictionary.decoder()).map_err(Error::from)?;
+        bincode::deserialize_from(decoder).map_err(Error::from)
+    }
+
     #[fastrace::trace(name = "foyer::storage::serde::deserialize_value")]
     pub fn deserialize_value<V>(buf: &[u8], compression: Compression) -> Result<V>
     where
@@ -226,3 +324,94 @@ impl EntryDeserializer {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compress::ZstdDictionary;
+
+    fn roundtrip(value: &Vec<u8>, compression: &CompressionConfig) -> (Codec, usize) {
+        let mut buf = vec![];
+        let codec = EntrySerializer::compress_value(value, compression, &mut buf).unwrap();
+        let v: Vec<u8> = EntryDeserializer::decompress_value(&buf, codec, compression).unwrap();
+        assert_eq!(&v, value);
+        (codec, buf.len())
+    }
+
+    #[test]
+    fn test_compress_value() {
+        let compressible = vec![b'x'; 4096];
+        // Pseudo-random bytes by xorshift.
+        let mut x = 0x2545f4914f6cdd1du64;
+        let incompressible = (0..4096)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                x as u8
+            })
+            .collect::<Vec<_>>();
+
+        for algorithm in [Compression::Zstd, Compression::Lz4] {
+            let config = CompressionConfig::new(algorithm).with_level(1);
+            let (codec, len) = roundtrip(&compressible, &config);
+            assert_eq!(codec.compression, algorithm);
+            assert!(len < 4096);
+            assert_eq!(roundtrip(&incompressible, &config).0, Codec::RAW);
+
+            // Skip the small values.
+            let config = CompressionConfig::new(algorithm).with_min_size(8192);
+            assert_eq!(roundtrip(&compressible, &config).0, Codec::RAW);
+        }
+
+        // Fall back to raw if the compressed value is not small enough.
+        let value = [compressible.clone(), incompressible.clone()].concat();
+        let config = CompressionConfig::new(Compression::Zstd);
+        assert_eq!(roundtrip(&value, &config).0.compression, Compression::Zstd);
+        let config = config.with_fallback_ratio(0.25);
+        assert_eq!(roundtrip(&value, &config).0, Codec::RAW);
+    }
+
+    #[test]
+    fn test_compress_value_with_zstd_dictionary() {
+        let samples = (0..1000u64)
+            .map(|i| {
+                format!(
+                    "{{\"id\":{i},\"name\":\"user-{}\",\"role\":\"member\",\"active\":true}}",
+                    i * 7
+                )
+                .into_bytes()
+            })
+            .collect::<Vec<_>>();
+        let dictionary = ZstdDictionary::train(&samples, 4096).unwrap();
+        assert_ne!(dictionary.id(), 0);
+        assert_eq!(
+            ZstdDictionary::new(dictionary.as_bytes().to_vec()).unwrap().id(),
+            dictionary.id()
+        );
+        assert!(ZstdDictionary::new(vec![0; 1024]).is_err());
+
+        let value = samples[42].clone();
+        let plain = CompressionConfig::new(Compression::Zstd);
+        let config = plain.clone().with_zstd_dictionary(dictionary.clone());
+
+        let (codec, len) = roundtrip(&value, &config);
+        assert_eq!(
+            codec,
+            Codec {
+                compression: Compression::Zstd,
+                dictionary: dictionary.id(),
+            }
+        );
+        let (_, plain_len) = roundtrip(&value, &plain);
+        assert!(len < plain_len, "len: {len}, plain len: {plain_len}");
+
+        // The value compressed with a dictionary cannot be decompressed without it.
+        let mut buf = vec![];
+        EntrySerializer::compress_value(&value, &config, &mut buf).unwrap();
+        assert!(matches!(
+            EntryDeserializer::decompress_value::<Vec<u8>>(&buf, codec, &plain),
+            Err(Error::ZstdDictionaryMismatch { get: None, .. })
+        ));
+    }
+}
@@ -34,7 +34,7 @@ use crate::{
     io_buffer_pool::IoBufferPool,
     serde::EntrySerializer,
     small::{serde::EntryHeader, set::SetId, set_manager::SetPicker},
-    Compression, IoBuffer, IoBytes,
+    CompressionConfig, IoBuffer, IoBytes,
 };
 
 type Sequence = usize;
@@ -96,6 +96,7 @@ where
 
     init: Option<Instant>,
 
+    compression: CompressionConfig,
     metrics: Arc<Metrics>,
 }
 
@@ -105,7 +106,7 @@ where
     V: StorageValue,
     S: HashBuilder + Debug,
 {
-    pub fn new(sets: usize, buffer_size: usize, metrics: Arc<Metrics>) -> Self {
+    pub fn new(sets: usize, buffer_size: usize, compression: CompressionConfig, metrics: Arc<Metrics>) -> Self {
         let buffer_size = bits::align_up(ALIGN, buffer_size);
 
         Self {
@@ -117,12 +118,14 @@ where
             set_picker: SetPicker::new(sets),
             waiters: vec![],
             init: None,
+            compression,
             metrics,
         }
     }
 
     pub fn insert(&mut self, entry: CacheEntry<K, V, S>, estimated_size: usize) -> bool {
-        // For the small object disk cache does NOT compress entries, `estimated_size` is actually `exact_size`.
+        // The value is never serialized larger than `estimated_size`, for the compressed value that is no smaller than
+        // the raw value falls back to the raw value.
         tracing::trace!("[sodc batch]: insert entry");
 
         if self.init.is_none() {
@@ -144,7 +147,7 @@ where
         let info = match EntrySerializer::serialize(
             entry.key(),
             entry.value(),
-            &Compression::None,
+            &self.compression,
             &mut self.buffer[self.len + EntryHeader::ENTRY_HEADER_SIZE..self.len + len],
             &self.metrics,
         ) {
@@ -154,8 +157,8 @@ where
                 return false;
             }
         };
-        assert_eq!(info.key_len + info.value_len + EntryHeader::ENTRY_HEADER_SIZE, len);
-        let header = EntryHeader::new(entry.hash(), info.key_len, info.value_len);
+        let len = EntryHeader::ENTRY_HEADER_SIZE + info.key_len + info.value_len;
+        let header = EntryHeader::new(entry.hash(), info.key_len, info.value_len, info.codec);
         header.write(&mut self.buffer[self.len..self.len + EntryHeader::ENTRY_HEADER_SIZE]);
 
         set.items.push(ItemMut {
@@ -100,7 +100,12 @@ where
 
         let buffer_size = config.buffer_pool_size / config.flushers;
 
-        let batch = BatchMut::new(set_manager.sets() as _, buffer_size, metrics.clone());
+        let batch = BatchMut::new(
+            set_manager.sets() as _,
+            buffer_size,
+            config.compression.clone(),
+            metrics.clone(),
+        );
 
         let runner = Runner {
             rx,
@@ -35,7 +35,7 @@ use crate::{
         set_manager::SetManager,
     },
     storage::Storage,
-    DeviceStats, Runtime, Statistics,
+    CompressionConfig, DeviceStats, Runtime, Statistics,
 };
 
 pub struct GenericSmallStorageConfig<K, V, S>
@@ -49,6 +49,7 @@ where
     pub set_cache_shards: usize,
     pub device: MonitoredDevice,
     pub regions: Range<RegionId>,
+    pub compression: CompressionConfig,
     pub flush: bool,
     pub flushers: usize,
     pub buffer_pool_size: usize,
@@ -70,6 +71,7 @@ where
             .field("set_cache_shards", &self.set_cache_shards)
             .field("device", &self.device)
             .field("regions", &self.regions)
+            .field("compression", &self.compression)
             .field("flush", &self.flush)
             .field("flushers", &self.flushers)
             .field("buffer_pool_size", &self.buffer_pool_size)
@@ -198,7 +200,13 @@ where
                 .cache_read_bytes
                 .fetch_add(set_manager.set_size(), Ordering::Relaxed);
 
-            set_manager.load(hash).await
+            match set_manager.load(hash).await {
+                Ok(res) => Ok(res),
+                Err(e) => {
+                    tracing::warn!("[sodc]: load entry {hash} error: {e}, treat it as a miss");
+                    Ok(None)
+                }
+            }
         }
     }
 
@@ -290,10 +298,12 @@ mod tests {
     use crate::{
         device::{
             monitor::{Monitored, MonitoredConfig},
+            scheduler::IoSchedulerConfig,
+            throttle::Throttle,
             Dev,
         },
         serde::EntrySerializer,
-        DevExt, DirectFsDeviceOptions,
+        DevExt, DirectFsDeviceOptions, FaultInjectedDeviceOptions, FaultInjector, FaultPolicy, MemoryDeviceOptions,
     };
 
     fn cache_for_test() -> Cache<u64, Vec<u8>> {
@@ -311,6 +321,8 @@ mod tests {
                     .with_file_size(ByteSize::kib(16).as_u64() as _)
                     .into(),
                 metrics: Arc::new(Metrics::new("test")),
+                throttle: Throttle::default(),
+                scheduler: IoSchedulerConfig::default(),
             },
             runtime,
         )
@@ -319,7 +331,31 @@ mod tests {
     }
 
     async fn store_for_test(dir: impl AsRef<Path>) -> GenericSmallStorage<u64, Vec<u8>, RandomState> {
-        let device = device_for_test(dir).await;
+        store_for_test_with_device(device_for_test(dir).await).await
+    }
+
+    async fn store_for_test_with_fault(injector: FaultInjector) -> GenericSmallStorage<u64, Vec<u8>, RandomState> {
+        let device = Monitored::open(
+            MonitoredConfig {
+                config: FaultInjectedDeviceOptions::new(
+                    MemoryDeviceOptions::new()
+                        .with_capacity(ByteSize::kib(64).as_u64() as _)
+                        .with_region_size(ByteSize::kib(16).as_u64() as _),
+                    injector,
+                )
+                .into(),
+                metrics: Arc::new(Metrics::new("test")),
+                throttle: Throttle::default(),
+                scheduler: IoSchedulerConfig::default(),
+            },
+            Runtime::current(),
+        )
+        .await
+        .unwrap();
+        store_for_test_with_device(device).await
+    }
+
+    async fn store_for_test_with_device(device: MonitoredDevice) -> GenericSmallStorage<u64, Vec<u8>, RandomState> {
         let regions = 0..device.regions() as RegionId;
         let config = GenericSmallStorageConfig {
             set_size: ByteSize::kib(4).as_u64() as _,
@@ -327,6 +363,7 @@ mod tests {
             set_cache_shards: 1,
             device,
             regions,
+            compression: CompressionConfig::default(),
             flush: false,
             flushers: 1,
             buffer_pool_size: ByteSize::kib(64).as_u64() as _,
@@ -389,4 +426,40 @@ mod tests {
         assert_none(&store, &e2).await;
         assert_none(&store, &e3).await;
     }
+
+    #[test_log::test(tokio::test)]
+    async fn test_store_fault() {
+        let memory = cache_for_test();
+        let injector = FaultInjector::default();
+        let store = store_for_test_with_fault(injector.clone()).await;
+
+        let e1 = memory.insert(1, vec![1; 42]);
+        enqueue(&store, &e1);
+        store.wait().await;
+
+        // The set cache is invalidated on update, so the load reads the device.
+        injector.update(|policy| policy.read_error_rate = 1.0);
+        assert_none(&store, &e1).await;
+        injector.set_policy(FaultPolicy::default());
+        assert_some(&store, &e1).await;
+
+        let e2 = memory.insert(2, vec![2; 192]);
+        injector.update(|policy| policy.write_error_rate = 1.0);
+        enqueue(&store, &e2);
+        store.wait().await;
+        injector.set_policy(FaultPolicy::default());
+        assert_none(&store, &e2).await;
+
+        // A torn set fails the checksum and is treated as empty.
+        let e3 = memory.insert(3, vec![3; 168]);
+        injector.update(|policy| policy.torn_write_rate = 1.0);
+        enqueue(&store, &e3);
+        store.wait().await;
+        injector.set_policy(FaultPolicy::default());
+        for e in [&e1, &e2, &e3] {
+            if let Some(res) = store.load(e.hash()).await.unwrap() {
+                assert_eq!(res, (*e.key(), e.value().clone()));
+            }
+        }
+    }
 }
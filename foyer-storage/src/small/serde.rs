@@ -14,28 +14,37 @@
 
 use bytes::{Buf, BufMut};
 
+use crate::{
+    compress::{Codec, Compression},
+    error::Result,
+};
+
 /// max key/value len: `64 KiB - 1`
 ///
 /// # Format
 ///
 /// ```plain
-/// | hash 64b | key len 16b | value len 16b |
+/// | hash 64b | key len 16b | value len 16b | compression 8b | dictionary 32b |
 /// ```
 #[derive(Debug, PartialEq, Eq)]
 pub struct EntryHeader {
     hash: u64,
     key_len: u16,
     value_len: u16,
+    compression: u8,
+    dictionary: u32,
 }
 
 impl EntryHeader {
-    pub const ENTRY_HEADER_SIZE: usize = (16 + 16 + 64) / 8;
+    pub const ENTRY_HEADER_SIZE: usize = (16 + 16 + 64 + 8 + 32) / 8;
 
-    pub fn new(hash: u64, key_len: usize, value_len: usize) -> Self {
+    pub fn new(hash: u64, key_len: usize, value_len: usize, codec: Codec) -> Self {
         Self {
             hash,
             key_len: key_len as _,
             value_len: value_len as _,
+            compression: codec.compression.to_u8(),
+            dictionary: codec.dictionary,
         }
     }
 
@@ -54,6 +63,15 @@ impl EntryHeader {
         self.value_len as _
     }
 
+    /// The codec of the value, fails if the compression algorithm is not supported.
+    #[inline]
+    pub fn codec(&self) -> Result<Codec> {
+        Ok(Codec {
+            compression: Compression::try_from(self.compression)?,
+            dictionary: self.dictionary,
+        })
+    }
+
     #[inline]
     pub fn entry_len(&self) -> usize {
         Self::ENTRY_HEADER_SIZE + self.key_len() + self.value_len()
@@ -63,16 +81,22 @@ impl EntryHeader {
         buf.put_u64(self.hash);
         buf.put_u16(self.key_len);
         buf.put_u16(self.value_len);
+        buf.put_u8(self.compression);
+        buf.put_u32(self.dictionary);
     }
 
     pub fn read(mut buf: impl Buf) -> Self {
         let hash = buf.get_u64();
         let key_len = buf.get_u16();
         let value_len = buf.get_u16();
+        let compression = buf.get_u8();
+        let dictionary = buf.get_u32();
         Self {
             hash,
             key_len,
             value_len,
+            compression,
+            dictionary,
         }
     }
 }
@@ -88,10 +112,19 @@ mod tests {
             hash: 114514,
             key_len: 114,
             value_len: 514,
+            compression: Compression::Zstd.to_u8(),
+            dictionary: 1919810,
         };
         let mut buf = IoBytesMut::new();
         header.write(&mut buf);
         let h = EntryHeader::read(&buf[..]);
         assert_eq!(header, h);
+        assert_eq!(
+            h.codec().unwrap(),
+            Codec {
+                compression: Compression::Zstd,
+                dictionary: 1919810,
+            }
+        );
     }
 }
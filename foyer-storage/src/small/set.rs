@@ -26,7 +26,7 @@ use super::{batch::Item, bloom_filter::BloomFilterU64, serde::EntryHeader};
 use crate::{
     error::Result,
     serde::{Checksummer, EntryDeserializer},
-    IoBytes, IoBytesMut,
+    CompressionConfig, IoBytes, IoBytesMut,
 };
 
 pub type SetId = u64;
@@ -162,11 +162,15 @@ impl SetStorage {
         // Rebuild bloom filter.
         self.bloom_filter.clear();
 
-        while rcursor < self.len {
+        while rcursor + EntryHeader::ENTRY_HEADER_SIZE <= self.len {
             let header = EntryHeader::read(
                 &self.buffer
                     [Self::SET_HEADER_SIZE + rcursor..Self::SET_HEADER_SIZE + rcursor + EntryHeader::ENTRY_HEADER_SIZE],
             );
+            // Drop the bad entry and the entries after it.
+            if rcursor + header.entry_len() > self.len {
+                break;
+            }
 
             if !deletes.contains(&header.hash()) {
                 if rcursor != wcursor {
@@ -213,7 +217,7 @@ impl SetStorage {
         self.len = cursor - Self::SET_HEADER_SIZE;
     }
 
-    pub fn get<K, V>(&self, hash: u64) -> Result<Option<(K, V)>>
+    pub fn get<K, V>(&self, hash: u64, compression: &CompressionConfig) -> Result<Option<(K, V)>>
     where
         K: StorageKey,
         V: StorageValue,
@@ -224,7 +228,7 @@ impl SetStorage {
         for entry in self.iter() {
             if hash == entry.hash {
                 let k = EntryDeserializer::deserialize_key::<K>(entry.key)?;
-                let v = EntryDeserializer::deserialize_value::<V>(entry.value, crate::Compression::None)?;
+                let v = EntryDeserializer::decompress_value::<V>(entry.value, entry.header.codec()?, compression)?;
                 return Ok(Some((k, v)));
             }
         }
@@ -281,6 +285,7 @@ impl SetStorage {
 
 pub struct SetEntry<'a> {
     offset: usize,
+    pub header: EntryHeader,
     pub hash: u64,
     pub key: &'a [u8],
     pub value: &'a [u8],
@@ -317,15 +322,28 @@ impl<'a> SetIter<'a> {
         if !self.is_valid() {
             return None;
         }
-        let mut cursor = self.offset;
-        let header = EntryHeader::read(&self.set.data()[cursor..cursor + EntryHeader::ENTRY_HEADER_SIZE]);
-        cursor += EntryHeader::ENTRY_HEADER_SIZE;
+        // A bad entry, e.g. the one written in another format, ends the iteration, so it and the entries after it are
+        // treated as misses.
+        let data = &self.set.data()[..self.set.len];
+        let Some(header) = data
+            .get(self.offset..self.offset + EntryHeader::ENTRY_HEADER_SIZE)
+            .map(EntryHeader::read)
+        else {
+            self.offset = self.set.len;
+            return None;
+        };
+        if self.offset + header.entry_len() > data.len() {
+            self.offset = self.set.len;
+            return None;
+        }
+        let mut cursor = self.offset + EntryHeader::ENTRY_HEADER_SIZE;
         let value = &self.set.data()[cursor..cursor + header.value_len()];
         cursor += header.value_len();
         let key = &self.set.data()[cursor..cursor + header.key_len()];
         let entry = SetEntry {
             offset: self.offset,
             hash: header.hash(),
+            header,
             key,
             value,
         };
@@ -356,41 +374,47 @@ mod tests {
     use foyer_memory::{Cache, CacheBuilder, CacheEntry};
 
     use super::*;
-    use crate::{serde::EntrySerializer, test_utils::metrics_for_test, Compression};
+    use crate::{
+        compress::{Codec, Compression},
+        serde::EntrySerializer,
+        test_utils::metrics_for_test,
+    };
 
     const PAGE: usize = 4096;
 
     fn buffer(entry: &CacheEntry<u64, Vec<u8>>) -> IoBytes {
+        buffer_with_compression(entry, &CompressionConfig::default())
+    }
+
+    fn buffer_with_compression(entry: &CacheEntry<u64, Vec<u8>>, compression: &CompressionConfig) -> IoBytes {
         let mut buf = IoBytesMut::new();
 
         // reserve header
-        let header = EntryHeader::new(0, 0, 0);
+        let header = EntryHeader::new(0, 0, 0, Codec::RAW);
         header.write(&mut buf);
 
-        let info = EntrySerializer::serialize(
-            entry.key(),
-            entry.value(),
-            &Compression::None,
-            &mut buf,
-            metrics_for_test(),
-        )
-        .unwrap();
+        let info =
+            EntrySerializer::serialize(entry.key(), entry.value(), compression, &mut buf, metrics_for_test()).unwrap();
 
-        let header = EntryHeader::new(entry.hash(), info.key_len, info.value_len);
+        let header = EntryHeader::new(entry.hash(), info.key_len, info.value_len, info.codec);
         header.write(&mut buf[0..EntryHeader::ENTRY_HEADER_SIZE]);
 
         buf.freeze()
     }
 
     fn assert_some(storage: &SetStorage, entry: &CacheEntry<u64, Vec<u8>>) {
-        let ret = storage.get::<u64, Vec<u8>>(entry.hash()).unwrap();
+        let ret = storage
+            .get::<u64, Vec<u8>>(entry.hash(), &CompressionConfig::default())
+            .unwrap();
         let (k, v) = ret.unwrap();
         assert_eq!(&k, entry.key());
         assert_eq!(&v, entry.value());
     }
 
     fn assert_none(storage: &SetStorage, hash: u64) {
-        let ret = storage.get::<u64, Vec<u8>>(hash).unwrap();
+        let ret = storage
+            .get::<u64, Vec<u8>>(hash, &CompressionConfig::default())
+            .unwrap();
         assert!(ret.is_none());
     }
 
@@ -500,4 +524,89 @@ mod tests {
         assert_none(&storage, e3.hash());
         assert_some(&storage, &e4);
     }
+
+    #[test]
+    fn test_set_storage_compression() {
+        let memory = memory_for_test();
+
+        let mut buf = IoBytesMut::with_capacity(PAGE);
+        buf.put_bytes(0, PAGE);
+        let mut storage = SetStorage::load(buf, 0);
+        assert!(storage.is_empty());
+
+        let compression = CompressionConfig::new(Compression::Zstd);
+        let e1 = memory.insert(1, vec![b'1'; 1024]);
+        let b1 = buffer_with_compression(&e1, &compression);
+        assert!(b1.len() < buffer(&e1).len());
+
+        storage.apply(
+            &HashSet::new(),
+            vec![Item {
+                buffer: b1.clone(),
+                entry: e1.clone(),
+            }],
+        );
+        assert_eq!(storage.len(), b1.len());
+
+        // The codec is recorded per entry, the entry can be read with any compression config without dictionary.
+        assert_some(&storage, &e1);
+        let (k, v) = storage.get::<u64, Vec<u8>>(e1.hash(), &compression).unwrap().unwrap();
+        assert_eq!(&k, e1.key());
+        assert_eq!(&v, e1.value());
+    }
+
+    #[test]
+    fn test_set_storage_bad_entry() {
+        let memory = memory_for_test();
+
+        let mut buf = IoBytesMut::with_capacity(PAGE);
+        buf.put_bytes(0, PAGE);
+        let mut storage = SetStorage::load(buf, 0);
+
+        let e1 = memory.insert(1, vec![b'1'; 42]);
+        let e2 = memory.insert(2, vec![b'2'; 97]);
+        let b1 = buffer(&e1);
+        storage.apply(
+            &HashSet::new(),
+            vec![
+                Item {
+                    buffer: b1.clone(),
+                    entry: e1.clone(),
+                },
+                Item {
+                    buffer: buffer(&e2),
+                    entry: e2.clone(),
+                },
+            ],
+        );
+        assert_some(&storage, &e1);
+        assert_some(&storage, &e2);
+
+        // The second entry claims a value beyond the set, e.g. written in another format, but passes the set checksum.
+        let header = EntryHeader::new(e2.hash(), 8, 60000, Codec::RAW);
+        let offset = SetStorage::SET_HEADER_SIZE + b1.len();
+        header.write(&mut storage.buffer[offset..offset + EntryHeader::ENTRY_HEADER_SIZE]);
+        storage.update();
+        let mut buf = IoBytesMut::with_capacity(PAGE);
+        buf.put_slice(&storage.freeze());
+        let mut storage = SetStorage::load(buf, 0);
+
+        assert_some(&storage, &e1);
+        assert_none(&storage, e2.hash());
+
+        // The bad entry and the entries after it are dropped on the next deletion.
+        let e3 = memory.insert(3, vec![b'3'; 211]);
+        let b3 = buffer(&e3);
+        storage.apply(
+            &HashSet::from_iter([4]),
+            vec![Item {
+                buffer: b3.clone(),
+                entry: e3.clone(),
+            }],
+        );
+        assert_eq!(storage.len(), b1.len() + b3.len());
+        assert_some(&storage, &e1);
+        assert_none(&storage, e2.hash());
+        assert_some(&storage, &e3);
+    }
 }
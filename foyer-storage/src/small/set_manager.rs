@@ -28,9 +28,9 @@ use super::{
     set_cache::SetCache,
 };
 use crate::{
-    device::{Dev, MonitoredDevice, RegionId},
+    device::{scheduler::IoClass, Dev, MonitoredDevice, RegionId},
     error::Result,
-    IoBytesMut,
+    CompressionConfig, IoBytesMut,
 };
 
 /// # Lock Order
@@ -67,6 +67,7 @@ struct SetManagerInner {
     device: MonitoredDevice,
     regions: Range<RegionId>,
     flush: bool,
+    compression: CompressionConfig,
 }
 
 #[derive(Clone)]
@@ -86,6 +87,7 @@ impl Debug for SetManager {
             .field("device", &self.inner.device)
             .field("regions", &self.inner.regions)
             .field("flush", &self.inner.flush)
+            .field("compression", &self.inner.compression)
             .finish()
     }
 }
@@ -125,6 +127,7 @@ impl SetManager {
             device,
             regions,
             flush: config.flush,
+            compression: config.compression.clone(),
         };
         let inner = Arc::new(inner);
         Ok(Self { inner })
@@ -152,12 +155,12 @@ impl SetManager {
 
         // Query form set cache.
         if let Some(cached) = self.inner.set_cache.lookup(&sid) {
-            return cached.get(hash);
+            return cached.get(hash, &self.inner.compression);
         }
 
         // Set cache miss, load from disk.
-        let storage = self.storage(sid).await?;
-        let res = storage.get(hash);
+        let storage = self.storage(sid, IoClass::Foreground).await?;
+        let res = storage.get(hash, &self.inner.compression);
 
         // Update set cache on cache miss.
         self.inner.set_cache.insert(sid, storage);
@@ -179,7 +182,7 @@ impl SetManager {
 
         self.inner.set_cache.invalid(&sid);
 
-        let mut storage = self.storage(sid).await?;
+        let mut storage = self.storage(sid, IoClass::Flush).await?;
         storage.apply(deletions, items);
         storage.update();
 
@@ -187,7 +190,10 @@ impl SetManager {
 
         let buffer = storage.freeze();
         let (region, offset) = self.locate(sid);
-        self.inner.device.write(buffer, region, offset).await?;
+        self.inner
+            .device
+            .write_with_class(buffer, region, offset, IoClass::Flush)
+            .await?;
         if self.inner.flush {
             self.inner.device.flush(Some(region)).await?;
         }
@@ -224,9 +230,13 @@ impl SetManager {
         metadata.flush(&self.inner.device).await
     }
 
-    async fn storage(&self, id: SetId) -> Result<SetStorage> {
+    async fn storage(&self, id: SetId, class: IoClass) -> Result<SetStorage> {
         let (region, offset) = self.locate(id);
-        let buffer = self.inner.device.read(region, offset, self.inner.set_size).await?;
+        let buffer = self
+            .inner
+            .device
+            .read_with_class(region, offset, self.inner.set_size, class)
+            .await?;
         let storage = SetStorage::load(buffer, self.watermark().await);
         Ok(storage)
     }
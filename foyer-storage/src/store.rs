@@ -17,6 +17,7 @@ use std::{
     fmt::{Debug, Display},
     hash::Hash,
     marker::PhantomData,
+    path::PathBuf,
     str::FromStr,
     sync::Arc,
     time::Instant,
@@ -27,21 +28,25 @@ use foyer_common::{
     bits,
     code::{HashBuilder, StorageKey, StorageValue},
     metrics::Metrics,
+    mrc::{MissRatioCurve, MissRatioCurveConfig, MissRatioCurveTracker},
     runtime::BackgroundShutdownRuntime,
 };
 use foyer_memory::{Cache, CacheEntry};
 use serde::{Deserialize, Serialize};
-use tokio::runtime::Handle;
+use tokio::{runtime::Handle, sync::Mutex};
 
 use crate::{
-    compress::Compression,
+    compress::{Compression, CompressionConfig},
     device::{
         monitor::{DeviceStats, Monitored, MonitoredConfig},
+        scheduler::IoSchedulerConfig,
+        throttle::Throttle,
         DeviceConfig, RegionId, ALIGN,
     },
     engine::{EngineConfig, EngineEnum, SizeSelector},
     error::{Error, Result},
     large::{generic::GenericLargeStorageConfig, recover::RecoverMode, tombstone::TombstoneLogConfig},
+    manifest::{Manifest, Pinned},
     picker::{
         utils::{AdmitAllPicker, FifoPicker, InvalidRatioPicker, RejectAllPicker},
         AdmissionPicker, EvictionPicker, ReinsertionPicker,
@@ -54,7 +59,7 @@ use crate::{
         either::{EitherConfig, Order},
         Storage,
     },
-    Dev, DevExt, DirectFileDeviceOptions, DirectFsDeviceOptions,
+    Dev, DevExt, DirectFileDeviceOptions, DirectFsDeviceOptions, MemoryDeviceOptions, StripedDeviceOptions,
 };
 
 /// The disk cache engine that serves as the storage backend of `foyer`.
@@ -85,6 +90,11 @@ where
 
     statistics: Arc<Statistics>,
     metrics: Arc<Metrics>,
+
+    mrc: Option<MissRatioCurveTracker>,
+
+    /// The path and the content of the manifest, rewritten on resizing.
+    manifest: Mutex<Option<(PathBuf, Manifest)>>,
 }
 
 impl<K, V, S> Debug for Store<K, V, S>
@@ -141,6 +151,9 @@ where
 
         if force || self.pick(entry.key()) {
             let estimated_size = EntrySerializer::estimated_size(entry.key(), entry.value());
+            if let Some(mrc) = self.inner.mrc.as_ref() {
+                mrc.insert(entry.hash(), estimated_size);
+            }
             self.inner.engine.enqueue(entry, estimated_size);
         }
 
@@ -155,6 +168,9 @@ where
         Q: Hash + Eq + ?Sized + Send + Sync + 'static,
     {
         let hash = self.inner.memory.hash(key);
+        if let Some(mrc) = self.inner.mrc.as_ref() {
+            mrc.access(hash, None);
+        }
         let future = self.inner.engine.load(hash);
         match self.inner.runtime.read().spawn(future).await.unwrap() {
             Ok(Some((k, v))) if k.borrow() == key => Ok(Some((k, v))),
@@ -190,6 +206,32 @@ where
         self.inner.engine.destroy().await
     }
 
+    /// Resize the capacity of the disk cache online.
+    ///
+    /// Only the large object disk cache engine with a [`DirectFsDevice`] or a [`DirectFileDevice`] on a regular file
+    /// supports resizing. The capacity must be a multiplier of the region size.
+    ///
+    /// On growing, the device is extended with new regions. On shrinking, the regions with the highest ids are drained
+    /// by the reclaimers, the entries picked by the reinsertion picker are reinserted into the other regions, then the
+    /// device is truncated. The disk cache keeps serving during resizing.
+    ///
+    /// The manifest is updated with the new capacity, so the disk cache must be reopened with the new capacity later.
+    ///
+    /// [`DirectFsDevice`]: crate::DirectFsDevice
+    /// [`DirectFileDevice`]: crate::DirectFileDevice
+    pub async fn resize(&self, capacity: usize) -> Result<()> {
+        let mut manifest = self.inner.manifest.lock().await;
+
+        self.inner.engine.resize(capacity).await?;
+
+        if let Some((path, manifest)) = manifest.as_mut() {
+            manifest.capacity = capacity;
+            manifest.store(path)?;
+        }
+
+        Ok(())
+    }
+
     /// Get the statistics information of the disk cache.
     pub fn stats(&self) -> Arc<DeviceStats> {
         self.inner.engine.stats()
@@ -199,6 +241,14 @@ where
     pub fn runtime(&self) -> &Runtime {
         &self.inner.runtime
     }
+
+    /// Get the estimated miss ratio curve of the disk cache.
+    ///
+    /// Returns `None` if the miss ratio curve estimation is not enabled with
+    /// [`StoreBuilder::with_miss_ratio_curve`].
+    pub fn miss_ratio_curve(&self) -> Option<MissRatioCurve> {
+        self.inner.mrc.as_ref().map(|mrc| mrc.curve())
+    }
 }
 
 /// The configurations for the device.
@@ -222,12 +272,38 @@ impl From<DirectFsDeviceOptions> for DeviceOptions {
     }
 }
 
+impl From<MemoryDeviceOptions> for DeviceOptions {
+    fn from(options: MemoryDeviceOptions) -> Self {
+        Self::DeviceConfig(options.into())
+    }
+}
+
+#[cfg(any(test, feature = "fault_injection"))]
+impl From<crate::FaultInjectedDeviceOptions> for DeviceOptions {
+    fn from(options: crate::FaultInjectedDeviceOptions) -> Self {
+        Self::DeviceConfig(options.into())
+    }
+}
+
+impl From<StripedDeviceOptions> for DeviceOptions {
+    fn from(options: StripedDeviceOptions) -> Self {
+        Self::DeviceConfig(options.into())
+    }
+}
+
+#[cfg(all(feature = "io_uring", target_os = "linux"))]
+impl From<crate::IoUringDeviceOptions> for DeviceOptions {
+    fn from(options: crate::IoUringDeviceOptions) -> Self {
+        Self::DeviceConfig(options.into())
+    }
+}
+
 /// [`Engine`] controls the ratio of the large object disk cache and the small object disk cache.
 ///
 /// If [`Engine::Mixed`] is used, it will use the `Either` engine
 /// with the small object disk cache as the left engine,
 /// and the large object disk cache as the right engine.
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum Engine {
     /// All space are used as the large object disk cache.
     Large,
@@ -352,9 +428,13 @@ where
     runtime_config: RuntimeOptions,
 
     admission_picker: Arc<dyn AdmissionPicker<Key = K>>,
-    compression: Compression,
+    compression: CompressionConfig,
     recover_mode: RecoverMode,
+    wipe_on_manifest_mismatch: bool,
     flush: bool,
+    throttle: Throttle,
+    io_scheduler_config: IoSchedulerConfig,
+    miss_ratio_curve_config: Option<MissRatioCurveConfig>,
 
     large: LargeEngineOptions<K, V, S>,
     small: SmallEngineOptions<K, V, S>,
@@ -381,9 +461,13 @@ where
             runtime_config: RuntimeOptions::Disabled,
 
             admission_picker: Arc::<AdmitAllPicker<K>>::default(),
-            compression: Compression::None,
+            compression: CompressionConfig::default(),
             recover_mode: RecoverMode::Quiet,
+            wipe_on_manifest_mismatch: false,
             flush: false,
+            throttle: Throttle::default(),
+            io_scheduler_config: IoSchedulerConfig::default(),
+            miss_ratio_curve_config: None,
 
             large: LargeEngineOptions::new(),
             small: SmallEngineOptions::new(),
@@ -414,11 +498,37 @@ where
         self
     }
 
-    /// Set the compression algorithm of the disk cache store.
+    /// Set the i/o budgets of the disk cache device.
+    ///
+    /// The reads and writes on the device are throttled to the read throughput, the write throughput and the iops of
+    /// the budgets. The lookups are throttled last. See [`Throttle`] for details.
+    ///
+    /// Default: unlimited.
+    pub fn with_throttle(mut self, throttle: Throttle) -> Self {
+        self.throttle = throttle;
+        self
+    }
+
+    /// Set the config of the i/o scheduler of the disk cache device.
+    ///
+    /// The i/o scheduler limits the in-flight background i/o, e.g. the flushes and the reclamations, to the depth of
+    /// each class while the lookups are reading the device. See [`IoSchedulerConfig`] for details.
+    ///
+    /// Default: no limit.
+    pub fn with_io_scheduler_config(mut self, config: IoSchedulerConfig) -> Self {
+        self.io_scheduler_config = config;
+        self
+    }
+
+    /// Set the compression of the disk cache store, with either a [`Compression`] algorithm or a
+    /// [`CompressionConfig`] with the level, the thresholds and the zstd dictionary.
+    ///
+    /// Only the algorithm is pinned by the manifest. The entries compressed with another zstd dictionary are treated as
+    /// misses.
     ///
     /// Default: [`Compression::None`].
-    pub fn with_compression(mut self, compression: Compression) -> Self {
-        self.compression = compression;
+    pub fn with_compression(mut self, compression: impl Into<CompressionConfig>) -> Self {
+        self.compression = compression.into();
         self
     }
 
@@ -432,6 +542,40 @@ where
         self
     }
 
+    /// Set whether to wipe the disk cache if its layout mismatches the one pinned by the manifest.
+    ///
+    /// The layout of the disk cache, including the device geometry, the engine, the set size, the compression algorithm
+    /// and the hasher, is pinned by a manifest on the device on the first open. The disk cache cannot recover from the
+    /// data written with another layout. The data found on the device without a manifest is treated as a mismatch, for
+    /// its layout is unknown.
+    ///
+    /// If enabled, the disk cache is wiped and reinitialized with the new layout on mismatch. Otherwise, the build fails
+    /// with [`Error::ManifestMismatch`].
+    ///
+    /// NOTE: The disk cache directory written by the versions without the manifest has data but no manifest, so it
+    /// fails to open with [`Error::ManifestMismatch`] after upgrading. Set `with_wipe_on_manifest_mismatch(true)` to
+    /// wipe and reinitialize it, or remove the directory before upgrading. The raw block devices are not affected, for
+    /// their data cannot be detected.
+    ///
+    /// Default: `false`.
+    pub fn with_wipe_on_manifest_mismatch(mut self, wipe: bool) -> Self {
+        self.wipe_on_manifest_mismatch = wipe;
+        self
+    }
+
+    /// Enable the miss ratio curve estimation, which estimates the miss ratio of the disk cache with each of the given
+    /// capacities. The capacities are in bytes.
+    ///
+    /// Each load is counted as a reference, and each entry picked to be written refreshes the recency of the key.
+    ///
+    /// The estimation is exported as metrics, and can be read with [`Store::miss_ratio_curve`].
+    ///
+    /// The miss ratio curve estimation is disabled by default.
+    pub fn with_miss_ratio_curve(mut self, config: MissRatioCurveConfig) -> Self {
+        self.miss_ratio_curve_config = Some(config);
+        self
+    }
+
     /// Set the admission pickers for th disk cache store.
     ///
     /// The admission picker is used to pick the entries that can be inserted into the disk cache store.
@@ -478,7 +622,12 @@ where
         let metrics = Arc::new(Metrics::new(&self.name));
         let statistics = Arc::<Statistics>::default();
 
-        let compression = self.compression;
+        let compression = self.compression.algorithm();
+
+        let mrc = self
+            .miss_ratio_curve_config
+            .as_ref()
+            .map(|config| MissRatioCurveTracker::new(&self.name, "storage", config));
 
         let build_runtime = |config: &TokioRuntimeOptions, suffix: &str| {
             let mut builder = tokio::runtime::Builder::new_multi_thread();
@@ -517,7 +666,7 @@ where
         };
         let runtime = Runtime::new(read_runtime, write_runtime, user_runtime_handle);
 
-        let engine = {
+        let (engine, manifest) = {
             let statistics = statistics.clone();
             let metrics = metrics.clone();
             let runtime = runtime.clone();
@@ -528,28 +677,64 @@ where
                     tracing::warn!(
                         "[store builder]: No device config set. Use `NoneStore` which always returns `None` for queries."
                     );
-                    EngineEnum::open(EngineConfig::Noop).await
+                    EngineEnum::open(EngineConfig::Noop).await.map(|engine| (engine, None))
                 }
-                DeviceOptions::DeviceConfig(options) => {
+                DeviceOptions::DeviceConfig(mut options) => {
+                    if let DeviceConfig::Striped(config) = &mut options {
+                        config.set_default_name(&self.name);
+                    }
+                    // Check the layout before the device is opened, for opening the device with another layout may
+                    // resize its files and destroy the data even if the open is rejected later.
+                    let mut recover_mode = self.recover_mode;
+                    let mut pinned = None;
+                    if let Some(path) = options.manifest_path() {
+                        let (capacity, region_size) = options.layout();
+                        let manifest = Manifest {
+                            capacity,
+                            region_size,
+                            engine: self.engine,
+                            set_size: match self.engine {
+                                Engine::Large => None,
+                                Engine::Small | Engine::Mixed(_) => Some(self.small.set_size),
+                            },
+                            compression: self.compression.algorithm(),
+                            hasher: std::any::type_name::<S>().to_string(),
+                        };
+                        let res = manifest.check(&path, options.occupied(), self.wipe_on_manifest_mismatch)?;
+                        if res == Pinned::Replaced {
+                            recover_mode = RecoverMode::None;
+                            if let Some(config) = self.large.tombstone_log_config.as_ref() {
+                                match std::fs::remove_file(&config.path) {
+                                    Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+                                    _ => {}
+                                }
+                            }
+                        }
+                        pinned = Some((path, manifest, res));
+                    }
+
                     let device = match Monitored::open(MonitoredConfig {
                         config: options,
                         metrics: metrics.clone(),
+                        throttle: self.throttle.clone(),
+                        scheduler: self.io_scheduler_config.clone(),
                     }, runtime.clone())
                     .await {
                         Ok(device) => device,
                         Err(e) =>return Err(e),
                     };
-                    match self.engine {
+
+                    let engine = match self.engine {
                         Engine::Large => {
                             let regions = 0..device.regions() as RegionId;
                             EngineEnum::open(EngineConfig::Large(GenericLargeStorageConfig {
                                 name: self.name,
                                 device,
                                 regions,
-                                compression: self.compression,
+                                compression: self.compression.clone(),
                                 flush: self.flush,
                                 indexer_shards: self.large.indexer_shards,
-                                recover_mode: self.recover_mode,
+                                recover_mode,
                                 recover_concurrency: self.large.recover_concurrency,
                                 flushers: self.large.flushers,
                                 reclaimers: self.large.reclaimers,
@@ -573,6 +758,7 @@ where
                                 set_cache_shards: self.small.set_cache_shards,
                                 device,
                                 regions,
+                                compression: self.compression.clone(),
                                 flush: self.flush,
                                 flushers: self.small.flushers,
                                 buffer_pool_size: self.small.buffer_pool_size,
@@ -594,6 +780,7 @@ where
                                     set_cache_shards: self.small.set_cache_shards,
                                     device: device.clone(),
                                     regions: small_regions,
+                                    compression: self.compression.clone(),
                                     flush: self.flush,
                                     flushers: self.small.flushers,
                                     buffer_pool_size: self.small.buffer_pool_size,
@@ -605,10 +792,10 @@ where
                                     name: self.name,
                                     device,
                                     regions: large_regions,
-                                    compression: self.compression,
+                                    compression: self.compression.clone(),
                                     flush: self.flush,
                                     indexer_shards: self.large.indexer_shards,
-                                    recover_mode: self.recover_mode,
+                                    recover_mode,
                                     recover_concurrency: self.large.recover_concurrency,
                                     flushers: self.large.flushers,
                                     reclaimers: self.large.reclaimers,
@@ -626,7 +813,21 @@ where
                             }))
                             .await
                         }
-                    }
+                    }?;
+                    // Store the manifest only after the stale data is wiped, so the wipe is redone if it is interrupted.
+                    let manifest = match pinned {
+                        Some((path, manifest, res)) => {
+                            if res == Pinned::Replaced {
+                                engine.destroy().await?;
+                            }
+                            if res != Pinned::Matched {
+                                manifest.store(&path)?;
+                            }
+                            Some((path, manifest))
+                        }
+                        None => None,
+                    };
+                    Ok((engine, manifest))
                 }
             }
         }).await.unwrap()?
@@ -640,6 +841,8 @@ where
             runtime,
             statistics,
             metrics,
+            mrc,
+            manifest: Mutex::new(manifest),
         };
         let inner = Arc::new(inner);
         let store = Store { inner };
@@ -14,12 +14,21 @@
 
 #![expect(clippy::identity_op)]
 
-use std::{path::Path, sync::Arc, time::Duration};
+use std::{
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
 
 use ahash::RandomState;
+use foyer_common::mrc::MissRatioCurveConfig;
 use foyer_memory::{Cache, CacheBuilder, CacheEntry, FifoConfig};
 use foyer_storage::{
-    test_utils::Recorder, Compression, DirectFsDeviceOptions, Engine, LargeEngineOptions, StoreBuilder,
+    test_utils::Recorder, Compression, CompressionConfig, DirectFsDeviceOptions, Engine, Error, IoClass,
+    IoSchedulerConfig, LargeEngineOptions, MemoryDeviceOptions, StoreBuilder, Throttle, ZstdDictionary,
 };
 
 const KB: usize = 1024;
@@ -145,6 +154,54 @@ async fn test_direct_fs_store_zstd() {
     test_store(memory, builder, recorder).await;
 }
 
+#[test_log::test(tokio::test)]
+async fn test_direct_fs_store_zstd_dictionary() {
+    let value = |i: u64| format!("{{\"id\":{i},\"name\":\"user-{}\",\"role\":\"member\"}}", i * 7).into_bytes();
+    let samples = (0..1000).map(value).collect::<Vec<_>>();
+    let dictionary = ZstdDictionary::train(&samples, 4 * KB).unwrap();
+    let other = ZstdDictionary::train(&samples[..500], 2 * KB).unwrap();
+    assert_ne!(dictionary.id(), other.id());
+
+    let tempdir = tempfile::tempdir().unwrap();
+    let memory: Cache<u64, Vec<u8>> = CacheBuilder::new(1).with_eviction_config(FifoConfig::default()).build();
+    let builder = |dictionary: &ZstdDictionary| {
+        StoreBuilder::new(memory.clone(), Engine::Large)
+            .with_device_options(
+                DirectFsDeviceOptions::new(tempdir.path())
+                    .with_capacity(4 * MB)
+                    .with_file_size(MB),
+            )
+            .with_flush(true)
+            .with_compression(
+                CompressionConfig::new(Compression::Zstd)
+                    .with_min_size(16)
+                    .with_zstd_dictionary(dictionary.clone()),
+            )
+    };
+
+    let store = builder(&dictionary).build().await.unwrap();
+    for i in 0..INSERTS as u64 {
+        let e = memory.insert(i, value(i));
+        store.enqueue(e.clone(), false);
+        wait(e, 1).await;
+    }
+    store.close().await.unwrap();
+    drop(store);
+
+    let store = builder(&dictionary).build().await.unwrap();
+    for i in 0..INSERTS as u64 {
+        assert_eq!(store.load(&i).await.unwrap().map(|(_, v)| v), Some(value(i)));
+    }
+    store.close().await.unwrap();
+    drop(store);
+
+    // The entries compressed with another dictionary are treated as misses.
+    let store = builder(&other).build().await.unwrap();
+    for i in 0..INSERTS as u64 {
+        assert!(store.load(&i).await.unwrap().is_none());
+    }
+}
+
 #[test_log::test(tokio::test)]
 async fn test_direct_fs_store_lz4() {
     let tempdir = tempfile::tempdir().unwrap();
@@ -154,3 +211,352 @@ async fn test_direct_fs_store_lz4() {
     let builder = |memory: &Cache<u64, Vec<u8>>| basic(memory, tempdir.path(), &r).with_compression(Compression::Lz4);
     test_store(memory, builder, recorder).await;
 }
+
+#[test_log::test(tokio::test)]
+async fn test_direct_fs_mixed_store_compression() {
+    // Values below and above the object size threshold go to the small and the large object disk cache respectively.
+    let value = |i: u64| {
+        let len = if i % 2 == 0 { 1 * KB } else { 8 * KB };
+        format!("{{\"id\":{i},\"name\":\"user-{}\",\"role\":\"member\"}}", i * 7)
+            .into_bytes()
+            .into_iter()
+            .cycle()
+            .take(len)
+            .collect::<Vec<_>>()
+    };
+    let samples = (0..1000).map(value).filter(|v| v.len() < KB * 2).collect::<Vec<_>>();
+    let dictionary = ZstdDictionary::train(&samples, 4 * KB).unwrap();
+
+    for compression in [
+        CompressionConfig::new(Compression::Zstd),
+        CompressionConfig::new(Compression::Lz4),
+        CompressionConfig::new(Compression::Zstd).with_zstd_dictionary(dictionary),
+    ] {
+        let tempdir = tempfile::tempdir().unwrap();
+        let memory: Cache<u64, Vec<u8>> = CacheBuilder::new(INSERTS)
+            .with_shards(1)
+            .with_eviction_config(FifoConfig::default())
+            .build();
+        let store = StoreBuilder::new(memory.clone(), Engine::Mixed(0.5))
+            .with_device_options(
+                DirectFsDeviceOptions::new(tempdir.path())
+                    .with_capacity(16 * MB)
+                    .with_file_size(MB),
+            )
+            .with_flush(true)
+            .with_compression(compression.clone())
+            .build()
+            .await
+            .unwrap();
+
+        // The small object disk cache releases the entries before the sets are written, close the store to wait for the
+        // flushers. The memory cache holds all the entries, so that none of them is outdated before flushed.
+        for i in 0..INSERTS as u64 {
+            store.enqueue(memory.insert(i, value(i)), false);
+        }
+        store.close().await.unwrap();
+
+        for i in 0..INSERTS as u64 {
+            assert_eq!(
+                store.load(&i).await.unwrap().map(|(_, v)| v),
+                Some(value(i)),
+                "compression: {compression:?}, key: {i}"
+            );
+        }
+    }
+}
+
+#[test_log::test(tokio::test)]
+async fn test_direct_fs_store_io_scheduler() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let recorder = Arc::new(Recorder::default());
+    let memory = CacheBuilder::new(1).with_eviction_config(FifoConfig::default()).build();
+    let r = recorder.clone();
+    let builder = |memory: &Cache<u64, Vec<u8>>| {
+        basic(memory, tempdir.path(), &r).with_io_scheduler_config(
+            IoSchedulerConfig::new()
+                .with_depth(IoClass::Flush, 1)
+                .with_depth(IoClass::Reclaim, 1)
+                .with_depth(IoClass::Reinsertion, 1)
+                .with_depth(IoClass::Recovery, 1),
+        )
+    };
+    test_store(memory, builder, recorder).await;
+
+    // The flushes paused by the steady foreground reads are issued after the max wait.
+    let tempdir = tempfile::tempdir().unwrap();
+    let recorder = Arc::new(Recorder::default());
+    let memory = CacheBuilder::new(1).with_eviction_config(FifoConfig::default()).build();
+    let store = basic(&memory, tempdir.path(), &recorder)
+        .with_io_scheduler_config(
+            IoSchedulerConfig::new()
+                .with_depth(IoClass::Flush, 0)
+                .with_max_wait(Duration::from_millis(20)),
+        )
+        .build()
+        .await
+        .unwrap();
+
+    let e = memory.insert(0, vec![0; KB]);
+    store.enqueue(e.clone(), false);
+    wait(e, 1).await;
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let readers = (0..4)
+        .map(|_| {
+            let store = store.clone();
+            let stop = stop.clone();
+            tokio::spawn(async move {
+                let mut loads = 0;
+                while !stop.load(Ordering::Relaxed) {
+                    assert_eq!(store.load(&0).await.unwrap().map(|(_, v)| v), Some(vec![0; KB]));
+                    loads += 1;
+                }
+                loads
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for i in 1..=20 {
+        let e = memory.insert(i, vec![i as u8; KB]);
+        store.enqueue(e.clone(), false);
+        tokio::time::timeout(Duration::from_secs(1), wait(e, 1)).await.unwrap();
+    }
+    stop.store(true, Ordering::Relaxed);
+    for reader in readers {
+        assert!(reader.await.unwrap() > 0);
+    }
+    for i in 1..=20 {
+        assert_eq!(store.load(&i).await.unwrap().map(|(_, v)| v), Some(vec![i as u8; KB]));
+    }
+}
+
+#[test_log::test(tokio::test)]
+async fn test_memory_store() {
+    let device = MemoryDeviceOptions::new().with_capacity(4 * MB).with_region_size(MB);
+    let recorder = Arc::new(Recorder::default());
+    let memory = CacheBuilder::new(1).with_eviction_config(FifoConfig::default()).build();
+    let r = recorder.clone();
+    let builder = |memory: &Cache<u64, Vec<u8>>| {
+        StoreBuilder::new(memory.clone(), Engine::Large)
+            .with_device_options(device.clone())
+            .with_admission_picker(r.clone())
+            .with_flush(true)
+            .with_large_object_disk_cache_options(
+                LargeEngineOptions::new()
+                    .with_recover_concurrency(2)
+                    .with_indexer_shards(4)
+                    .with_reinsertion_picker(r.clone()),
+            )
+    };
+    test_store(memory, builder, recorder).await;
+}
+
+#[test_log::test(tokio::test)]
+async fn test_direct_fs_store_throttle() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let memory: Cache<u64, Vec<u8>> = CacheBuilder::new(1).with_eviction_config(FifoConfig::default()).build();
+    let builder = || {
+        StoreBuilder::new(memory.clone(), Engine::Large)
+            .with_device_options(
+                DirectFsDeviceOptions::new(tempdir.path())
+                    .with_capacity(4 * MB)
+                    .with_file_size(MB),
+            )
+            .with_flush(true)
+            .with_throttle(
+                Throttle::new()
+                    .with_read_throughput(16 * MB)
+                    .with_write_throughput(MB)
+                    .with_iops(1000),
+            )
+    };
+
+    // The budgets start empty, so writing 100 4 KiB-aligned entries at 1 MiB/s takes about 400 ms.
+    let store = builder().build().await.unwrap();
+    let now = Instant::now();
+    for i in 0..INSERTS as u64 {
+        let e = memory.insert(i, vec![i as u8; KB]);
+        store.enqueue(e.clone(), false);
+        wait(e, 1).await;
+    }
+    assert!(
+        now.elapsed() >= Duration::from_millis(350),
+        "elapsed: {:?}",
+        now.elapsed()
+    );
+    for i in 0..INSERTS as u64 {
+        assert_eq!(store.load(&i).await.unwrap().map(|(_, v)| v), Some(vec![i as u8; KB]));
+    }
+    store.close().await.unwrap();
+    drop(store);
+
+    // The recovery is throttled as well.
+    let store = builder().build().await.unwrap();
+    for i in 0..INSERTS as u64 {
+        assert_eq!(store.load(&i).await.unwrap().map(|(_, v)| v), Some(vec![i as u8; KB]));
+    }
+}
+
+#[test_log::test(tokio::test)]
+async fn test_direct_fs_store_manifest() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let memory: Cache<u64, Vec<u8>> = CacheBuilder::new(1).with_eviction_config(FifoConfig::default()).build();
+    let builder = |file_size: usize| {
+        StoreBuilder::new(memory.clone(), Engine::Large)
+            .with_device_options(
+                DirectFsDeviceOptions::new(tempdir.path())
+                    .with_capacity(4 * MB)
+                    .with_file_size(file_size),
+            )
+            .with_flush(true)
+    };
+
+    let store = builder(MB).build().await.unwrap();
+    let e = memory.insert(1, vec![1; KB]);
+    store.enqueue(e.clone(), false);
+    wait(e, 1).await;
+    let e = memory.insert(2, vec![2; 64 * KB]);
+    store.enqueue(e.clone(), false);
+    wait(e, 1).await;
+    store.close().await.unwrap();
+    drop(store);
+
+    // Reopen with another layout.
+    assert!(matches!(
+        builder(16 * KB).build().await,
+        Err(Error::ManifestMismatch {
+            field: "region size",
+            ..
+        })
+    ));
+    assert!(matches!(
+        builder(2 * MB).build().await,
+        Err(Error::ManifestMismatch {
+            field: "region size",
+            ..
+        })
+    ));
+    assert!(matches!(
+        builder(MB).with_compression(Compression::Lz4).build().await,
+        Err(Error::ManifestMismatch {
+            field: "compression",
+            ..
+        })
+    ));
+
+    // The rejected reopens keep the data, even the one that shrinks the files.
+    let store = builder(MB).build().await.unwrap();
+    assert_eq!(store.load(&1).await.unwrap().map(|(_, v)| v), Some(vec![1; KB]));
+    assert_eq!(store.load(&2).await.unwrap().map(|(_, v)| v), Some(vec![2; 64 * KB]));
+    store.close().await.unwrap();
+    drop(store);
+
+    // The data without a manifest is written with an unknown layout.
+    std::fs::remove_file(tempdir.path().join("foyer-storage-manifest")).unwrap();
+    assert!(matches!(
+        builder(MB).build().await,
+        Err(Error::ManifestMismatch { field: "manifest", .. })
+    ));
+
+    // Wipe and reinitialize with the new layout.
+    let store = builder(2 * MB)
+        .with_wipe_on_manifest_mismatch(true)
+        .build()
+        .await
+        .unwrap();
+    assert!(store.load(&1).await.unwrap().is_none());
+    store.close().await.unwrap();
+    drop(store);
+
+    assert!(builder(2 * MB).build().await.is_ok());
+    assert!(builder(MB).build().await.is_err());
+}
+
+#[test_log::test(tokio::test)]
+async fn test_direct_fs_store_resize() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let memory: Cache<u64, Vec<u8>> = CacheBuilder::new(1).with_eviction_config(FifoConfig::default()).build();
+    let builder = |capacity: usize| {
+        StoreBuilder::new(memory.clone(), Engine::Large)
+            .with_device_options(
+                DirectFsDeviceOptions::new(tempdir.path())
+                    .with_capacity(capacity)
+                    .with_file_size(MB),
+            )
+            .with_flush(true)
+    };
+
+    let store = builder(4 * MB).build().await.unwrap();
+    let e = memory.insert(1, vec![1; KB]);
+    store.enqueue(e.clone(), false);
+    wait(e, 1).await;
+
+    store.resize(8 * MB).await.unwrap();
+    assert_eq!(store.load(&1).await.unwrap().map(|(_, v)| v), Some(vec![1; KB]));
+    let e = memory.insert(2, vec![2; KB]);
+    store.enqueue(e.clone(), false);
+    wait(e, 1).await;
+    store.close().await.unwrap();
+    drop(store);
+
+    // The manifest pins the new capacity.
+    assert!(matches!(
+        builder(4 * MB).build().await,
+        Err(Error::ManifestMismatch { field: "capacity", .. })
+    ));
+    let store = builder(8 * MB).build().await.unwrap();
+    assert_eq!(store.load(&1).await.unwrap().map(|(_, v)| v), Some(vec![1; KB]));
+    assert_eq!(store.load(&2).await.unwrap().map(|(_, v)| v), Some(vec![2; KB]));
+
+    store.resize(4 * MB).await.unwrap();
+    store.close().await.unwrap();
+    drop(store);
+
+    assert!(builder(8 * MB).build().await.is_err());
+    assert!(builder(4 * MB).build().await.is_ok());
+
+    // Only the large object disk cache engine can be resized.
+    let store = StoreBuilder::new(memory.clone(), Engine::small())
+        .with_device_options(MemoryDeviceOptions::new().with_capacity(4 * MB))
+        .build()
+        .await
+        .unwrap();
+    assert!(store.resize(8 * MB).await.is_err());
+}
+
+#[test_log::test(tokio::test)]
+async fn test_memory_store_miss_ratio_curve() {
+    let memory: Cache<u64, Vec<u8>> = CacheBuilder::new(1).with_eviction_config(FifoConfig::default()).build();
+    // The estimated size of each entry: the serialized `u64` key and the length-prefixed 1 KiB value.
+    let size = 8 + 8 + KB;
+    let store = StoreBuilder::new(memory.clone(), Engine::Large)
+        .with_device_options(MemoryDeviceOptions::new().with_capacity(4 * MB))
+        .with_miss_ratio_curve(MissRatioCurveConfig {
+            capacities: vec![50 * size, 100 * size, 200 * size],
+            sample_rate: 1.0,
+            ..Default::default()
+        })
+        .build()
+        .await
+        .unwrap();
+
+    // Loop over 150 keys: the disk cache misses everything with capacity 50 or 100 entries, and only misses the cold
+    // keys with capacity 200 entries.
+    for _ in 0..10 {
+        for i in 0..150 {
+            if store.load(&i).await.unwrap().is_none() {
+                let e = memory.insert(i, vec![i as u8; KB]);
+                store.enqueue(e.clone(), false);
+                wait(e, 1).await;
+            }
+        }
+    }
+
+    let curve = store.miss_ratio_curve().unwrap();
+    assert_eq!(curve.references, 1500);
+    assert_eq!(
+        curve.points,
+        vec![(50 * size, 1.0), (100 * size, 1.0), (200 * size, 0.1)]
+    );
+}
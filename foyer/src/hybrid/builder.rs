@@ -18,21 +18,26 @@ use ahash::RandomState;
 use foyer_common::{
     code::{HashBuilder, StorageKey, StorageValue},
     event::EventListener,
+    mrc::MissRatioCurveConfig,
     tracing::TracingOptions,
 };
-use foyer_memory::{Cache, CacheBuilder, EvictionConfig, Weighter};
+#[cfg(target_os = "linux")]
+use foyer_memory::SharedMemoryCache;
+use foyer_memory::{AdmissionConfig, Cache, CacheBuilder, Eviction, EvictionConfig, Handle, Weighter};
 use foyer_storage::{
-    AdmissionPicker, Compression, DeviceOptions, Engine, LargeEngineOptions, RecoverMode, RuntimeOptions,
-    SmallEngineOptions, StoreBuilder,
+    AdmissionPicker, CompressedCacheBuilder, Compression, CompressionConfig, DeviceOptions, Engine, IoSchedulerConfig,
+    LargeEngineOptions, RecoverMode, RuntimeOptions, SmallEngineOptions, StoreBuilder, Throttle,
 };
 
-use crate::HybridCache;
+use super::tier::MemoryTiers;
+use crate::{HotKeyConfig, HybridCache};
 
 /// Hybrid cache builder.
 pub struct HybridCacheBuilder<K, V> {
     name: String,
     event_listener: Option<Arc<dyn EventListener<Key = K, Value = V>>>,
     tracing_options: TracingOptions,
+    hot_key_config: Option<HotKeyConfig>,
 }
 
 impl<K, V> Default for HybridCacheBuilder<K, V> {
@@ -48,6 +53,7 @@ impl<K, V> HybridCacheBuilder<K, V> {
             name: "foyer".to_string(),
             event_listener: None,
             tracing_options: TracingOptions::default(),
+            hot_key_config: None,
         }
     }
 
@@ -77,6 +83,15 @@ impl<K, V> HybridCacheBuilder<K, V> {
         self
     }
 
+    /// Enable the hot key tracking, which reports the most frequently accessed keys of the memory hits, the disk hits,
+    /// the misses and the insertions with [`HybridCache::hot_keys`].
+    ///
+    /// Default: Hot key tracking disabled.
+    pub fn with_hot_key_tracking(mut self, config: HotKeyConfig) -> Self {
+        self.hot_key_config = Some(config);
+        self
+    }
+
     /// Continue to modify the in-memory cache configurations.
     pub fn memory(self, capacity: usize) -> HybridCacheBuilderPhaseMemory<K, V, RandomState>
     where
@@ -91,6 +106,8 @@ impl<K, V> HybridCacheBuilder<K, V> {
             builder,
             name: self.name,
             tracing_options: self.tracing_options,
+            hot_key_config: self.hot_key_config,
+            tiers: MemoryTiers::default(),
         }
     }
 }
@@ -104,6 +121,8 @@ where
 {
     name: String,
     tracing_options: TracingOptions,
+    hot_key_config: Option<HotKeyConfig>,
+    tiers: MemoryTiers<K, V>,
     builder: CacheBuilder<K, V, S>,
 }
 
@@ -120,6 +139,8 @@ where
         HybridCacheBuilderPhaseMemory {
             name: self.name,
             tracing_options: self.tracing_options,
+            hot_key_config: self.hot_key_config,
+            tiers: self.tiers,
             builder,
         }
     }
@@ -132,6 +153,26 @@ where
         HybridCacheBuilderPhaseMemory {
             name: self.name,
             tracing_options: self.tracing_options,
+            hot_key_config: self.hot_key_config,
+            tiers: self.tiers,
+            builder,
+        }
+    }
+
+    /// Set a user-defined in-memory cache eviction algorithm `E`.
+    ///
+    /// The implementation of `E` must follow the contract described on [`Eviction`].
+    pub fn with_custom_eviction<E>(self, eviction_config: E::Config) -> Self
+    where
+        E: Eviction,
+        E::Handle: Handle<Data = (K, V)>,
+    {
+        let builder = self.builder.with_custom_eviction::<E>(eviction_config);
+        HybridCacheBuilderPhaseMemory {
+            name: self.name,
+            tracing_options: self.tracing_options,
+            hot_key_config: self.hot_key_config,
+            tiers: self.tiers,
             builder,
         }
     }
@@ -146,6 +187,80 @@ where
         HybridCacheBuilderPhaseMemory {
             name: self.name,
             tracing_options: self.tracing_options,
+            hot_key_config: self.hot_key_config,
+            tiers: self.tiers,
+            builder,
+        }
+    }
+
+    /// Set whether to maintain the metadata of the in-memory cache entries, including the insertion time, the last
+    /// access time and the hit count. See [`CacheEntry::metadata`](crate::CacheEntry::metadata).
+    ///
+    /// The default value is `false`.
+    pub fn with_entry_metadata(self, enabled: bool) -> Self {
+        let builder = self.builder.with_entry_metadata(enabled);
+        HybridCacheBuilderPhaseMemory {
+            name: self.name,
+            tracing_options: self.tracing_options,
+            hot_key_config: self.hot_key_config,
+            tiers: self.tiers,
+            builder,
+        }
+    }
+
+    /// Enable the background reclamation of the in-memory cache, which drops the released entries on a dedicated
+    /// thread. See [`CacheBuilder::with_background_reclamation`].
+    ///
+    /// The background reclamation is disabled by default.
+    pub fn with_background_reclamation(self, queue_capacity: usize) -> Self {
+        let builder = self.builder.with_background_reclamation(queue_capacity);
+        HybridCacheBuilderPhaseMemory {
+            name: self.name,
+            tracing_options: self.tracing_options,
+            hot_key_config: self.hot_key_config,
+            tiers: self.tiers,
+            builder,
+        }
+    }
+
+    /// Set the admission filter config of the in-memory cache. See [`CacheBuilder::with_admission_config`].
+    ///
+    /// The entries rejected by the in-memory cache are still written to the disk cache.
+    pub fn with_admission_config(self, admission_config: AdmissionConfig) -> Self {
+        let builder = self.builder.with_admission_config(admission_config);
+        HybridCacheBuilderPhaseMemory {
+            name: self.name,
+            tracing_options: self.tracing_options,
+            hot_key_config: self.hot_key_config,
+            tiers: self.tiers,
+            builder,
+        }
+    }
+
+    /// Enable the miss ratio curve estimation of the in-memory cache. See [`CacheBuilder::with_miss_ratio_curve`].
+    ///
+    /// The miss ratio curve estimation is disabled by default.
+    pub fn with_miss_ratio_curve(self, config: MissRatioCurveConfig) -> Self {
+        let builder = self.builder.with_miss_ratio_curve(config);
+        HybridCacheBuilderPhaseMemory {
+            name: self.name,
+            tracing_options: self.tracing_options,
+            hot_key_config: self.hot_key_config,
+            tiers: self.tiers,
+            builder,
+        }
+    }
+
+    /// Set the max total weight of the pinned entries of the in-memory cache. See [`HybridCache::pin`].
+    ///
+    /// The default value is `0`, which means no entry can be pinned.
+    pub fn with_pinned_capacity(self, pinned_capacity: usize) -> Self {
+        let builder = self.builder.with_pinned_capacity(pinned_capacity);
+        HybridCacheBuilderPhaseMemory {
+            name: self.name,
+            tracing_options: self.tracing_options,
+            hot_key_config: self.hot_key_config,
+            tiers: self.tiers,
             builder,
         }
     }
@@ -159,6 +274,8 @@ where
         HybridCacheBuilderPhaseMemory {
             name: self.name,
             tracing_options: self.tracing_options,
+            hot_key_config: self.hot_key_config,
+            tiers: self.tiers,
             builder,
         }
     }
@@ -169,10 +286,58 @@ where
         HybridCacheBuilderPhaseMemory {
             name: self.name,
             tracing_options: self.tracing_options,
+            hot_key_config: self.hot_key_config,
+            tiers: self.tiers,
             builder,
         }
     }
 
+    /// Enable the compressed in-memory cache between the in-memory cache and the disk cache.
+    ///
+    /// With the compressed in-memory cache enabled, the in-memory cache keeps the decompressed hot entries, and the
+    /// compressed in-memory cache keeps the serialized and compressed entries with at most `capacity` bytes. On
+    /// in-memory cache misses, the compressed in-memory cache is looked up before the disk cache.
+    ///
+    /// The compressed in-memory cache is disabled by default.
+    pub fn with_compressed_cache(self, capacity: usize, compression: Compression) -> Self {
+        let compressed = CompressedCacheBuilder::new(capacity)
+            .with_name(&format!("{}-compressed", self.name))
+            .with_compression(compression)
+            .build();
+        HybridCacheBuilderPhaseMemory {
+            tiers: self.tiers.with_compressed(compressed),
+            name: self.name,
+            tracing_options: self.tracing_options,
+            hot_key_config: self.hot_key_config,
+            builder: self.builder,
+        }
+    }
+
+    /// Enable the shared memory cache between the in-memory cache and the disk cache, so the entries can be shared by
+    /// the hybrid caches of the processes on the same host that open the same shared memory segment.
+    ///
+    /// The entries are serialized into the shared memory cache, and the entries larger than the slot of the shared
+    /// memory cache are skipped. On in-memory cache misses, the shared memory cache is looked up before the disk
+    /// cache, and after the compressed in-memory cache if it is enabled.
+    ///
+    /// The entries are inserted into the shared memory cache on every insertion and every disk cache hit. The insertion
+    /// is best-effort and skipped if the segment lock is held by another thread or process, so the insertion never
+    /// waits for the lock, but an entry may be absent from the shared memory cache under contention.
+    ///
+    /// All processes sharing the segment must use the same key and value types.
+    ///
+    /// The shared memory cache is disabled by default.
+    #[cfg(target_os = "linux")]
+    pub fn with_shared_memory_cache(self, cache: SharedMemoryCache) -> Self {
+        HybridCacheBuilderPhaseMemory {
+            tiers: self.tiers.with_shared(cache),
+            name: self.name,
+            tracing_options: self.tracing_options,
+            hot_key_config: self.hot_key_config,
+            builder: self.builder,
+        }
+    }
+
     /// Continue to modify the disk cache configurations.
     pub fn storage(self, engine: Engine) -> HybridCacheBuilderPhaseStorage<K, V, S> {
         let memory = self.builder.build();
@@ -180,7 +345,9 @@ where
             builder: StoreBuilder::new(memory.clone(), engine).with_name(&self.name),
             name: self.name,
             tracing_options: self.tracing_options,
+            hot_key_config: self.hot_key_config,
             memory,
+            tiers: self.tiers,
         }
     }
 }
@@ -194,7 +361,9 @@ where
 {
     name: String,
     tracing_options: TracingOptions,
+    hot_key_config: Option<HotKeyConfig>,
     memory: Cache<K, V, S>,
+    tiers: MemoryTiers<K, V>,
     builder: StoreBuilder<K, V, S>,
 }
 
@@ -210,7 +379,9 @@ where
         Self {
             name: self.name,
             tracing_options: self.tracing_options,
+            hot_key_config: self.hot_key_config,
             memory: self.memory,
+            tiers: self.tiers,
             builder,
         }
     }
@@ -223,7 +394,43 @@ where
         Self {
             name: self.name,
             tracing_options: self.tracing_options,
+            hot_key_config: self.hot_key_config,
+            memory: self.memory,
+            tiers: self.tiers,
+            builder,
+        }
+    }
+
+    /// Set the i/o budgets of the disk cache device.
+    ///
+    /// See more in [`Throttle`].
+    ///
+    /// Default: unlimited.
+    pub fn with_throttle(self, throttle: Throttle) -> Self {
+        let builder = self.builder.with_throttle(throttle);
+        Self {
+            name: self.name,
+            tracing_options: self.tracing_options,
+            hot_key_config: self.hot_key_config,
+            memory: self.memory,
+            tiers: self.tiers,
+            builder,
+        }
+    }
+
+    /// Set the config of the i/o scheduler of the disk cache device.
+    ///
+    /// See more in [`IoSchedulerConfig`].
+    ///
+    /// Default: no limit.
+    pub fn with_io_scheduler_config(self, config: IoSchedulerConfig) -> Self {
+        let builder = self.builder.with_io_scheduler_config(config);
+        Self {
+            name: self.name,
+            tracing_options: self.tracing_options,
+            hot_key_config: self.hot_key_config,
             memory: self.memory,
+            tiers: self.tiers,
             builder,
         }
     }
@@ -238,7 +445,27 @@ where
         Self {
             name: self.name,
             tracing_options: self.tracing_options,
+            hot_key_config: self.hot_key_config,
+            memory: self.memory,
+            tiers: self.tiers,
+            builder,
+        }
+    }
+
+    /// Set whether to wipe the disk cache if its layout mismatches the one pinned by the manifest.
+    ///
+    /// Otherwise, the build fails on mismatch, including the disk cache directory written by the versions without the
+    /// manifest. See more in [`StoreBuilder::with_wipe_on_manifest_mismatch`].
+    ///
+    /// Default: `false`.
+    pub fn with_wipe_on_manifest_mismatch(self, wipe: bool) -> Self {
+        let builder = self.builder.with_wipe_on_manifest_mismatch(wipe);
+        Self {
+            name: self.name,
+            tracing_options: self.tracing_options,
+            hot_key_config: self.hot_key_config,
             memory: self.memory,
+            tiers: self.tiers,
             builder,
         }
     }
@@ -253,20 +480,40 @@ where
         Self {
             name: self.name,
             tracing_options: self.tracing_options,
+            hot_key_config: self.hot_key_config,
             memory: self.memory,
+            tiers: self.tiers,
             builder,
         }
     }
 
-    /// Set the compression algorithm of the disk cache store.
+    /// Set the compression of the disk cache store, with either a [`Compression`](crate::Compression) algorithm or a
+    /// [`CompressionConfig`]. See more in [`StoreBuilder::with_compression`].
     ///
-    /// Default: [`Compression::None`].
-    pub fn with_compression(self, compression: Compression) -> Self {
+    /// Default: [`Compression::None`](crate::Compression::None).
+    pub fn with_compression(self, compression: impl Into<CompressionConfig>) -> Self {
         let builder = self.builder.with_compression(compression);
         Self {
             name: self.name,
             tracing_options: self.tracing_options,
+            hot_key_config: self.hot_key_config,
+            memory: self.memory,
+            tiers: self.tiers,
+            builder,
+        }
+    }
+
+    /// Enable the miss ratio curve estimation of the disk cache store. See [`StoreBuilder::with_miss_ratio_curve`].
+    ///
+    /// The miss ratio curve estimation is disabled by default.
+    pub fn with_miss_ratio_curve(self, config: MissRatioCurveConfig) -> Self {
+        let builder = self.builder.with_miss_ratio_curve(config);
+        Self {
+            name: self.name,
+            tracing_options: self.tracing_options,
+            hot_key_config: self.hot_key_config,
             memory: self.memory,
+            tiers: self.tiers,
             builder,
         }
     }
@@ -277,7 +524,9 @@ where
         Self {
             name: self.name,
             tracing_options: self.tracing_options,
+            hot_key_config: self.hot_key_config,
             memory: self.memory,
+            tiers: self.tiers,
             builder,
         }
     }
@@ -290,7 +539,9 @@ where
         Self {
             name: self.name,
             tracing_options: self.tracing_options,
+            hot_key_config: self.hot_key_config,
             memory: self.memory,
+            tiers: self.tiers,
             builder,
         }
     }
@@ -303,7 +554,9 @@ where
         Self {
             name: self.name,
             tracing_options: self.tracing_options,
+            hot_key_config: self.hot_key_config,
             memory: self.memory,
+            tiers: self.tiers,
             builder,
         }
     }
@@ -311,6 +564,13 @@ where
     /// Build and open the hybrid cache with the given configurations.
     pub async fn build(self) -> anyhow::Result<HybridCache<K, V, S>> {
         let storage = self.builder.build().await?;
-        Ok(HybridCache::new(self.name, self.memory, storage, self.tracing_options))
+        Ok(HybridCache::new(
+            self.name,
+            self.memory,
+            self.tiers,
+            storage,
+            self.tracing_options,
+            self.hot_key_config,
+        ))
     }
 }
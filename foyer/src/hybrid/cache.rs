@@ -35,14 +35,20 @@ use foyer_common::{
     metrics::Metrics,
     tracing::{InRootSpan, TracingConfig, TracingOptions},
 };
+#[cfg(target_os = "linux")]
+use foyer_memory::SharedMemoryCache;
 use foyer_memory::{Cache, CacheContext, CacheEntry, Fetch, FetchMark, FetchState};
-use foyer_storage::{DeviceStats, Store};
+use foyer_storage::{CompressedCache, DeviceStats, Store};
 use futures::FutureExt;
 use pin_project::pin_project;
 use tokio::sync::oneshot;
 
-use super::writer::HybridCacheStorageWriter;
-use crate::HybridCacheWriter;
+use super::{
+    hotkey::{HotKeyKind, HotKeyTracker},
+    tier::MemoryTiers,
+    writer::HybridCacheStorageWriter,
+};
+use crate::{HotKeyConfig, HotKeys, HybridCacheWriter};
 
 macro_rules! root_span {
     ($self:ident, mut $name:ident, $label:expr) => {
@@ -74,6 +80,12 @@ macro_rules! try_cancel {
 pub type HybridCacheEntry<K, V, S = RandomState> = CacheEntry<K, V, S>;
 
 /// Hybrid cache that integrates in-memory cache and disk cache.
+///
+/// With [`HybridCacheBuilderPhaseMemory::with_compressed_cache`] or `with_shared_memory_cache` (Linux only) enabled,
+/// the hybrid cache integrates the compressed in-memory cache or the shared memory cache between the in-memory cache
+/// and the disk cache.
+///
+/// [`HybridCacheBuilderPhaseMemory::with_compressed_cache`]: crate::HybridCacheBuilderPhaseMemory::with_compressed_cache
 pub struct HybridCache<K, V, S = RandomState>
 where
     K: StorageKey,
@@ -81,10 +93,12 @@ where
     S: HashBuilder + Debug,
 {
     memory: Cache<K, V, S>,
+    tiers: MemoryTiers<K, V>,
     storage: Store<K, V, S>,
     metrics: Arc<Metrics>,
     tracing_config: Arc<TracingConfig>,
     tracing: Arc<AtomicBool>,
+    hot_keys: Option<Arc<HotKeyTracker<K>>>,
 }
 
 impl<K, V, S> Debug for HybridCache<K, V, S>
@@ -96,6 +110,7 @@ where
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("HybridCache")
             .field("memory", &self.memory)
+            .field("tiers", &self.tiers)
             .field("storage", &self.storage)
             .field("tracing_config", &self.tracing_config)
             .field("tracing", &self.tracing)
@@ -112,10 +127,12 @@ where
     fn clone(&self) -> Self {
         Self {
             memory: self.memory.clone(),
+            tiers: self.tiers.clone(),
             storage: self.storage.clone(),
             metrics: self.metrics.clone(),
             tracing_config: self.tracing_config.clone(),
             tracing: self.tracing.clone(),
+            hot_keys: self.hot_keys.clone(),
         }
     }
 }
@@ -129,22 +146,38 @@ where
     pub(crate) fn new(
         name: String,
         memory: Cache<K, V, S>,
+        tiers: MemoryTiers<K, V>,
         storage: Store<K, V, S>,
         tracing_options: TracingOptions,
+        hot_key_config: Option<HotKeyConfig>,
     ) -> Self {
         let metrics = Arc::new(Metrics::new(&name));
         let tracing_config = Arc::<TracingConfig>::default();
         tracing_config.update(tracing_options);
         let tracing = Arc::new(AtomicBool::new(false));
+        let hot_keys = hot_key_config.map(|config| Arc::new(HotKeyTracker::new(&config)));
         Self {
             memory,
+            tiers,
             storage,
             metrics,
             tracing_config,
             tracing,
+            hot_keys,
         }
     }
 
+    /// Access the compressed in-memory cache if it is enabled.
+    pub fn compressed(&self) -> Option<&CompressedCache<K, V>> {
+        self.tiers.compressed()
+    }
+
+    /// Access the shared memory cache if it is enabled.
+    #[cfg(target_os = "linux")]
+    pub fn shared_memory(&self) -> Option<&SharedMemoryCache> {
+        self.tiers.shared()
+    }
+
     /// Access the trace config with options.
     pub fn update_tracing_options(&self, options: TracingOptions) {
         self.tracing_config.update(options);
@@ -170,6 +203,20 @@ where
         self.tracing.load(Ordering::Relaxed)
     }
 
+    /// Get the current hot keys of the memory hits, the disk hits, the misses and the insertions.
+    ///
+    /// Returns `None` if the hot key tracking is not enabled with
+    /// [`HybridCacheBuilder::with_hot_key_tracking`](crate::HybridCacheBuilder::with_hot_key_tracking).
+    pub fn hot_keys(&self) -> Option<HotKeys<K>> {
+        self.hot_keys.as_ref().map(|tracker| tracker.hot_keys())
+    }
+
+    fn record_hot_key(&self, kind: HotKeyKind, hash: u64, key: Option<&K>) {
+        if let Some(tracker) = self.hot_keys.as_ref() {
+            tracker.record(kind, hash, key);
+        }
+    }
+
     /// Insert cache entry to the hybrid cache.
     pub fn insert(&self, key: K, value: V) -> HybridCacheEntry<K, V, S> {
         root_span!(self, mut span, "foyer::hybrid::cache::insert");
@@ -179,7 +226,9 @@ where
         let now = Instant::now();
 
         let entry = self.memory.insert(key, value);
+        self.tiers.insert(entry.key(), entry.value());
         self.storage.enqueue(entry.clone(), false);
+        self.record_hot_key(HotKeyKind::Insert, entry.hash(), Some(entry.key()));
 
         self.metrics.hybrid_insert.increment(1);
         self.metrics.hybrid_insert_duration.record(now.elapsed());
@@ -198,7 +247,9 @@ where
         let now = Instant::now();
 
         let entry = self.memory.insert_with_context(key, value, context);
+        self.tiers.insert(entry.key(), entry.value());
         self.storage.enqueue(entry.clone(), false);
+        self.record_hot_key(HotKeyKind::Insert, entry.hash(), Some(entry.key()));
 
         self.metrics.hybrid_insert.increment(1);
         self.metrics.hybrid_insert_duration.record(now.elapsed());
@@ -230,6 +281,14 @@ where
         let guard = span.set_local_parent();
         if let Some(entry) = self.memory.get(key) {
             record_hit();
+            self.record_hot_key(HotKeyKind::MemoryHit, entry.hash(), Some(entry.key()));
+            try_cancel!(self, span, record_hybrid_get_threshold);
+            return Ok(Some(entry));
+        }
+        if let Some((k, v)) = self.tiers.load(key, &self.metrics) {
+            record_hit();
+            let entry = self.memory.insert(k, v);
+            self.record_hot_key(HotKeyKind::MemoryHit, entry.hash(), Some(entry.key()));
             try_cancel!(self, span, record_hybrid_get_threshold);
             return Ok(Some(entry));
         }
@@ -243,10 +302,14 @@ where
         {
             Some((k, v)) => {
                 record_hit();
-                Some(self.memory.insert(k, v))
+                let entry = self.memory.insert(k, v);
+                self.tiers.insert(entry.key(), entry.value());
+                self.record_hot_key(HotKeyKind::DiskHit, entry.hash(), Some(entry.key()));
+                Some(entry)
             }
             None => {
                 record_miss();
+                self.record_hot_key(HotKeyKind::Miss, self.memory.hash(key), None);
                 None
             }
         };
@@ -273,20 +336,45 @@ where
         let guard = span.set_local_parent();
         let fetch = self.memory.fetch(key.clone(), || {
             let store = self.storage.clone();
+            let tiers = self.tiers.clone();
+            let metrics = self.metrics.clone();
+            let hot_keys = self.hot_keys.clone();
+            let hash = self.memory.hash(&key);
             async move {
+                if let Some((k, v)) = tiers.load(&key, &metrics) {
+                    if let Some(hot_keys) = hot_keys {
+                        hot_keys.record(HotKeyKind::MemoryHit, hash, Some(&k));
+                    }
+                    return Ok(v);
+                }
                 match store.load(&key).await.map_err(anyhow::Error::from) {
-                    Ok(Some((_, v))) => Ok(v),
-                    Ok(None) => Err(ObtainFetchError::NotExist),
+                    Ok(Some((k, v))) => {
+                        tiers.insert(&k, &v);
+                        if let Some(hot_keys) = hot_keys {
+                            hot_keys.record(HotKeyKind::DiskHit, hash, Some(&k));
+                        }
+                        Ok(v)
+                    }
+                    Ok(None) => {
+                        if let Some(hot_keys) = hot_keys {
+                            hot_keys.record(HotKeyKind::Miss, hash, Some(&key));
+                        }
+                        Err(ObtainFetchError::NotExist)
+                    }
                     Err(e) => Err(ObtainFetchError::Err(e)),
                 }
             }
         });
         drop(guard);
 
+        let hit = fetch.state() == FetchState::Hit;
         let res = fetch.await;
 
         match res {
             Ok(entry) => {
+                if hit {
+                    self.record_hot_key(HotKeyKind::MemoryHit, entry.hash(), Some(entry.key()));
+                }
                 self.metrics.hybrid_hit.increment(1);
                 self.metrics.hybrid_hit_duration.record(now.elapsed());
                 try_cancel!(self, span, record_hybrid_obtain_threshold);
@@ -322,6 +410,7 @@ where
         let now = Instant::now();
 
         self.memory.remove(key);
+        self.tiers.remove(key);
         self.storage.delete(key);
 
         self.metrics.hybrid_remove.increment(1);
@@ -338,16 +427,57 @@ where
         K: Borrow<Q>,
         Q: Hash + Eq + ?Sized,
     {
-        self.memory.contains(key) || self.storage.may_contains(key)
+        self.memory.contains(key) || self.tiers.contains(key) || self.storage.may_contains(key)
+    }
+
+    /// Pin the cached entry with the given key in the in-memory cache, so it is never evicted from the in-memory cache
+    /// until unpinned or removed.
+    ///
+    /// Return `false` if the entry is not in the in-memory cache, or pinning it would exceed the pinned capacity.
+    ///
+    /// See [`Cache::pin`].
+    pub fn pin<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.memory.pin(key)
+    }
+
+    /// Unpin the cached entry with the given key in the in-memory cache.
+    ///
+    /// Return `false` if the entry is not in the in-memory cache or not pinned.
+    ///
+    /// See [`Cache::unpin`].
+    pub fn unpin<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.memory.unpin(key)
+    }
+
+    /// Get the usage of the pinned entries of the in-memory cache.
+    pub fn pinned_usage(&self) -> usize {
+        self.memory.pinned_usage()
     }
 
     /// Clear the hybrid cache.
     pub async fn clear(&self) -> anyhow::Result<()> {
         self.memory.clear();
+        self.tiers.clear();
         self.storage.destroy().await?;
         Ok(())
     }
 
+    /// Resize the capacity of the disk cache online.
+    ///
+    /// See [`Store::resize`] for details.
+    pub async fn resize_disk_cache(&self, capacity: usize) -> anyhow::Result<()> {
+        self.storage.resize(capacity).await?;
+        Ok(())
+    }
+
     /// Gracefully close the hybrid cache.
     ///
     /// `close` will wait for the ongoing flush and reclaim tasks to finish.
@@ -407,6 +537,7 @@ where
     #[pin]
     inner: Fetch<K, V, anyhow::Error, S>,
 
+    tiers: MemoryTiers<K, V>,
     storage: Store<K, V, S>,
 }
 
@@ -424,6 +555,7 @@ where
 
         if let Ok(entry) = res.as_ref() {
             if this.inner.store().is_some() {
+                this.tiers.insert(entry.key(), entry.value());
                 this.storage.enqueue(entry.clone(), false);
             }
         }
@@ -479,6 +611,9 @@ where
         let now = Instant::now();
 
         let store = self.storage.clone();
+        let hash = self.memory.hash(&key);
+        // Keep a key sample for the hot key tracker, for the key is moved into the fetch.
+        let sample = self.hot_keys.as_ref().map(|_| key.clone());
 
         let future = fetch();
         let inner = self.memory.fetch_inner(
@@ -487,12 +622,28 @@ where
             || {
                 let metrics = self.metrics.clone();
                 let runtime = self.storage().runtime().clone();
+                let hot_keys = self.hot_keys.clone();
+                let tiers = self.tiers.clone();
 
                 async move {
+                    if let Some((k, v)) = tiers.load(&key, &metrics) {
+                        metrics.hybrid_hit.increment(1);
+                        metrics.hybrid_hit_duration.record(now.elapsed());
+                        if let Some(hot_keys) = hot_keys {
+                            hot_keys.record(HotKeyKind::MemoryHit, hash, Some(&k));
+                        }
+
+                        return Ok(v).into();
+                    }
+
                     match store.load(&key).await.map_err(anyhow::Error::from) {
-                        Ok(Some((_k, v))) => {
+                        Ok(Some((k, v))) => {
+                            tiers.insert(&k, &v);
                             metrics.hybrid_hit.increment(1);
                             metrics.hybrid_hit_duration.record(now.elapsed());
+                            if let Some(hot_keys) = hot_keys {
+                                hot_keys.record(HotKeyKind::DiskHit, hash, Some(&k));
+                            }
 
                             return Ok(v).into();
                         }
@@ -502,6 +653,9 @@ where
 
                     metrics.hybrid_miss.increment(1);
                     metrics.hybrid_miss_duration.record(now.elapsed());
+                    if let Some(hot_keys) = hot_keys {
+                        hot_keys.record(HotKeyKind::Miss, hash, Some(&key));
+                    }
 
                     runtime
                         .user()
@@ -523,10 +677,12 @@ where
         if inner.state() == FetchState::Hit {
             self.metrics.hybrid_hit.increment(1);
             self.metrics.hybrid_hit_duration.record(now.elapsed());
+            self.record_hot_key(HotKeyKind::MemoryHit, hash, sample.as_ref());
         }
 
         let inner = HybridFetchInner {
             inner,
+            tiers: self.tiers.clone(),
             storage: self.storage.clone(),
         };
 
@@ -550,6 +706,7 @@ mod tests {
         HybridCacheBuilder::new()
             .with_name("test")
             .memory(4 * MB)
+            .with_pinned_capacity(MB)
             // TODO(MrCroxx): Test with `Engine::Mixed`.
             .storage(Engine::Large)
             .with_device_options(
@@ -613,6 +770,11 @@ mod tests {
         let e2g = hybrid.obtain(2).await.unwrap().unwrap();
         assert_eq!(e2g.value(), &vec![2; 7 * KB]);
 
+        assert!(hybrid.pin(&1));
+        assert!(hybrid.pinned_usage() > 0);
+        assert!(hybrid.unpin(&1));
+        assert_eq!(hybrid.pinned_usage(), 0);
+
         assert!(hybrid.contains(&3));
         hybrid.remove(&3);
         assert!(!hybrid.contains(&3));
@@ -622,6 +784,174 @@ mod tests {
         assert!(!hybrid.contains(&4));
     }
 
+    #[test_log::test(tokio::test)]
+    async fn test_hybrid_cache_hot_keys() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let hybrid: HybridCache<u64, Vec<u8>> = HybridCacheBuilder::new()
+            .with_name("test")
+            .with_hot_key_tracking(HotKeyConfig {
+                top_k: 2,
+                ..Default::default()
+            })
+            .memory(4 * MB)
+            .storage(Engine::Large)
+            .with_device_options(
+                DirectFsDeviceOptions::new(dir.path())
+                    .with_capacity(16 * MB)
+                    .with_file_size(MB),
+            )
+            .build()
+            .await
+            .unwrap();
+
+        for i in 0..4 {
+            hybrid.insert(i, vec![i as u8; KB]);
+        }
+        for _ in 0..10 {
+            hybrid.get(&1).await.unwrap().unwrap();
+            hybrid.obtain(2).await.unwrap().unwrap();
+            assert!(hybrid.get(&42).await.unwrap().is_none());
+        }
+
+        let hot_keys = hybrid.hot_keys().unwrap();
+        let memory_hits = hot_keys.memory_hits.iter().map(|key| key.key).collect::<Vec<_>>();
+        assert_eq!(memory_hits.len(), 2);
+        assert!(memory_hits.contains(&Some(1)));
+        assert!(memory_hits.contains(&Some(2)));
+        assert!(hot_keys.memory_hits.iter().all(|key| key.count == 10));
+        assert_eq!(hot_keys.inserts.len(), 2);
+        assert!(hot_keys.disk_hits.is_empty());
+        assert_eq!(hot_keys.misses.len(), 1);
+        assert_eq!(hot_keys.misses[0].hash, hybrid.memory().hash(&42));
+        assert_eq!(hot_keys.misses[0].key, None);
+        assert_eq!(hot_keys.misses[0].count, 10);
+
+        let dir = tempfile::tempdir().unwrap();
+        assert!(open(dir.path()).await.hot_keys().is_none());
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn test_hybrid_cache_compressed() {
+        let dir = tempfile::tempdir().unwrap();
+
+        // The disk cache admits nothing, so the entries evicted from the in-memory cache can only be served by the
+        // compressed in-memory cache.
+        let hybrid: HybridCache<u64, Vec<u8>> = HybridCacheBuilder::new()
+            .with_name("test")
+            .memory(2)
+            .with_shards(1)
+            .with_compressed_cache(4 * MB, Compression::Zstd)
+            .storage(Engine::Large)
+            .with_device_options(
+                DirectFsDeviceOptions::new(dir)
+                    .with_capacity(16 * MB)
+                    .with_file_size(MB),
+            )
+            .with_admission_picker(Arc::new(BiasedPicker::<u64, u64>::new([])))
+            .build()
+            .await
+            .unwrap();
+
+        for i in 0..8 {
+            hybrid.insert(i, vec![i as u8; 64 * KB]);
+        }
+        let compressed = hybrid.compressed().unwrap();
+        assert!(!hybrid.memory().contains(&0));
+        assert!(compressed.contains(&0));
+        assert!(compressed.usage() < 8 * KB, "usage: {}", compressed.usage());
+
+        let e0 = hybrid.get(&0).await.unwrap().unwrap();
+        assert_eq!(e0.value(), &vec![0; 64 * KB]);
+        assert!(hybrid.memory().contains(&0));
+
+        let e1 = hybrid.obtain(1).await.unwrap().unwrap();
+        assert_eq!(e1.value(), &vec![1; 64 * KB]);
+
+        let e2 = hybrid
+            .fetch(2, || async move {
+                Err(anyhow::anyhow!("must be served by the compressed cache"))
+            })
+            .await
+            .unwrap();
+        assert_eq!(e2.value(), &vec![2; 64 * KB]);
+
+        // Entries fetched from the remote storage are compressed as well.
+        hybrid.fetch(8, || async move { Ok(vec![8; 64 * KB]) }).await.unwrap();
+        assert!(compressed.contains(&8));
+
+        hybrid.remove(&3);
+        assert!(!hybrid.contains(&3));
+        assert!(hybrid.get(&3).await.unwrap().is_none());
+
+        assert!(hybrid.contains(&4));
+        hybrid.clear().await.unwrap();
+        assert!(!hybrid.contains(&4));
+        assert_eq!(compressed.usage(), 0);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test_log::test(tokio::test)]
+    async fn test_hybrid_cache_shared_memory() {
+        let dir = tempfile::tempdir().unwrap();
+
+        // Both hybrid caches admit nothing to their own disk caches, so the entries can only be shared by the shared
+        // memory cache.
+        let open = |name: &str| {
+            let shared = SharedMemoryCacheBuilder::new(dir.path().join("shm"))
+                .with_slot_size(16 * KB)
+                .with_slots(64)
+                .build()
+                .unwrap();
+            HybridCacheBuilder::new()
+                .with_name(name)
+                .memory(4 * MB)
+                .with_shared_memory_cache(shared)
+                .storage(Engine::Large)
+                .with_device_options(
+                    DirectFsDeviceOptions::new(dir.path().join(name))
+                        .with_capacity(16 * MB)
+                        .with_file_size(MB),
+                )
+                .with_admission_picker(Arc::new(BiasedPicker::<u64, u64>::new([])))
+                .build()
+        };
+        let a: HybridCache<u64, Vec<u8>> = open("a").await.unwrap();
+        let b: HybridCache<u64, Vec<u8>> = open("b").await.unwrap();
+
+        a.insert(1, vec![1; 7 * KB]);
+        a.fetch(2, || async move { Ok(vec![2; 7 * KB]) }).await.unwrap();
+        // The entry larger than the slot is skipped by the shared memory cache.
+        a.insert(3, vec![3; 32 * KB]);
+
+        assert!(b.contains(&1));
+        assert_eq!(b.get(&1).await.unwrap().unwrap().value(), &vec![1; 7 * KB]);
+        assert!(b.memory().contains(&1));
+        assert_eq!(b.obtain(2).await.unwrap().unwrap().value(), &vec![2; 7 * KB]);
+        assert!(b.get(&3).await.unwrap().is_none());
+
+        // The removal is visible to the other processes, except for the entries in their own in-memory caches.
+        b.remove(&1);
+        assert_eq!(a.shared_memory().unwrap().len(), 1);
+        assert!(a.get(&1).await.unwrap().is_some());
+        a.memory().remove(&1);
+        assert!(a.get(&1).await.unwrap().is_none());
+
+        // The entry that cannot be decoded is treated as a miss and dropped from the shared memory cache.
+        use crate::hybrid::tier::shared_hash;
+
+        let shared = a.shared_memory().unwrap();
+        let key = bincode::serialize(&4u64).unwrap();
+        assert!(shared.insert_with_hash(shared_hash(&4u64), &key, &[0xff; 4]));
+        assert!(b.get(&4).await.unwrap().is_none());
+        assert!(!shared.contains_with_hash(shared_hash(&4u64), |k| k == key));
+        shared.insert_with_hash(shared_hash(&4u64), &key, &[0xff; 4]);
+        assert!(b.obtain(4).await.unwrap().is_none());
+        shared.insert_with_hash(shared_hash(&4u64), &key, &[0xff; 4]);
+        let e4 = b.fetch(4, || async move { Ok(vec![4; KB]) }).await.unwrap();
+        assert_eq!(e4.value(), &vec![4; KB]);
+    }
+
     #[test_log::test(tokio::test)]
     async fn test_hybrid_cache_writer() {
         let dir = tempfile::tempdir().unwrap();
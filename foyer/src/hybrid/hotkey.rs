@@ -0,0 +1,360 @@
+//  Copyright 2024 foyer Project Authors
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::{
+    fmt::Debug,
+    marker::PhantomData,
+    time::{Duration, Instant},
+};
+
+use cmsketch::CMSketchU32;
+use foyer_common::code::StorageKey;
+use parking_lot::Mutex;
+
+/// Drop all the history instead of halving it window by window if the tracker has been idle for so many windows.
+const MAX_HALVES: u32 = 32;
+
+/// The kind of the key accesses recorded by the hot key tracker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HotKeyKind {
+    /// Hit in the in-memory cache.
+    MemoryHit = 0,
+    /// Miss in the in-memory cache but hit in the disk cache.
+    DiskHit = 1,
+    /// Miss in both the in-memory cache and the disk cache.
+    Miss = 2,
+    /// Insertion.
+    Insert = 3,
+}
+
+impl HotKeyKind {
+    const COUNT: usize = 4;
+}
+
+/// Hot key tracker config.
+///
+/// The hot key tracker counts the accesses of each kind with a count-min sketch, and keeps the `top_k` most frequently
+/// accessed key hashes as the candidates. The counts are halved every `window`, so the reported counts and rates
+/// reflect the recent few windows.
+#[derive(Debug, Clone)]
+pub struct HotKeyConfig {
+    /// The count of the hot keys to report for each kind.
+    pub top_k: usize,
+    /// The halving period of the counts.
+    pub window: Duration,
+    /// The count of the shards of each kind, to reduce the lock contention.
+    pub shards: usize,
+    /// Error of the count-min sketch of each shard.
+    ///
+    /// See [`CMSketchU32::new`].
+    pub cmsketch_eps: f64,
+    /// Confidence of the count-min sketch of each shard.
+    ///
+    /// See [`CMSketchU32::new`].
+    pub cmsketch_confidence: f64,
+}
+
+impl Default for HotKeyConfig {
+    fn default() -> Self {
+        Self {
+            top_k: 16,
+            window: Duration::from_secs(10),
+            shards: 16,
+            cmsketch_eps: 0.001,
+            cmsketch_confidence: 0.9,
+        }
+    }
+}
+
+/// A hot key reported by the hot key tracker.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HotKey<K> {
+    /// The hash of the key.
+    pub hash: u64,
+    /// The sample of the key.
+    ///
+    /// The sample is `None` if the key has not been seen by the tracker, e.g. it has only missed in `get` with a
+    /// borrowed key of another type.
+    pub key: Option<K>,
+    /// The estimated count of the accesses, halved every window.
+    pub count: u64,
+    /// The estimated accesses per second.
+    pub rate: f64,
+}
+
+/// The hot keys of each kind, ordered by the estimated count descending.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HotKeys<K> {
+    /// The hot keys that hit in the in-memory cache.
+    pub memory_hits: Vec<HotKey<K>>,
+    /// The hot keys that hit in the disk cache.
+    pub disk_hits: Vec<HotKey<K>>,
+    /// The hot keys that miss in both the in-memory cache and the disk cache.
+    pub misses: Vec<HotKey<K>>,
+    /// The hot keys that are inserted.
+    pub inserts: Vec<HotKey<K>>,
+}
+
+#[derive(Debug)]
+struct Candidate {
+    hash: u64,
+    /// The key encoded with bincode, so the key type needs no `Clone`.
+    key: Option<Vec<u8>>,
+    count: u64,
+}
+
+struct HotKeyShard {
+    frequencies: CMSketchU32,
+    candidates: Vec<Candidate>,
+    window_start: Instant,
+    /// The effective duration covered by the halved counts before the current window, in seconds.
+    span: f64,
+}
+
+impl HotKeyShard {
+    /// Halve the counts for each window elapsed.
+    fn rotate(&mut self, now: Instant, window: Duration) {
+        let windows = (now.saturating_duration_since(self.window_start).as_nanos() / window.as_nanos()) as u32;
+        if windows == 0 {
+            return;
+        }
+        if windows >= MAX_HALVES {
+            self.frequencies.clear();
+            self.candidates.clear();
+            self.span = 0.0;
+            self.window_start = now;
+            return;
+        }
+        for _ in 0..windows {
+            self.frequencies.halve();
+            self.candidates.iter_mut().for_each(|candidate| candidate.count >>= 1);
+            self.candidates.retain(|candidate| candidate.count > 0);
+            self.span = (self.span + window.as_secs_f64()) / 2.0;
+        }
+        self.window_start += window * windows;
+    }
+
+    fn record(&mut self, hash: u64, key: impl FnOnce() -> Option<Vec<u8>>, top_k: usize) {
+        self.frequencies.inc(hash);
+        let count = self.frequencies.estimate(hash) as u64;
+
+        if let Some(candidate) = self.candidates.iter_mut().find(|candidate| candidate.hash == hash) {
+            candidate.count = count;
+            if candidate.key.is_none() {
+                candidate.key = key();
+            }
+            return;
+        }
+
+        let candidate = Candidate { hash, key: None, count };
+        if self.candidates.len() < top_k {
+            self.candidates.push(Candidate {
+                key: key(),
+                ..candidate
+            });
+            return;
+        }
+        let (index, min) = self
+            .candidates
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, candidate)| candidate.count)
+            .map(|(index, candidate)| (index, candidate.count))
+            .unwrap();
+        if count > min {
+            self.candidates[index] = Candidate {
+                key: key(),
+                ..candidate
+            };
+        }
+    }
+}
+
+/// Hot key tracker of the hybrid cache.
+///
+/// See [`HotKeyConfig`] for details.
+pub(crate) struct HotKeyTracker<K> {
+    /// The shards of each kind, indexed by [`HotKeyKind`].
+    shards: Vec<Vec<Mutex<HotKeyShard>>>,
+    top_k: usize,
+    window: Duration,
+    _marker: PhantomData<K>,
+}
+
+impl<K> Debug for HotKeyTracker<K> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HotKeyTracker")
+            .field("top_k", &self.top_k)
+            .field("window", &self.window)
+            .finish()
+    }
+}
+
+impl<K> HotKeyTracker<K>
+where
+    K: StorageKey,
+{
+    /// Create a new hot key tracker.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the config is invalid.
+    pub fn new(config: &HotKeyConfig) -> Self {
+        assert!(config.top_k > 0, "top_k must be positive");
+        assert!(config.shards > 0, "shards must be positive");
+        assert!(!config.window.is_zero(), "window must be positive");
+
+        let now = Instant::now();
+        let shards = (0..HotKeyKind::COUNT)
+            .map(|_| {
+                (0..config.shards)
+                    .map(|_| {
+                        Mutex::new(HotKeyShard {
+                            frequencies: CMSketchU32::new(config.cmsketch_eps, config.cmsketch_confidence),
+                            candidates: Vec::with_capacity(config.top_k),
+                            window_start: now,
+                            span: 0.0,
+                        })
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Self {
+            shards,
+            top_k: config.top_k,
+            window: config.window,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Record an access of the given kind to the key hash, with the key sample if it is available.
+    pub fn record(&self, kind: HotKeyKind, hash: u64, key: Option<&K>) {
+        let shards = &self.shards[kind as usize];
+        let mut shard = shards[hash as usize % shards.len()].lock();
+        shard.rotate(Instant::now(), self.window);
+        shard.record(hash, || key.and_then(|key| bincode::serialize(key).ok()), self.top_k);
+    }
+
+    /// Get the current hot keys of each kind.
+    pub fn hot_keys(&self) -> HotKeys<K> {
+        HotKeys {
+            memory_hits: self.top(HotKeyKind::MemoryHit),
+            disk_hits: self.top(HotKeyKind::DiskHit),
+            misses: self.top(HotKeyKind::Miss),
+            inserts: self.top(HotKeyKind::Insert),
+        }
+    }
+
+    fn top(&self, kind: HotKeyKind) -> Vec<HotKey<K>> {
+        let now = Instant::now();
+        // The key hashes of different shards never overlap, so the candidates can be simply merged.
+        let mut keys = self.shards[kind as usize]
+            .iter()
+            .flat_map(|shard| {
+                let mut shard = shard.lock();
+                shard.rotate(now, self.window);
+                let span = shard.span + now.saturating_duration_since(shard.window_start).as_secs_f64();
+                shard
+                    .candidates
+                    .iter()
+                    .map(|candidate| HotKey {
+                        hash: candidate.hash,
+                        key: candidate.key.as_ref().and_then(|buf| bincode::deserialize(buf).ok()),
+                        count: candidate.count,
+                        rate: if span > 0.0 { candidate.count as f64 / span } else { 0.0 },
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        keys.sort_by_key(|key| std::cmp::Reverse(key.count));
+        keys.truncate(self.top_k);
+        keys
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hot_key_tracker() {
+        let tracker = HotKeyTracker::<u64>::new(&HotKeyConfig {
+            top_k: 4,
+            shards: 2,
+            ..Default::default()
+        });
+
+        for i in 0..1000u64 {
+            tracker.record(HotKeyKind::MemoryHit, i, Some(&i));
+            if i % 10 == 0 {
+                // Hot keys 1, 2 and 3, without key samples at first.
+                for hot in 1..=3u64 {
+                    let key = (i > 500).then_some(&hot);
+                    tracker.record(HotKeyKind::MemoryHit, 10000 + hot, key);
+                    for _ in 0..hot {
+                        tracker.record(HotKeyKind::Miss, 10000 + hot, None);
+                    }
+                }
+            }
+        }
+
+        let hot_keys = tracker.hot_keys();
+        assert!(hot_keys.disk_hits.is_empty());
+        assert!(hot_keys.inserts.is_empty());
+
+        let hashes = hot_keys.memory_hits.iter().map(|key| key.hash).collect::<Vec<_>>();
+        for hot in 1..=3 {
+            assert!(hashes.contains(&(10000 + hot)), "{hashes:?}");
+        }
+        for key in hot_keys.memory_hits.iter().filter(|key| key.hash > 10000) {
+            assert_eq!(key.key, Some(key.hash - 10000));
+            assert!(key.count >= 100);
+            assert!(key.rate > 0.0);
+        }
+
+        let misses = hot_keys
+            .misses
+            .iter()
+            .map(|key| (key.hash, key.key))
+            .collect::<Vec<_>>();
+        assert_eq!(misses, vec![(10003, None), (10002, None), (10001, None)]);
+    }
+
+    #[test]
+    fn test_hot_key_tracker_window() {
+        let mut shard = HotKeyShard {
+            frequencies: CMSketchU32::new(0.001, 0.9),
+            candidates: vec![],
+            window_start: Instant::now(),
+            span: 0.0,
+        };
+        let window = Duration::from_secs(10);
+
+        for _ in 0..100 {
+            shard.record(1, || None, 4);
+        }
+        assert_eq!(shard.candidates[0].count, 100);
+
+        let now = shard.window_start;
+        shard.rotate(now + Duration::from_secs(25), window);
+        assert_eq!(shard.candidates[0].count, 25);
+        assert_eq!(shard.span, 7.5);
+        assert_eq!(shard.window_start, now + Duration::from_secs(20));
+
+        shard.rotate(now + Duration::from_secs(1000), window);
+        assert!(shard.candidates.is_empty());
+        assert_eq!(shard.span, 0.0);
+    }
+}
@@ -14,4 +14,6 @@
 
 pub mod builder;
 pub mod cache;
+pub mod hotkey;
+mod tier;
 pub mod writer;
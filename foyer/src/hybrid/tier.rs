@@ -0,0 +1,243 @@
+//  Copyright 2024 foyer Project Authors
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+//! The serialized in-memory tiers between the in-memory cache and the disk cache of the hybrid cache.
+
+use std::{borrow::Borrow, fmt::Debug, hash::Hash};
+
+use foyer_common::{
+    code::{StorageKey, StorageValue},
+    metrics::Metrics,
+};
+#[cfg(target_os = "linux")]
+use foyer_memory::SharedMemoryCache;
+use foyer_storage::CompressedCache;
+
+/// The serialized in-memory tiers of the hybrid cache.
+///
+/// The entries inserted into the in-memory cache are inserted into all enabled tiers as well, and the tiers are
+/// looked up in order on in-memory cache misses before the disk cache.
+pub(crate) struct MemoryTiers<K, V>
+where
+    K: StorageKey,
+    V: StorageValue,
+{
+    compressed: Option<CompressedCache<K, V>>,
+    #[cfg(target_os = "linux")]
+    shared: Option<SharedMemoryCache>,
+}
+
+impl<K, V> Debug for MemoryTiers<K, V>
+where
+    K: StorageKey,
+    V: StorageValue,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut s = f.debug_struct("MemoryTiers");
+        s.field("compressed", &self.compressed);
+        #[cfg(target_os = "linux")]
+        s.field("shared", &self.shared);
+        s.finish()
+    }
+}
+
+impl<K, V> Clone for MemoryTiers<K, V>
+where
+    K: StorageKey,
+    V: StorageValue,
+{
+    fn clone(&self) -> Self {
+        Self {
+            compressed: self.compressed.clone(),
+            #[cfg(target_os = "linux")]
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<K, V> Default for MemoryTiers<K, V>
+where
+    K: StorageKey,
+    V: StorageValue,
+{
+    fn default() -> Self {
+        Self {
+            compressed: None,
+            #[cfg(target_os = "linux")]
+            shared: None,
+        }
+    }
+}
+
+/// The hash of the key in the shared memory cache, which must be the same among the processes.
+#[cfg(target_os = "linux")]
+pub(super) fn shared_hash<Q>(key: &Q) -> u64
+where
+    Q: Hash + ?Sized,
+{
+    use std::hash::Hasher;
+
+    let mut hasher = twox_hash::XxHash64::with_seed(0);
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl<K, V> MemoryTiers<K, V>
+where
+    K: StorageKey,
+    V: StorageValue,
+{
+    pub(crate) fn with_compressed(mut self, compressed: CompressedCache<K, V>) -> Self {
+        self.compressed = Some(compressed);
+        self
+    }
+
+    #[cfg(target_os = "linux")]
+    pub(crate) fn with_shared(mut self, shared: SharedMemoryCache) -> Self {
+        self.shared = Some(shared);
+        self
+    }
+
+    pub(crate) fn compressed(&self) -> Option<&CompressedCache<K, V>> {
+        self.compressed.as_ref()
+    }
+
+    #[cfg(target_os = "linux")]
+    pub(crate) fn shared(&self) -> Option<&SharedMemoryCache> {
+        self.shared.as_ref()
+    }
+
+    /// Insert the entry into all enabled tiers.
+    ///
+    /// The error is not returned, for the entry can still be served by the in-memory cache and the disk cache.
+    ///
+    /// The insertion into the shared memory cache is best-effort: it is skipped if the segment lock is held by
+    /// others, so the hot path never waits for the other processes.
+    pub(crate) fn insert(&self, key: &K, value: &V) {
+        if let Some(compressed) = self.compressed.as_ref() {
+            if let Err(e) = compressed.insert(key, value) {
+                tracing::warn!("[hybrid]: insert entry into compressed cache error: {e}");
+            }
+        }
+
+        #[cfg(target_os = "linux")]
+        if let Some(shared) = self.shared.as_ref() {
+            match bincode::serialize(key).and_then(|k| bincode::serialize(value).map(|v| (k, v))) {
+                // The entry larger than the slot is skipped.
+                Ok((k, v)) => {
+                    shared.try_insert_with_hash(shared_hash(key), &k, &v);
+                }
+                Err(e) => tracing::warn!("[hybrid]: insert entry into shared memory cache error: {e}"),
+            }
+        }
+    }
+
+    /// Load the entry with the given key from the first tier that contains it.
+    ///
+    /// An entry that fails to be loaded (e.g. corrupted or not decodable) is treated as a miss and removed from the
+    /// tiers, for it can still be loaded from the disk cache. The error is logged and counted.
+    pub(crate) fn load<Q>(&self, key: &Q, metrics: &Metrics) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.try_load(key) {
+            Ok(entry) => entry,
+            Err(e) => {
+                tracing::warn!("[hybrid]: load entry from memory tiers error, treat as miss: {e}");
+                metrics.hybrid_tier_error.increment(1);
+                self.remove(key);
+                None
+            }
+        }
+    }
+
+    fn try_load<Q>(&self, key: &Q) -> anyhow::Result<Option<(K, V)>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if let Some(compressed) = self.compressed.as_ref() {
+            if let Some(entry) = compressed.load(key)? {
+                return Ok(Some(entry));
+            }
+        }
+
+        #[cfg(target_os = "linux")]
+        if let Some(shared) = self.shared.as_ref() {
+            if let Some((k, v)) = shared.get_with_hash(shared_hash(key), |k| Self::matches(k, key)) {
+                return Ok(Some((bincode::deserialize(&k)?, bincode::deserialize(&v)?)));
+            }
+        }
+
+        Ok(None)
+    }
+
+    pub(crate) fn remove<Q>(&self, key: &Q)
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if let Some(compressed) = self.compressed.as_ref() {
+            compressed.remove(key);
+        }
+
+        #[cfg(target_os = "linux")]
+        if let Some(shared) = self.shared.as_ref() {
+            shared.remove_with_hash(shared_hash(key), |k| Self::matches(k, key));
+        }
+    }
+
+    pub(crate) fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if self
+            .compressed
+            .as_ref()
+            .is_some_and(|compressed| compressed.contains(key))
+        {
+            return true;
+        }
+
+        #[cfg(target_os = "linux")]
+        if let Some(shared) = self.shared.as_ref() {
+            return shared.contains_with_hash(shared_hash(key), |k| Self::matches(k, key));
+        }
+
+        false
+    }
+
+    pub(crate) fn clear(&self) {
+        if let Some(compressed) = self.compressed.as_ref() {
+            compressed.clear();
+        }
+
+        #[cfg(target_os = "linux")]
+        if let Some(shared) = self.shared.as_ref() {
+            shared.clear();
+        }
+    }
+
+    /// Check if the serialized key in the shared memory cache is the given key.
+    #[cfg(target_os = "linux")]
+    fn matches<Q>(buf: &[u8], key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+    {
+        bincode::deserialize::<K>(buf).is_ok_and(|k| k.borrow() == key)
+    }
+}
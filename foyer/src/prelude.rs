@@ -17,23 +17,36 @@ pub use crate::{
         buf::{BufExt, BufMutExt},
         code::{Key, StorageKey, StorageValue, Value},
         event::EventListener,
+        mrc::{MissRatioCurve, MissRatioCurveConfig},
         range::RangeBoundsExt,
         tracing::TracingOptions,
     },
     hybrid::{
         builder::{HybridCacheBuilder, HybridCacheBuilderPhaseMemory, HybridCacheBuilderPhaseStorage},
         cache::{HybridCache, HybridCacheEntry, HybridFetch, HybridFetchInner},
+        hotkey::{HotKey, HotKeyConfig, HotKeyKind, HotKeys},
         writer::{HybridCacheStorageWriter, HybridCacheWriter},
     },
     memory::{
-        Cache, CacheBuilder, CacheContext, CacheEntry, EvictionConfig, FetchState, FifoConfig, LfuConfig, LruConfig,
-        S3FifoConfig, Weighter,
+        AdmissionConfig, BaseHandle, Cache, CacheBuilder, CacheContext, CacheEntry, CustomCache, CustomCacheEntry,
+        DoorkeeperConfig, EntryMetadata, Eviction, EvictionConfig, FetchState, FifoConfig, Handle, LfuConfig,
+        LruConfig, MemoryPressureConfig, MemoryPressureMonitor, MemoryPressureMonitorHandle, S3FifoConfig, Weighter,
     },
     storage::{
-        AdmissionPicker, AdmitAllPicker, Compression, Dev, DevConfig, DevExt, DeviceStats, DirectFileDevice,
-        DirectFileDeviceOptions, DirectFsDevice, DirectFsDeviceOptions, Engine, EvictionPicker, FifoPicker,
-        InvalidRatioPicker, LargeEngineOptions, RateLimitPicker, RecoverMode, ReinsertionPicker, RejectAllPicker,
-        Runtime, RuntimeOptions, SmallEngineOptions, Storage, Store, StoreBuilder, TokioRuntimeOptions,
-        TombstoneLogConfigBuilder,
+        AdmissionPicker, AdmitAllPicker, CompressedCache, CompressedCacheBuilder, Compression, CompressionConfig, Dev,
+        DevConfig, DevExt, DeviceStats, DirectFileDevice, DirectFileDeviceOptions, DirectFsDevice,
+        DirectFsDeviceOptions, Engine, EvictionPicker, FifoPicker, InvalidRatioPicker, IoClass, IoSchedulerConfig,
+        LargeEngineOptions, MemoryDevice, MemoryDeviceOptions, RateLimitPicker, RecoverMode, ReinsertionPicker,
+        RejectAllPicker, Runtime, RuntimeOptions, SmallEngineOptions, Storage, Store, StoreBuilder, StripedDevice,
+        StripedDeviceOptions, Throttle, TokioRuntimeOptions, TombstoneLogConfigBuilder, ZstdDictionary,
     },
 };
+
+#[cfg(target_os = "linux")]
+pub use crate::memory::{SharedMemoryCache, SharedMemoryCacheBuilder};
+
+#[cfg(feature = "fault_injection")]
+pub use crate::storage::{FaultInjected, FaultInjectedDeviceOptions, FaultInjector, FaultPolicy, FaultStats};
+
+#[cfg(all(feature = "io_uring", target_os = "linux"))]
+pub use crate::storage::{IoUringDevice, IoUringDeviceOptions};